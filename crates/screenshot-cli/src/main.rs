@@ -1,15 +1,26 @@
 //! screenshot-cli: Command-line tool for screenshot capture debugging
 //!
-//! Provides commands for listing windows, capturing screenshots, and managing
-//! Wayland consent tokens without the MCP protocol overhead.
+//! Provides commands for listing windows, capturing screenshots and
+//! time-lapses, checking baselines, reviewing the capture audit log, and
+//! managing Wayland consent tokens without the MCP protocol overhead.
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use screenshot_core::capture::create_default_backend;
-use screenshot_core::model::{CaptureOptions, ImageFormat, WindowSelector};
+use clap::{Args, Parser, Subcommand};
+use screenshot_core::capture::baseline::{BaselineStatus, junit_report};
+use screenshot_core::capture::timelapse::{
+    TIMELAPSE_MANIFEST, check_timelapse_options, run_timelapse,
+};
+use screenshot_core::capture::{
+    BaselineStore, ImageBuffer, TargetRegistry, create_default_backend,
+};
+use screenshot_core::model::{
+    BaselineCheckOptions, CaptureOptions, CompareOptions, GridOptions, ImageFormat,
+    RedactionReport, Region, TimelapseManifest, TimelapseOptions, WindowSelector,
+};
+use screenshot_core::util::audit::{AuditEntry, AuditFilter, AuditLog, parse_audit_time};
 use screenshot_core::util::encode::encode_image;
 
 #[derive(Parser)]
@@ -24,29 +35,28 @@ struct Cli {
 enum Commands {
     /// List all capturable windows
     ListWindows,
+    /// List the targets defined in the targets config file
+    ListTargets,
     /// Capture a screenshot of a specific window
     CaptureWindow {
-        /// Window title substring or regex pattern
-        #[arg(long)]
-        title: Option<String>,
-        /// Window class name
-        #[arg(long)]
-        class: Option<String>,
-        /// Executable name
-        #[arg(long)]
-        exe: Option<String>,
+        #[command(flatten)]
+        window: WindowArgs,
         /// Output file path
         #[arg(short, long)]
         out: PathBuf,
-        /// Image format (png, jpeg, webp)
-        #[arg(long, default_value = "png")]
-        format: String,
-        /// Image quality (0-100, for JPEG/WebP)
-        #[arg(long, default_value_t = 80)]
-        quality: u8,
-        /// Scale factor (0.1-2.0)
-        #[arg(long, default_value_t = 1.0)]
-        scale: f32,
+        /// Image format (png, jpeg, webp) [default: png, or with --target the
+        /// target's format, which is webp unless it sets one]
+        #[arg(long)]
+        format: Option<String>,
+        /// Image quality (0-100, for JPEG/WebP) [default: 80, or the target's]
+        #[arg(long)]
+        quality: Option<u8>,
+        /// Scale factor (0.1-2.0) [default: 1.0, or the target's]
+        #[arg(long)]
+        scale: Option<f32>,
+        /// Overlay a coordinate grid with lines every SPACING screen pixels
+        #[arg(long, value_name = "SPACING")]
+        grid: Option<u32>,
     },
     /// Capture a screenshot of an entire display
     CaptureDisplay {
@@ -65,6 +75,82 @@ enum Commands {
         /// Scale factor (0.1-2.0)
         #[arg(long, default_value_t = 1.0)]
         scale: f32,
+        /// Overlay a coordinate grid with lines every SPACING screen pixels
+        #[arg(long, value_name = "SPACING")]
+        grid: Option<u32>,
+    },
+    /// Capture a window into a directory at a fixed interval
+    ///
+    /// Frames and a manifest.json listing them are written to DIR. Runs until
+    /// --count frames or --duration seconds, or until Ctrl-C; a window that
+    /// disappears is looked up again at every tick.
+    Timelapse {
+        #[command(flatten)]
+        window: WindowArgs,
+        /// Directory for the frames and manifest.json
+        #[arg(long)]
+        dir: PathBuf,
+        /// Seconds between captures (1-86400)
+        #[arg(long, default_value_t = TimelapseOptions::default().interval_secs)]
+        interval: u64,
+        /// Stop after this many frames
+        #[arg(long)]
+        count: Option<u32>,
+        /// Stop after this many seconds
+        #[arg(long)]
+        duration: Option<u64>,
+        /// File name template with {target}, {index}, {timestamp} and {ext}
+        #[arg(long, default_value_t = TimelapseOptions::default().filename)]
+        filename: String,
+        /// Keep only the newest this many frame files
+        #[arg(long)]
+        keep: Option<u32>,
+        /// Stop once the window has been missing this many seconds
+        #[arg(long, default_value_t = TimelapseOptions::default().max_missing_secs)]
+        max_missing: u64,
+        /// Image format (png, jpeg, webp) [default: png, or with --target the
+        /// target's format, which is webp unless it sets one]
+        #[arg(long)]
+        format: Option<String>,
+        /// Image quality (0-100, for JPEG/WebP) [default: 80, or the target's]
+        #[arg(long)]
+        quality: Option<u8>,
+        /// Scale factor (0.1-2.0) [default: 1.0, or the target's]
+        #[arg(long)]
+        scale: Option<f32>,
+    },
+    /// Save, check and approve golden-image baselines
+    ///
+    /// `baseline check` exits with 0 when the capture matches, 1 when it
+    /// differs (or no baseline exists yet), and 2 on errors.
+    Baseline {
+        /// Baseline directory [default: $SCREENSHOT_BASELINE_DIR, or
+        /// ~/.local/share/screenshot-mcp/baselines]
+        #[arg(long, global = true)]
+        dir: Option<PathBuf>,
+        #[command(subcommand)]
+        command: BaselineCommand,
+    },
+    /// Show what the MCP server captured, from its audit log
+    Audit {
+        /// Audit log file [default: $SCREENSHOT_AUDIT_FILE, or
+        /// ~/.local/state/screenshot-mcp/audit.jsonl]
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Only entries at or after this time: RFC 3339, a date
+        /// (YYYY-MM-DD), or a duration ago such as 90m or 7d
+        #[arg(long)]
+        since: Option<String>,
+        /// Only entries before this time (same formats as --since)
+        #[arg(long)]
+        until: Option<String>,
+        /// Only captures of windows whose class or application contains
+        /// this text (case-insensitive)
+        #[arg(long)]
+        app: Option<String>,
+        /// Print matching entries as JSON lines
+        #[arg(long)]
+        json: bool,
     },
     /// Prime Wayland consent for headless capture (Linux Wayland only)
     #[cfg(target_os = "linux")]
@@ -78,6 +164,65 @@ enum Commands {
     },
 }
 
+/// Window selection flags shared by the capturing commands
+#[derive(Args)]
+struct WindowArgs {
+    /// Name of a target from the targets config file (supplies the
+    /// selector and capture options unless overridden)
+    #[arg(long, short = 't')]
+    target: Option<String>,
+    /// Selector query, e.g. 'class:firefox !title:Private' (the flags
+    /// below override the same field in the query)
+    #[arg(long, short = 'q')]
+    query: Option<String>,
+    /// Window title substring or regex pattern
+    #[arg(long)]
+    title: Option<String>,
+    /// Window class name
+    #[arg(long)]
+    class: Option<String>,
+    /// Executable name
+    #[arg(long)]
+    exe: Option<String>,
+}
+
+#[derive(Subcommand)]
+enum BaselineCommand {
+    /// Capture a window and save it as a baseline
+    Save {
+        /// Baseline name (letters, digits, '-', '_' and '.')
+        name: String,
+        #[command(flatten)]
+        window: WindowArgs,
+    },
+    /// Capture a window and compare it with a baseline
+    Check {
+        /// Baseline name
+        name: String,
+        #[command(flatten)]
+        window: WindowArgs,
+        /// Largest per-channel difference treated as unchanged (0-255)
+        #[arg(long, default_value_t = CompareOptions::default().pixel_tolerance)]
+        tolerance: u8,
+        /// Largest fraction of changed pixels that still passes (0.0-1.0)
+        #[arg(long, default_value_t = 0.0)]
+        max_changed_ratio: f64,
+        /// Region to ignore as x,y,width,height (repeatable)
+        #[arg(long = "ignore", value_parser = parse_region)]
+        ignore: Vec<Region>,
+        /// Write a JUnit XML report to this path
+        #[arg(long)]
+        junit: Option<PathBuf>,
+    },
+    /// Accept the capture from the last failed check as the new baseline
+    Approve {
+        /// Baseline name
+        name: String,
+    },
+    /// List saved baselines
+    List,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     // Initialize logging
@@ -95,16 +240,23 @@ async fn main() -> Result<()> {
         Commands::ListWindows => {
             list_windows().await?;
         }
+        Commands::ListTargets => {
+            list_targets().await?;
+        }
         Commands::CaptureWindow {
-            title,
-            class,
-            exe,
+            window,
             out,
             format,
             quality,
             scale,
+            grid,
         } => {
-            capture_window(title, class, exe, out, format, quality, scale).await?;
+            let (selector, defaults) = resolve_window_args(window)?;
+            let mut opts = build_options(defaults, format, quality, scale)?;
+            if let Some(spacing) = grid {
+                opts.grid = Some(grid_options(spacing));
+            }
+            capture_window(selector, out, opts).await?;
         }
         Commands::CaptureDisplay {
             display_id,
@@ -112,8 +264,70 @@ async fn main() -> Result<()> {
             format,
             quality,
             scale,
+            grid,
+        } => {
+            capture_display(display_id, out, format, quality, scale, grid).await?;
+        }
+        Commands::Timelapse {
+            window,
+            dir,
+            interval,
+            count,
+            duration,
+            filename,
+            keep,
+            max_missing,
+            format,
+            quality,
+            scale,
+        } => {
+            if count.is_none() && duration.is_none() {
+                anyhow::bail!("At least one of --count or --duration must be specified");
+            }
+            let label = window
+                .target
+                .clone()
+                .or_else(|| window.query.clone())
+                .unwrap_or_else(|| "window".to_string());
+            let (selector, defaults) = resolve_window_args(window)?;
+            let opts = build_options(defaults, format, quality, scale)?;
+            let mut options = TimelapseOptions {
+                interval_secs: interval,
+                max_frames: count,
+                max_duration_secs: duration,
+                filename,
+                keep,
+                max_missing_secs: max_missing,
+            };
+            options.validate();
+            check_timelapse_options(&options, &label, opts.format)?;
+            let manifest = timelapse(&dir, &label, selector, &options, &opts).await?;
+            report_timelapse(&dir, &manifest);
+        }
+        Commands::Baseline { dir, command } => {
+            let store = dir
+                .map(BaselineStore::new)
+                .unwrap_or_else(BaselineStore::open_default);
+            run_baseline_command(store, command).await?;
+        }
+        Commands::Audit {
+            file,
+            since,
+            until,
+            app,
+            json,
         } => {
-            capture_display(display_id, out, format, quality, scale).await?;
+            let log = match file {
+                Some(file) => AuditLog::new(file),
+                None => AuditLog::open_default()?,
+            };
+            let now = chrono::Utc::now();
+            let filter = AuditFilter {
+                since: since.map(|t| parse_audit_time(&t, now)).transpose()?,
+                until: until.map(|t| parse_audit_time(&t, now)).transpose()?,
+                app,
+            };
+            show_audit(&log, &filter, json)?;
         }
         #[cfg(target_os = "linux")]
         Commands::PrimeWaylandConsent {
@@ -159,47 +373,165 @@ async fn list_windows() -> Result<()> {
     Ok(())
 }
 
-async fn capture_window(
+async fn list_targets() -> Result<()> {
+    let path = TargetRegistry::default_path();
+    let targets = TargetRegistry::load_default()?;
+    if targets.is_empty() {
+        println!("No targets defined in {}", path.display());
+        return Ok(());
+    }
+
+    let backend = create_default_backend()?;
+    let windows = match backend.enumerator.as_ref() {
+        Some(enumerator) => enumerator.list_windows().await?,
+        None => Vec::new(),
+    };
+
+    println!("Found {} targets in {}:\n", targets.len(), path.display());
+    for status in targets.statuses(&windows) {
+        println!("  {}: {}", status.name, status.query);
+        match (status.window, status.error) {
+            (Some(window), _) => println!("    → {} ({})", window.id, window.title),
+            (None, Some(error)) => println!("    ✗ {}", error),
+            (None, None) => println!("    ✗ not resolved"),
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Builds the selector and default capture options from the window flags
+fn resolve_window_args(window: WindowArgs) -> Result<(WindowSelector, CaptureOptions)> {
+    let (base, defaults) = match window.target {
+        Some(name) => {
+            let targets = TargetRegistry::load_default()?;
+            let target = targets.require(&name)?;
+            (target.selector.clone(), target.options.clone())
+        }
+        None => (
+            WindowSelector::default(),
+            CaptureOptions::builder().format(ImageFormat::Png).build(),
+        ),
+    };
+    let selector = build_selector(base, window.query, window.title, window.class, window.exe)?;
+    Ok((selector, defaults))
+}
+
+/// Builds a selector from `--query` over a target's selector, with explicit
+/// flags taking precedence over both
+fn build_selector(
+    base: WindowSelector,
+    query: Option<String>,
     title: Option<String>,
     class: Option<String>,
     exe: Option<String>,
-    out: PathBuf,
-    format_str: String,
-    quality: u8,
-    scale: f32,
-) -> Result<()> {
-    // Validate selector
-    if title.is_none() && class.is_none() && exe.is_none() {
-        anyhow::bail!("At least one of --title, --class, or --exe must be specified");
+) -> Result<WindowSelector> {
+    let mut selector = match query {
+        Some(query) => query.parse::<WindowSelector>()?.with_fallback(base),
+        None => base,
+    };
+    if title.is_some() {
+        selector.title_substring_or_regex = title;
+    }
+    if class.is_some() {
+        selector.class = class;
+    }
+    if exe.is_some() {
+        selector.exe = exe;
     }
 
-    // Parse format
-    let format = match format_str.to_lowercase().as_str() {
-        "png" => ImageFormat::Png,
-        "jpeg" | "jpg" => ImageFormat::Jpeg,
-        "webp" => ImageFormat::Webp,
-        _ => anyhow::bail!("Invalid format '{}'. Must be png, jpeg, or webp", format_str),
-    };
+    if selector.is_empty() {
+        anyhow::bail!(
+            "At least one of --target, --query, --title, --class, or --exe must be specified"
+        );
+    }
+    Ok(selector)
+}
 
-    // Validate quality
-    if quality > 100 {
-        anyhow::bail!("Quality must be between 0 and 100");
+/// Applies explicit `--format`, `--quality` and `--scale` over `defaults`
+fn build_options(
+    defaults: CaptureOptions,
+    format: Option<String>,
+    quality: Option<u8>,
+    scale: Option<f32>,
+) -> Result<CaptureOptions> {
+    let mut opts = defaults;
+
+    if let Some(format_str) = format {
+        opts.format = match format_str.to_lowercase().as_str() {
+            "png" => ImageFormat::Png,
+            "jpeg" | "jpg" => ImageFormat::Jpeg,
+            "webp" => ImageFormat::Webp,
+            _ => anyhow::bail!("Invalid format '{}'. Must be png, jpeg, or webp", format_str),
+        };
     }
 
-    // Validate scale
-    if !(0.1..=2.0).contains(&scale) {
-        anyhow::bail!("Scale must be between 0.1 and 2.0");
+    if let Some(quality) = quality {
+        if quality > 100 {
+            anyhow::bail!("Quality must be between 0 and 100");
+        }
+        opts.quality = quality;
     }
 
-    // Create backend
-    let backend = create_default_backend()?;
+    if let Some(scale) = scale {
+        if !(0.1..=2.0).contains(&scale) {
+            anyhow::bail!("Scale must be between 0.1 and 2.0");
+        }
+        opts.scale = scale;
+    }
+
+    Ok(opts)
+}
 
-    // Build selector
-    let selector = WindowSelector {
-        title_substring_or_regex: title,
-        class,
-        exe,
+/// Tells the user what the privacy policy or options redacted
+fn report_redaction(report: Option<&RedactionReport>) {
+    let Some(report) = report else {
+        return;
     };
+    if report.full {
+        println!("Redacted the whole capture (denied by privacy policy)");
+    } else {
+        println!(
+            "Redacted {} region(s) and {} denied window(s)",
+            report.regions,
+            report.windows.len() + report.blocked.len()
+        );
+    }
+}
+
+/// Grid options with lines every `spacing` screen pixels
+fn grid_options(spacing: u32) -> GridOptions {
+    let mut grid = GridOptions {
+        spacing,
+        ..Default::default()
+    };
+    grid.validate();
+    grid
+}
+
+async fn capture_window(
+    selector: WindowSelector,
+    out: PathBuf,
+    opts: CaptureOptions,
+) -> Result<()> {
+    let image_buffer = capture_image(&selector, &opts).await?;
+
+    // Encode and save
+    println!("Encoding image...");
+    let image_data = encode_image(&image_buffer, &opts)?;
+
+    println!("Saving to {}...", out.display());
+    fs::write(&out, image_data)?;
+
+    println!("✓ Screenshot saved to {}", out.display());
+    Ok(())
+}
+
+/// Resolves and captures a window (repeatedly if the options ask to settle)
+async fn capture_image(selector: &WindowSelector, opts: &CaptureOptions) -> Result<ImageBuffer> {
+    // Create backend
+    let backend = create_default_backend()?;
 
     // Resolve window using WindowResolver capability
     println!("Resolving window...");
@@ -207,36 +539,222 @@ async fn capture_window(
         .resolver
         .as_ref()
         .ok_or_else(|| anyhow::anyhow!("Window resolution is not available on this backend"))?;
-    let handle = resolver.resolve(&selector).await?;
+    let handle = resolver.resolve(selector).await?;
     println!("Found window: {}", handle);
 
-    // Capture using ScreenCapture capability
+    // Capture using ScreenCapture capability (repeatedly if the target settles)
     println!("Capturing window...");
-    let opts = CaptureOptions::builder()
-        .format(format)
-        .quality(quality)
-        .scale(scale)
-        .build();
+    let capture = backend.capture_window_settled(handle, opts).await?;
+    report_redaction(capture.redaction.as_ref());
+    Ok(capture.image)
+}
 
-    let image_buffer = backend.capture.capture_window(handle, &opts).await?;
+/// Captures a window into `dir` until a limit is reached or Ctrl-C
+async fn timelapse(
+    dir: &Path,
+    label: &str,
+    selector: WindowSelector,
+    options: &TimelapseOptions,
+    opts: &CaptureOptions,
+) -> Result<TimelapseManifest> {
+    let backend = create_default_backend()?;
+    let resolver = backend
+        .resolver
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Window resolution is not available on this backend"))?;
 
-    // Encode and save
-    println!("Encoding image...");
-    let image_data = encode_image(&image_buffer, &opts)?;
+    println!(
+        "Capturing every {}s into {} (Ctrl-C to stop)...",
+        options.interval_secs,
+        dir.display()
+    );
+    let capture = || async {
+        let handle = resolver.resolve(&selector).await?;
+        let capture = backend.capture_window_settled(handle.clone(), opts).await?;
+        Ok((handle, capture.image))
+    };
+    let cancel = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    Ok(run_timelapse(dir, label, options, opts, capture, cancel).await?)
+}
 
-    println!("Saving to {}...", out.display());
-    fs::write(&out, image_data)?;
+/// Prints how a time-lapse ended
+fn report_timelapse(dir: &Path, manifest: &TimelapseManifest) {
+    let stopped = manifest
+        .stopped
+        .map_or("running", |stopped| stopped.as_str());
+    println!(
+        "✓ Captured {} frames ({} missed), stopped: {}",
+        manifest.captured, manifest.missed, stopped
+    );
+    if let Some(error) = &manifest.error {
+        println!("  Error: {}", error);
+    }
+    println!("  Manifest: {}", dir.join(TIMELAPSE_MANIFEST).display());
+}
 
-    println!("✓ Screenshot saved to {}", out.display());
+async fn run_baseline_command(store: BaselineStore, command: BaselineCommand) -> Result<()> {
+    match command {
+        BaselineCommand::Save { name, window } => {
+            let (selector, opts) = resolve_window_args(window)?;
+            let image = capture_image(&selector, &opts).await?;
+            let path = store.save(&name, &image)?;
+            println!("✓ Baseline '{}' saved to {}", name, path.display());
+        }
+        BaselineCommand::Check {
+            name,
+            window,
+            tolerance,
+            max_changed_ratio,
+            ignore,
+            junit,
+        } => {
+            let options = BaselineCheckOptions {
+                compare: CompareOptions {
+                    pixel_tolerance: tolerance,
+                    ..Default::default()
+                },
+                max_changed_ratio,
+                ignore_regions: ignore,
+            };
+            // JUnit convention: 1 for a failed check, 2 for an error
+            let code = match baseline_check(&store, &name, window, &options, junit).await {
+                Ok(true) => 0,
+                Ok(false) => 1,
+                Err(e) => {
+                    eprintln!("Error: {:#}", e);
+                    2
+                }
+            };
+            std::process::exit(code);
+        }
+        BaselineCommand::Approve { name } => {
+            let path = store.approve(&name)?;
+            println!("✓ Baseline '{}' approved ({})", name, path.display());
+        }
+        BaselineCommand::List => {
+            let names = store.list()?;
+            if names.is_empty() {
+                println!("No baselines in {}", store.dir().display());
+            } else {
+                println!("Found {} baselines in {}:\n", names.len(), store.dir().display());
+                for name in names {
+                    println!("  {}", name);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints audit entries matching `filter`, as text or one JSON object per line
+fn show_audit(log: &AuditLog, filter: &AuditFilter, json: bool) -> Result<()> {
+    let entries = log.read(filter)?;
+    if json {
+        for entry in &entries {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No matching audit entries in {}", log.path().display());
+        return Ok(());
+    }
+    println!("Found {} audit entries in {}:\n", entries.len(), log.path().display());
+    for entry in &entries {
+        print_audit_entry(entry);
+    }
     Ok(())
 }
 
+/// Prints one audit entry as an indented block
+fn print_audit_entry(entry: &AuditEntry) {
+    println!("  {}  {} ({})", entry.timestamp, entry.tool, entry.backend);
+    match &entry.window {
+        Some(window) if !window.class.is_empty() => println!(
+            "    Window: {} ({}, {}, PID {})",
+            window.title, window.class, window.owner, window.pid
+        ),
+        Some(window) => println!("    Window: {}", window.id),
+        None => {}
+    }
+    if let (Some(width), Some(height)) = (entry.width, entry.height) {
+        println!("    Image: {}x{}", width, height);
+    }
+    for output in &entry.output {
+        println!("    Output: {}", output);
+    }
+    if let Some(reason) = &entry.denied {
+        println!("    ✗ {}", reason);
+    }
+    match &entry.redaction {
+        Some(report) if report.full => println!("    Redacted: whole image"),
+        Some(report) if entry.redacted() => println!(
+            "    Redacted: {} region(s), {} window(s) by redaction, {} by policy",
+            report.regions,
+            report.windows.len(),
+            report.blocked.len()
+        ),
+        _ => {}
+    }
+    println!();
+}
+
+/// Checks a fresh capture against a baseline, returning whether it passed
+async fn baseline_check(
+    store: &BaselineStore,
+    name: &str,
+    window: WindowArgs,
+    options: &BaselineCheckOptions,
+    junit: Option<PathBuf>,
+) -> Result<bool> {
+    let (selector, opts) = resolve_window_args(window)?;
+    let image = capture_image(&selector, &opts).await?;
+    let report = store.check(name, &image, options)?;
+
+    let mark = if report.passed() { "✓" } else { "✗" };
+    println!("{} {}", mark, report.message);
+    if let Some(path) = &report.actual_path {
+        println!("  Capture: {}", path.display());
+    }
+    if let Some(path) = &report.diff_path {
+        println!("  Diff:    {}", path.display());
+    }
+    if matches!(report.status, BaselineStatus::Failed | BaselineStatus::Missing) {
+        println!("  Run `screenshot-cli baseline approve {}` to accept it", name);
+    }
+
+    if let Some(path) = junit {
+        fs::write(&path, junit_report("screenshot-baselines", std::slice::from_ref(&report)))?;
+        println!("  JUnit report: {}", path.display());
+    }
+
+    Ok(report.passed())
+}
+
+/// Parses an `x,y,width,height` region flag
+fn parse_region(value: &str) -> Result<Region, String> {
+    let parts: Vec<u32> = value
+        .split(',')
+        .map(|part| part.trim().parse::<u32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid region '{}': expected x,y,width,height", value))?;
+    match parts[..] {
+        [x, y, width, height] if width > 0 && height > 0 => Ok(Region::new(x, y, width, height)),
+        _ => Err(format!("invalid region '{}': expected x,y,width,height", value)),
+    }
+}
+
 async fn capture_display(
     display_id: Option<u32>,
     out: PathBuf,
     format_str: String,
     quality: u8,
     scale: f32,
+    grid: Option<u32>,
 ) -> Result<()> {
     // Parse format
     let format = match format_str.to_lowercase().as_str() {
@@ -265,13 +783,18 @@ async fn capture_display(
         .unwrap_or_else(|| "primary display".to_string());
     println!("Capturing {}...", display_name);
 
-    let opts = CaptureOptions::builder()
+    let mut builder = CaptureOptions::builder()
         .format(format)
         .quality(quality)
-        .scale(scale)
-        .build();
+        .scale(scale);
+    if let Some(spacing) = grid {
+        builder = builder.grid(grid_options(spacing));
+    }
+    let opts = builder.build();
 
-    let image_buffer = backend.capture.capture_display(display_id, &opts).await?;
+    let capture = backend.capture_display_settled(display_id, &opts).await?;
+    report_redaction(capture.redaction.as_ref());
+    let image_buffer = capture.image;
 
     // Encode and save
     println!("Encoding image...");
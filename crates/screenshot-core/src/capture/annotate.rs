@@ -0,0 +1,569 @@
+//! Annotation overlays: boxes, arrows and labels drawn on captures
+//!
+//! [`ImageBuffer::annotate`] draws a list of [`Annotation`]s onto a copy of
+//! a capture, e.g. to circle the broken part of a UI before handing the
+//! screenshot to a human. Shapes are anti-aliased; text labels use the
+//! bundled DejaVu Sans Mono Bold font, so output looks the same on every
+//! machine.
+//!
+//! Shapes are drawn in list order, so later shapes cover earlier ones.
+//! Coordinates are image pixels; convert screen coordinates first with
+//! [`Annotation::map_from_screen`].
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::ImageBuffer,
+//!     model::{Annotation, AnnotationShape},
+//! };
+//!
+//! let capture = ImageBuffer::from_test_pattern(320, 200);
+//! let annotated = capture
+//!     .annotate(&[
+//!         Annotation::new(AnnotationShape::Ellipse {
+//!             x: 100.0,
+//!             y: 60.0,
+//!             width: 120.0,
+//!             height: 50.0,
+//!         }),
+//!         Annotation::new(AnnotationShape::Text {
+//!             x: 100.0,
+//!             y: 20.0,
+//!             text: "Misaligned".to_string(),
+//!             size: 16.0,
+//!         }),
+//!     ])
+//!     .unwrap();
+//! assert_eq!(annotated.dimensions(), (320, 200));
+//! ```
+
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use image::{DynamicImage, RgbaImage};
+use once_cell::sync::Lazy;
+use tiny_skia::{
+    FillRule, LineCap, LineJoin, Paint, PathBuilder, Pixmap, PremultipliedColorU8, Rect, Stroke,
+    Transform,
+};
+
+use super::ImageBuffer;
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{Annotation, AnnotationShape},
+};
+
+/// Most annotations accepted in one call
+pub const MAX_ANNOTATIONS: usize = 256;
+
+/// Bundled label font (see `assets/fonts/LICENSE`)
+static FONT: Lazy<FontRef<'static>> = Lazy::new(|| {
+    FontRef::try_from_slice(include_bytes!("../../assets/fonts/DejaVuSansMono-Bold.ttf"))
+        .expect("bundled font is a valid TrueType font")
+});
+
+/// Straight (not premultiplied) RGBA colour
+type Rgba = [u8; 4];
+
+/// Parses "#rgb", "#rrggbb", "#rrggbbaa" or a basic colour name
+fn parse_color(value: &str) -> Option<Rgba> {
+    let value = value.trim().to_ascii_lowercase();
+    let named = match value.as_str() {
+        "red" => Some([230, 30, 30, 255]),
+        "green" => Some([30, 170, 60, 255]),
+        "blue" => Some([30, 100, 230, 255]),
+        "yellow" => Some([250, 210, 0, 255]),
+        "orange" => Some([255, 140, 0, 255]),
+        "magenta" => Some([230, 0, 200, 255]),
+        "cyan" => Some([0, 190, 220, 255]),
+        "black" => Some([0, 0, 0, 255]),
+        "white" => Some([255, 255, 255, 255]),
+        _ => None,
+    };
+    if named.is_some() {
+        return named;
+    }
+
+    let hex = value.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => {
+            let mut rgba = [255; 4];
+            for (i, c) in hex.chars().enumerate() {
+                let v = c.to_digit(16)? as u8;
+                rgba[i] = v * 17;
+            }
+            Some(rgba)
+        }
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
+/// Solid anti-aliased paint of a colour
+fn paint(color: Rgba) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(color[0], color[1], color[2], color[3]);
+    paint.anti_alias = true;
+    paint
+}
+
+/// Draws one annotation onto the overlay, or says what is wrong with it
+fn draw(overlay: &mut Pixmap, annotation: &Annotation) -> Result<(), String> {
+    let color = parse_color(&annotation.color).ok_or_else(|| {
+        format!(
+            "unknown color '{}' (use #rrggbb, #rrggbbaa or a name like red)",
+            annotation.color
+        )
+    })?;
+    if !annotation.stroke_width.is_finite() {
+        return Err("strokeWidth must be a number".to_string());
+    }
+    let stroke = Stroke {
+        width: annotation.stroke_width.clamp(1.0, 64.0),
+        line_cap: LineCap::Round,
+        line_join: LineJoin::Round,
+        ..Default::default()
+    };
+    let paint = paint(color);
+    let identity = Transform::identity();
+
+    let path = match &annotation.shape {
+        AnnotationShape::Rectangle {
+            x,
+            y,
+            width,
+            height,
+        } => bounding_box(*x, *y, *width, *height)?.map(PathBuilder::from_rect),
+        AnnotationShape::Ellipse {
+            x,
+            y,
+            width,
+            height,
+        } => bounding_box(*x, *y, *width, *height)?.and_then(PathBuilder::from_oval),
+        AnnotationShape::Arrow { from, to } => {
+            return draw_arrow(overlay, *from, *to, &paint, &stroke);
+        }
+        AnnotationShape::Polyline { points } => {
+            if points.len() < 2 {
+                return Err("polyline needs at least two points".to_string());
+            }
+            let mut builder = PathBuilder::new();
+            builder.move_to(points[0][0], points[0][1]);
+            for p in &points[1..] {
+                builder.line_to(p[0], p[1]);
+            }
+            builder.finish()
+        }
+        AnnotationShape::Text { x, y, text, size } => {
+            return draw_label(overlay, (*x, *y), text, *size, color);
+        }
+    };
+
+    let path = path.ok_or("shape needs finite coordinates")?;
+    overlay.stroke_path(&path, &paint, &stroke, identity, None);
+    Ok(())
+}
+
+/// Box of a rectangle or ellipse, which must have a positive size
+fn bounding_box(x: f32, y: f32, width: f32, height: f32) -> Result<Option<Rect>, String> {
+    if !(width > 0.0 && height > 0.0) {
+        return Err("width and height must be positive".to_string());
+    }
+    Ok(Rect::from_xywh(x, y, width, height))
+}
+
+/// Draws a line ending in a filled arrowhead at `to`
+fn draw_arrow(
+    overlay: &mut Pixmap,
+    from: [f32; 2],
+    to: [f32; 2],
+    paint: &Paint,
+    stroke: &Stroke,
+) -> Result<(), String> {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let length = (dx * dx + dy * dy).sqrt();
+    if !length.is_finite() || length < 1.0 {
+        return Err("arrow needs two distinct, finite points".to_string());
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let head = (stroke.width * 4.0).max(12.0).min(length);
+
+    // Stop the shaft inside the head so its round cap does not poke out
+    let mut shaft = PathBuilder::new();
+    shaft.move_to(from[0], from[1]);
+    shaft.line_to(to[0] - ux * head * 0.5, to[1] - uy * head * 0.5);
+    if let Some(path) = shaft.finish() {
+        overlay.stroke_path(&path, paint, stroke, Transform::identity(), None);
+    }
+
+    let (bx, by) = (to[0] - ux * head, to[1] - uy * head);
+    let (px, py) = (-uy * head * 0.5, ux * head * 0.5);
+    let mut tip = PathBuilder::new();
+    tip.move_to(to[0], to[1]);
+    tip.line_to(bx + px, by + py);
+    tip.line_to(bx - px, by - py);
+    tip.close();
+    if let Some(path) = tip.finish() {
+        overlay.fill_path(&path, paint, FillRule::Winding, Transform::identity(), None);
+    }
+    Ok(())
+}
+
+/// Width and height of the box [`draw_label`] draws for `text` at `size`
+pub(super) fn label_size(text: &str, size: f32) -> (f32, f32) {
+    let scale = PxScale::from(size.clamp(6.0, 200.0));
+    let font = FONT.as_scaled(scale);
+    let line_height = font.height() + font.line_gap();
+    let line_width = |line: &str| {
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(prev) = previous {
+                width += font.kern(prev, id);
+            }
+            width += font.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    };
+
+    let lines: Vec<&str> = text.lines().collect();
+    let padding = (scale.y * 0.3).round();
+    (
+        lines.iter().map(|l| line_width(l)).fold(0.0, f32::max) + 2.0 * padding,
+        lines.len() as f32 * line_height + 2.0 * padding,
+    )
+}
+
+/// Draws text on a box filled with `color`, kept inside the image if it fits
+fn draw_label(
+    overlay: &mut Pixmap,
+    (x, y): (f32, f32),
+    text: &str,
+    size: f32,
+    color: Rgba,
+) -> Result<(), String> {
+    if text.trim().is_empty() {
+        return Err("text must not be empty".to_string());
+    }
+    if !(x.is_finite() && y.is_finite() && size.is_finite()) {
+        return Err("text position and size must be numbers".to_string());
+    }
+
+    let scale = PxScale::from(size.clamp(6.0, 200.0));
+    let font = FONT.as_scaled(scale);
+    let line_height = font.height() + font.line_gap();
+    let lines: Vec<&str> = text.lines().collect();
+    let padding = (scale.y * 0.3).round();
+    let (box_width, box_height) = label_size(text, size);
+    let left = x.min(overlay.width() as f32 - box_width).max(0.0);
+    let top = y.min(overlay.height() as f32 - box_height).max(0.0);
+
+    if let Some(rect) = Rect::from_xywh(left, top, box_width, box_height) {
+        overlay.fill_rect(rect, &paint(color), Transform::identity(), None);
+    }
+
+    // Dark text on light boxes, light text on dark ones
+    let luma =
+        0.299 * f32::from(color[0]) + 0.587 * f32::from(color[1]) + 0.114 * f32::from(color[2]);
+    let ink = if luma > 150.0 {
+        [0, 0, 0, 255]
+    } else {
+        [255, 255, 255, 255]
+    };
+
+    for (row, line) in lines.iter().enumerate() {
+        let baseline = top + padding + row as f32 * line_height + font.ascent();
+        let mut caret = left + padding;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(prev) = previous {
+                caret += font.kern(prev, id);
+            }
+            let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+            caret += font.h_advance(id);
+            previous = Some(id);
+
+            let Some(outline) = FONT.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i64 + i64::from(gx);
+                let py = bounds.min.y as i64 + i64::from(gy);
+                blend_pixel(overlay, px, py, ink, coverage);
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Blends `color` at `coverage` (0.0-1.0) over one overlay pixel
+fn blend_pixel(overlay: &mut Pixmap, x: i64, y: i64, color: Rgba, coverage: f32) {
+    if x < 0 || y < 0 || x >= i64::from(overlay.width()) || y >= i64::from(overlay.height()) {
+        return;
+    }
+    let index = (y as usize) * overlay.width() as usize + x as usize;
+    let alpha = coverage.clamp(0.0, 1.0) * f32::from(color[3]) / 255.0;
+    let dst = overlay.pixels()[index];
+    let mix =
+        |src: u8, dst: u8| (f32::from(src) * alpha + f32::from(dst) * (1.0 - alpha)).round() as u8;
+    let blended = PremultipliedColorU8::from_rgba(
+        mix(color[0], dst.red()),
+        mix(color[1], dst.green()),
+        mix(color[2], dst.blue()),
+        (alpha * 255.0 + f32::from(dst.alpha()) * (1.0 - alpha)).round() as u8,
+    );
+    if let Some(blended) = blended {
+        overlay.pixels_mut()[index] = blended;
+    }
+}
+
+/// Composites the premultiplied overlay onto the image (source over)
+fn composite(image: &mut RgbaImage, overlay: &Pixmap) {
+    for (pixel, src) in image.pixels_mut().zip(overlay.pixels()) {
+        let sa = f32::from(src.alpha()) / 255.0;
+        if sa == 0.0 {
+            continue;
+        }
+        let da = f32::from(pixel[3]) / 255.0;
+        let out_alpha = sa + da * (1.0 - sa);
+        let channel = |s: u8, d: u8| {
+            ((f32::from(s) + f32::from(d) * da * (1.0 - sa)) / out_alpha).round() as u8
+        };
+        *pixel = image::Rgba([
+            channel(src.red(), pixel[0]),
+            channel(src.green(), pixel[1]),
+            channel(src.blue(), pixel[2]),
+            (out_alpha * 255.0).round() as u8,
+        ]);
+    }
+}
+
+impl ImageBuffer {
+    /// Returns a copy of the image with `annotations` drawn on it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CaptureError::InvalidParameter`] for `annotations` if there
+    /// are more than [`MAX_ANNOTATIONS`], or if one has an unknown colour,
+    /// empty text, a non-positive size, or too few points. The message names
+    /// the offending annotation by index.
+    pub fn annotate(&self, annotations: &[Annotation]) -> CaptureResult<Self> {
+        let invalid = |reason: String| CaptureError::InvalidParameter {
+            parameter: "annotations".to_string(),
+            reason,
+        };
+        if annotations.len() > MAX_ANNOTATIONS {
+            return Err(invalid(format!(
+                "At most {} annotations can be drawn at once (got {})",
+                MAX_ANNOTATIONS,
+                annotations.len()
+            )));
+        }
+
+        let (width, height) = self.dimensions();
+        let mut overlay = Pixmap::new(width, height).ok_or_else(|| {
+            CaptureError::ImageError("Cannot annotate an empty image".to_string())
+        })?;
+        for (index, annotation) in annotations.iter().enumerate() {
+            draw(&mut overlay, annotation)
+                .map_err(|reason| invalid(format!("Annotation {}: {}", index, reason)))?;
+        }
+
+        let mut image = self.to_rgba8();
+        composite(&mut image, &overlay);
+        Ok(Self::new(DynamicImage::ImageRgba8(image)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WHITE: image::Rgba<u8> = image::Rgba([255, 255, 255, 255]);
+
+    fn blank() -> ImageBuffer {
+        ImageBuffer::new(DynamicImage::ImageRgba8(RgbaImage::from_pixel(120, 80, WHITE)))
+    }
+
+    fn changed_pixels(image: &RgbaImage) -> usize {
+        image.pixels().filter(|p| **p != WHITE).count()
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#ff8800"), Some([255, 136, 0, 255]));
+        assert_eq!(parse_color("#FF880080"), Some([255, 136, 0, 128]));
+        assert_eq!(parse_color("#f80"), Some([255, 136, 0, 255]));
+        assert_eq!(parse_color(" Red "), Some([230, 30, 30, 255]));
+        assert_eq!(parse_color("#12345"), None);
+        assert_eq!(parse_color("#gg0000"), None);
+        assert_eq!(parse_color("chartreuse"), None);
+    }
+
+    #[test]
+    fn test_rectangle_outline_leaves_inside_untouched() {
+        let annotated = blank()
+            .annotate(&[Annotation {
+                color: "#0000ff".to_string(),
+                ..Annotation::new(AnnotationShape::Rectangle {
+                    x: 20.0,
+                    y: 20.0,
+                    width: 60.0,
+                    height: 40.0,
+                })
+            }])
+            .unwrap()
+            .to_rgba8();
+
+        assert_eq!(*annotated.get_pixel(20, 40), image::Rgba([0, 0, 255, 255]));
+        assert_eq!(*annotated.get_pixel(50, 40), WHITE);
+        assert_eq!(*annotated.get_pixel(5, 5), WHITE);
+    }
+
+    #[test]
+    fn test_arrow_polyline_and_ellipse_draw() {
+        let shapes = [
+            AnnotationShape::Arrow {
+                from: [10.0, 70.0],
+                to: [60.0, 20.0],
+            },
+            AnnotationShape::Polyline {
+                points: vec![[70.0, 10.0], [90.0, 30.0], [110.0, 10.0]],
+            },
+            AnnotationShape::Ellipse {
+                x: 70.0,
+                y: 40.0,
+                width: 40.0,
+                height: 30.0,
+            },
+        ];
+        for shape in shapes {
+            let annotated = blank()
+                .annotate(&[Annotation::new(shape.clone())])
+                .unwrap()
+                .to_rgba8();
+            assert!(changed_pixels(&annotated) > 50, "{:?} drew nothing", shape);
+        }
+
+        // The arrowhead is filled up to its tip
+        let arrow = blank()
+            .annotate(&[Annotation::new(AnnotationShape::Arrow {
+                from: [10.0, 40.0],
+                to: [100.0, 40.0],
+            })])
+            .unwrap()
+            .to_rgba8();
+        assert_ne!(*arrow.get_pixel(97, 40), WHITE);
+        assert_ne!(*arrow.get_pixel(92, 42), WHITE);
+    }
+
+    #[test]
+    fn test_text_label_has_box_and_contrasting_text() {
+        let annotated = blank()
+            .annotate(&[Annotation {
+                color: "black".to_string(),
+                ..Annotation::new(AnnotationShape::Text {
+                    x: 10.0,
+                    y: 10.0,
+                    text: "Bug\nhere".to_string(),
+                    size: 16.0,
+                })
+            }])
+            .unwrap()
+            .to_rgba8();
+
+        // Box corner is the fill colour, white glyph pixels sit inside it
+        assert_eq!(*annotated.get_pixel(11, 11), image::Rgba([0, 0, 0, 255]));
+        let text_pixels = (10..60)
+            .flat_map(|x| (10..50).map(move |y| (x, y)))
+            .filter(|&(x, y)| annotated.get_pixel(x, y)[0] > 200)
+            .count();
+        assert!(text_pixels > 20);
+
+        // Labels near the edge are pulled back inside the image
+        let edge = blank()
+            .annotate(&[Annotation::new(AnnotationShape::Text {
+                x: 115.0,
+                y: 75.0,
+                text: "Edge".to_string(),
+                size: 16.0,
+            })])
+            .unwrap()
+            .to_rgba8();
+        assert_ne!(*edge.get_pixel(119, 79), WHITE);
+    }
+
+    #[test]
+    fn test_translucent_colors_blend() {
+        let annotated = blank()
+            .annotate(&[Annotation {
+                color: "#ff000080".to_string(),
+                stroke_width: 6.0,
+                ..Annotation::new(AnnotationShape::Polyline {
+                    points: vec![[0.0, 40.0], [120.0, 40.0]],
+                })
+            }])
+            .unwrap()
+            .to_rgba8();
+        let pixel = annotated.get_pixel(60, 40);
+        assert_eq!(pixel[0], 255);
+        assert!((120..=135).contains(&pixel[1]), "{:?}", pixel);
+        assert_eq!(pixel[3], 255);
+    }
+
+    #[test]
+    fn test_invalid_annotations_are_reported_by_index() {
+        let ok = Annotation::new(AnnotationShape::Rectangle {
+            x: 1.0,
+            y: 1.0,
+            width: 5.0,
+            height: 5.0,
+        });
+        let bad = [
+            Annotation {
+                color: "#zzz".to_string(),
+                ..ok.clone()
+            },
+            Annotation::new(AnnotationShape::Rectangle {
+                x: 1.0,
+                y: 1.0,
+                width: 0.0,
+                height: 5.0,
+            }),
+            Annotation::new(AnnotationShape::Polyline {
+                points: vec![[1.0, 1.0]],
+            }),
+            Annotation::new(AnnotationShape::Arrow {
+                from: [5.0, 5.0],
+                to: [5.0, 5.0],
+            }),
+            Annotation::new(AnnotationShape::Text {
+                x: 0.0,
+                y: 0.0,
+                text: " ".to_string(),
+                size: 16.0,
+            }),
+        ];
+
+        for annotation in bad {
+            match blank().annotate(&[ok.clone(), annotation.clone()]) {
+                Err(CaptureError::InvalidParameter { parameter, reason }) => {
+                    assert_eq!(parameter, "annotations");
+                    assert!(reason.starts_with("Annotation 1:"), "{}", reason);
+                }
+                other => panic!("{:?} was accepted: {:?}", annotation, other.map(|_| ())),
+            }
+        }
+
+        let too_many = vec![ok; MAX_ANNOTATIONS + 1];
+        assert!(blank().annotate(&too_many).is_err());
+    }
+}
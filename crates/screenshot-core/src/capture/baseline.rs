@@ -0,0 +1,598 @@
+//! Golden-image baselines for visual regression checks
+//!
+//! A baseline is a named reference capture ("the settings dialog should look
+//! like this"). Later captures are checked against it with the tolerances of
+//! [`compare_images`](super::compare::compare_images), and a failed check
+//! leaves the new capture and a diff image next to the baseline so a person
+//! or agent can review it and approve it as the new baseline.
+//!
+//! # Files
+//!
+//! Each baseline `name` is stored as PNG files in the store directory:
+//!
+//! - `name.png` - the approved baseline
+//! - `name.actual.png` - the capture from the last failed check
+//! - `name.diff.png` - the diff image from the last failed check
+//!
+//! A passing check removes stale `actual` and `diff` files. Names may use
+//! letters, digits, `-`, `_` and `.`.
+//!
+//! # Location
+//!
+//! [`BaselineStore::open_default`] uses `$SCREENSHOT_BASELINE_DIR` if set,
+//! otherwise `$XDG_DATA_HOME/screenshot-mcp/baselines` (falling back to
+//! `~/.local/share`).
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{ImageBuffer, baseline::{BaselineStatus, BaselineStore}},
+//!     model::BaselineCheckOptions,
+//! };
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! let store = BaselineStore::new(dir.path());
+//! let capture = ImageBuffer::from_test_pattern(64, 48);
+//!
+//! store.save("login", &capture).unwrap();
+//! let report = store.check("login", &capture, &BaselineCheckOptions::default()).unwrap();
+//! assert_eq!(report.status, BaselineStatus::Passed);
+//! ```
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::{ImageBuffer, compare::compare_images};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{BaselineCheckOptions, CaptureOptions, ImageComparison, ImageFormat, Region},
+    util::encode::encode_image,
+};
+
+/// Environment variable that overrides the baseline directory
+pub const BASELINE_DIR_ENV: &str = "SCREENSHOT_BASELINE_DIR";
+
+/// Outcome of a baseline check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BaselineStatus {
+    /// The capture matches the baseline within the tolerances
+    Passed,
+    /// The capture differs from the baseline
+    Failed,
+    /// No baseline exists yet under this name
+    Missing,
+    /// The capture and baseline have different dimensions
+    SizeMismatch,
+}
+
+/// Result of checking a capture against a baseline
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BaselineReport {
+    /// Baseline name
+    pub name: String,
+    /// Outcome of the check
+    pub status: BaselineStatus,
+    /// Comparison statistics, if the images could be compared
+    pub comparison: Option<ImageComparison>,
+    /// Path of the approved baseline image
+    pub baseline_path: PathBuf,
+    /// Path where the checked capture was kept, unless the check passed
+    pub actual_path: Option<PathBuf>,
+    /// Path of the diff image, if the images differ
+    pub diff_path: Option<PathBuf>,
+    /// Human-readable summary
+    pub message: String,
+}
+
+impl BaselineReport {
+    /// Returns true if the check passed
+    pub fn passed(&self) -> bool {
+        self.status == BaselineStatus::Passed
+    }
+}
+
+/// Directory of named baseline images
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaselineStore {
+    dir: PathBuf,
+}
+
+impl BaselineStore {
+    /// Creates a store in `dir` (created on first save)
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Creates a store in the default directory (see the module docs)
+    pub fn open_default() -> Self {
+        Self::new(Self::default_dir())
+    }
+
+    /// Gets the default baseline directory
+    pub fn default_dir() -> PathBuf {
+        if let Ok(dir) = std::env::var(BASELINE_DIR_ENV) {
+            return PathBuf::from(dir);
+        }
+
+        let data_dir = if let Ok(dir) = std::env::var("XDG_DATA_HOME") {
+            PathBuf::from(dir)
+        } else if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".local").join("share")
+        } else {
+            PathBuf::from("/tmp")
+        };
+
+        data_dir.join("screenshot-mcp").join("baselines")
+    }
+
+    /// The directory holding the baseline files
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Gets the path of the approved baseline image for `name`
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `name` if it is empty, contains
+    /// characters other than letters, digits, `-`, `_` and `.`, or ends in
+    /// `.actual` or `.diff`.
+    pub fn baseline_path(&self, name: &str) -> CaptureResult<PathBuf> {
+        self.file_path(name, "png")
+    }
+
+    fn actual_path(&self, name: &str) -> CaptureResult<PathBuf> {
+        self.file_path(name, "actual.png")
+    }
+
+    fn diff_path(&self, name: &str) -> CaptureResult<PathBuf> {
+        self.file_path(name, "diff.png")
+    }
+
+    fn file_path(&self, name: &str, suffix: &str) -> CaptureResult<PathBuf> {
+        // The suffixes would collide with another baseline's pending files
+        let valid = !name.is_empty()
+            && !name.starts_with('.')
+            && !name.ends_with(".actual")
+            && !name.ends_with(".diff")
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(CaptureError::InvalidParameter {
+                parameter: "name".to_string(),
+                reason: format!(
+                    "invalid baseline name '{}': use letters, digits, '-', '_' and '.'",
+                    name
+                ),
+            });
+        }
+        Ok(self.dir.join(format!("{}.{}", name, suffix)))
+    }
+
+    /// Saves `image` as the baseline `name`, replacing any existing one
+    ///
+    /// # Returns
+    ///
+    /// The path of the saved baseline image.
+    pub fn save(&self, name: &str, image: &ImageBuffer) -> CaptureResult<PathBuf> {
+        let path = self.baseline_path(name)?;
+        self.write_png(&path, image)?;
+        self.remove_pending(name)?;
+        tracing::debug!("Saved baseline '{}' to {}", name, path.display());
+        Ok(path)
+    }
+
+    /// Loads the baseline image `name`
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `name` if no such baseline exists.
+    pub fn load(&self, name: &str) -> CaptureResult<ImageBuffer> {
+        let path = self.baseline_path(name)?;
+        if !path.exists() {
+            return Err(self.unknown_baseline(name));
+        }
+        ImageBuffer::open(&path)
+    }
+
+    /// Lists the names of all approved baselines, sorted
+    pub fn list(&self) -> CaptureResult<Vec<String>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(CaptureError::IoError(e)),
+        };
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let file_name = entry.map_err(CaptureError::IoError)?.file_name();
+            let Some(name) = file_name.to_str().and_then(|n| n.strip_suffix(".png")) else {
+                continue;
+            };
+            if !name.ends_with(".actual") && !name.ends_with(".diff") {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Checks `image` against the baseline `name`
+    ///
+    /// Areas in `options.ignore_regions` are excluded. The check passes when
+    /// the fraction of changed pixels is at most `options.max_changed_ratio`.
+    /// Unless it passes, `image` is kept as the pending capture (see
+    /// [`approve`](Self::approve)), along with a diff image if the sizes
+    /// match.
+    ///
+    /// A missing baseline is reported as [`BaselineStatus::Missing`], not as
+    /// an error, so the first check of a new baseline can be approved.
+    pub fn check(
+        &self,
+        name: &str,
+        image: &ImageBuffer,
+        options: &BaselineCheckOptions,
+    ) -> CaptureResult<BaselineReport> {
+        let mut options = options.clone();
+        options.validate();
+
+        let baseline_path = self.baseline_path(name)?;
+        let actual_path = self.actual_path(name)?;
+        let diff_path = self.diff_path(name)?;
+
+        let mut report = BaselineReport {
+            name: name.to_string(),
+            status: BaselineStatus::Missing,
+            comparison: None,
+            baseline_path: baseline_path.clone(),
+            actual_path: None,
+            diff_path: None,
+            message: String::new(),
+        };
+
+        if !baseline_path.exists() {
+            self.write_png(&actual_path, image)?;
+            report.actual_path = Some(actual_path);
+            report.message =
+                format!("No baseline '{}' exists yet; approve the capture to create it", name);
+            return Ok(report);
+        }
+
+        let baseline = ImageBuffer::open(&baseline_path)?;
+        if baseline.dimensions() != image.dimensions() {
+            self.write_png(&actual_path, image)?;
+            let _ = fs::remove_file(&diff_path);
+            let (bw, bh) = baseline.dimensions();
+            let (w, h) = image.dimensions();
+            report.status = BaselineStatus::SizeMismatch;
+            report.actual_path = Some(actual_path);
+            report.message =
+                format!("Capture is {}x{} but baseline '{}' is {}x{}", w, h, name, bw, bh);
+            return Ok(report);
+        }
+
+        let (masked_baseline, masked_image) =
+            mask_regions(&baseline, image, &options.ignore_regions);
+        let (comparison, diff) = compare_images(&masked_baseline, &masked_image, &options.compare)?;
+
+        if comparison.changed_ratio <= options.max_changed_ratio {
+            self.remove_pending(name)?;
+            report.status = BaselineStatus::Passed;
+            report.message = format!(
+                "Matches baseline '{}' ({} changed pixels, SSIM {:.4})",
+                name, comparison.changed_pixels, comparison.ssim
+            );
+        } else {
+            self.write_png(&actual_path, image)?;
+            self.write_png(&diff_path, &diff)?;
+            report.status = BaselineStatus::Failed;
+            report.actual_path = Some(actual_path);
+            report.diff_path = Some(diff_path);
+            report.message = format!(
+                "Differs from baseline '{}': {} changed pixels ({:.2}%) in {} regions",
+                name,
+                comparison.changed_pixels,
+                comparison.changed_ratio * 100.0,
+                comparison.regions.len()
+            );
+        }
+        report.comparison = Some(comparison);
+        Ok(report)
+    }
+
+    /// Approves the pending capture of `name` as its new baseline
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `name` if no check left a pending
+    /// capture.
+    pub fn approve(&self, name: &str) -> CaptureResult<PathBuf> {
+        let baseline_path = self.baseline_path(name)?;
+        let actual_path = self.actual_path(name)?;
+        if !actual_path.exists() {
+            return Err(CaptureError::InvalidParameter {
+                parameter: "name".to_string(),
+                reason: format!("baseline '{}' has no pending capture; check it first", name),
+            });
+        }
+
+        fs::rename(&actual_path, &baseline_path).map_err(CaptureError::IoError)?;
+        self.remove_pending(name)?;
+        tracing::debug!("Approved baseline '{}'", name);
+        Ok(baseline_path)
+    }
+
+    /// Removes the pending capture and diff image of `name`, if any
+    fn remove_pending(&self, name: &str) -> CaptureResult<()> {
+        for path in [self.actual_path(name)?, self.diff_path(name)?] {
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(CaptureError::IoError(e)),
+            }
+        }
+        Ok(())
+    }
+
+    fn write_png(&self, path: &Path, image: &ImageBuffer) -> CaptureResult<()> {
+        fs::create_dir_all(&self.dir).map_err(CaptureError::IoError)?;
+        let opts = CaptureOptions {
+            format: ImageFormat::Png,
+            max_dimension: None,
+            ..Default::default()
+        };
+        let data = encode_image(image, &opts)?;
+        fs::write(path, data).map_err(CaptureError::IoError)
+    }
+
+    fn unknown_baseline(&self, name: &str) -> CaptureError {
+        let known = self.list().unwrap_or_default();
+        let known = if known.is_empty() {
+            "none".to_string()
+        } else {
+            known.join(", ")
+        };
+        CaptureError::InvalidParameter {
+            parameter: "name".to_string(),
+            reason: format!("unknown baseline '{}' (known baselines: {})", name, known),
+        }
+    }
+}
+
+/// Copies the ignored regions of `baseline` into a copy of `image`
+///
+/// Both images are returned as RGBA so the ignored areas compare equal.
+fn mask_regions(
+    baseline: &ImageBuffer,
+    image: &ImageBuffer,
+    ignore: &[Region],
+) -> (ImageBuffer, ImageBuffer) {
+    let baseline = baseline.to_rgba8();
+    let mut masked = image.to_rgba8();
+    let (width, height) = masked.dimensions();
+
+    for region in ignore {
+        let x_end = region.x.saturating_add(region.width).min(width);
+        let y_end = region.y.saturating_add(region.height).min(height);
+        for y in region.y.min(y_end)..y_end {
+            for x in region.x.min(x_end)..x_end {
+                masked.put_pixel(x, y, *baseline.get_pixel(x, y));
+            }
+        }
+    }
+
+    (
+        ImageBuffer::new(image::DynamicImage::ImageRgba8(baseline)),
+        ImageBuffer::new(image::DynamicImage::ImageRgba8(masked)),
+    )
+}
+
+/// Renders baseline reports as a JUnit XML test suite
+///
+/// Each report becomes a test case; failed, missing and mismatched
+/// baselines become failures carrying the report message.
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::capture::baseline::junit_report;
+///
+/// let xml = junit_report("visual", &[]);
+/// assert!(xml.contains(r#"<testsuite name="visual" tests="0" failures="0">"#));
+/// ```
+pub fn junit_report(suite: &str, reports: &[BaselineReport]) -> String {
+    let failures = reports.iter().filter(|r| !r.passed()).count();
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\">\n",
+        xml_escape(suite),
+        reports.len(),
+        failures
+    ));
+    for report in reports {
+        let name = xml_escape(&report.name);
+        if report.passed() {
+            xml.push_str(&format!("  <testcase classname=\"baseline\" name=\"{}\"/>\n", name));
+        } else {
+            let status = serde_json::to_value(report.status)
+                .ok()
+                .and_then(|v| v.as_str().map(str::to_string))
+                .unwrap_or_default();
+            xml.push_str(&format!(
+                "  <testcase classname=\"baseline\" name=\"{}\">\n    <failure type=\"{}\" \
+                 message=\"{}\"/>\n  </testcase>\n",
+                name,
+                status,
+                xml_escape(&report.message)
+            ));
+        }
+    }
+    xml.push_str("</testsuite>\n");
+    xml
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    fn screen(patch: Option<Region>) -> ImageBuffer {
+        let mut image = RgbaImage::from_pixel(80, 60, Rgba([250, 250, 250, 255]));
+        if let Some(r) = patch {
+            for y in r.y..r.y + r.height {
+                for x in r.x..r.x + r.width {
+                    image.put_pixel(x, y, Rgba([200, 0, 0, 255]));
+                }
+            }
+        }
+        ImageBuffer::new(image::DynamicImage::ImageRgba8(image))
+    }
+
+    #[test]
+    fn test_save_list_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(dir.path().join("nested"));
+        assert!(store.list().unwrap().is_empty());
+
+        let path = store.save("settings.dark", &screen(None)).unwrap();
+        assert!(path.ends_with("settings.dark.png"));
+        store.save("login", &screen(None)).unwrap();
+
+        assert_eq!(store.list().unwrap(), vec!["login", "settings.dark"]);
+        assert_eq!(store.load("login").unwrap().dimensions(), (80, 60));
+    }
+
+    #[test]
+    fn test_invalid_and_unknown_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(dir.path());
+
+        for name in ["", "../escape", "a/b", ".hidden", "x.diff"] {
+            assert!(
+                matches!(
+                    store.save(name, &screen(None)),
+                    Err(CaptureError::InvalidParameter { .. })
+                ),
+                "{:?} should be rejected",
+                name
+            );
+        }
+
+        store.save("known", &screen(None)).unwrap();
+        match store.load("other") {
+            Err(CaptureError::InvalidParameter { parameter, reason }) => {
+                assert_eq!(parameter, "name");
+                assert!(reason.contains("known baselines: known"), "{}", reason);
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_check_pass_fail_and_approve() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(dir.path());
+        let options = BaselineCheckOptions::default();
+        store.save("dialog", &screen(None)).unwrap();
+
+        let report = store.check("dialog", &screen(None), &options).unwrap();
+        assert!(report.passed());
+        assert!(report.actual_path.is_none());
+
+        let changed = screen(Some(Region::new(10, 10, 20, 10)));
+        let report = store.check("dialog", &changed, &options).unwrap();
+        assert_eq!(report.status, BaselineStatus::Failed);
+        assert_eq!(report.comparison.as_ref().unwrap().regions, vec![Region::new(10, 10, 20, 10)]);
+        assert!(report.actual_path.as_ref().unwrap().exists());
+        assert!(report.diff_path.as_ref().unwrap().exists());
+
+        store.approve("dialog").unwrap();
+        assert!(!report.actual_path.unwrap().exists());
+        assert!(!report.diff_path.unwrap().exists());
+        assert!(store.check("dialog", &changed, &options).unwrap().passed());
+
+        // Nothing is pending after a pass
+        assert!(matches!(store.approve("dialog"), Err(CaptureError::InvalidParameter { .. })));
+    }
+
+    #[test]
+    fn test_check_tolerance_and_ignore_regions() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(dir.path());
+        store.save("clock", &screen(None)).unwrap();
+        let changed = screen(Some(Region::new(70, 0, 10, 10)));
+
+        let ignoring = BaselineCheckOptions {
+            ignore_regions: vec![Region::new(65, 0, 100, 12)],
+            ..Default::default()
+        };
+        assert!(store.check("clock", &changed, &ignoring).unwrap().passed());
+
+        let lenient = BaselineCheckOptions {
+            max_changed_ratio: 0.05,
+            ..Default::default()
+        };
+        assert!(store.check("clock", &changed, &lenient).unwrap().passed());
+
+        let strict = BaselineCheckOptions::default();
+        assert!(!store.check("clock", &changed, &strict).unwrap().passed());
+    }
+
+    #[test]
+    fn test_check_missing_and_size_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(dir.path());
+        let options = BaselineCheckOptions::default();
+
+        let report = store.check("new", &screen(None), &options).unwrap();
+        assert_eq!(report.status, BaselineStatus::Missing);
+        store.approve("new").unwrap();
+        assert!(
+            store
+                .check("new", &screen(None), &options)
+                .unwrap()
+                .passed()
+        );
+
+        let report = store
+            .check("new", &ImageBuffer::from_test_pattern(40, 30), &options)
+            .unwrap();
+        assert_eq!(report.status, BaselineStatus::SizeMismatch);
+        assert!(report.message.contains("40x30"));
+        assert!(report.diff_path.is_none());
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BaselineStore::new(dir.path());
+        let options = BaselineCheckOptions::default();
+        store.save("ok", &screen(None)).unwrap();
+
+        let reports = vec![
+            store.check("ok", &screen(None), &options).unwrap(),
+            store.check("missing", &screen(None), &options).unwrap(),
+        ];
+        let xml = junit_report("ui <smoke>", &reports);
+
+        assert!(xml.contains(r#"<testsuite name="ui &lt;smoke&gt;" tests="2" failures="1">"#));
+        assert!(xml.contains(r#"<testcase classname="baseline" name="ok"/>"#));
+        assert!(xml.contains(r#"<failure type="missing""#));
+    }
+}
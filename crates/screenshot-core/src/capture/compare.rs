@@ -0,0 +1,626 @@
+//! Pixel comparison of two captures
+//!
+//! [`compare_images`] answers "did anything change between these two
+//! screenshots, and where?". It reports pixel-difference statistics, a
+//! structural similarity (SSIM) score and the bounding boxes of changed
+//! areas, and renders a diff image that highlights them.
+//!
+//! # Noise Handling
+//!
+//! Two tolerances keep encoding and rendering noise out of the result (see
+//! [`CompareOptions`]):
+//! - `pixel_tolerance` ignores small per-channel colour shifts such as lossy
+//!   compression artifacts
+//! - `ignore_antialiasing` ignores pixels that sit on a one-pixel gradient
+//!   between flat darker and brighter areas, which is what an anti-aliased
+//!   edge re-rendered a little differently looks like
+//!
+//! # Diff Image
+//!
+//! The diff image is the "after" image washed out to light grey, with
+//! changed pixels in red, anti-aliasing noise in yellow and each changed
+//! region outlined in magenta.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{ImageBuffer, compare::compare_images},
+//!     model::CompareOptions,
+//! };
+//!
+//! let before = ImageBuffer::from_test_pattern(64, 64);
+//! let after = before.clone();
+//! let (comparison, diff) = compare_images(&before, &after, &CompareOptions::default()).unwrap();
+//! assert!(comparison.identical);
+//! assert_eq!(diff.dimensions(), (64, 64));
+//! ```
+
+use image::{Rgba, RgbaImage};
+
+use super::ImageBuffer;
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{CompareOptions, ImageComparison, Region},
+};
+
+/// Side length of the windows averaged into the SSIM score
+const SSIM_WINDOW: u32 = 8;
+
+/// Colour of changed pixels in the diff image
+const CHANGED_COLOR: Rgba<u8> = Rgba([255, 0, 0, 255]);
+
+/// Colour of anti-aliasing noise in the diff image
+const ANTIALIASED_COLOR: Rgba<u8> = Rgba([255, 200, 0, 255]);
+
+/// Colour of region outlines in the diff image
+const REGION_COLOR: Rgba<u8> = Rgba([255, 0, 255, 255]);
+
+/// How a pixel differs between the two images
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PixelChange {
+    Unchanged,
+    Antialiased,
+    Changed,
+}
+
+/// Compares two images of the same size
+///
+/// # Arguments
+///
+/// * `before` - The earlier image
+/// * `after` - The later image
+/// * `options` - Noise tolerances and region merging
+///
+/// # Returns
+///
+/// The comparison statistics and a highlighted diff image with the same
+/// dimensions as the inputs.
+///
+/// # Errors
+///
+/// `CaptureError::InvalidParameter` for `after` if the dimensions differ.
+pub fn compare_images(
+    before: &ImageBuffer,
+    after: &ImageBuffer,
+    options: &CompareOptions,
+) -> CaptureResult<(ImageComparison, ImageBuffer)> {
+    let (comparison, changes, after) = analyze(before, after, options)?;
+    let diff_image = render_diff(&after, &changes, &comparison.regions);
+    Ok((comparison, ImageBuffer::new(image::DynamicImage::ImageRgba8(diff_image))))
+}
+
+/// Compares two images of the same size without rendering a diff image
+///
+/// Returns the same statistics as [`compare_images`] for callers that only
+/// need the numbers or the changed regions.
+///
+/// # Errors
+///
+/// `CaptureError::InvalidParameter` for `after` if the dimensions differ.
+pub fn measure_changes(
+    before: &ImageBuffer,
+    after: &ImageBuffer,
+    options: &CompareOptions,
+) -> CaptureResult<ImageComparison> {
+    analyze(before, after, options).map(|(comparison, _, _)| comparison)
+}
+
+/// Classifies every pixel and gathers the comparison statistics
+fn analyze(
+    before: &ImageBuffer,
+    after: &ImageBuffer,
+    options: &CompareOptions,
+) -> CaptureResult<(ImageComparison, Vec<PixelChange>, RgbaImage)> {
+    let (width, height) = before.dimensions();
+    if after.dimensions() != (width, height) {
+        let (after_width, after_height) = after.dimensions();
+        return Err(CaptureError::InvalidParameter {
+            parameter: "after".to_string(),
+            reason: format!(
+                "dimensions {}x{} differ from the before image ({}x{})",
+                after_width, after_height, width, height
+            ),
+        });
+    }
+
+    let mut options = *options;
+    options.validate();
+
+    let a = before.to_rgba8();
+    let b = after.to_rgba8();
+
+    let mut changes = vec![PixelChange::Unchanged; (width as usize) * (height as usize)];
+    let mut changed_pixels = 0u64;
+    let mut antialiased_pixels = 0u64;
+    let mut max_channel_diff = 0u8;
+    let mut diff_sum = 0u64;
+
+    for y in 0..height {
+        for x in 0..width {
+            let pa = a.get_pixel(x, y);
+            let pb = b.get_pixel(x, y);
+            let diff = channel_diff(pa, pb);
+            max_channel_diff = max_channel_diff.max(diff);
+            diff_sum += (0..3)
+                .map(|c| u64::from(pa[c].abs_diff(pb[c])))
+                .sum::<u64>();
+
+            if diff <= options.pixel_tolerance {
+                continue;
+            }
+
+            let change = if options.ignore_antialiasing
+                && (is_antialiased(&a, &b, x, y, options.pixel_tolerance)
+                    || is_antialiased(&b, &a, x, y, options.pixel_tolerance))
+            {
+                antialiased_pixels += 1;
+                PixelChange::Antialiased
+            } else {
+                changed_pixels += 1;
+                PixelChange::Changed
+            };
+            changes[(y * width + x) as usize] = change;
+        }
+    }
+
+    let total_pixels = u64::from(width) * u64::from(height);
+    let regions = changed_regions(&changes, width, height, &options);
+
+    let comparison = ImageComparison {
+        dimensions: (width, height),
+        identical: changed_pixels == 0,
+        changed_pixels,
+        antialiased_pixels,
+        changed_ratio: ratio(changed_pixels, total_pixels),
+        mean_abs_diff: ratio(diff_sum, total_pixels * 3 * 255),
+        max_channel_diff,
+        ssim: ssim(before, after),
+        regions,
+    };
+
+    Ok((comparison, changes, b))
+}
+
+fn ratio(part: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f64 / total as f64
+    }
+}
+
+/// Largest difference of the colour channels (alpha is ignored)
+fn channel_diff(a: &Rgba<u8>, b: &Rgba<u8>) -> u8 {
+    (0..3).map(|c| a[c].abs_diff(b[c])).max().unwrap_or(0)
+}
+
+/// Perceived brightness of a pixel (0-255)
+fn luma(pixel: &Rgba<u8>) -> i32 {
+    (i32::from(pixel[0]) * 299 + i32::from(pixel[1]) * 587 + i32::from(pixel[2]) * 114) / 1000
+}
+
+/// In-bounds neighbours of `(x, y)`, and whether the pixel is on the border
+fn neighbours(image: &RgbaImage, x: u32, y: u32) -> (impl Iterator<Item = (u32, u32)>, bool) {
+    let (width, height) = image.dimensions();
+    let (x0, x1) = (x.saturating_sub(1), (x + 1).min(width - 1));
+    let (y0, y1) = (y.saturating_sub(1), (y + 1).min(height - 1));
+    let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+    let iter = (y0..=y1)
+        .flat_map(move |ny| (x0..=x1).map(move |nx| (nx, ny)))
+        .filter(move |&p| p != (x, y));
+    (iter, on_border)
+}
+
+/// Returns true if the pixel at `(x, y)` of `image` is anti-aliasing
+///
+/// An anti-aliased pixel has both a darker and a brighter neighbour, few
+/// neighbours of its own colour, and its darkest or brightest neighbour lies
+/// in a flat area in both images (the shape whose edge was smoothed).
+fn is_antialiased(image: &RgbaImage, other: &RgbaImage, x: u32, y: u32, tolerance: u8) -> bool {
+    let center = luma(image.get_pixel(x, y));
+    let tolerance = i32::from(tolerance);
+    let (neighbours, on_border) = neighbours(image, x, y);
+
+    let mut similar = u32::from(on_border);
+    let mut darkest: Option<((u32, u32), i32)> = None;
+    let mut brightest: Option<((u32, u32), i32)> = None;
+    for (nx, ny) in neighbours {
+        let delta = luma(image.get_pixel(nx, ny)) - center;
+        if delta.abs() <= tolerance {
+            similar += 1;
+        } else if delta < 0 {
+            if darkest.is_none_or(|(_, d)| delta < d) {
+                darkest = Some(((nx, ny), delta));
+            }
+        } else if brightest.is_none_or(|(_, d)| delta > d) {
+            brightest = Some(((nx, ny), delta));
+        }
+    }
+
+    if similar > 2 {
+        return false;
+    }
+    let (Some((dark, _)), Some((bright, _))) = (darkest, brightest) else {
+        return false;
+    };
+
+    let flat = |(px, py): (u32, u32)| {
+        has_many_siblings(image, px, py, tolerance) && has_many_siblings(other, px, py, tolerance)
+    };
+    flat(dark) || flat(bright)
+}
+
+/// Returns true if at least three neighbours share the pixel's colour
+fn has_many_siblings(image: &RgbaImage, x: u32, y: u32, tolerance: i32) -> bool {
+    let center = image.get_pixel(x, y);
+    let (neighbours, on_border) = neighbours(image, x, y);
+    let siblings = neighbours
+        .filter(|&(nx, ny)| i32::from(channel_diff(image.get_pixel(nx, ny), center)) <= tolerance)
+        .count();
+    siblings + usize::from(on_border) >= 3
+}
+
+/// Groups changed pixels into bounding boxes
+///
+/// The image is divided into cells of `merge_distance` pixels; cells with
+/// changed pixels that touch (including diagonally) form one region, whose
+/// box is tightened to the changed pixels it contains.
+fn changed_regions(
+    changes: &[PixelChange],
+    width: u32,
+    height: u32,
+    options: &CompareOptions,
+) -> Vec<Region> {
+    #[derive(Clone, Copy)]
+    struct Cell {
+        count: u32,
+        min_x: u32,
+        min_y: u32,
+        max_x: u32,
+        max_y: u32,
+    }
+
+    let cell_size = options.merge_distance;
+    let cols = width.div_ceil(cell_size) as usize;
+    let rows = height.div_ceil(cell_size) as usize;
+    let mut cells: Vec<Option<Cell>> = vec![None; cols * rows];
+
+    for y in 0..height {
+        for x in 0..width {
+            if changes[(y * width + x) as usize] != PixelChange::Changed {
+                continue;
+            }
+            let index = (y / cell_size) as usize * cols + (x / cell_size) as usize;
+            let cell = cells[index].get_or_insert(Cell {
+                count: 0,
+                min_x: x,
+                min_y: y,
+                max_x: x,
+                max_y: y,
+            });
+            cell.count += 1;
+            cell.min_x = cell.min_x.min(x);
+            cell.min_y = cell.min_y.min(y);
+            cell.max_x = cell.max_x.max(x);
+            cell.max_y = cell.max_y.max(y);
+        }
+    }
+
+    let mut regions = Vec::new();
+    let mut visited = vec![false; cells.len()];
+    for start in 0..cells.len() {
+        let Some(first) = cells[start] else {
+            continue;
+        };
+        if visited[start] {
+            continue;
+        }
+
+        // Flood-fill the connected cells
+        visited[start] = true;
+        let mut merged = first;
+        merged.count = 0;
+        let mut stack = vec![start];
+        while let Some(index) = stack.pop() {
+            let cell = cells[index].expect("only occupied cells are queued");
+            merged.count += cell.count;
+            merged.min_x = merged.min_x.min(cell.min_x);
+            merged.min_y = merged.min_y.min(cell.min_y);
+            merged.max_x = merged.max_x.max(cell.max_x);
+            merged.max_y = merged.max_y.max(cell.max_y);
+
+            let (col, row) = (index % cols, index / cols);
+            for next_row in row.saturating_sub(1)..=(row + 1).min(rows - 1) {
+                for next_col in col.saturating_sub(1)..=(col + 1).min(cols - 1) {
+                    let next = next_row * cols + next_col;
+                    if cells[next].is_some() && !visited[next] {
+                        visited[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+
+        if merged.count >= options.min_region_pixels {
+            regions.push(Region::new(
+                merged.min_x,
+                merged.min_y,
+                merged.max_x - merged.min_x + 1,
+                merged.max_y - merged.min_y + 1,
+            ));
+        }
+    }
+
+    regions.sort_by_key(|r| (r.y, r.x));
+    regions
+}
+
+/// Mean SSIM over non-overlapping windows of the grayscale images
+fn ssim(before: &ImageBuffer, after: &ImageBuffer) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let a = before.inner().to_luma8();
+    let b = after.inner().to_luma8();
+    let (width, height) = a.dimensions();
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    // Images smaller than a window are compared as a single window
+    let window_w = SSIM_WINDOW.min(width);
+    let window_h = SSIM_WINDOW.min(height);
+
+    let mut total = 0.0;
+    let mut windows = 0u32;
+    for wy in window_starts(height, window_h) {
+        for wx in window_starts(width, window_w) {
+            let n = f64::from(window_w * window_h);
+            let (mut sum_a, mut sum_b) = (0.0, 0.0);
+            for y in wy..wy + window_h {
+                for x in wx..wx + window_w {
+                    sum_a += f64::from(a.get_pixel(x, y)[0]);
+                    sum_b += f64::from(b.get_pixel(x, y)[0]);
+                }
+            }
+            let (mean_a, mean_b) = (sum_a / n, sum_b / n);
+
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for y in wy..wy + window_h {
+                for x in wx..wx + window_w {
+                    let da = f64::from(a.get_pixel(x, y)[0]) - mean_a;
+                    let db = f64::from(b.get_pixel(x, y)[0]) - mean_b;
+                    var_a += da * da;
+                    var_b += db * db;
+                    covariance += da * db;
+                }
+            }
+            let (var_a, var_b, covariance) = (var_a / n, var_b / n, covariance / n);
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a * mean_a + mean_b * mean_b + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    total / f64::from(windows.max(1))
+}
+
+/// Start offsets of SSIM windows tiling `len` pixels
+///
+/// When `window` doesn't divide `len`, a last window is aligned with the far
+/// edge so the remainder pixels are compared too.
+fn window_starts(len: u32, window: u32) -> impl Iterator<Item = u32> {
+    let last = len - window;
+    let edge = (last % window != 0).then_some(last);
+    (0..=last).step_by(window as usize).chain(edge)
+}
+
+/// Draws the highlighted diff image on top of a washed-out `after` image
+fn render_diff(after: &RgbaImage, changes: &[PixelChange], regions: &[Region]) -> RgbaImage {
+    let (width, height) = after.dimensions();
+    let mut diff =
+        RgbaImage::from_fn(width, height, |x, y| match changes[(y * width + x) as usize] {
+            PixelChange::Changed => CHANGED_COLOR,
+            PixelChange::Antialiased => ANTIALIASED_COLOR,
+            PixelChange::Unchanged => {
+                let faded = (170 + luma(after.get_pixel(x, y)) / 3) as u8;
+                Rgba([faded, faded, faded, 255])
+            }
+        });
+
+    for region in regions {
+        let right = region.x + region.width - 1;
+        let bottom = region.y + region.height - 1;
+        for x in region.x..=right {
+            diff.put_pixel(x, region.y, REGION_COLOR);
+            diff.put_pixel(x, bottom, REGION_COLOR);
+        }
+        for y in region.y..=bottom {
+            diff.put_pixel(region.x, y, REGION_COLOR);
+            diff.put_pixel(right, y, REGION_COLOR);
+        }
+    }
+
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [u8; 3]) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, Rgba([color[0], color[1], color[2], 255]))
+    }
+
+    fn buffer(image: RgbaImage) -> ImageBuffer {
+        ImageBuffer::new(image::DynamicImage::ImageRgba8(image))
+    }
+
+    fn fill(image: &mut RgbaImage, region: Region, color: [u8; 3]) {
+        for y in region.y..region.y + region.height {
+            for x in region.x..region.x + region.width {
+                image.put_pixel(x, y, Rgba([color[0], color[1], color[2], 255]));
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_images() {
+        let image = ImageBuffer::from_test_pattern(40, 30);
+        let (comparison, diff) =
+            compare_images(&image, &image, &CompareOptions::default()).unwrap();
+
+        assert!(comparison.identical);
+        assert_eq!(comparison.changed_pixels, 0);
+        assert_eq!(comparison.mean_abs_diff, 0.0);
+        assert!((comparison.ssim - 1.0).abs() < 1e-9);
+        assert!(comparison.regions.is_empty());
+        assert_eq!(diff.dimensions(), (40, 30));
+    }
+
+    #[test]
+    fn test_changed_blocks_become_regions() {
+        let before = solid(100, 80, [255, 255, 255]);
+        let mut after = before.clone();
+        fill(&mut after, Region::new(10, 10, 20, 10), [0, 0, 0]);
+        fill(&mut after, Region::new(70, 50, 15, 15), [0, 0, 200]);
+
+        let (comparison, diff) =
+            compare_images(&buffer(before), &buffer(after), &CompareOptions::default()).unwrap();
+
+        assert!(!comparison.identical);
+        assert_eq!(comparison.max_channel_diff, 255);
+        assert_eq!(
+            comparison.regions,
+            vec![Region::new(10, 10, 20, 10), Region::new(70, 50, 15, 15)]
+        );
+        assert!(comparison.ssim < 0.95);
+
+        // Outlines are drawn on the region borders, changes filled in red
+        let diff = diff.to_rgba8();
+        assert_eq!(*diff.get_pixel(10, 10), REGION_COLOR);
+        assert_eq!(*diff.get_pixel(15, 15), CHANGED_COLOR);
+        assert_ne!(*diff.get_pixel(50, 40), CHANGED_COLOR);
+    }
+
+    #[test]
+    fn test_pixel_tolerance_ignores_small_shifts() {
+        let before = solid(20, 20, [100, 100, 100]);
+        let after = solid(20, 20, [110, 95, 100]);
+
+        let (comparison, _) = compare_images(
+            &buffer(before.clone()),
+            &buffer(after.clone()),
+            &CompareOptions::default(),
+        )
+        .unwrap();
+        assert!(comparison.identical);
+        assert_eq!(comparison.max_channel_diff, 10);
+        assert!(comparison.mean_abs_diff > 0.0);
+
+        let strict = CompareOptions {
+            pixel_tolerance: 0,
+            ignore_antialiasing: false,
+            ..Default::default()
+        };
+        let (comparison, _) = compare_images(&buffer(before), &buffer(after), &strict).unwrap();
+        assert_eq!(comparison.changed_pixels, 400);
+        assert_eq!(comparison.changed_ratio, 1.0);
+    }
+
+    #[test]
+    fn test_antialiasing_shift_is_noise() {
+        // A smoothed vertical edge that moves one pixel to the right
+        let mut before = solid(30, 30, [255, 255, 255]);
+        fill(&mut before, Region::new(0, 0, 15, 30), [0, 0, 0]);
+        fill(&mut before, Region::new(15, 0, 1, 30), [128, 128, 128]);
+        let mut after = solid(30, 30, [255, 255, 255]);
+        fill(&mut after, Region::new(0, 0, 16, 30), [0, 0, 0]);
+        fill(&mut after, Region::new(16, 0, 1, 30), [128, 128, 128]);
+
+        let (comparison, _) = compare_images(
+            &buffer(before.clone()),
+            &buffer(after.clone()),
+            &CompareOptions::default(),
+        )
+        .unwrap();
+        assert!(comparison.identical);
+        assert_eq!(comparison.antialiased_pixels, 60);
+
+        let options = CompareOptions {
+            ignore_antialiasing: false,
+            ..Default::default()
+        };
+        let (comparison, _) = compare_images(&buffer(before), &buffer(after), &options).unwrap();
+        assert_eq!(comparison.changed_pixels, 60);
+        assert_eq!(comparison.regions, vec![Region::new(15, 0, 2, 30)]);
+    }
+
+    #[test]
+    fn test_merge_distance_and_min_region_pixels() {
+        let before = solid(60, 20, [255, 255, 255]);
+        let mut after = before.clone();
+        fill(&mut after, Region::new(5, 5, 3, 3), [0, 0, 0]);
+        fill(&mut after, Region::new(12, 5, 3, 3), [0, 0, 0]);
+        after.put_pixel(50, 10, Rgba([0, 0, 0, 255]));
+
+        let (comparison, _) = compare_images(
+            &buffer(before.clone()),
+            &buffer(after.clone()),
+            &CompareOptions::default(),
+        )
+        .unwrap();
+        // The two nearby squares merge; the lone pixel is dropped as noise
+        assert_eq!(comparison.regions, vec![Region::new(5, 5, 10, 3)]);
+        assert_eq!(comparison.changed_pixels, 19);
+
+        let options = CompareOptions {
+            merge_distance: 1,
+            min_region_pixels: 1,
+            ..Default::default()
+        };
+        let (comparison, _) = compare_images(&buffer(before), &buffer(after), &options).unwrap();
+        assert_eq!(comparison.regions.len(), 3);
+    }
+
+    #[test]
+    fn test_measure_changes_matches_compare_images() {
+        let before = solid(40, 40, [255, 255, 255]);
+        let mut after = before.clone();
+        fill(&mut after, Region::new(5, 20, 10, 10), [0, 120, 0]);
+        let (before, after) = (buffer(before), buffer(after));
+
+        let options = CompareOptions::default();
+        let (compared, _) = compare_images(&before, &after, &options).unwrap();
+        let measured = measure_changes(&before, &after, &options).unwrap();
+        assert_eq!(measured, compared);
+    }
+
+    #[test]
+    fn test_ssim_covers_right_and_bottom_edges() {
+        // 20 isn't a multiple of the window size; the change sits past the
+        // last full window in both directions
+        let before = solid(20, 20, [255, 255, 255]);
+        let mut after = before.clone();
+        fill(&mut after, Region::new(17, 17, 3, 3), [0, 0, 0]);
+
+        assert!(ssim(&buffer(before), &buffer(after)) < 1.0);
+        assert_eq!(window_starts(20, 8).collect::<Vec<_>>(), vec![0, 8, 12]);
+        assert_eq!(window_starts(16, 8).collect::<Vec<_>>(), vec![0, 8]);
+    }
+
+    #[test]
+    fn test_dimension_mismatch() {
+        let before = ImageBuffer::from_test_pattern(20, 20);
+        let after = ImageBuffer::from_test_pattern(20, 10);
+        match compare_images(&before, &after, &CompareOptions::default()) {
+            Err(CaptureError::InvalidParameter { parameter, reason }) => {
+                assert_eq!(parameter, "after");
+                assert!(reason.contains("20x10"));
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other.map(|(c, _)| c)),
+        }
+    }
+}
@@ -25,9 +25,20 @@
 use std::sync::Arc;
 
 use super::{
-    BackendCapabilities, ScreenCapture, WaylandRestoreCapable, WindowEnumerator, WindowResolver,
+    BackendCapabilities, ImageBuffer, PointerResolver, ScreenCapture, WaylandRestoreCapable,
+    WindowEnumerator, WindowEventSource, WindowInspector, WindowResolver,
+    overview::{capture_thumbnails, compose_overview},
+    policy::{CapturePolicy, PolicyResolver},
+    record::Recording,
+    redact::{RedactionPolicy, screen_to_image},
+};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{
+        Capabilities, CaptureOptions, OverviewOptions, OverviewReport, RecordOptions,
+        RedactionMode, RedactionReport, Region, SettleReport, WindowHandle, WindowInfo,
+    },
 };
-use crate::model::Capabilities;
 
 /// Composite backend holding optional capability trait objects.
 ///
@@ -39,8 +50,14 @@ use crate::model::Capabilities;
 ///
 /// - `enumerator`: Window enumeration (X11, Windows - not Wayland)
 /// - `resolver`: Window selector resolution (all backends)
+/// - `pointer`: Pointer position and window-under-point resolution (X11)
+/// - `events`: Window lifecycle event subscription (X11)
+/// - `inspector`: Window mapping state and geometry queries (X11)
 /// - `capture`: Screenshot capture (all backends)
 /// - `wayland_restore`: Wayland restore token workflow (Wayland only)
+///
+/// The `policy` and `redaction` settings are applied by the resolver and
+/// the settled capture methods.
 pub struct CompositeBackend {
     /// Window enumeration capability.
     ///
@@ -53,6 +70,22 @@ pub struct CompositeBackend {
     /// Present on all backends. Wayland resolves via restore token lookup.
     pub resolver: Option<Arc<dyn WindowResolver>>,
 
+    /// Pointer-aware resolution capability.
+    ///
+    /// Present on X11. Not present on Wayland (clients cannot query the
+    /// global pointer position) or Windows (not yet implemented).
+    pub pointer: Option<Arc<dyn PointerResolver>>,
+
+    /// Window lifecycle event capability.
+    ///
+    /// Present on X11. Not present on Wayland or Windows.
+    pub events: Option<Arc<dyn WindowEventSource>>,
+
+    /// Window state inspection capability.
+    ///
+    /// Present on X11. Not present on Wayland or Windows.
+    pub inspector: Option<Arc<dyn WindowInspector>>,
+
     /// Screen capture capability.
     ///
     /// Present on all backends. This is the core capture functionality.
@@ -69,6 +102,29 @@ pub struct CompositeBackend {
 
     /// Backend name for diagnostics.
     pub name: &'static str,
+
+    /// Windows hidden from every settled capture.
+    ///
+    /// Empty by default. Denied windows are only found where window
+    /// enumeration is available.
+    pub redaction: RedactionPolicy,
+
+    /// Windows and tools that may be used at all.
+    ///
+    /// Empty by default. Set with [`with_policy`](Self::with_policy), which
+    /// also makes the resolver enforce it.
+    pub policy: Arc<CapturePolicy>,
+}
+
+/// Frame returned by the settled capture methods
+#[derive(Debug, Clone)]
+pub struct SettledCapture {
+    /// Final frame, redacted and with any grid drawn
+    pub image: ImageBuffer,
+    /// Settle outcome, when settling was requested
+    pub settle: Option<SettleReport>,
+    /// What was redacted, when anything was
+    pub redaction: Option<RedactionReport>,
 }
 
 impl CompositeBackend {
@@ -84,11 +140,421 @@ impl CompositeBackend {
         Self {
             enumerator,
             resolver,
+            pointer: None,
+            events: None,
+            inspector: None,
             capture,
             wayland_restore,
             capabilities,
             name,
+            redaction: RedactionPolicy::default(),
+            policy: Arc::new(CapturePolicy::default()),
+        }
+    }
+
+    /// Attaches a pointer resolver capability.
+    pub fn with_pointer(mut self, pointer: Arc<dyn PointerResolver>) -> Self {
+        self.pointer = Some(pointer);
+        self
+    }
+
+    /// Attaches a window event source capability.
+    pub fn with_events(mut self, events: Arc<dyn WindowEventSource>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Attaches a window inspector capability.
+    pub fn with_inspector(mut self, inspector: Arc<dyn WindowInspector>) -> Self {
+        self.inspector = Some(inspector);
+        self
+    }
+
+    /// Sets the redaction policy.
+    pub fn with_redaction(mut self, redaction: RedactionPolicy) -> Self {
+        self.redaction = redaction;
+        self
+    }
+
+    /// Sets the capture policy and wraps the resolver to enforce it.
+    pub fn with_policy(mut self, policy: CapturePolicy) -> Self {
+        let policy = Arc::new(policy);
+        if policy.has_window_rules() {
+            self.resolver = self.resolver.take().map(|inner| {
+                Arc::new(PolicyResolver::new(inner, self.enumerator.clone(), policy.clone()))
+                    as Arc<dyn WindowResolver>
+            });
+        }
+        self.policy = policy;
+        self
+    }
+
+    /// Captures a window, honouring `opts.settle`, redaction and `opts.grid`
+    ///
+    /// Without `settle` this is a single
+    /// [`ScreenCapture::capture_window`] call. With `settle` the window is
+    /// captured repeatedly until the content stops changing (see
+    /// [`capture_until_settled`](super::settle::capture_until_settled)).
+    ///
+    /// A window the [`policy`](Self::policy) denies is not captured. The
+    /// final frame is redacted: `opts.redact` regions, and the parts of
+    /// windows denied by [`redaction`](Self::redaction) or the policy that
+    /// lie above the captured window. A capture of a window denied by
+    /// `redaction` is hidden entirely. With `grid` a coordinate grid is drawn last, labelled with
+    /// screen coordinates if the inspector knows where the window is, or
+    /// window coordinates otherwise.
+    pub async fn capture_window_settled(
+        &self,
+        handle: WindowHandle,
+        opts: &CaptureOptions,
+    ) -> CaptureResult<SettledCapture> {
+        self.policy
+            .check_handle(self.enumerator.as_deref(), &handle)
+            .await?;
+
+        let (image, settle) = match opts.settle {
+            None => (self.capture.capture_window(handle.clone(), opts).await?, None),
+            Some(ref settle) => {
+                let (image, report) = super::settle::capture_until_settled(settle, || {
+                    self.capture.capture_window(handle.clone(), opts)
+                })
+                .await?;
+                (image, Some(report))
+            }
+        };
+
+        let placed = self.window_screen_area(&handle, opts).await;
+        let area = placed.unwrap_or_else(|| unplaced_area(&image, opts));
+        let (image, redaction) = self.redact_frame(image, placed, Some(&handle), opts).await;
+        let image = match opts.grid {
+            None => image,
+            Some(ref grid) => image.overlay_grid(grid, area)?,
+        };
+        Ok(SettledCapture {
+            image,
+            settle,
+            redaction,
+        })
+    }
+
+    /// Records a window as a sequence of frames
+    ///
+    /// Each frame is captured like
+    /// [`capture_window_settled`](Self::capture_window_settled) without
+    /// settling: the policy is checked and every frame is redacted. Frames
+    /// are then fitted to `opts.max_dimension`. See
+    /// [`record_frames`](super::record::record_frames) for the schedule and
+    /// how duplicate frames are merged.
+    pub async fn record_window(
+        &self,
+        handle: WindowHandle,
+        opts: &CaptureOptions,
+        record: &RecordOptions,
+    ) -> CaptureResult<Recording> {
+        let frame_opts = CaptureOptions {
+            settle: None,
+            ..opts.clone()
+        };
+        let handle = &handle;
+        let frame_opts = &frame_opts;
+        super::record::record_frames(record, move || async move {
+            let frame = self
+                .capture_window_settled(handle.clone(), frame_opts)
+                .await?;
+            Ok(SettledCapture {
+                image: frame.image.fit_to_max_dimension(frame_opts.max_dimension)?,
+                ..frame
+            })
+        })
+        .await
+    }
+
+    /// Captures several windows into one captioned grid image
+    ///
+    /// The first `overview.max_windows` of `windows` are captured like
+    /// [`capture_window_settled`](Self::capture_window_settled) without
+    /// settling or a grid, so denied windows are skipped and every thumbnail
+    /// is redacted. See [`capture_thumbnails`] for concurrency and the time
+    /// budget, and [`compose_overview`] for the layout.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` if no window could be captured.
+    pub async fn capture_overview(
+        &self,
+        mut windows: Vec<WindowInfo>,
+        opts: &CaptureOptions,
+        overview: &OverviewOptions,
+    ) -> CaptureResult<(ImageBuffer, OverviewReport)> {
+        let omitted = windows.len().saturating_sub(overview.max_windows as usize) as u32;
+        windows.truncate(overview.max_windows as usize);
+
+        let frame_opts = &CaptureOptions {
+            settle: None,
+            grid: None,
+            max_dimension: None,
+            ..opts.clone()
+        };
+        let thumbnails = capture_thumbnails(windows, overview, |window| async move {
+            self.capture_window_settled(window.id, frame_opts)
+                .await
+                .map(|capture| capture.image)
+        })
+        .await;
+
+        if thumbnails.captured.is_empty() {
+            let reason = match thumbnails.skipped.first() {
+                Some(skip) => format!("no window could be captured ({})", skip.reason),
+                None => "no windows to show".to_string(),
+            };
+            return Err(CaptureError::InvalidParameter {
+                parameter: "windows".to_string(),
+                reason,
+            });
+        }
+        let (image, cells) = compose_overview(&thumbnails.captured, overview)?;
+        let report = OverviewReport {
+            cells,
+            skipped: thumbnails.skipped,
+            omitted,
+            timed_out: thumbnails.timed_out,
+        };
+        Ok((image, report))
+    }
+
+    /// Captures a display, honouring `opts.settle`, redaction and `opts.grid`
+    ///
+    /// See [`capture_window_settled`](Self::capture_window_settled). Nothing
+    /// is captured if the [`policy`](Self::policy) denies full-display
+    /// captures; asking the user first is up to the caller. Every
+    /// visible denied window is redacted; windows the policy denies are
+    /// blacked out. Screen coordinates are placed using the backend's
+    /// [`display_origin`](ScreenCapture::display_origin). If it is unknown,
+    /// grid labels and `opts.redact` regions assume the display starts at
+    /// the screen origin, and the whole frame is hidden if any denied window
+    /// is visible.
+    pub async fn capture_display_settled(
+        &self,
+        display_id: Option<u32>,
+        opts: &CaptureOptions,
+    ) -> CaptureResult<SettledCapture> {
+        self.policy.check_display()?;
+
+        let (image, settle) = match opts.settle {
+            None => (self.capture.capture_display(display_id, opts).await?, None),
+            Some(ref settle) => {
+                let (image, report) = super::settle::capture_until_settled(settle, || {
+                    self.capture.capture_display(display_id, opts)
+                })
+                .await?;
+                (image, Some(report))
+            }
+        };
+
+        let placed = match self.capture.display_origin(display_id).await {
+            Some((x, y)) if x >= 0 && y >= 0 => {
+                let area = unplaced_area(&image, opts);
+                Some(Region::new(x as u32 + area.x, y as u32 + area.y, area.width, area.height))
+            }
+            _ => None,
+        };
+        let area = placed.unwrap_or_else(|| unplaced_area(&image, opts));
+        let (image, redaction) = self.redact_frame(image, placed, None, opts).await;
+        let image = match opts.grid {
+            None => image,
+            Some(ref grid) => image.overlay_grid(grid, area)?,
+        };
+        Ok(SettledCapture {
+            image,
+            settle,
+            redaction,
+        })
+    }
+
+    /// Redacts a frame showing the screen area `placed`
+    ///
+    /// `captured` is the window the frame shows, or None for a display. If
+    /// `placed` is unknown, `opts.redact` regions assume the frame starts at
+    /// the screen origin, and any denied window hides the whole frame.
+    async fn redact_frame(
+        &self,
+        image: ImageBuffer,
+        placed: Option<Region>,
+        captured: Option<&WindowHandle>,
+        opts: &CaptureOptions,
+    ) -> (ImageBuffer, Option<RedactionReport>) {
+        let area = placed.unwrap_or_else(|| unplaced_area(&image, opts));
+        let dims = image.dimensions();
+        let mut image = image;
+        let mut report = RedactionReport::default();
+
+        if let Some(ref redact) = opts.redact {
+            let regions: Vec<Region> = redact
+                .regions
+                .iter()
+                .filter_map(|r| screen_to_image(*r, area, dims))
+                .collect();
+            if !regions.is_empty() {
+                image = image.redact(&regions, redact.mode);
+                report.regions = regions.len();
+            }
+        }
+
+        let denied = match self.denied_windows(captured).await {
+            Ok(denied) => denied,
+            Err(e) => {
+                // Denied windows may be anywhere in the frame, so hide it all
+                tracing::warn!("Redacting whole capture: listing windows failed: {}", e);
+                report.full = true;
+                let image = image.redact(&[Region::new(0, 0, dims.0, dims.1)], RedactionMode::Fill);
+                return (image, Some(report));
+            }
+        };
+        // Windows the capture policy denies are always blacked out
+        let mode_for = |blocked: bool| {
+            if blocked {
+                RedactionMode::Fill
+            } else {
+                self.redaction.mode
+            }
+        };
+        let whole = Region::new(0, 0, dims.0, dims.1);
+        let mut hidden = Vec::new();
+        for window in denied {
+            let region = match window.geometry {
+                _ if window.captured => Some(whole),
+                Some(geometry) => match placed {
+                    Some(area) => screen_to_image(geometry, area, dims),
+                    None => Some(whole),
+                },
+                // Cannot tell where it is, so hide everything
+                None => Some(whole),
+            };
+            let Some(region) = region else {
+                continue;
+            };
+            report.full |= region == whole;
+            hidden.push((region, mode_for(window.blocked)));
+            if window.blocked {
+                report.blocked.push(window.handle);
+            } else {
+                report.windows.push(window.handle);
+            }
+        }
+        // Fill last, so black wins where rectangles overlap
+        for mode in [
+            RedactionMode::Pixelate,
+            RedactionMode::Blur,
+            RedactionMode::Fill,
+        ] {
+            let regions: Vec<Region> = hidden
+                .iter()
+                .filter(|(_, m)| *m == mode)
+                .map(|(r, _)| *r)
+                .collect();
+            if !regions.is_empty() {
+                image = image.redact(&regions, mode);
+            }
+        }
+
+        let report = (!report.is_empty()).then_some(report);
+        (image, report)
+    }
+
+    /// Finds the denied windows that can show up in a capture
+    ///
+    /// Windows are denied by the redaction policy or, `blocked`, by the
+    /// capture policy. For a window capture, this is the captured window
+    /// itself if it is denied, or else the denied windows stacked above it
+    /// (all of them if the stacking order is unknown). Invisible windows
+    /// are left out.
+    ///
+    /// # Errors
+    ///
+    /// Returns the enumeration error if there are deny rules but the windows
+    /// cannot be listed; callers must then hide the whole frame.
+    async fn denied_windows(
+        &self,
+        captured: Option<&WindowHandle>,
+    ) -> CaptureResult<Vec<DeniedWindow>> {
+        if self.redaction.is_empty() && !self.policy.has_window_rules() {
+            return Ok(Vec::new());
+        }
+        let Some(ref enumerator) = self.enumerator else {
+            tracing::debug!("Window redaction skipped: {} cannot list windows", self.name);
+            return Ok(Vec::new());
+        };
+        let windows = enumerator.list_windows().await?;
+        let redacted = self.redaction.denied_windows(&windows);
+        let blocked = self.policy.denied_windows(&windows);
+        let mut denied: Vec<(WindowHandle, bool)> = windows
+            .into_iter()
+            .filter_map(|w| {
+                let is_blocked = blocked.contains(&w.id);
+                (is_blocked || redacted.contains(&w.id)).then_some((w.id, is_blocked))
+            })
+            .collect();
+
+        if let Some(handle) = captured {
+            if let Some((_, blocked)) = denied.iter().find(|(h, _)| h == handle) {
+                return Ok(vec![DeniedWindow {
+                    handle: handle.clone(),
+                    geometry: None,
+                    blocked: *blocked,
+                    captured: true,
+                }]);
+            }
+            let stacking = match self.inspector {
+                Some(ref inspector) => inspector.stacking_order().await.ok(),
+                None => None,
+            };
+            let position = stacking
+                .as_ref()
+                .and_then(|stacking| stacking.iter().position(|h| h == handle));
+            if let (Some(stacking), Some(position)) = (stacking, position) {
+                let above = &stacking[position + 1..];
+                denied.retain(|(h, _)| above.contains(h));
+            }
+        }
+
+        let mut result = Vec::with_capacity(denied.len());
+        for (handle, blocked) in denied {
+            let state = match self.inspector {
+                Some(ref inspector) => inspector.window_state(&handle).await.ok(),
+                None => None,
+            };
+            if state.as_ref().is_some_and(|state| !state.is_visible()) {
+                continue;
+            }
+            result.push(DeniedWindow {
+                handle,
+                geometry: state.map(|state| state.geometry),
+                blocked,
+                captured: false,
+            });
         }
+        Ok(result)
+    }
+
+    /// Screen area shown by a window capture with `opts`, if the inspector
+    /// knows where the window is
+    pub async fn window_screen_area(
+        &self,
+        handle: &WindowHandle,
+        opts: &CaptureOptions,
+    ) -> Option<Region> {
+        let inspector = self.inspector.as_ref()?;
+        let geometry = match inspector.window_state(handle).await {
+            Ok(state) => state.geometry,
+            Err(e) => {
+                tracing::debug!("No geometry for window {}: {}", handle, e);
+                return None;
+            }
+        };
+        Some(match opts.region {
+            Some(r) => Region::new(geometry.x + r.x, geometry.y + r.y, r.width, r.height),
+            None => geometry,
+        })
     }
 
     /// Returns true if window enumeration is available.
@@ -101,6 +567,21 @@ impl CompositeBackend {
         self.resolver.is_some()
     }
 
+    /// Returns true if pointer-aware resolution is available.
+    pub fn has_pointer_resolver(&self) -> bool {
+        self.pointer.is_some()
+    }
+
+    /// Returns true if window lifecycle events are available.
+    pub fn has_window_events(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Returns true if window state inspection is available.
+    pub fn has_window_inspector(&self) -> bool {
+        self.inspector.is_some()
+    }
+
     /// Returns true if Wayland restore tokens are supported.
     pub fn has_wayland_restore(&self) -> bool {
         self.wayland_restore.is_some()
@@ -118,8 +599,13 @@ impl std::fmt::Debug for CompositeBackend {
             .field("name", &self.name)
             .field("has_enumerator", &self.enumerator.is_some())
             .field("has_resolver", &self.resolver.is_some())
+            .field("has_pointer", &self.pointer.is_some())
+            .field("has_events", &self.events.is_some())
+            .field("has_inspector", &self.inspector.is_some())
             .field("has_wayland_restore", &self.wayland_restore.is_some())
             .field("capabilities", &self.capabilities)
+            .field("redaction", &self.redaction)
+            .field("policy", &self.policy)
             .finish()
     }
 }
@@ -164,6 +650,9 @@ pub fn composite_from_mock(backend: Arc<super::MockBackend>) -> CompositeBackend
         Capabilities::full(),
         "mock",
     )
+    .with_events(backend.clone() as Arc<dyn WindowEventSource>)
+    .with_inspector(backend.clone() as Arc<dyn WindowInspector>)
+    .with_pointer(backend as Arc<dyn PointerResolver>)
 }
 
 /// Creates a CompositeBackend for Windows.
@@ -202,6 +691,9 @@ pub fn composite_from_x11(backend: Arc<super::X11Backend>) -> CompositeBackend {
         },
         "x11",
     )
+    .with_events(backend.clone() as Arc<dyn WindowEventSource>)
+    .with_inspector(backend.clone() as Arc<dyn WindowInspector>)
+    .with_pointer(backend as Arc<dyn PointerResolver>)
 }
 
 /// Creates a CompositeBackend for Wayland.
@@ -223,10 +715,290 @@ pub fn composite_from_wayland(backend: Arc<super::WaylandBackend>) -> CompositeB
     )
 }
 
+/// A window [`CompositeBackend::denied_windows`] says to hide
+struct DeniedWindow {
+    handle: WindowHandle,
+    /// Screen geometry, if the inspector knows it
+    geometry: Option<Region>,
+    /// Denied by the capture policy rather than the redaction policy
+    blocked: bool,
+    /// The window being captured
+    captured: bool,
+}
+
+/// Area shown by a capture placed at the origin: the crop region, or the
+/// whole unscaled image
+fn unplaced_area(image: &ImageBuffer, opts: &CaptureOptions) -> Region {
+    let (width, height) = image.dimensions();
+    let unscale = |v: u32| ((v as f32 / opts.scale).round() as u32).max(1);
+    opts.region
+        .unwrap_or_else(|| Region::new(0, 0, unscale(width), unscale(height)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_capture_window_settled() {
+        use crate::model::SettleOptions;
+
+        let composite = composite_from_mock(Arc::new(super::super::MockBackend::new()));
+
+        let opts = CaptureOptions::default();
+        let capture = composite
+            .capture_window_settled("mock-0x1".to_string(), &opts)
+            .await
+            .unwrap();
+        assert!(capture.settle.is_none());
+        assert!(capture.redaction.is_none());
+
+        // Mock frames are identical, so the second frame settles
+        let opts = CaptureOptions::builder()
+            .settle(SettleOptions {
+                interval_ms: 10,
+                ..Default::default()
+            })
+            .build();
+        let capture = composite
+            .capture_window_settled("mock-0x1".to_string(), &opts)
+            .await
+            .unwrap();
+        let report = capture.settle.unwrap();
+        assert!(report.settled);
+        assert_eq!(report.frames, 2);
+        assert_eq!(capture.image.dimensions(), (1920, 1080));
+    }
+
+    #[tokio::test]
+    async fn test_capture_with_grid() {
+        use crate::model::GridOptions;
+
+        let composite = composite_from_mock(Arc::new(super::super::MockBackend::new()));
+        let plain = CaptureOptions::builder().scale(0.5).build();
+        let gridded = CaptureOptions::builder()
+            .scale(0.5)
+            .grid(GridOptions {
+                spacing: 100,
+                rulers: false,
+            })
+            .build();
+
+        let before = composite
+            .capture_window_settled("mock-0x2".to_string(), &plain)
+            .await
+            .unwrap()
+            .image;
+        let after = composite
+            .capture_window_settled("mock-0x2".to_string(), &gridded)
+            .await
+            .unwrap()
+            .image;
+        assert_eq!(after.dimensions(), before.dimensions());
+        assert_ne!(after.to_rgba8(), before.to_rgba8());
+
+        let display = composite
+            .capture_display_settled(None, &gridded)
+            .await
+            .unwrap()
+            .image;
+        // Grid lines at screen x = 100 land 50 output pixels in
+        let plain_display = composite
+            .capture_display_settled(None, &plain)
+            .await
+            .unwrap()
+            .image;
+        let (display, plain_display) = (display.to_rgba8(), plain_display.to_rgba8());
+        assert_ne!(display.get_pixel(50, 30), plain_display.get_pixel(50, 30));
+        assert_eq!(display.get_pixel(75, 30), plain_display.get_pixel(75, 30));
+    }
+
+    #[tokio::test]
+    async fn test_capture_with_redaction() {
+        use crate::model::{RedactOptions, RedactionMode};
+
+        const BLACK: [u8; 4] = [0, 0, 0, 255];
+        let composite = composite_from_mock(Arc::new(super::super::MockBackend::new()))
+            .with_redaction(
+                RedactionPolicy::from_toml_str("[redaction]\ndeny = [\"class:Alacritty\"]")
+                    .unwrap(),
+            );
+
+        // Caller regions are screen coordinates: mock-0x2 starts at x = 640
+        let opts = CaptureOptions::builder()
+            .full_resolution()
+            .redact(RedactOptions {
+                regions: vec![Region::new(640, 0, 10, 10)],
+                mode: RedactionMode::Fill,
+            })
+            .build();
+        let capture = composite
+            .capture_window_settled("mock-0x2".to_string(), &opts)
+            .await
+            .unwrap();
+        // mock-0x3 is stacked above mock-0x2 and redacted from it too
+        let report = capture.redaction.unwrap();
+        assert_eq!(report.regions, 1);
+        assert_eq!(report.windows, vec!["mock-0x3"]);
+        let image = capture.image.to_rgba8();
+        assert_eq!(image.get_pixel(5, 5).0, BLACK);
+        assert_eq!(image.get_pixel(1000, 800).0, BLACK);
+        assert_ne!(image.get_pixel(100, 100).0, BLACK);
+
+        // mock-0x3 does not overlap mock-0x1, so nothing is hidden there
+        let capture = composite
+            .capture_window_settled("mock-0x1".to_string(), &CaptureOptions::default())
+            .await
+            .unwrap();
+        assert!(capture.redaction.is_none());
+
+        // A capture of the denied window is hidden entirely
+        let capture = composite
+            .capture_window_settled("mock-0x3".to_string(), &CaptureOptions::default())
+            .await
+            .unwrap();
+        assert!(capture.redaction.unwrap().full);
+        let image = capture.image.to_rgba8();
+        assert!(image.pixels().all(|p| p.0 == BLACK));
+
+        // Display captures hide the denied window where it is on screen
+        let capture = composite
+            .capture_display_settled(None, &CaptureOptions::builder().full_resolution().build())
+            .await
+            .unwrap();
+        let report = capture.redaction.unwrap();
+        assert!(!report.full);
+        assert_eq!(report.windows, vec!["mock-0x3"]);
+        let image = capture.image.to_rgba8();
+        assert_eq!(image.get_pixel(1300, 600).0, BLACK);
+        assert_ne!(image.get_pixel(100, 100).0, BLACK);
+    }
+
+    /// Enumerator whose window list is always unavailable
+    struct FailingEnumerator;
+
+    #[async_trait::async_trait]
+    impl WindowEnumerator for FailingEnumerator {
+        async fn list_windows(&self) -> CaptureResult<Vec<crate::model::WindowInfo>> {
+            Err(crate::error::CaptureError::BackendNotAvailable {
+                backend: crate::model::BackendType::None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redaction_fails_closed() {
+        const BLACK: [u8; 4] = [0, 0, 0, 255];
+        let mut composite = composite_from_mock(Arc::new(super::super::MockBackend::new()))
+            .with_redaction(
+                RedactionPolicy::from_toml_str("[redaction]\ndeny = [\"class:Alacritty\"]")
+                    .unwrap(),
+            );
+        composite.enumerator = Some(Arc::new(FailingEnumerator));
+        let opts = CaptureOptions::builder().full_resolution().build();
+
+        // Without a window list the denied window could be anywhere
+        let capture = composite
+            .capture_window_settled("mock-0x1".to_string(), &opts)
+            .await
+            .unwrap();
+        assert!(capture.redaction.unwrap().full);
+        assert!(capture.image.to_rgba8().pixels().all(|p| p.0 == BLACK));
+
+        let capture = composite
+            .capture_display_settled(None, &opts)
+            .await
+            .unwrap();
+        assert!(capture.redaction.unwrap().full);
+        assert!(capture.image.to_rgba8().pixels().all(|p| p.0 == BLACK));
+    }
+
+    #[tokio::test]
+    async fn test_display_redaction_uses_display_origin() {
+        const BLACK: [u8; 4] = [0, 0, 0, 255];
+        let policy =
+            || CapturePolicy::from_toml_str("[policy]\ndeny = [\"class:Alacritty\"]").unwrap();
+        let opts = CaptureOptions::builder().full_resolution().build();
+
+        // The display starts at x = 1000, so mock-0x3 (x >= 1280) is 280 in
+        let mock = super::super::MockBackend::new().with_display_origin(1000, 0);
+        let composite = composite_from_mock(Arc::new(mock)).with_policy(policy());
+        let capture = composite
+            .capture_display_settled(None, &opts)
+            .await
+            .unwrap();
+        assert!(!capture.redaction.unwrap().full);
+        let image = capture.image.to_rgba8();
+        assert_eq!(image.get_pixel(300, 600).0, BLACK);
+        assert_ne!(image.get_pixel(1300, 600).0, BLACK);
+
+        // Where the display is cannot be told, so all of it is hidden
+        let mock = super::super::MockBackend::new().with_display_origin(-1920, 0);
+        let composite = composite_from_mock(Arc::new(mock)).with_policy(policy());
+        let capture = composite
+            .capture_display_settled(None, &opts)
+            .await
+            .unwrap();
+        assert!(capture.redaction.unwrap().full);
+        assert!(capture.image.to_rgba8().pixels().all(|p| p.0 == BLACK));
+    }
+
+    #[tokio::test]
+    async fn test_capture_with_policy() {
+        use crate::{error::CaptureError, model::WindowSelector};
+
+        let composite = composite_from_mock(Arc::new(super::super::MockBackend::new()))
+            .with_policy(
+                CapturePolicy::from_toml_str("[policy]\ndeny = [\"class:Alacritty\"]").unwrap(),
+            );
+        let opts = CaptureOptions::builder().full_resolution().build();
+
+        // Resolution and capture both refuse the denied window
+        let resolver = composite.resolver.as_ref().unwrap();
+        assert!(matches!(
+            resolver
+                .resolve(&WindowSelector::by_title("Terminal"))
+                .await,
+            Err(CaptureError::PolicyDenied { .. })
+        ));
+        assert!(matches!(
+            composite
+                .capture_window_settled("mock-0x3".to_string(), &opts)
+                .await,
+            Err(CaptureError::PolicyDenied { .. })
+        ));
+
+        // Display captures black it out, even with pixelating redaction
+        let composite = composite.with_redaction(RedactionPolicy {
+            mode: RedactionMode::Pixelate,
+            deny: vec![WindowSelector::by_title("Visual Studio")],
+        });
+        let capture = composite
+            .capture_display_settled(None, &opts)
+            .await
+            .unwrap();
+        let report = capture.redaction.unwrap();
+        assert_eq!(report.blocked, vec!["mock-0x3"]);
+        assert_eq!(report.windows, vec!["mock-0x2"]);
+        assert!(!report.full);
+        let image = capture.image.to_rgba8();
+        assert_eq!(image.get_pixel(1300, 600).0, [0, 0, 0, 255]);
+        assert_ne!(image.get_pixel(700, 100).0, [0, 0, 0, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_display_capture_denied_by_policy() {
+        use crate::error::CaptureError;
+
+        let composite = composite_from_mock(Arc::new(super::super::MockBackend::new()))
+            .with_policy(CapturePolicy::from_toml_str("[policy]\ndisplay = \"deny\"").unwrap());
+        let opts = CaptureOptions::builder().full_resolution().build();
+        assert!(matches!(
+            composite.capture_display_settled(None, &opts).await,
+            Err(CaptureError::PolicyDenied { .. })
+        ));
+    }
+
     #[tokio::test]
     async fn test_composite_from_mock() {
         let mock = Arc::new(super::super::MockBackend::new());
@@ -234,6 +1006,9 @@ mod tests {
 
         assert!(composite.has_window_enumeration());
         assert!(composite.has_window_resolver());
+        assert!(composite.has_pointer_resolver());
+        assert!(composite.has_window_events());
+        assert!(composite.has_window_inspector());
         assert!(!composite.has_wayland_restore());
         assert_eq!(composite.name, "mock");
 
@@ -114,6 +114,51 @@ pub const PIPEWIRE_FRAME_TIMEOUT_SECS: u64 = 5;
 /// consistency with other timeout constants.
 pub const PIPEWIRE_FRAME_TIMEOUT_MS: u64 = PIPEWIRE_FRAME_TIMEOUT_SECS * 1000;
 
+/// Poll interval for the X11 window event watcher.
+///
+/// The watcher thread polls its dedicated connection for events and sleeps
+/// this long when the queue is empty. It also bounds how quickly the thread
+/// notices that its subscriber has gone away.
+///
+/// Used by: X11 window event subscriptions
+pub const X11_EVENT_POLL_INTERVAL_MS: u64 = 20;
+
+/// Default deadline for `wait_for_window`.
+///
+/// Long enough for a typical application to launch and draw its first
+/// window, short enough that a wrong selector fails in reasonable time.
+///
+/// Used by: [`wait_for_window`](super::wait::wait_for_window)
+pub const DEFAULT_WAIT_TIMEOUT_MS: u64 = 10_000;
+
+/// Upper bound accepted for the `wait_for_window` deadline.
+///
+/// Keeps a single tool call from blocking an MCP client for minutes.
+///
+/// Used by: [`wait_for_window`](super::wait::wait_for_window)
+pub const MAX_WAIT_TIMEOUT_MS: u64 = 120_000;
+
+/// Default interval between `wait_for_window` polls.
+///
+/// Each poll enumerates windows, so 100ms keeps the load on the window
+/// system modest while still reacting quickly to a new window.
+///
+/// Used by: [`wait_for_window`](super::wait::wait_for_window)
+pub const DEFAULT_WAIT_POLL_INTERVAL_MS: u64 = 100;
+
+/// Smallest poll interval accepted by `wait_for_window`.
+///
+/// Used by: [`wait_for_window`](super::wait::wait_for_window)
+pub const MIN_WAIT_POLL_INTERVAL_MS: u64 = 10;
+
+/// Default memory cap for frames remembered by delta captures, in bytes.
+///
+/// Frames are stored as RGBA, so 128 MiB holds about sixteen 1920x1080
+/// frames. The least recently captured frames are evicted first.
+///
+/// Used by: [`FrameCache`](super::delta::FrameCache)
+pub const DEFAULT_FRAME_CACHE_BYTES: usize = 128 * 1024 * 1024;
+
 /// PipeWire main loop iteration timeout.
 ///
 /// How long to wait in each PipeWire main loop iteration before checking
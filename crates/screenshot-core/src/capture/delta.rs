@@ -0,0 +1,356 @@
+//! Delta captures: remember the last frame and report what changed
+//!
+//! Sending a full screenshot after every small UI action wastes bandwidth
+//! and, for agents, context. [`FrameCache`] remembers the last frame
+//! captured under a key (typically one per window) and tells the caller
+//! whether the next frame is unchanged, differs only in a few regions, or
+//! should be sent in full.
+//!
+//! # Outcomes
+//!
+//! - [`FrameDelta::Full`] when no earlier frame is remembered, the size
+//!   changed, or changed regions cover more than
+//!   [`DeltaOptions::max_changed_ratio`] of the frame
+//! - [`FrameDelta::Unchanged`] when no region changed
+//! - [`FrameDelta::Regions`] otherwise, with bounding boxes in frame
+//!   coordinates that the caller crops and sends
+//!
+//! After an unchanged result the remembered frame is kept as is, so changes
+//! too small to report on their own accumulate until they are reported.
+//! Every other result replaces the remembered frame.
+//!
+//! # Memory
+//!
+//! Frames are stored decoded (4 bytes per pixel). The cache evicts the least
+//! recently used frames once its byte limit is reached, and does not store
+//! frames larger than the limit at all. Frames are compared outside the
+//! cache's lock, so a slow comparison does not hold up other windows.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{ImageBuffer, delta::FrameCache},
+//!     model::{DeltaOptions, FrameDelta},
+//! };
+//!
+//! let cache = FrameCache::default();
+//! let frame = ImageBuffer::from_test_pattern(320, 200);
+//! let options = DeltaOptions::default();
+//!
+//! assert!(matches!(cache.delta("editor", &frame, &options), FrameDelta::Full { .. }));
+//! assert_eq!(cache.delta("editor", &frame, &options), FrameDelta::Unchanged);
+//! ```
+
+use std::sync::Arc;
+
+use lru::LruCache;
+use parking_lot::Mutex;
+
+use super::{ImageBuffer, compare::measure_changes, constants::DEFAULT_FRAME_CACHE_BYTES};
+use crate::model::{DeltaOptions, FrameDelta, FullFrameReason, Region, WindowHandle};
+
+/// Builds the cache key for captures of `handle`, cropped to `region` if
+/// given
+///
+/// Crops of different regions of one window are remembered separately.
+pub fn frame_key(handle: &WindowHandle, region: Option<&Region>) -> String {
+    match region {
+        None => handle.clone(),
+        Some(r) => format!("{}@{},{},{}x{}", handle, r.x, r.y, r.width, r.height),
+    }
+}
+
+/// Memory-capped store of the last frame per key
+#[derive(Debug)]
+pub struct FrameCache {
+    /// Byte limit for all stored frames together
+    max_bytes: usize,
+    state: Mutex<FrameCacheState>,
+}
+
+#[derive(Debug)]
+struct FrameCacheState {
+    frames: LruCache<String, Arc<ImageBuffer>>,
+    used_bytes: usize,
+}
+
+/// Memory a frame occupies in the cache
+fn frame_bytes(frame: &ImageBuffer) -> usize {
+    let (width, height) = frame.dimensions();
+    width as usize * height as usize * 4
+}
+
+impl FrameCache {
+    /// Creates an empty cache holding at most `max_bytes` of frames
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            state: Mutex::new(FrameCacheState {
+                frames: LruCache::unbounded(),
+                used_bytes: 0,
+            }),
+        }
+    }
+
+    /// Compares `frame` with the frame last remembered under `key`
+    ///
+    /// Remembers `frame` under `key` for the next call unless nothing
+    /// changed (see the module docs).
+    pub fn delta(&self, key: &str, frame: &ImageBuffer, options: &DeltaOptions) -> FrameDelta {
+        let mut options = *options;
+        options.validate();
+
+        // Compare without the lock; the frame is shared, not copied
+        let previous = self.state.lock().frames.get(key).cloned();
+
+        let delta = match previous.as_deref() {
+            None => FrameDelta::Full {
+                reason: FullFrameReason::NoPreviousFrame,
+                changed_ratio: None,
+            },
+            Some(previous) => match measure_changes(previous, frame, &options.compare) {
+                Err(_) => FrameDelta::Full {
+                    reason: FullFrameReason::DimensionsChanged,
+                    changed_ratio: None,
+                },
+                Ok(comparison) if comparison.regions.is_empty() => FrameDelta::Unchanged,
+                Ok(comparison) => {
+                    let (width, height) = comparison.dimensions;
+                    let changed_area: u64 = comparison
+                        .regions
+                        .iter()
+                        .map(|r| u64::from(r.width) * u64::from(r.height))
+                        .sum();
+                    let changed_ratio =
+                        changed_area as f64 / (u64::from(width) * u64::from(height)) as f64;
+
+                    if changed_ratio > options.max_changed_ratio {
+                        FrameDelta::Full {
+                            reason: FullFrameReason::TooManyChanges,
+                            changed_ratio: Some(changed_ratio),
+                        }
+                    } else {
+                        FrameDelta::Regions {
+                            regions: comparison.regions,
+                            changed_ratio,
+                        }
+                    }
+                }
+            },
+        };
+
+        if delta != FrameDelta::Unchanged {
+            self.store(&mut self.state.lock(), key, Arc::new(frame.clone()));
+        }
+        delta
+    }
+
+    /// Replaces the frame stored under `key`, evicting old frames as needed
+    fn store(&self, state: &mut FrameCacheState, key: &str, frame: Arc<ImageBuffer>) {
+        if let Some(old) = state.frames.pop(key) {
+            state.used_bytes -= frame_bytes(&old);
+        }
+
+        let bytes = frame_bytes(&frame);
+        if bytes > self.max_bytes {
+            tracing::debug!(
+                "Frame for '{}' ({} bytes) exceeds the frame cache limit, not stored",
+                key,
+                bytes
+            );
+            return;
+        }
+
+        while state.used_bytes + bytes > self.max_bytes {
+            match state.frames.pop_lru() {
+                Some((_, evicted)) => state.used_bytes -= frame_bytes(&evicted),
+                None => break,
+            }
+        }
+        state.used_bytes += bytes;
+        state.frames.put(key.to_string(), frame);
+    }
+
+    /// Forgets the frame stored under `key`, so the next delta is full
+    pub fn forget(&self, key: &str) {
+        let mut state = self.state.lock();
+        if let Some(old) = state.frames.pop(key) {
+            state.used_bytes -= frame_bytes(&old);
+        }
+    }
+
+    /// Forgets all stored frames
+    pub fn clear(&self) {
+        let mut state = self.state.lock();
+        state.frames.clear();
+        state.used_bytes = 0;
+    }
+
+    /// Number of stored frames
+    pub fn len(&self) -> usize {
+        self.state.lock().frames.len()
+    }
+
+    /// Returns true if no frame is stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Memory used by the stored frames, in bytes
+    pub fn used_bytes(&self) -> usize {
+        self.state.lock().used_bytes
+    }
+
+    /// Byte limit for all stored frames together
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+}
+
+impl Default for FrameCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_FRAME_CACHE_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    fn frame(width: u32, height: u32, patch: Option<Region>) -> ImageBuffer {
+        let mut image = RgbaImage::from_pixel(width, height, Rgba([240, 240, 240, 255]));
+        if let Some(r) = patch {
+            for y in r.y..r.y + r.height {
+                for x in r.x..r.x + r.width {
+                    image.put_pixel(x, y, Rgba([20, 40, 200, 255]));
+                }
+            }
+        }
+        ImageBuffer::new(image::DynamicImage::ImageRgba8(image))
+    }
+
+    #[test]
+    fn test_first_frame_is_full_then_unchanged() {
+        let cache = FrameCache::default();
+        let options = DeltaOptions::default();
+        let base = frame(100, 100, None);
+
+        assert_eq!(
+            cache.delta("w", &base, &options),
+            FrameDelta::Full {
+                reason: FullFrameReason::NoPreviousFrame,
+                changed_ratio: None
+            }
+        );
+        assert_eq!(cache.delta("w", &base, &options), FrameDelta::Unchanged);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.used_bytes(), 100 * 100 * 4);
+    }
+
+    #[test]
+    fn test_small_change_reports_regions() {
+        let cache = FrameCache::default();
+        let options = DeltaOptions::default();
+        cache.delta("w", &frame(100, 100, None), &options);
+
+        let patch = Region::new(30, 40, 10, 5);
+        match cache.delta("w", &frame(100, 100, Some(patch)), &options) {
+            FrameDelta::Regions {
+                regions,
+                changed_ratio,
+            } => {
+                assert_eq!(regions, vec![patch]);
+                assert!((changed_ratio - 0.005).abs() < 1e-9);
+            }
+            other => panic!("expected regions, got {:?}", other),
+        }
+
+        // The changed frame is now the reference
+        assert_eq!(
+            cache.delta("w", &frame(100, 100, Some(patch)), &options),
+            FrameDelta::Unchanged
+        );
+    }
+
+    #[test]
+    fn test_large_change_and_resize_are_full() {
+        let cache = FrameCache::default();
+        let options = DeltaOptions {
+            max_changed_ratio: 0.25,
+            ..Default::default()
+        };
+        cache.delta("w", &frame(100, 100, None), &options);
+
+        let big = Some(Region::new(0, 0, 60, 60));
+        assert!(matches!(
+            cache.delta("w", &frame(100, 100, big), &options),
+            FrameDelta::Full {
+                reason: FullFrameReason::TooManyChanges,
+                changed_ratio: Some(_)
+            }
+        ));
+        assert!(matches!(
+            cache.delta("w", &frame(80, 100, None), &options),
+            FrameDelta::Full {
+                reason: FullFrameReason::DimensionsChanged,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_memory_cap_evicts_least_recent() {
+        // Room for two 10x10 frames
+        let cache = FrameCache::new(2 * 10 * 10 * 4);
+        let options = DeltaOptions::default();
+        let small = frame(10, 10, None);
+
+        cache.delta("a", &small, &options);
+        cache.delta("b", &small, &options);
+        cache.delta("a", &small, &options);
+        cache.delta("c", &small, &options);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.used_bytes(), cache.max_bytes());
+        assert_eq!(cache.delta("a", &small, &options), FrameDelta::Unchanged);
+        assert!(matches!(cache.delta("b", &small, &options), FrameDelta::Full { .. }));
+
+        // Frames above the limit are never stored
+        cache.delta("huge", &frame(20, 20, None), &options);
+        assert!(matches!(
+            cache.delta("huge", &frame(20, 20, None), &options),
+            FrameDelta::Full {
+                reason: FullFrameReason::NoPreviousFrame,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_forget_and_clear() {
+        let cache = FrameCache::default();
+        let options = DeltaOptions::default();
+        let base = frame(10, 10, None);
+        cache.delta("a", &base, &options);
+        cache.delta("b", &base, &options);
+
+        cache.forget("a");
+        assert_eq!(cache.len(), 1);
+        assert!(matches!(cache.delta("a", &base, &options), FrameDelta::Full { .. }));
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert_eq!(cache.used_bytes(), 0);
+    }
+
+    #[test]
+    fn test_frame_key_separates_regions() {
+        let handle = "mock-0x1".to_string();
+        let region = Region::new(1, 2, 30, 40);
+        assert_eq!(frame_key(&handle, None), "mock-0x1");
+        assert_eq!(frame_key(&handle, Some(&region)), "mock-0x1@1,2,30x40");
+    }
+}
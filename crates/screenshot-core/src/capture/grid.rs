@@ -0,0 +1,391 @@
+//! Coordinate grid overlays for precise pointing
+//!
+//! Vision models are poor at estimating pixel positions in a screenshot.
+//! [`ImageBuffer::overlay_grid`] draws grid lines at round screen
+//! coordinates and labels them, so a position can be read off the image
+//! instead of guessed. Labels always show screen coordinates, whatever the
+//! output scale.
+//!
+//! Each grid line pixel is lightened or darkened depending on the content
+//! underneath, so lines stay visible on both light and dark UIs. Rulers
+//! along the top and left edges carry the labels and tick marks; their
+//! colour contrasts with the content along that edge.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::ImageBuffer,
+//!     model::{GridOptions, Region},
+//! };
+//!
+//! // A 1280x720 screen area captured at half scale
+//! let capture = ImageBuffer::from_test_pattern(640, 360);
+//! let gridded = capture
+//!     .overlay_grid(&GridOptions::default(), Region::new(0, 0, 1280, 720))
+//!     .unwrap();
+//! assert_eq!(gridded.dimensions(), (640, 360));
+//! ```
+
+use image::{DynamicImage, RgbaImage};
+
+use super::{
+    ImageBuffer,
+    annotate::{MAX_ANNOTATIONS, label_size},
+};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{Annotation, AnnotationShape, GridOptions, Region},
+};
+
+/// Fewest output pixels between neighbouring grid lines
+const MIN_LINE_GAP: f64 = 12.0;
+
+/// Label font size in pixels
+const LABEL_SIZE: f32 = 11.0;
+
+/// Opacity of labelled and unlabelled grid lines
+const MAJOR_LINE_ALPHA: f32 = 0.7;
+const MINOR_LINE_ALPHA: f32 = 0.4;
+
+/// Opacity of the ruler background
+const RULER_ALPHA: f32 = 0.75;
+
+/// Perceived brightness of a pixel (0-255)
+fn luma(pixel: &image::Rgba<u8>) -> f32 {
+    0.299 * f32::from(pixel[0]) + 0.587 * f32::from(pixel[1]) + 0.114 * f32::from(pixel[2])
+}
+
+/// White on dark content, black on light content
+fn contrast(luma: f32) -> [u8; 3] {
+    if luma < 128.0 { [255; 3] } else { [0; 3] }
+}
+
+/// Blends `color` at `alpha` over one pixel, leaving its alpha as is
+fn blend(pixel: &mut image::Rgba<u8>, color: [u8; 3], alpha: f32) {
+    for (channel, value) in pixel.0.iter_mut().zip(color) {
+        *channel = (f32::from(value) * alpha + f32::from(*channel) * (1.0 - alpha)).round() as u8;
+    }
+}
+
+/// Smallest of 1, 2, 5, 10, 20, 50, ... times `spacing` that is at least
+/// `min_gap` output pixels long at `scale` output pixels per screen pixel
+fn coarsen(spacing: u32, scale: f64, min_gap: f64) -> u64 {
+    let mut magnitude = 1u64;
+    loop {
+        for factor in [1, 2, 5] {
+            let step = u64::from(spacing) * factor * magnitude;
+            if step as f64 * scale >= min_gap || step > u64::from(u32::MAX) {
+                return step;
+            }
+        }
+        magnitude *= 10;
+    }
+}
+
+/// Grid lines along one axis as (screen coordinate, output pixel) pairs
+///
+/// Lines fall on multiples of `step` strictly inside the area and away from
+/// the image edge.
+fn grid_lines(start: u32, length: u32, step: u64, scale: f64, pixels: u32) -> Vec<(u64, u32)> {
+    let start = u64::from(start);
+    let end = start + u64::from(length);
+    let first = start.div_ceil(step) * step;
+    (0..)
+        .map(|k| first + k * step)
+        .take_while(|&screen| screen < end)
+        .filter_map(|screen| {
+            let pixel = ((screen - start) as f64 * scale).round() as u32;
+            (pixel > 0 && pixel < pixels).then_some((screen, pixel))
+        })
+        .collect()
+}
+
+/// Mean brightness of the pixels in a rectangle
+fn mean_luma(image: &RgbaImage, x: u32, y: u32, width: u32, height: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut count = 0u32;
+    for py in y..(y + height).min(image.height()) {
+        for px in x..(x + width).min(image.width()) {
+            sum += luma(image.get_pixel(px, py));
+            count += 1;
+        }
+    }
+    sum / count.max(1) as f32
+}
+
+/// Colour as "#rrggbb" for annotations
+fn hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+impl ImageBuffer {
+    /// Returns a copy of the image with a labelled coordinate grid on it
+    ///
+    /// `area` is the screen rectangle the image shows, in root window
+    /// coordinates; the image may be scaled relative to it. Lines are drawn
+    /// every `grid.spacing` screen pixels, or a round multiple of it if they
+    /// would be closer than a few output pixels.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CaptureError::InvalidParameter`] for `grid` if `area` is
+    /// empty, and [`CaptureError::ImageError`] if the image is empty.
+    pub fn overlay_grid(&self, grid: &GridOptions, area: Region) -> CaptureResult<Self> {
+        if area.width == 0 || area.height == 0 {
+            return Err(CaptureError::InvalidParameter {
+                parameter: "grid".to_string(),
+                reason: "The screen area under the grid must not be empty".to_string(),
+            });
+        }
+        let (width, height) = self.dimensions();
+        if width == 0 || height == 0 {
+            return Err(CaptureError::ImageError(
+                "Cannot draw a grid on an empty image".to_string(),
+            ));
+        }
+
+        let scale_x = f64::from(width) / f64::from(area.width);
+        let scale_y = f64::from(height) / f64::from(area.height);
+        let spacing = grid.spacing.max(1);
+        let step = coarsen(spacing, scale_x.min(scale_y), MIN_LINE_GAP);
+        let columns = grid_lines(area.x, area.width, step, scale_x, width);
+        let rows = grid_lines(area.y, area.height, step, scale_y, height);
+
+        // Label every line, or every 2nd, 5th, ... one if labels would touch
+        let widest = |start: u32, length: u32| {
+            label_size(&(u64::from(start) + u64::from(length)).to_string(), LABEL_SIZE)
+        };
+        let (column_label_width, label_height) = widest(area.x, area.width);
+        let (row_label_width, _) = widest(area.y, area.height);
+        let column_label_step =
+            coarsen(1, step as f64 * scale_x, f64::from(column_label_width) + 4.0);
+        let row_label_step = coarsen(1, step as f64 * scale_y, f64::from(label_height) + 2.0);
+        let labelled = |screen: u64, every: u64| (screen / step) % every == 0;
+
+        let mut image = self.to_rgba8();
+        let top = label_height.ceil() as u32;
+        let left = row_label_width.ceil() as u32;
+        let top_color = contrast(mean_luma(&image, 0, 0, width, top));
+        let left_color = contrast(mean_luma(&image, 0, top, left, height));
+        let (ruler_top, ruler_left) = if grid.rulers {
+            (top.min(height), left.min(width))
+        } else {
+            (0, 0)
+        };
+
+        for &(screen, x) in &columns {
+            let alpha = if labelled(screen, column_label_step) {
+                MAJOR_LINE_ALPHA
+            } else {
+                MINOR_LINE_ALPHA
+            };
+            for y in ruler_top..height {
+                let pixel = image.get_pixel_mut(x, y);
+                blend(pixel, contrast(luma(pixel)), alpha);
+            }
+        }
+        for &(screen, y) in &rows {
+            let alpha = if labelled(screen, row_label_step) {
+                MAJOR_LINE_ALPHA
+            } else {
+                MINOR_LINE_ALPHA
+            };
+            for x in ruler_left..width {
+                let pixel = image.get_pixel_mut(x, y);
+                blend(pixel, contrast(luma(pixel)), alpha);
+            }
+        }
+
+        if grid.rulers {
+            let ink = |color: [u8; 3]| color.map(|c| 255 - c);
+            for y in 0..ruler_top {
+                for x in 0..width {
+                    blend(image.get_pixel_mut(x, y), top_color, RULER_ALPHA);
+                }
+            }
+            for y in ruler_top..height {
+                for x in 0..ruler_left {
+                    blend(image.get_pixel_mut(x, y), left_color, RULER_ALPHA);
+                }
+            }
+            // Full-height ticks at grid lines, short ones at fifths between
+            let minor = step / 5;
+            let show_minor = minor > 0 && minor as f64 * scale_x.min(scale_y) >= 4.0;
+            let ticks = |start: u32, length: u32, scale: f64, pixels: u32| {
+                let mut ticks: Vec<(u32, bool)> = grid_lines(start, length, step, scale, pixels)
+                    .into_iter()
+                    .map(|(_, p)| (p, true))
+                    .collect();
+                if show_minor {
+                    ticks.extend(
+                        grid_lines(start, length, minor, scale, pixels)
+                            .into_iter()
+                            .filter(|(screen, _)| screen % step != 0)
+                            .map(|(_, p)| (p, false)),
+                    );
+                }
+                ticks
+            };
+            for (x, major) in ticks(area.x, area.width, scale_x, width) {
+                let from = if major { 0 } else { ruler_top * 2 / 3 };
+                for y in from..ruler_top {
+                    blend(image.get_pixel_mut(x, y), ink(top_color), 1.0);
+                }
+            }
+            for (y, major) in ticks(area.y, area.height, scale_y, height) {
+                if y < ruler_top {
+                    continue;
+                }
+                let from = if major { 0 } else { ruler_left * 2 / 3 };
+                for x in from..ruler_left {
+                    blend(image.get_pixel_mut(x, y), ink(left_color), 1.0);
+                }
+            }
+        }
+
+        // Labels sit just right of / below their line, on the ruler colour
+        let mut labels = Vec::new();
+        for &(screen, x) in &columns {
+            if labelled(screen, column_label_step)
+                && x as f32 + 2.0 + column_label_width <= width as f32
+            {
+                labels.push(Annotation {
+                    color: hex(top_color),
+                    ..Annotation::new(AnnotationShape::Text {
+                        x: x as f32 + 2.0,
+                        y: 0.0,
+                        text: screen.to_string(),
+                        size: LABEL_SIZE,
+                    })
+                });
+            }
+        }
+        for &(screen, y) in &rows {
+            if labelled(screen, row_label_step)
+                && y >= top
+                && y as f32 + 1.0 + label_height <= height as f32
+            {
+                labels.push(Annotation {
+                    color: hex(left_color),
+                    ..Annotation::new(AnnotationShape::Text {
+                        x: 0.0,
+                        y: y as f32 + 1.0,
+                        text: screen.to_string(),
+                        size: LABEL_SIZE,
+                    })
+                });
+            }
+        }
+
+        let mut gridded = Self::new(DynamicImage::ImageRgba8(image));
+        for chunk in labels.chunks(MAX_ANNOTATIONS) {
+            gridded = gridded.annotate(chunk)?;
+        }
+        Ok(gridded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, value: u8) -> ImageBuffer {
+        ImageBuffer::new(DynamicImage::ImageRgba8(RgbaImage::from_pixel(
+            width,
+            height,
+            image::Rgba([value, value, value, 255]),
+        )))
+    }
+
+    #[test]
+    fn test_coarsen() {
+        assert_eq!(coarsen(100, 1.0, 12.0), 100);
+        assert_eq!(coarsen(10, 1.0, 12.0), 20);
+        assert_eq!(coarsen(100, 0.05, 12.0), 500);
+        assert_eq!(coarsen(100, 0.01, 12.0), 2000);
+    }
+
+    #[test]
+    fn test_grid_lines_use_screen_coordinates() {
+        // Screen x 150..550 shown at half scale in 200 pixels
+        let lines = grid_lines(150, 400, 100, 0.5, 200);
+        assert_eq!(lines, vec![(200, 25), (300, 75), (400, 125), (500, 175)]);
+        // A line on the very edge of the image is not drawn
+        assert_eq!(grid_lines(0, 400, 100, 1.0, 400)[0], (100, 100));
+    }
+
+    #[test]
+    fn test_grid_lines_contrast_with_content() {
+        let grid = GridOptions {
+            spacing: 100,
+            rulers: false,
+        };
+        let area = Region::new(0, 0, 400, 300);
+
+        let dark = solid(400, 300, 0)
+            .overlay_grid(&grid, area)
+            .unwrap()
+            .to_rgba8();
+        assert!(dark.get_pixel(200, 150)[0] > 100, "line lightens dark content");
+        assert_eq!(dark.get_pixel(150, 150)[0], 0, "cells are untouched");
+
+        let light = solid(400, 300, 255)
+            .overlay_grid(&grid, area)
+            .unwrap()
+            .to_rgba8();
+        assert!(light.get_pixel(200, 150)[0] < 155, "line darkens light content");
+        assert_eq!(light.get_pixel(150, 150)[0], 255);
+    }
+
+    #[test]
+    fn test_scaled_grid_follows_screen_positions() {
+        // Screen area at (1000, 500) captured at half scale
+        let area = Region::new(1000, 500, 800, 600);
+        let gridded = solid(400, 300, 0)
+            .overlay_grid(
+                &GridOptions {
+                    spacing: 100,
+                    rulers: false,
+                },
+                area,
+            )
+            .unwrap()
+            .to_rgba8();
+
+        // Screen x = 1100 is 50 output pixels in; x = 1150 is mid-cell
+        assert!(gridded.get_pixel(50, 225)[0] > 0);
+        assert_eq!(gridded.get_pixel(75, 225)[0], 0);
+        // Screen y = 600 is 50 output pixels down
+        assert!(gridded.get_pixel(300, 50)[0] > 0);
+    }
+
+    #[test]
+    fn test_rulers_and_labels_are_drawn() {
+        let area = Region::new(0, 0, 400, 300);
+        let plain = solid(400, 300, 255);
+        let gridded = plain
+            .overlay_grid(&GridOptions::default(), area)
+            .unwrap()
+            .to_rgba8();
+
+        // Dark ruler along the top of light content, with light label ink
+        let top_band = (0..400).map(|x| gridded.get_pixel(x, 3)[0]);
+        assert!(top_band.clone().min().unwrap() < 100);
+        assert!(top_band.max().unwrap() > 200);
+        // Left ruler, clear of the top ruler
+        assert!(gridded.get_pixel(2, 60)[0] < 100);
+        // Cell interiors are untouched
+        assert_eq!(gridded.get_pixel(250, 250)[0], 255);
+    }
+
+    #[test]
+    fn test_empty_area_is_rejected() {
+        let err = solid(10, 10, 0)
+            .overlay_grid(&GridOptions::default(), Region::new(0, 0, 0, 10))
+            .unwrap_err();
+        assert!(
+            matches!(err, CaptureError::InvalidParameter { parameter, .. } if parameter == "grid")
+        );
+    }
+}
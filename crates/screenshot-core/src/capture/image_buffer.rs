@@ -29,6 +29,9 @@ use crate::{
     model::Region,
 };
 
+/// Side length of the grayscale thumbnails compared by `perceptual_diff`
+const PERCEPTUAL_DIFF_SIZE: u32 = 64;
+
 /// Wrapper around `image::DynamicImage` with transformation methods
 ///
 /// Provides a simplified interface for common image operations needed
@@ -57,6 +60,31 @@ impl ImageBuffer {
         Self { inner: image }
     }
 
+    /// Loads an image file (PNG, JPEG or WebP)
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::IoError` if the file cannot be read, or
+    /// `CaptureError::ImageError` if it cannot be decoded.
+    pub fn open(path: &std::path::Path) -> CaptureResult<Self> {
+        match image::open(path) {
+            Ok(image) => Ok(Self::new(image)),
+            Err(image::ImageError::IoError(e)) => Err(CaptureError::IoError(e)),
+            Err(e) => Err(CaptureError::ImageError(format!("{}: {}", path.display(), e))),
+        }
+    }
+
+    /// Decodes an encoded image (PNG, JPEG or WebP) held in memory
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::ImageError` if the data cannot be decoded.
+    pub fn from_bytes(data: &[u8]) -> CaptureResult<Self> {
+        image::load_from_memory(data)
+            .map(Self::new)
+            .map_err(|e| CaptureError::ImageError(format!("Cannot decode image data: {}", e)))
+    }
+
     /// Scales the image by the given factor
     ///
     /// Uses Lanczos3 filtering for high-quality scaling. The scale factor
@@ -260,6 +288,51 @@ impl ImageBuffer {
         self.dimensions().1
     }
 
+    /// Computes a perceptual difference to another image
+    ///
+    /// Both images are downsampled to small grayscale thumbnails, so
+    /// sub-pixel noise and compression artifacts barely register while
+    /// visible changes (animations, loading content) do. The result is the
+    /// mean absolute luminance difference, from 0.0 (identical) to 1.0
+    /// (black vs. white). Images with different dimensions return 1.0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::ImageBuffer;
+    ///
+    /// let a = ImageBuffer::from_test_pattern(200, 100);
+    /// let b = ImageBuffer::from_test_pattern(200, 100);
+    /// assert_eq!(a.perceptual_diff(&b), 0.0);
+    /// ```
+    pub fn perceptual_diff(&self, other: &Self) -> f32 {
+        if self.dimensions() != other.dimensions() {
+            return 1.0;
+        }
+
+        let thumbnail = |image: &image::DynamicImage| {
+            image
+                .resize_exact(
+                    PERCEPTUAL_DIFF_SIZE,
+                    PERCEPTUAL_DIFF_SIZE,
+                    image::imageops::FilterType::Triangle,
+                )
+                .to_luma8()
+        };
+        let a = thumbnail(&self.inner);
+        let b = thumbnail(&other.inner);
+
+        let total: u64 = a
+            .as_raw()
+            .iter()
+            .zip(b.as_raw())
+            .map(|(&pa, &pb)| u64::from(pa.abs_diff(pb)))
+            .sum();
+        let pixels = a.as_raw().len().max(1) as f64;
+
+        (total as f64 / (pixels * 255.0)) as f32
+    }
+
     /// Converts the image to RGBA8 format
     ///
     /// Returns an `ImageBuffer<Rgba<u8>, Vec<u8>>` which can be used for
@@ -552,4 +625,77 @@ mod tests {
         let cloned = img.clone();
         assert_eq!(img.dimensions(), cloned.dimensions());
     }
+
+    #[test]
+    fn test_perceptual_diff_identical_and_opposite() {
+        let black = ImageBuffer::new(image::DynamicImage::new_rgb8(100, 100));
+        let white = ImageBuffer::new(image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(
+            100,
+            100,
+            image::Rgb([255, 255, 255]),
+        )));
+
+        assert_eq!(black.perceptual_diff(&black.clone()), 0.0);
+        assert!((black.perceptual_diff(&white) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_perceptual_diff_small_change() {
+        let base = image::RgbImage::from_pixel(200, 200, image::Rgb([128, 128, 128]));
+        let mut changed = base.clone();
+        // A 20x20 white patch covers 1% of the image
+        for y in 0..20 {
+            for x in 0..20 {
+                changed.put_pixel(x, y, image::Rgb([255, 255, 255]));
+            }
+        }
+
+        let a = ImageBuffer::new(image::DynamicImage::ImageRgb8(base));
+        let b = ImageBuffer::new(image::DynamicImage::ImageRgb8(changed));
+        let diff = a.perceptual_diff(&b);
+        assert!(diff > 0.0 && diff < 0.01, "diff was {}", diff);
+    }
+
+    #[test]
+    fn test_perceptual_diff_dimension_mismatch() {
+        let a = ImageBuffer::from_test_pattern(100, 100);
+        let b = ImageBuffer::from_test_pattern(100, 50);
+        assert_eq!(a.perceptual_diff(&b), 1.0);
+    }
+
+    #[test]
+    fn test_open_roundtrip_and_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pattern.png");
+        ImageBuffer::from_test_pattern(32, 16)
+            .inner()
+            .save(&path)
+            .unwrap();
+
+        let loaded = ImageBuffer::open(&path).unwrap();
+        assert_eq!(loaded.dimensions(), (32, 16));
+
+        assert!(matches!(
+            ImageBuffer::open(&dir.path().join("missing.png")),
+            Err(CaptureError::IoError(_))
+        ));
+
+        let garbage = dir.path().join("garbage.png");
+        std::fs::write(&garbage, b"not an image").unwrap();
+        assert!(matches!(ImageBuffer::open(&garbage), Err(CaptureError::ImageError(_))));
+    }
+
+    #[test]
+    fn test_from_bytes() {
+        let mut png = Vec::new();
+        ImageBuffer::from_test_pattern(20, 10)
+            .inner()
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        assert_eq!(ImageBuffer::from_bytes(&png).unwrap().dimensions(), (20, 10));
+        assert!(matches!(
+            ImageBuffer::from_bytes(b"not an image"),
+            Err(CaptureError::ImageError(_))
+        ));
+    }
 }
@@ -0,0 +1,509 @@
+//! Template matching: find a reference image inside a capture
+//!
+//! [`locate_image`] answers "where is the Save button?" given a small image
+//! of the button. It slides the template over the capture and scores every
+//! position with zero-mean normalized cross-correlation (NCC), which is
+//! insensitive to uniform brightness and contrast changes.
+//!
+//! # Search Strategy
+//!
+//! Scoring every position at full resolution is too slow for large
+//! templates, so the search runs coarse to fine: both images are
+//! box-downsampled by a power of two that keeps the template's short side at
+//! six pixels or more, the best coarse positions are picked, and each is
+//! refined at full resolution around its upscaled position.
+//!
+//! For UIs rendered at a different scale than the template, list several
+//! factors in [`LocateOptions::scales`]; the template is resized to each and
+//! the matches of all scales are merged.
+//!
+//! # Examples
+//!
+//! ```
+//! use image::{DynamicImage, Rgba, RgbaImage, imageops};
+//! use screenshot_core::{
+//!     capture::{ImageBuffer, locate::locate_image},
+//!     model::{LocateOptions, Region},
+//! };
+//!
+//! let icon = ImageBuffer::from_test_pattern(16, 16);
+//! let mut screen = RgbaImage::from_pixel(120, 80, Rgba([255, 255, 255, 255]));
+//! imageops::replace(&mut screen, &icon.to_rgba8(), 40, 20);
+//! let screen = ImageBuffer::new(DynamicImage::ImageRgba8(screen));
+//!
+//! let matches = locate_image(&screen, &icon, &LocateOptions::default()).unwrap();
+//! assert_eq!(matches[0].region, Region::new(40, 20, 16, 16));
+//! ```
+
+use image::{DynamicImage, imageops::FilterType};
+
+use super::ImageBuffer;
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{LocateOptions, Region, TemplateMatch},
+};
+
+/// Smallest template side searched; smaller scaled templates are skipped
+const MIN_TEMPLATE_SIDE: u32 = 4;
+
+/// Shortest template side kept when downsampling for the coarse search
+const MIN_COARSE_SIDE: u32 = 6;
+
+/// Largest downsampling factor of the coarse search
+const MAX_PYRAMID_FACTOR: u32 = 16;
+
+/// Greyscale image as 0.0-1.0 luma values
+#[derive(Debug, Clone)]
+struct Plane {
+    width: u32,
+    height: u32,
+    data: Vec<f32>,
+}
+
+impl Plane {
+    fn from_image(image: &DynamicImage) -> Self {
+        let luma = image.to_luma32f();
+        Self {
+            width: luma.width(),
+            height: luma.height(),
+            data: luma.into_raw(),
+        }
+    }
+
+    /// Averages `factor` x `factor` blocks into single pixels
+    fn downsample(&self, factor: u32) -> Self {
+        let width = self.width / factor;
+        let height = self.height / factor;
+        let area = (factor * factor) as f32;
+        let mut data = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let mut sum = 0.0;
+                for dy in 0..factor {
+                    let row = ((y * factor + dy) * self.width + x * factor) as usize;
+                    sum += self.data[row..row + factor as usize].iter().sum::<f32>();
+                }
+                data.push(sum / area);
+            }
+        }
+        Self {
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// Summed-area tables of a plane's values and squared values
+///
+/// Gives the mean and variance under any window in constant time.
+struct Integral {
+    stride: usize,
+    sum: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Integral {
+    fn new(plane: &Plane) -> Self {
+        let stride = plane.width as usize + 1;
+        let len = stride * (plane.height as usize + 1);
+        let mut sum = vec![0.0; len];
+        let mut squares = vec![0.0; len];
+        for y in 0..plane.height as usize {
+            let (mut row_sum, mut row_squares) = (0.0, 0.0);
+            for x in 0..plane.width as usize {
+                let v = f64::from(plane.data[y * plane.width as usize + x]);
+                row_sum += v;
+                row_squares += v * v;
+                let i = (y + 1) * stride + x + 1;
+                sum[i] = sum[i - stride] + row_sum;
+                squares[i] = squares[i - stride] + row_squares;
+            }
+        }
+        Self {
+            stride,
+            sum,
+            squares,
+        }
+    }
+
+    /// Sum and sum of squares of the window at `(x, y)`
+    fn window(&self, x: u32, y: u32, width: u32, height: u32) -> (f64, f64) {
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = (x0 + width as usize, y0 + height as usize);
+        let at = |table: &[f64]| {
+            table[y1 * self.stride + x1]
+                - table[y0 * self.stride + x1]
+                - table[y1 * self.stride + x0]
+                + table[y0 * self.stride + x0]
+        };
+        (at(&self.sum), at(&self.squares))
+    }
+}
+
+/// Zero-mean template ready for correlation
+struct Template {
+    width: u32,
+    height: u32,
+    data: Vec<f32>,
+    norm: f64,
+}
+
+impl Template {
+    /// Returns None for flat templates, which correlate with nothing
+    fn new(plane: &Plane) -> Option<Self> {
+        let n = plane.data.len() as f64;
+        let mean = plane.data.iter().map(|&v| f64::from(v)).sum::<f64>() / n;
+        let data: Vec<f32> = plane.data.iter().map(|&v| v - mean as f32).collect();
+        let norm = data.iter().map(|&v| f64::from(v * v)).sum::<f64>().sqrt();
+        (norm > 1e-4 * n.sqrt()).then_some(Self {
+            width: plane.width,
+            height: plane.height,
+            data,
+            norm,
+        })
+    }
+
+    /// NCC score of the template placed at `(x, y)` in `image`
+    fn score(&self, image: &Plane, integral: &Integral, x: u32, y: u32) -> f32 {
+        let n = self.data.len() as f64;
+        let (sum, squares) = integral.window(x, y, self.width, self.height);
+        let variance = squares - sum * sum / n;
+        if variance <= 1e-8 * n {
+            return 0.0;
+        }
+
+        // The template is zero-mean, so the image mean cancels out
+        let width = self.width as usize;
+        let mut cross = 0.0;
+        for ty in 0..self.height as usize {
+            let start = (y as usize + ty) * image.width as usize + x as usize;
+            let row = &image.data[start..start + width];
+            let template_row = &self.data[ty * width..(ty + 1) * width];
+            cross += f64::from(
+                row.iter()
+                    .zip(template_row)
+                    .map(|(a, b)| a * b)
+                    .sum::<f32>(),
+            );
+        }
+        (cross / (variance.sqrt() * self.norm)) as f32
+    }
+
+    /// Best-scoring positions, at most `limit`, none closer to a better one
+    /// than half the template size
+    fn best_positions(&self, image: &Plane, limit: usize) -> Vec<(u32, u32, f32)> {
+        let integral = Integral::new(image);
+        let mut scored = Vec::new();
+        for y in 0..=image.height - self.height {
+            for x in 0..=image.width - self.width {
+                let score = self.score(image, &integral, x, y);
+                if score > 0.0 {
+                    scored.push((x, y, score));
+                }
+            }
+        }
+        scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+
+        let (min_dx, min_dy) = (self.width.div_ceil(2), self.height.div_ceil(2));
+        let mut best: Vec<(u32, u32, f32)> = Vec::new();
+        for candidate in scored {
+            if best.len() >= limit {
+                break;
+            }
+            let near = best.iter().any(|&(x, y, _)| {
+                x.abs_diff(candidate.0) < min_dx && y.abs_diff(candidate.1) < min_dy
+            });
+            if !near {
+                best.push(candidate);
+            }
+        }
+        best
+    }
+
+    /// Best position within `radius` pixels of `(x, y)`
+    fn refine(
+        &self,
+        image: &Plane,
+        integral: &Integral,
+        x: u32,
+        y: u32,
+        radius: u32,
+    ) -> (u32, u32, f32) {
+        let max_x = image.width - self.width;
+        let max_y = image.height - self.height;
+        let mut best = (x.min(max_x), y.min(max_y), f32::MIN);
+        for cy in y.saturating_sub(radius)..=(y + radius).min(max_y) {
+            for cx in x.saturating_sub(radius)..=(x + radius).min(max_x) {
+                let score = self.score(image, integral, cx, cy);
+                if score > best.2 {
+                    best = (cx, cy, score);
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Downsampling factor for the coarse search of a template
+fn pyramid_factor(width: u32, height: u32) -> u32 {
+    let short_side = width.min(height);
+    let mut factor = 1;
+    while factor < MAX_PYRAMID_FACTOR && short_side / (factor * 2) >= MIN_COARSE_SIDE {
+        factor *= 2;
+    }
+    factor
+}
+
+/// Area shared by two regions
+fn overlap(a: &Region, b: &Region) -> u64 {
+    let width = (a.x + a.width)
+        .min(b.x + b.width)
+        .saturating_sub(a.x.max(b.x));
+    let height = (a.y + a.height)
+        .min(b.y + b.height)
+        .saturating_sub(a.y.max(b.y));
+    u64::from(width) * u64::from(height)
+}
+
+/// Searches `image` for one scaled template, returning positions and scores
+fn search(image: &Plane, template: &Plane, limit: usize) -> Vec<(u32, u32, f32)> {
+    let Some(full) = Template::new(template) else {
+        return Vec::new();
+    };
+
+    let factor = pyramid_factor(template.width, template.height);
+    let coarse = (factor > 1)
+        .then(|| Template::new(&template.downsample(factor)))
+        .flatten();
+    match coarse {
+        Some(coarse) => {
+            let integral = Integral::new(image);
+            coarse
+                .best_positions(&image.downsample(factor), limit)
+                .into_iter()
+                .map(|(x, y, _)| full.refine(image, &integral, x * factor, y * factor, factor))
+                .collect()
+        }
+        None => full.best_positions(image, limit),
+    }
+}
+
+/// Finds where `template` appears in `image`
+///
+/// Returns up to `options.max_matches` non-overlapping matches scoring at
+/// least `options.min_confidence`, best first, with regions in `image`
+/// pixels. `screen_region` is left unset; see
+/// [`TemplateMatch::map_to_screen`].
+///
+/// # Errors
+///
+/// Returns [`CaptureError::InvalidParameter`] for `template` if it has no
+/// contrast (a flat colour matches everywhere) or is larger than `image` at
+/// every searched scale.
+pub fn locate_image(
+    image: &ImageBuffer,
+    template: &ImageBuffer,
+    options: &LocateOptions,
+) -> CaptureResult<Vec<TemplateMatch>> {
+    let mut options = options.clone();
+    options.validate();
+
+    let template_plane = Plane::from_image(template.inner());
+    if Template::new(&template_plane).is_none() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "template".to_string(),
+            reason: "Template has no contrast to match (it is a single flat colour)".to_string(),
+        });
+    }
+
+    let haystack = Plane::from_image(image.inner());
+    let (template_width, template_height) = template.dimensions();
+    // Refine more candidates than requested so near-misses cannot crowd out
+    // the real matches
+    let limit = options.max_matches * 4 + 8;
+    let mut matches = Vec::new();
+    let mut searched = false;
+
+    for &scale in &options.scales {
+        let width = (template_width as f32 * scale).round() as u32;
+        let height = (template_height as f32 * scale).round() as u32;
+        if width < MIN_TEMPLATE_SIDE
+            || height < MIN_TEMPLATE_SIDE
+            || width > haystack.width
+            || height > haystack.height
+        {
+            continue;
+        }
+        searched = true;
+
+        let scaled = if (width, height) == (template_width, template_height) {
+            template_plane.clone()
+        } else {
+            Plane::from_image(
+                &template
+                    .inner()
+                    .resize_exact(width, height, FilterType::Triangle),
+            )
+        };
+        matches.extend(
+            search(&haystack, &scaled, limit)
+                .into_iter()
+                .filter(|&(_, _, score)| score >= options.min_confidence)
+                .map(|(x, y, score)| TemplateMatch {
+                    region: Region::new(x, y, width, height),
+                    screen_region: None,
+                    confidence: score.min(1.0),
+                    scale,
+                }),
+        );
+    }
+
+    if !searched {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "template".to_string(),
+            reason: format!(
+                "Template ({}x{}) does not fit the image ({}x{}) at any searched scale",
+                template_width, template_height, haystack.width, haystack.height
+            ),
+        });
+    }
+
+    // Keep the best of matches that mostly cover each other, e.g. the same
+    // element found at two scales
+    matches.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    let mut kept: Vec<TemplateMatch> = Vec::new();
+    for candidate in matches {
+        if kept.len() >= options.max_matches {
+            break;
+        }
+        let area = candidate.region.width as u64 * candidate.region.height as u64;
+        let duplicate = kept.iter().any(|m| {
+            let other = m.region.width as u64 * m.region.height as u64;
+            overlap(&m.region, &candidate.region) * 2 > area.min(other)
+        });
+        if !duplicate {
+            kept.push(candidate);
+        }
+    }
+    Ok(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{Rgba, RgbaImage, imageops};
+
+    use super::*;
+
+    /// Random flat-coloured cells, roughly like a busy UI
+    fn cells(width: u32, height: u32, cell: u32, seed: u32) -> RgbaImage {
+        let mut state = seed.wrapping_mul(2_654_435_761).wrapping_add(1);
+        let columns = width.div_ceil(cell);
+        let colours: Vec<Rgba<u8>> = (0..columns * height.div_ceil(cell))
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let [r, g, b, _] = state.to_le_bytes();
+                Rgba([r, g, b, 255])
+            })
+            .collect();
+        RgbaImage::from_fn(width, height, |x, y| {
+            colours[((y / cell) * columns + x / cell) as usize]
+        })
+    }
+
+    fn buffer(image: RgbaImage) -> ImageBuffer {
+        ImageBuffer::new(DynamicImage::ImageRgba8(image))
+    }
+
+    #[test]
+    fn test_locates_exact_crop() {
+        let image = buffer(cells(240, 160, 6, 1));
+        let target = Region::new(61, 43, 40, 28);
+        let template = image.crop(target).unwrap();
+
+        let matches = locate_image(&image, &template, &LocateOptions::default()).unwrap();
+        assert_eq!(matches.len(), 1, "{:?}", matches);
+        assert_eq!(matches[0].region, target);
+        assert!(matches[0].confidence > 0.99);
+        assert_eq!(matches[0].scale, 1.0);
+    }
+
+    #[test]
+    fn test_locates_every_instance() {
+        let button = cells(30, 20, 5, 7);
+        let mut screen = RgbaImage::from_pixel(200, 120, Rgba([240, 240, 240, 255]));
+        imageops::replace(&mut screen, &button, 10, 10);
+        imageops::replace(&mut screen, &button, 150, 85);
+
+        let matches =
+            locate_image(&buffer(screen), &buffer(button), &LocateOptions::default()).unwrap();
+        let mut regions: Vec<Region> = matches.iter().map(|m| m.region).collect();
+        regions.sort_by_key(|r| r.x);
+        assert_eq!(regions, vec![Region::new(10, 10, 30, 20), Region::new(150, 85, 30, 20)]);
+    }
+
+    #[test]
+    fn test_multi_scale_search() {
+        let icon = DynamicImage::ImageRgba8(cells(24, 16, 4, 3));
+        let large = icon.resize_exact(48, 32, FilterType::Triangle).to_rgba8();
+        let mut screen = cells(200, 150, 9, 5);
+        imageops::replace(&mut screen, &large, 100, 60);
+        let screen = buffer(screen);
+        let icon = ImageBuffer::new(icon);
+
+        // At the template's own size nothing matches well
+        assert!(
+            locate_image(&screen, &icon, &LocateOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+
+        let options = LocateOptions {
+            scales: vec![1.0, 2.0],
+            ..Default::default()
+        };
+        let matches = locate_image(&screen, &icon, &options).unwrap();
+        assert_eq!(matches[0].region, Region::new(100, 60, 48, 32));
+        assert_eq!(matches[0].scale, 2.0);
+    }
+
+    #[test]
+    fn test_confidence_threshold_and_limit() {
+        let image = buffer(cells(160, 120, 6, 11));
+        let unrelated = buffer(cells(30, 30, 6, 99));
+        assert!(
+            locate_image(&image, &unrelated, &LocateOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+
+        let options = LocateOptions {
+            min_confidence: 0.0,
+            max_matches: 3,
+            ..Default::default()
+        };
+        let matches = locate_image(&image, &unrelated, &options).unwrap();
+        assert_eq!(matches.len(), 3);
+        assert!(
+            matches
+                .windows(2)
+                .all(|w| w[0].confidence >= w[1].confidence)
+        );
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        let image = buffer(cells(100, 80, 6, 1));
+        let flat = buffer(RgbaImage::from_pixel(10, 10, Rgba([9, 9, 9, 255])));
+        let huge = buffer(cells(120, 40, 6, 2));
+
+        for template in [flat, huge] {
+            match locate_image(&image, &template, &LocateOptions::default()) {
+                Err(CaptureError::InvalidParameter { parameter, .. }) => {
+                    assert_eq!(parameter, "template")
+                }
+                other => panic!("expected invalid template, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,324 @@
+//! Set-of-marks overlays: numbered window outlines on a display capture
+//!
+//! Picking a window by title is hard when several look alike. A set-of-marks
+//! capture shows the whole display with every visible window outlined and
+//! tagged with a number, so an agent can point at "window 7" and look up its
+//! handle in the returned [`WindowMark`]s.
+//!
+//! # Occlusion
+//!
+//! [`compute_window_marks`] takes the windows in stacking order and works
+//! out which pixels of each are not covered by windows above it. Each
+//! window is outlined along the largest uncovered rectangle, so outlines
+//! never overlap and labels are never hidden under another window. Windows
+//! with no uncovered rectangle of at least [`MIN_MARK_SIDE`] pixels a side
+//! are left out.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{
+//!         ImageBuffer,
+//!         marks::{compute_window_marks, draw_window_marks},
+//!     },
+//!     model::{BackendType, Region, WindowInfo},
+//! };
+//!
+//! let window = |id: &str| {
+//!     WindowInfo::new(id.into(), id.into(), "Demo".into(), "demo".into(), 1, BackendType::None)
+//! };
+//! // Bottom first: "editor" is partly covered by "dialog"
+//! let windows = [
+//!     (window("editor"), Region::new(0, 0, 400, 300)),
+//!     (window("dialog"), Region::new(250, 100, 200, 150)),
+//! ];
+//! let marks = compute_window_marks(&windows, (640, 480));
+//! assert_eq!(marks[0].handle, "dialog");
+//! assert_eq!(marks[1].visible, Region::new(0, 0, 250, 300));
+//!
+//! let display = ImageBuffer::from_test_pattern(640, 480);
+//! let marked = draw_window_marks(&display, &marks).unwrap();
+//! assert_eq!(marked.dimensions(), (640, 480));
+//! ```
+
+use super::{ImageBuffer, annotate::MAX_ANNOTATIONS};
+use crate::{
+    error::CaptureResult,
+    model::{Annotation, AnnotationShape, Region, WindowInfo, WindowMark},
+};
+
+/// Shortest side of an uncovered rectangle that still gets a mark
+pub const MIN_MARK_SIDE: u32 = 8;
+
+/// Outline width in pixels
+const OUTLINE_WIDTH: f32 = 3.0;
+
+/// Label font size in pixels
+const LABEL_SIZE: f32 = 18.0;
+
+/// Outline colours, cycled by label so neighbours are told apart
+const PALETTE: [&str; 10] = [
+    "#e6194b", "#3cb44b", "#4363d8", "#f58231", "#911eb4", "#0fa3b1", "#f032e6", "#808000",
+    "#9a6324", "#000075",
+];
+
+/// Part of `region` inside a `screen`-sized area, if any
+fn clip(region: Region, (width, height): (u32, u32)) -> Option<Region> {
+    let right = region.x.saturating_add(region.width).min(width);
+    let bottom = region.y.saturating_add(region.height).min(height);
+    (right > region.x && bottom > region.y)
+        .then(|| Region::new(region.x, region.y, right - region.x, bottom - region.y))
+}
+
+/// Largest rectangle under a histogram of pixel heights
+///
+/// Column `j` spans `xs[j]..xs[j + 1]`; all bars stand on `bottom`.
+fn largest_rectangle(heights: &[u32], xs: &[u32], bottom: u32) -> Option<(u64, Region)> {
+    let mut best: Option<(u64, Region)> = None;
+    let mut stack: Vec<(usize, u32)> = Vec::new();
+
+    for j in 0..=heights.len() {
+        let height = heights.get(j).copied().unwrap_or(0);
+        let mut start = j;
+        while let Some(&(s, bar)) = stack.last() {
+            if bar < height {
+                break;
+            }
+            stack.pop();
+            let width = xs[j] - xs[s];
+            let area = u64::from(bar) * u64::from(width);
+            if best.is_none_or(|(best_area, _)| area > best_area) {
+                best = Some((area, Region::new(xs[s], bottom - bar, width, bar)));
+            }
+            start = s;
+        }
+        if height > 0 {
+            stack.push((start, height));
+        }
+    }
+    best
+}
+
+/// Numbers the visible windows, topmost first, taking occlusion into account
+///
+/// `windows` are the mapped windows with their screen geometry, in stacking
+/// order, bottom first. Parts outside a `screen`-sized area at the origin
+/// are ignored.
+pub fn compute_window_marks(
+    windows: &[(WindowInfo, Region)],
+    screen: (u32, u32),
+) -> Vec<WindowMark> {
+    let clipped: Vec<Option<Region>> = windows.iter().map(|(_, r)| clip(*r, screen)).collect();
+
+    // Split the screen into cells along every window edge, so each cell is
+    // owned by exactly one window (the topmost covering it) or none
+    let mut xs = vec![0, screen.0];
+    let mut ys = vec![0, screen.1];
+    for r in clipped.iter().flatten() {
+        xs.extend([r.x, r.x + r.width]);
+        ys.extend([r.y, r.y + r.height]);
+    }
+    xs.sort_unstable();
+    xs.dedup();
+    ys.sort_unstable();
+    ys.dedup();
+    let columns = xs.len() - 1;
+    let rows = ys.len() - 1;
+
+    let mut owner = vec![None; columns * rows];
+    for (index, r) in clipped.iter().enumerate() {
+        let Some(r) = r else { continue };
+        let edge = |edges: &[u32], value: u32| edges.binary_search(&value).unwrap_or_default();
+        for row in edge(&ys, r.y)..edge(&ys, r.y + r.height) {
+            for column in edge(&xs, r.x)..edge(&xs, r.x + r.width) {
+                owner[row * columns + column] = Some(index);
+            }
+        }
+    }
+
+    let mut marks = Vec::new();
+    for index in (0..windows.len()).rev() {
+        let Some(on_screen) = clipped[index] else {
+            continue;
+        };
+
+        let mut visible_pixels = 0u64;
+        let mut heights = vec![0u32; columns];
+        let mut best: Option<(u64, Region)> = None;
+        for row in 0..rows {
+            let row_height = ys[row + 1] - ys[row];
+            for (column, height) in heights.iter_mut().enumerate() {
+                if owner[row * columns + column] == Some(index) {
+                    *height += row_height;
+                    visible_pixels +=
+                        u64::from(row_height) * u64::from(xs[column + 1] - xs[column]);
+                } else {
+                    *height = 0;
+                }
+            }
+            if let Some((area, rect)) = largest_rectangle(&heights, &xs, ys[row + 1]) {
+                if best.is_none_or(|(best_area, _)| area > best_area) {
+                    best = Some((area, rect));
+                }
+            }
+        }
+
+        let Some((_, visible)) = best else {
+            continue;
+        };
+        if visible.width < MIN_MARK_SIDE || visible.height < MIN_MARK_SIDE {
+            continue;
+        }
+
+        let (info, bounds) = &windows[index];
+        let on_screen_pixels = u64::from(on_screen.width) * u64::from(on_screen.height);
+        marks.push(WindowMark {
+            label: marks.len() as u32 + 1,
+            handle: info.id.clone(),
+            title: info.title.clone(),
+            class: info.class.clone(),
+            bounds: *bounds,
+            visible,
+            visible_ratio: visible_pixels as f64 / on_screen_pixels as f64,
+        });
+    }
+    marks
+}
+
+/// Returns a copy of a display capture with the marks drawn on it
+///
+/// Mark rectangles are used as image pixels, so `image` should be an
+/// unscaled capture of the screen the marks were computed for.
+pub fn draw_window_marks(image: &ImageBuffer, marks: &[WindowMark]) -> CaptureResult<ImageBuffer> {
+    let mut outlines = Vec::with_capacity(marks.len() * 2);
+    let mut labels = Vec::with_capacity(marks.len());
+    for mark in marks {
+        let color = PALETTE[(mark.label as usize).saturating_sub(1) % PALETTE.len()];
+        let v = mark.visible;
+        // Keep the whole stroke inside the visible rectangle
+        outlines.push(Annotation {
+            color: color.to_string(),
+            stroke_width: OUTLINE_WIDTH,
+            ..Annotation::new(AnnotationShape::Rectangle {
+                x: v.x as f32 + OUTLINE_WIDTH / 2.0,
+                y: v.y as f32 + OUTLINE_WIDTH / 2.0,
+                width: v.width as f32 - OUTLINE_WIDTH,
+                height: v.height as f32 - OUTLINE_WIDTH,
+            })
+        });
+        labels.push(Annotation {
+            color: color.to_string(),
+            ..Annotation::new(AnnotationShape::Text {
+                x: v.x as f32 + OUTLINE_WIDTH,
+                y: v.y as f32 + OUTLINE_WIDTH,
+                text: mark.label.to_string(),
+                size: LABEL_SIZE,
+            })
+        });
+    }
+    // Labels go on top of every outline
+    outlines.append(&mut labels);
+
+    let mut marked = image.clone();
+    for chunk in outlines.chunks(MAX_ANNOTATIONS) {
+        marked = marked.annotate(chunk)?;
+    }
+    Ok(marked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::BackendType;
+
+    fn window(id: &str) -> WindowInfo {
+        WindowInfo::new(
+            id.to_string(),
+            format!("{} title", id),
+            "Demo".to_string(),
+            "demo".to_string(),
+            1,
+            BackendType::None,
+        )
+    }
+
+    #[test]
+    fn test_largest_rectangle() {
+        // Columns of width 10, 20, 10 with heights 5, 8, 8
+        let xs = [0, 10, 30, 40];
+        assert_eq!(
+            largest_rectangle(&[5, 8, 8], &xs, 100),
+            Some((240, Region::new(10, 92, 30, 8)))
+        );
+        assert_eq!(largest_rectangle(&[5, 5, 0], &xs, 100), Some((150, Region::new(0, 95, 30, 5))));
+        assert_eq!(largest_rectangle(&[0, 0, 0], &xs, 100), None);
+    }
+
+    #[test]
+    fn test_marks_follow_stacking_and_occlusion() {
+        // Same layout as the mock backend, bottom first
+        let windows = [
+            (window("a"), Region::new(0, 0, 1280, 1080)),
+            (window("b"), Region::new(640, 0, 1280, 1080)),
+            (window("c"), Region::new(1280, 540, 640, 540)),
+        ];
+        let marks = compute_window_marks(&windows, (1920, 1080));
+
+        let summary: Vec<(u32, &str, Region, f64)> = marks
+            .iter()
+            .map(|m| (m.label, m.handle.as_str(), m.visible, m.visible_ratio))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, "c", Region::new(1280, 540, 640, 540), 1.0),
+                (2, "b", Region::new(640, 0, 1280, 540), 0.75),
+                (3, "a", Region::new(0, 0, 640, 1080), 0.5),
+            ]
+        );
+        assert_eq!(marks[1].bounds, Region::new(640, 0, 1280, 1080));
+        assert_eq!(marks[1].title, "b title");
+    }
+
+    #[test]
+    fn test_hidden_tiny_and_offscreen_windows() {
+        let windows = [
+            // Fully covered by "top"
+            (window("covered"), Region::new(100, 100, 50, 50)),
+            // Only a 4 pixel strip shows below "top"
+            (window("sliver"), Region::new(0, 0, 300, 204)),
+            (window("top"), Region::new(0, 0, 300, 200)),
+            // Entirely off screen
+            (window("offscreen"), Region::new(900, 900, 50, 50)),
+            // Partly off screen
+            (window("edge"), Region::new(600, 400, 100, 100)),
+        ];
+        let marks = compute_window_marks(&windows, (640, 480));
+
+        let handles: Vec<&str> = marks.iter().map(|m| m.handle.as_str()).collect();
+        assert_eq!(handles, vec!["edge", "top"]);
+        assert_eq!(marks[0].visible, Region::new(600, 400, 40, 80));
+        assert_eq!(marks[0].visible_ratio, 1.0);
+    }
+
+    #[test]
+    fn test_draw_window_marks() {
+        let windows = [
+            (window("a"), Region::new(0, 0, 100, 80)),
+            (window("b"), Region::new(60, 40, 100, 80)),
+        ];
+        let marks = compute_window_marks(&windows, (200, 160));
+        let display = ImageBuffer::from_test_pattern(200, 160);
+        let marked = draw_window_marks(&display, &marks).unwrap().to_rgba8();
+
+        // Window "b" (label 1) is outlined in the first palette colour
+        assert_eq!(*marked.get_pixel(61, 100), image::Rgba([0xe6, 0x19, 0x4b, 255]));
+        // Its label box sits in its top-left corner
+        assert_eq!(*marked.get_pixel(64, 44), image::Rgba([0xe6, 0x19, 0x4b, 255]));
+        // Window "a" is outlined along its uncovered left part
+        assert_eq!(*marked.get_pixel(1, 60), image::Rgba([0x3c, 0xb4, 0x4b, 255]));
+        // Outside every window nothing changes
+        assert_eq!(marked.get_pixel(180, 10), display.to_rgba8().get_pixel(180, 10));
+    }
+}
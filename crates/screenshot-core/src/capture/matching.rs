@@ -11,15 +11,46 @@
 //! 2. **Substring match** - Case-insensitive substring search on title
 //! 3. **Fuzzy match** - Fuzzy matching on title using SkimMatcherV2
 //!
+//! Substring and fuzzy matching compare Unicode-folded text (see
+//! [`fold_for_matching`](crate::capture::normalize::fold_for_matching)):
+//! NFKC normalization, full case folding and, unless the selector sets
+//! `strict`, diacritic stripping and homoglyph transliteration. `Ｃａｆé`
+//! therefore matches `cafe`, and `STRASSE` matches `Straße`.
+//!
 //! For class and exe matching:
 //! - **Exact match** - Case-insensitive exact match
 //!
+//! `pid` must equal the window's process ID, and `window_id` must equal its
+//! handle. Numeric handles compare by value, so `0x3a00007` and `60817415`
+//! select the same X11 window.
+//!
 //! # AND Semantics
 //!
 //! When multiple fields are specified in `WindowSelector`, all must match:
 //! - `title="Firefox"` AND `class="Navigator"` → window must match both
 //! - `title="Code"` AND `exe="code"` → window must match both
 //!
+//! Two fields go beyond plain AND:
+//! - `exclude` rejects any window whose title matches one of its patterns
+//!   (regex or folded substring; no fuzzy matching)
+//! - `any_of` requires at least one of its selectors to match; the best
+//!   matching alternative counts towards the window's rank
+//!
+//! # Ambiguity
+//!
+//! `find_match` always breaks ties by window ID. Backends resolve through
+//! `select_match`, which honours the selector's `AmbiguityPolicy` and can
+//! report a tie as `CaptureError::AmbiguousWindow` instead.
+//!
+//! # Sticky Resolution
+//!
+//! `select_match_sticky` remembers the window each selector last resolved to
+//! and keeps returning it while it still matches, so repeated captures of
+//! "Terminal" do not flip between two terminals as their titles change.
+//! Selectors opt in with `sticky`, or a backend can be built to resolve every
+//! selector stickily; destroyed windows are dropped via
+//! `forget_sticky_window`.
+//!
 //! # Security
 //!
 //! Regex patterns are limited to 1MB to prevent ReDoS attacks.
@@ -27,11 +58,12 @@
 //! # Performance
 //!
 //! Compiled regex patterns are cached in a global LRU cache to avoid
-//! recompilation when the same pattern is used repeatedly. The global cache
+//! recompilation when the same pattern is used repeatedly. Sticky
+//! resolutions live in a second global LRU cache next to it. The global cache
 //! is more memory-efficient than thread-local caches in async/tokio contexts
 //! where tasks migrate between threads.
 
-use std::num::NonZeroUsize;
+use std::{cmp::Ordering, num::NonZeroUsize};
 
 use once_cell::sync::Lazy;
 
@@ -40,7 +72,14 @@ use lru::LruCache;
 use parking_lot::Mutex;
 use regex::RegexBuilder;
 
-use crate::model::{WindowHandle, WindowInfo, WindowSelector};
+use crate::{
+    capture::normalize::fold_for_matching,
+    error::{CaptureError, CaptureResult},
+    model::{
+        AmbiguityPolicy, CriteriaMatch, MatchCandidate, MatchStrategy, SelectorExplanation,
+        TieBreakRule, WindowHandle, WindowInfo, WindowSelector,
+    },
+};
 
 /// Maximum regex pattern size (1MB) to prevent ReDoS attacks
 const MAX_REGEX_SIZE: usize = 1_048_576;
@@ -78,7 +117,7 @@ static REGEX_CACHE: Lazy<Mutex<LruCache<String, Option<regex::Regex>>>> =
 /// Results (including failures) are cached to avoid repeated compilation.
 /// Uses a global LRU cache with automatic eviction of least-recently-used
 /// patterns, which is more efficient than thread-local caches in async contexts.
-fn get_or_compile_regex(pattern: &str) -> Option<regex::Regex> {
+pub(crate) fn get_or_compile_regex(pattern: &str) -> Option<regex::Regex> {
     let mut cache = REGEX_CACHE.lock();
 
     // Check if pattern is already in cache (also updates LRU order)
@@ -116,7 +155,70 @@ fn compile_regex_with_limits(pattern: &str) -> Option<regex::Regex> {
         .ok()
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Maximum number of selectors whose last resolved window is remembered.
+const MAX_STICKY_CACHE_SIZE: usize = 64;
+
+/// Global LRU cache of the window each selector last resolved to.
+///
+/// Keyed by [`sticky_key`], so selectors that differ only in case, width or
+/// diacritics share an entry. Only consulted by
+/// [`WindowMatcher::select_match_sticky`], which resolvers opt into.
+static STICKY_CACHE: Lazy<Mutex<LruCache<String, WindowHandle>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(NonZeroUsize::new(MAX_STICKY_CACHE_SIZE).unwrap())));
+
+/// Builds the sticky cache key for a selector
+///
+/// Title and exclusion patterns are folded as for matching; class and exe
+/// are lowercased since they compare case-insensitively.
+fn sticky_key(selector: &WindowSelector) -> String {
+    fn normalize(selector: &WindowSelector, inherited: bool) -> WindowSelector {
+        let strict = selector.strict.unwrap_or(inherited);
+        WindowSelector {
+            title_substring_or_regex: selector
+                .title_substring_or_regex
+                .as_deref()
+                .map(|t| fold_for_matching(t, strict)),
+            class: selector.class.as_deref().map(str::to_lowercase),
+            exe: selector.exe.as_deref().map(str::to_lowercase),
+            exclude: selector
+                .exclude
+                .iter()
+                .map(|p| fold_for_matching(p, strict))
+                .collect(),
+            any_of: selector
+                .any_of
+                .iter()
+                .map(|alt| normalize(alt, strict))
+                .collect(),
+            ..selector.clone()
+        }
+    }
+
+    format!("{:?}", normalize(selector, false))
+}
+
+/// Forgets every sticky resolution that points at `handle`
+///
+/// Called when a window is destroyed, so a later window that reuses the
+/// handle is not preferred by accident.
+pub fn forget_sticky_window(handle: &str) {
+    let mut cache = STICKY_CACHE.lock();
+    let stale: Vec<String> = cache
+        .iter()
+        .filter(|(_, cached)| handles_equal(cached, handle))
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in stale {
+        cache.pop(&key);
+    }
+}
+
+/// Forgets all sticky resolutions
+pub fn clear_sticky_cache() {
+    STICKY_CACHE.lock().clear();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct MatchScore {
     /// Title match strategy rank (higher is better)
     /// - 3: regex
@@ -128,6 +230,91 @@ struct MatchScore {
     fuzzy_score: i64,
 }
 
+impl MatchScore {
+    /// Returns the title strategy this score represents
+    fn strategy(self) -> Option<MatchStrategy> {
+        match self.title_rank {
+            3 => Some(MatchStrategy::Regex),
+            2 => Some(MatchStrategy::Substring),
+            1 => Some(MatchStrategy::Fuzzy),
+            _ => None,
+        }
+    }
+}
+
+/// A selector with its patterns compiled once for a matching pass
+struct PreparedSelector<'a> {
+    selector: &'a WindowSelector,
+    /// Title pattern folded for substring and fuzzy matching
+    title_folded: Option<String>,
+    title_regex: Option<regex::Regex>,
+    /// Folded exclusion patterns paired with their compiled regex
+    exclude: Vec<(String, Option<regex::Regex>)>,
+    any_of: Vec<PreparedSelector<'a>>,
+    strict: bool,
+}
+
+impl<'a> PreparedSelector<'a> {
+    /// Prepares `selector`; alternatives that leave `strict` unset inherit
+    /// it from their parent
+    fn new(selector: &'a WindowSelector, inherited: bool) -> Self {
+        let strict = selector.strict.unwrap_or(inherited);
+        let title_pattern = selector.title_substring_or_regex.as_deref();
+        Self {
+            selector,
+            title_folded: title_pattern.map(|p| fold_for_matching(p, strict)),
+            title_regex: title_pattern.and_then(get_or_compile_regex),
+            exclude: selector
+                .exclude
+                .iter()
+                .map(|p| (fold_for_matching(p, strict), get_or_compile_regex(p)))
+                .collect(),
+            any_of: selector
+                .any_of
+                .iter()
+                .map(|alt| PreparedSelector::new(alt, strict))
+                .collect(),
+            strict,
+        }
+    }
+
+    /// Folds a window title the same way as this selector's patterns
+    fn fold_title(&self, window: &WindowInfo) -> String {
+        fold_for_matching(&window.title, self.strict)
+    }
+
+    /// Returns true if the window title matches any exclusion pattern
+    fn excludes(&self, window: &WindowInfo) -> bool {
+        if self.exclude.is_empty() {
+            return false;
+        }
+        let title_folded = self.fold_title(window);
+        self.exclude.iter().any(|(pattern_folded, regex)| {
+            regex.as_ref().is_some_and(|r| r.is_match(&window.title))
+                || title_folded.contains(pattern_folded.as_str())
+        })
+    }
+}
+
+/// Compares window handles, treating numeric IDs in decimal and `0x` hex as
+/// equal; other handles compare case-insensitively
+fn handles_equal(a: &str, b: &str) -> bool {
+    match (parse_numeric_handle(a), parse_numeric_handle(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.eq_ignore_ascii_case(b),
+    }
+}
+
+fn parse_numeric_handle(handle: &str) -> Option<u64> {
+    match handle
+        .strip_prefix("0x")
+        .or_else(|| handle.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => handle.parse().ok(),
+    }
+}
+
 /// Unified window matcher implementing AND semantics
 ///
 /// `WindowMatcher` is the single authority for window matching across all
@@ -154,6 +341,7 @@ struct MatchScore {
 ///     title_substring_or_regex: Some("Firefox".to_string()),
 ///     class: Some("Navigator".to_string()),
 ///     exe: None,
+///     ..Default::default()
 /// };
 ///
 /// // Find window matching both title AND class
@@ -201,6 +389,7 @@ impl WindowMatcher {
     ///     title_substring_or_regex: Some("Firefox".to_string()),
     ///     class: Some("Navigator".to_string()),
     ///     exe: None,
+    ///     ..Default::default()
     /// };
     ///
     /// // Only windows with title containing "Firefox" AND class "Navigator" match
@@ -212,34 +401,18 @@ impl WindowMatcher {
         windows: &[WindowInfo],
     ) -> Option<WindowHandle> {
         // Validate selector has at least one criterion
-        if selector.title_substring_or_regex.is_none()
-            && selector.class.is_none()
-            && selector.exe.is_none()
-        {
+        if selector.is_empty() {
             tracing::debug!("WindowMatcher: empty selector, no match");
             return None;
         }
 
-        let title_pattern = selector.title_substring_or_regex.as_deref();
-
-        let (title_regex, title_lower) = match title_pattern {
-            Some(p) => (self.try_compile_regex(p), Some(p.to_lowercase())),
-            None => (None, None),
-        };
-
+        let prepared = PreparedSelector::new(selector, false);
         let fuzzy_matcher = SkimMatcherV2::default();
 
         let mut best: Option<(&WindowInfo, MatchScore)> = None;
 
         for window in windows {
-            let Some(score) = self.score_window(
-                selector,
-                window,
-                title_pattern,
-                title_lower.as_deref(),
-                title_regex.as_ref(),
-                &fuzzy_matcher,
-            ) else {
+            let Some(score) = self.score_window(&prepared, window, &fuzzy_matcher) else {
                 continue;
             };
 
@@ -268,6 +441,270 @@ impl WindowMatcher {
         None
     }
 
+    /// Returns every window matching the selector, best match first
+    ///
+    /// Uses the same criteria and ranking as [`find_match`](Self::find_match);
+    /// an empty selector matches nothing.
+    pub fn all_matches(
+        &self,
+        selector: &WindowSelector,
+        windows: &[WindowInfo],
+    ) -> Vec<WindowHandle> {
+        self.ranked_matches(selector, windows)
+            .into_iter()
+            .map(|(window, _)| window.id.clone())
+            .collect()
+    }
+
+    /// Resolves the selector to one window, honouring its ambiguity policy
+    ///
+    /// Matching windows are ranked as in [`find_match`](Self::find_match),
+    /// then `selector.ambiguity` decides which one to return:
+    /// - `First` - the best-ranked window (same as `find_match`)
+    /// - `Error` - the best-ranked window, unless other windows tie with it
+    ///   and only the window ID would separate them
+    /// - `Index(n)` - the n-th (0-based) window in rank order
+    ///
+    /// # Arguments
+    ///
+    /// - `selector` - Window selector with criteria and ambiguity policy
+    /// - `windows` - List of windows to search
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(WindowHandle)` - The selected window
+    /// - `Err(CaptureError::WindowNotFound)` - No match, or fewer matches
+    ///   than the requested index
+    /// - `Err(CaptureError::AmbiguousWindow)` - Tied best matches under the
+    ///   `Error` policy; carries the tied windows in rank order
+    pub fn select_match(
+        &self,
+        selector: &WindowSelector,
+        windows: &[WindowInfo],
+    ) -> CaptureResult<WindowHandle> {
+        let ranked = self.ranked_matches(selector, windows);
+
+        let selected = match selector.ambiguity {
+            AmbiguityPolicy::First => ranked.first(),
+            AmbiguityPolicy::Index(index) => ranked.get(index),
+            AmbiguityPolicy::Error => {
+                let tied: Vec<WindowInfo> = match ranked.first() {
+                    Some(&(_, best)) => ranked
+                        .iter()
+                        .take_while(|(_, score)| *score == best)
+                        .map(|(window, _)| (*window).clone())
+                        .collect(),
+                    None => Vec::new(),
+                };
+                if tied.len() > 1 {
+                    tracing::debug!(
+                        "WindowMatcher: {} windows tie for selector {:?}",
+                        tied.len(),
+                        selector
+                    );
+                    return Err(CaptureError::AmbiguousWindow {
+                        selector: Box::new(selector.clone()),
+                        candidates: tied,
+                    });
+                }
+                ranked.first()
+            }
+        };
+
+        selected
+            .map(|(window, _)| window.id.clone())
+            .ok_or_else(|| CaptureError::WindowNotFound {
+                selector: Box::new(selector.clone()),
+            })
+    }
+
+    /// Resolves the selector like [`select_match`](Self::select_match), but
+    /// prefers the window it resolved to last time
+    ///
+    /// The remembered window is kept while it is still listed and still
+    /// matches the selector, even if another window now ranks higher, so
+    /// repeated captures do not flip between similar candidates. When it no
+    /// longer matches the selector is resolved afresh and the new result is
+    /// remembered; when it is no longer listed it is forgotten.
+    ///
+    /// `AmbiguityPolicy::Index` asks for a specific rank and
+    /// `AmbiguityPolicy::Error` must report ties on every call, so both
+    /// bypass the cache.
+    ///
+    /// # Arguments
+    ///
+    /// - `selector` - Window selector with criteria and ambiguity policy
+    /// - `windows` - List of windows to search
+    ///
+    /// # Returns
+    ///
+    /// Same as [`select_match`](Self::select_match).
+    pub fn select_match_sticky(
+        &self,
+        selector: &WindowSelector,
+        windows: &[WindowInfo],
+    ) -> CaptureResult<WindowHandle> {
+        if matches!(selector.ambiguity, AmbiguityPolicy::Index(_) | AmbiguityPolicy::Error)
+            || selector.is_empty()
+        {
+            return self.select_match(selector, windows);
+        }
+
+        let key = sticky_key(selector);
+        let remembered = STICKY_CACHE.lock().get(&key).cloned();
+
+        if let Some(handle) = remembered {
+            match windows.iter().find(|w| w.id == handle) {
+                Some(window) => {
+                    let prepared = PreparedSelector::new(selector, false);
+                    let fuzzy_matcher = SkimMatcherV2::default();
+                    if self
+                        .score_window(&prepared, window, &fuzzy_matcher)
+                        .is_some()
+                    {
+                        tracing::debug!("WindowMatcher: reusing sticky window {}", handle);
+                        return Ok(handle);
+                    }
+                }
+                None => {
+                    tracing::debug!("WindowMatcher: sticky window {} is gone", handle);
+                    STICKY_CACHE.lock().pop(&key);
+                }
+            }
+        }
+
+        let handle = self.select_match(selector, windows)?;
+        STICKY_CACHE.lock().put(key, handle.clone());
+        Ok(handle)
+    }
+
+    /// Explains how a selector resolves against a window list
+    ///
+    /// Evaluates every window against every criterion (without
+    /// short-circuiting) and ranks the matching windows with the same
+    /// tie-breaking as [`find_match`](Self::find_match), so
+    /// `explain_selector(..).selected == find_match(..)` always holds.
+    ///
+    /// # Arguments
+    ///
+    /// - `selector` - Window selector with title/class/exe criteria
+    /// - `windows` - List of windows to evaluate
+    ///
+    /// # Returns
+    ///
+    /// A [`SelectorExplanation`] with one candidate per window: matching
+    /// windows first in rank order, then non-matching windows by ID.
+    ///
+    /// # Examples
+    ///
+    /// ```ignore
+    /// let explanation = WindowMatcher::new().explain_selector(&selector, &windows);
+    /// for candidate in &explanation.candidates {
+    ///     println!("{:?} {:?} {:?}", candidate.rank, candidate.title_strategy, candidate.criteria);
+    /// }
+    /// ```
+    pub fn explain_selector(
+        &self,
+        selector: &WindowSelector,
+        windows: &[WindowInfo],
+    ) -> SelectorExplanation {
+        let prepared = PreparedSelector::new(selector, false);
+        let title_pattern = selector.title_substring_or_regex.as_deref();
+        let fuzzy_matcher = SkimMatcherV2::default();
+
+        let mut scored: Vec<(MatchCandidate, Option<MatchScore>)> = windows
+            .iter()
+            .map(|window| {
+                let title_score =
+                    title_pattern
+                        .zip(prepared.title_folded.as_deref())
+                        .map(|(p, folded)| {
+                            self.score_title(p, folded, &prepared, &fuzzy_matcher, window)
+                        });
+                let criteria = CriteriaMatch {
+                    title: title_score.map(|score| score.is_some()),
+                    class: selector
+                        .class
+                        .as_ref()
+                        .map(|class| window.class.eq_ignore_ascii_case(class)),
+                    exe: selector
+                        .exe
+                        .as_ref()
+                        .map(|exe| window.owner.eq_ignore_ascii_case(exe)),
+                    pid: selector.pid.map(|pid| window.pid == pid),
+                    window_id: selector
+                        .window_id
+                        .as_deref()
+                        .map(|id| handles_equal(&window.id, id)),
+                    exclude: (!prepared.exclude.is_empty()).then(|| !prepared.excludes(window)),
+                    any_of: (!prepared.any_of.is_empty()).then(|| {
+                        prepared
+                            .any_of
+                            .iter()
+                            .any(|alt| self.score_window(alt, window, &fuzzy_matcher).is_some())
+                    }),
+                };
+
+                // Scored exactly as find_match does, so the two always agree
+                let score = self.score_window(&prepared, window, &fuzzy_matcher);
+                let matched = score.is_some();
+
+                let candidate = MatchCandidate {
+                    window: window.clone(),
+                    matched,
+                    rank: None,
+                    selected: false,
+                    title_strategy: title_score.flatten().and_then(MatchScore::strategy),
+                    fuzzy_score: prepared
+                        .title_folded
+                        .as_deref()
+                        .and_then(|p| fuzzy_matcher.fuzzy_match(&prepared.fold_title(window), p)),
+                    criteria,
+                    decided_by: None,
+                };
+                (candidate, score)
+            })
+            .collect();
+
+        scored.sort_by(|(a, a_score), (b, b_score)| match (a_score, b_score) {
+            (Some(sa), Some(sb)) => Self::rank_order(*sa, &a.window.id, *sb, &b.window.id),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.window.id.cmp(&b.window.id),
+        });
+
+        let mut previous: Option<MatchScore> = None;
+        let mut match_count = 0;
+        for (rank, (candidate, score)) in scored.iter_mut().enumerate() {
+            let Some(score) = *score else {
+                break;
+            };
+            match_count += 1;
+            candidate.rank = Some(rank as u32 + 1);
+            candidate.selected = rank == 0;
+            candidate.decided_by = previous.map(|prev| Self::deciding_rule(prev, score));
+            previous = Some(score);
+        }
+
+        let candidates: Vec<MatchCandidate> = scored.into_iter().map(|(c, _)| c).collect();
+        let selected = candidates
+            .iter()
+            .find(|c| c.selected)
+            .map(|c| c.window.id.clone());
+
+        SelectorExplanation {
+            selector: selector.clone(),
+            selected,
+            match_count,
+            tie_break_order: vec![
+                TieBreakRule::TitleStrategy,
+                TieBreakRule::FuzzyScore,
+                TieBreakRule::WindowId,
+            ],
+            candidates,
+        }
+    }
+
     /// Checks if a single window matches all specified criteria (AND semantics).
     ///
     /// This is primarily intended for tests; production selection should use
@@ -287,33 +724,66 @@ impl WindowMatcher {
     /// - `false` - Window does not match one or more criteria
     #[cfg(test)]
     fn matches_window(&self, selector: &WindowSelector, window: &WindowInfo) -> bool {
-        let title_pattern = selector.title_substring_or_regex.as_deref();
-        let (title_regex, title_lower) = match title_pattern {
-            Some(p) => (self.try_compile_regex(p), Some(p.to_lowercase())),
-            None => (None, None),
-        };
+        let prepared = PreparedSelector::new(selector, false);
         let fuzzy_matcher = SkimMatcherV2::default();
 
-        self.score_window(
-            selector,
-            window,
-            title_pattern,
-            title_lower.as_deref(),
-            title_regex.as_ref(),
-            &fuzzy_matcher,
-        )
-        .is_some()
+        self.score_window(&prepared, window, &fuzzy_matcher)
+            .is_some()
     }
 
-    fn score_window(
+    /// Returns every matching window with its score, best first
+    fn ranked_matches<'a>(
         &self,
         selector: &WindowSelector,
+        windows: &'a [WindowInfo],
+    ) -> Vec<(&'a WindowInfo, MatchScore)> {
+        if selector.is_empty() {
+            return Vec::new();
+        }
+
+        let prepared = PreparedSelector::new(selector, false);
+        let fuzzy_matcher = SkimMatcherV2::default();
+
+        let mut ranked: Vec<(&WindowInfo, MatchScore)> = windows
+            .iter()
+            .filter_map(|window| {
+                self.score_window(&prepared, window, &fuzzy_matcher)
+                    .map(|score| (window, score))
+            })
+            .collect();
+        ranked.sort_by(|(a, a_score), (b, b_score)| {
+            Self::rank_order(*a_score, &a.id, *b_score, &b.id)
+        });
+        ranked
+    }
+
+    fn score_window(
+        &self,
+        prepared: &PreparedSelector<'_>,
         window: &WindowInfo,
-        title_pattern: Option<&str>,
-        title_pattern_lower: Option<&str>,
-        title_regex: Option<&regex::Regex>,
         fuzzy_matcher: &SkimMatcherV2,
     ) -> Option<MatchScore> {
+        let selector = prepared.selector;
+
+        // An empty selector (or empty any_of alternative) matches nothing
+        if selector.is_empty() {
+            return None;
+        }
+
+        // Window ID match (if specified) - numeric IDs compare by value
+        if let Some(ref id) = selector.window_id {
+            if !handles_equal(&window.id, id) {
+                return None;
+            }
+        }
+
+        // PID match (if specified)
+        if let Some(pid) = selector.pid {
+            if window.pid != pid {
+                return None;
+            }
+        }
+
         // Class match (if specified) - case-insensitive exact match
         if let Some(ref class) = selector.class {
             if !window.class.eq_ignore_ascii_case(class) {
@@ -328,34 +798,57 @@ impl WindowMatcher {
             }
         }
 
+        // Exclusions veto the window regardless of other criteria
+        if prepared.excludes(window) {
+            return None;
+        }
+
         // Title match (if specified)
-        let title_score = match (title_pattern, title_pattern_lower) {
-            (Some(pattern), Some(pattern_lower)) => {
-                self.score_title(pattern, pattern_lower, title_regex, fuzzy_matcher, window)?
+        let title_pattern = selector.title_substring_or_regex.as_deref();
+        let title_score = match (title_pattern, prepared.title_folded.as_deref()) {
+            (Some(pattern), Some(pattern_folded)) => {
+                self.score_title(pattern, pattern_folded, prepared, fuzzy_matcher, window)?
             }
             (None, None) => MatchScore {
                 title_rank: 0,
                 fuzzy_score: 0,
             },
             _ => {
-                // Should be impossible: title_pattern and title_pattern_lower are built together.
+                // Should be impossible: title_pattern and title_folded are built together.
                 return None;
             }
         };
 
-        Some(title_score)
+        if prepared.any_of.is_empty() {
+            return Some(title_score);
+        }
+
+        // At least one alternative must match; the best one can raise the rank
+        let alternative = prepared
+            .any_of
+            .iter()
+            .filter_map(|alt| self.score_window(alt, window, fuzzy_matcher))
+            .max_by_key(|score| (score.title_rank, score.fuzzy_score))?;
+
+        if (alternative.title_rank, alternative.fuzzy_score)
+            > (title_score.title_rank, title_score.fuzzy_score)
+        {
+            Some(alternative)
+        } else {
+            Some(title_score)
+        }
     }
 
     fn score_title(
         &self,
         pattern: &str,
-        pattern_lower: &str,
-        compiled: Option<&regex::Regex>,
+        pattern_folded: &str,
+        prepared: &PreparedSelector<'_>,
         fuzzy_matcher: &SkimMatcherV2,
         window: &WindowInfo,
     ) -> Option<MatchScore> {
-        // Strategy 1: Regex match (strongest)
-        if let Some(regex) = compiled {
+        // Strategy 1: Regex match (strongest), always on the raw title
+        if let Some(regex) = prepared.title_regex.as_ref() {
             if regex.is_match(&window.title) {
                 tracing::trace!("Title matched via regex: '{}'", window.title);
                 return Some(MatchScore {
@@ -365,8 +858,11 @@ impl WindowMatcher {
             }
         }
 
+        // Strategies 2 and 3 compare Unicode-folded text
+        let title_folded = prepared.fold_title(window);
+
         // Strategy 2: Substring match
-        if window.title.to_lowercase().contains(pattern_lower) {
+        if title_folded.contains(pattern_folded) {
             tracing::trace!("Title matched via substring: '{}'", window.title);
             return Some(MatchScore {
                 title_rank: 2,
@@ -375,7 +871,7 @@ impl WindowMatcher {
         }
 
         // Strategy 3: Fuzzy match (weakest)
-        if let Some(score) = fuzzy_matcher.fuzzy_match(&window.title, pattern) {
+        if let Some(score) = fuzzy_matcher.fuzzy_match(&title_folded, pattern_folded) {
             if score >= FUZZY_THRESHOLD {
                 tracing::trace!(
                     "Fuzzy match: '{}' vs '{}' (score: {})",
@@ -393,37 +889,35 @@ impl WindowMatcher {
         None
     }
 
-    /// Attempts to compile a regex pattern with safety limits and caching.
-    ///
-    /// Uses thread-local caching to avoid recompilation of the same pattern.
-    ///
-    /// Returns `None` if:
-    /// - Pattern is too large (>1MB)
-    /// - Pattern has too many repetition operators (>10)
-    /// - Pattern compiles to a DFA that's too large (>10MB)
-    /// - Pattern is invalid regex syntax
-    fn try_compile_regex(&self, pattern: &str) -> Option<regex::Regex> {
-        get_or_compile_regex(pattern)
-    }
-
     fn is_better_match(
         candidate: MatchScore,
         candidate_id: &str,
         best: MatchScore,
         best_id: &str,
     ) -> bool {
+        Self::rank_order(candidate, candidate_id, best, best_id) == Ordering::Less
+    }
+
+    /// Orders two matching windows, best first
+    fn rank_order(a: MatchScore, a_id: &str, b: MatchScore, b_id: &str) -> Ordering {
         // Primary: stronger title strategy
-        if candidate.title_rank != best.title_rank {
-            return candidate.title_rank > best.title_rank;
-        }
+        b.title_rank
+            .cmp(&a.title_rank)
+            // Secondary: higher fuzzy score (only meaningful for fuzzy matches)
+            .then(b.fuzzy_score.cmp(&a.fuzzy_score))
+            // Final: deterministic stable tie-breaker
+            .then_with(|| a_id.cmp(b_id))
+    }
 
-        // Secondary: higher fuzzy score (only meaningful for fuzzy matches)
-        if candidate.fuzzy_score != best.fuzzy_score {
-            return candidate.fuzzy_score > best.fuzzy_score;
+    /// Returns the rule that ranks `worse` behind `better`
+    fn deciding_rule(better: MatchScore, worse: MatchScore) -> TieBreakRule {
+        if better.title_rank != worse.title_rank {
+            TieBreakRule::TitleStrategy
+        } else if better.fuzzy_score != worse.fuzzy_score {
+            TieBreakRule::FuzzyScore
+        } else {
+            TieBreakRule::WindowId
         }
-
-        // Final: deterministic stable tie-breaker
-        candidate_id < best_id
     }
 }
 
@@ -479,10 +973,10 @@ pub fn try_regex_match(pattern: &str, windows: &[WindowInfo]) -> Option<WindowHa
 /// - `Some(WindowHandle)` - First matching window
 /// - `None` - No match
 pub fn try_substring_match(substring: &str, windows: &[WindowInfo]) -> Option<WindowHandle> {
-    let substring_lower = substring.to_lowercase();
+    let substring_folded = fold_for_matching(substring, false);
 
     for window in windows {
-        if window.title.to_lowercase().contains(&substring_lower) {
+        if fold_for_matching(&window.title, false).contains(&substring_folded) {
             tracing::debug!("Substring matched window: {} (title: {})", window.id, window.title);
             return Some(window.id.clone());
         }
@@ -557,11 +1051,13 @@ pub fn try_exe_match(exe: &str, windows: &[WindowInfo]) -> Option<WindowHandle>
 /// - `None` - No match above threshold
 pub fn try_fuzzy_match(pattern: &str, windows: &[WindowInfo]) -> Option<WindowHandle> {
     let matcher = SkimMatcherV2::default();
+    let pattern_folded = fold_for_matching(pattern, false);
 
     let mut best_match: Option<(WindowHandle, i64)> = None;
 
     for window in windows {
-        if let Some(score) = matcher.fuzzy_match(&window.title, pattern) {
+        let title_folded = fold_for_matching(&window.title, false);
+        if let Some(score) = matcher.fuzzy_match(&title_folded, &pattern_folded) {
             if score >= FUZZY_THRESHOLD {
                 tracing::debug!(
                     "Fuzzy match candidate: {} (title: {}, score: {})",
@@ -790,6 +1286,7 @@ mod tests {
             title_substring_or_regex: Some("Firefox".to_string()),
             class: Some("Navigator".to_string()),
             exe: None,
+            ..Default::default()
         };
 
         // Should match window 1 (Firefox with Navigator class)
@@ -801,6 +1298,7 @@ mod tests {
             title_substring_or_regex: Some("Code".to_string()),
             class: Some("Navigator".to_string()),
             exe: None,
+            ..Default::default()
         };
         let result2 = matcher.find_match(&selector2, &windows);
         assert_eq!(result2, None);
@@ -814,6 +1312,7 @@ mod tests {
             title_substring_or_regex: Some("Visual Studio".to_string()),
             class: None,
             exe: Some("code".to_string()),
+            ..Default::default()
         };
 
         // Should match window 2 (VS Code with code exe)
@@ -825,6 +1324,7 @@ mod tests {
             title_substring_or_regex: Some("Visual Studio".to_string()),
             class: None,
             exe: Some("firefox".to_string()),
+            ..Default::default()
         };
         let result2 = matcher.find_match(&selector2, &windows);
         assert_eq!(result2, None);
@@ -838,6 +1338,7 @@ mod tests {
             title_substring_or_regex: None,
             class: Some("Code".to_string()),
             exe: Some("code".to_string()),
+            ..Default::default()
         };
 
         // Should match window 2 (Code class and code exe)
@@ -849,6 +1350,7 @@ mod tests {
             title_substring_or_regex: None,
             class: Some("Navigator".to_string()),
             exe: Some("code".to_string()),
+            ..Default::default()
         };
         let result2 = matcher.find_match(&selector2, &windows);
         assert_eq!(result2, None);
@@ -862,6 +1364,7 @@ mod tests {
             title_substring_or_regex: Some("Visual Studio Code".to_string()),
             class: Some("Code".to_string()),
             exe: Some("code".to_string()),
+            ..Default::default()
         };
 
         // Should match window 2 (all three match)
@@ -873,6 +1376,7 @@ mod tests {
             title_substring_or_regex: Some("Visual Studio Code".to_string()),
             class: Some("Code".to_string()),
             exe: Some("firefox".to_string()), // Wrong exe
+            ..Default::default()
         };
         let result2 = matcher.find_match(&selector2, &windows);
         assert_eq!(result2, None);
@@ -886,6 +1390,7 @@ mod tests {
             title_substring_or_regex: None,
             class: Some("CODE".to_string()), // Uppercase
             exe: None,
+            ..Default::default()
         };
 
         // Should match window 2 (case-insensitive class match)
@@ -901,6 +1406,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: Some("FIREFOX".to_string()), // Uppercase
+            ..Default::default()
         };
 
         // Should match window 1 (case-insensitive exe match)
@@ -927,6 +1433,7 @@ mod tests {
             title_substring_or_regex: Some("Visual.*Code".to_string()), // Regex pattern
             class: None,
             exe: None,
+            ..Default::default()
         };
 
         // Should match window 2 via regex
@@ -943,6 +1450,7 @@ mod tests {
             title_substring_or_regex: Some("[invalid(".to_string()), // Invalid regex
             class: None,
             exe: None,
+            ..Default::default()
         };
 
         // Should still match via substring fallback (if any window title contains "[invalid(")
@@ -955,6 +1463,7 @@ mod tests {
             title_substring_or_regex: Some("Studio".to_string()),
             class: None,
             exe: None,
+            ..Default::default()
         };
         let result2 = matcher.find_match(&selector2, &windows);
         assert_eq!(result2, Some("2".to_string()));
@@ -968,6 +1477,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: None,
+            ..Default::default()
         };
 
         // Empty selector should return None
@@ -996,6 +1506,7 @@ mod tests {
             title_substring_or_regex: Some(large_pattern),
             class: None,
             exe: None,
+            ..Default::default()
         };
 
         // Should fallback to substring match (which will also fail for such a large pattern)
@@ -1013,6 +1524,7 @@ mod tests {
             title_substring_or_regex: Some("Firefox".to_string()),
             class: Some("Navigator".to_string()),
             exe: Some("firefox".to_string()),
+            ..Default::default()
         };
 
         // All criteria match
@@ -1023,6 +1535,7 @@ mod tests {
             title_substring_or_regex: Some("Chrome".to_string()),
             class: Some("Navigator".to_string()),
             exe: Some("firefox".to_string()),
+            ..Default::default()
         };
         assert!(!matcher.matches_window(&selector2, &window));
 
@@ -1031,6 +1544,7 @@ mod tests {
             title_substring_or_regex: Some("Firefox".to_string()),
             class: Some("Chrome".to_string()),
             exe: Some("firefox".to_string()),
+            ..Default::default()
         };
         assert!(!matcher.matches_window(&selector3, &window));
 
@@ -1039,6 +1553,7 @@ mod tests {
             title_substring_or_regex: Some("Firefox".to_string()),
             class: Some("Navigator".to_string()),
             exe: Some("chrome".to_string()),
+            ..Default::default()
         };
         assert!(!matcher.matches_window(&selector4, &window));
     }
@@ -1125,4 +1640,540 @@ mod tests {
         let regex = compile_regex_with_limits("file-name-pattern*");
         assert!(regex.is_some());
     }
+
+    #[test]
+    fn test_explain_selector_agrees_with_find_match() {
+        let matcher = WindowMatcher::new();
+        let windows = sample_windows();
+
+        for pattern in ["Firefox", "o", "Terminal.*", "vsc", "Nonexistent"] {
+            let selector = WindowSelector::by_title(pattern);
+            let explanation = matcher.explain_selector(&selector, &windows);
+            assert_eq!(
+                explanation.selected,
+                matcher.find_match(&selector, &windows),
+                "pattern '{}'",
+                pattern
+            );
+            assert_eq!(explanation.candidates.len(), windows.len());
+        }
+    }
+
+    #[test]
+    fn test_explain_selector_ranks_and_tie_breaks() {
+        let matcher = WindowMatcher::new();
+        let windows = vec![
+            make_window("b", "Editor - notes", "Code", "code"),
+            make_window("a", "Editor - todo", "Code", "code"),
+            make_window("c", "Terminal", "Alacritty", "alacritty"),
+        ];
+
+        let explanation = matcher.explain_selector(&WindowSelector::by_title("editor"), &windows);
+
+        assert_eq!(explanation.match_count, 2);
+        assert_eq!(explanation.selected.as_deref(), Some("a"));
+
+        let first = &explanation.candidates[0];
+        assert_eq!((first.window.id.as_str(), first.rank), ("a", Some(1)));
+        assert!(first.selected);
+        // "editor" is also a valid regex, which outranks substring
+        assert_eq!(first.title_strategy, Some(MatchStrategy::Regex));
+        assert_eq!(first.decided_by, None);
+
+        let second = &explanation.candidates[1];
+        assert_eq!((second.window.id.as_str(), second.rank), ("b", Some(2)));
+        assert_eq!(second.decided_by, Some(TieBreakRule::WindowId));
+
+        let third = &explanation.candidates[2];
+        assert!(!third.matched);
+        assert_eq!(third.rank, None);
+        assert_eq!(third.criteria.title, Some(false));
+    }
+
+    #[test]
+    fn test_explain_selector_reports_each_criterion() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector {
+            title_substring_or_regex: Some("Firefox".to_string()),
+            class: Some("Code".to_string()),
+            exe: None,
+            ..Default::default()
+        };
+
+        let explanation = matcher.explain_selector(&selector, &sample_windows());
+        assert_eq!(explanation.selected, None);
+
+        let firefox = explanation
+            .candidates
+            .iter()
+            .find(|c| c.window.id == "1")
+            .unwrap();
+        assert_eq!(firefox.criteria.title, Some(true));
+        assert_eq!(firefox.criteria.class, Some(false));
+        assert_eq!(firefox.criteria.exe, None);
+        assert!(!firefox.matched);
+    }
+
+    #[test]
+    fn test_explain_selector_strategy_tie_break() {
+        let matcher = WindowMatcher::new();
+        // As a regex "a+b" matches "aab" but not the literal "a+b", which
+        // only matches as a substring
+        let windows = vec![
+            make_window("1", "calc a+b", "Calc", "calc"),
+            make_window("2", "aab", "Editor", "editor"),
+        ];
+        let explanation = matcher.explain_selector(&WindowSelector::by_title("a+b"), &windows);
+
+        assert_eq!(explanation.selected.as_deref(), Some("2"));
+        assert_eq!(explanation.candidates[0].title_strategy, Some(MatchStrategy::Regex));
+        assert_eq!(explanation.candidates[1].title_strategy, Some(MatchStrategy::Substring));
+        assert_eq!(explanation.candidates[1].decided_by, Some(TieBreakRule::TitleStrategy));
+    }
+
+    fn github_windows() -> Vec<WindowInfo> {
+        vec![
+            make_window("b", "GitHub - Mozilla Firefox", "Navigator", "firefox"),
+            make_window("a", "GitHub - Mozilla Firefox", "Navigator", "firefox"),
+            make_window("c", "Terminal", "Alacritty", "alacritty"),
+        ]
+    }
+
+    #[test]
+    fn test_all_matches_returns_every_match_ranked() {
+        let matcher = WindowMatcher::new();
+        let windows = github_windows();
+        let selector = WindowSelector::by_title("GitHub");
+
+        let all = matcher.all_matches(&selector, &windows);
+        assert!(all.len() > 1);
+        assert_eq!(all.first(), matcher.find_match(&selector, &windows).as_ref());
+        assert!(
+            matcher
+                .all_matches(&WindowSelector::default(), &windows)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_select_match_first_policy_agrees_with_find_match() {
+        let matcher = WindowMatcher::new();
+        let windows = github_windows();
+        let selector = WindowSelector::by_title("GitHub");
+
+        assert_eq!(
+            matcher.select_match(&selector, &windows).ok(),
+            matcher.find_match(&selector, &windows)
+        );
+        assert!(matches!(
+            matcher.select_match(&WindowSelector::by_title("Nonexistent"), &windows),
+            Err(CaptureError::WindowNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_select_match_error_policy_reports_tied_candidates() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector {
+            ambiguity: AmbiguityPolicy::Error,
+            ..WindowSelector::by_title("GitHub")
+        };
+
+        let Err(CaptureError::AmbiguousWindow { candidates, .. }) =
+            matcher.select_match(&selector, &github_windows())
+        else {
+            panic!("expected AmbiguousWindow");
+        };
+        let ids: Vec<&str> = candidates.iter().map(|w| w.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_select_match_error_policy_accepts_clear_winner() {
+        let matcher = WindowMatcher::new();
+        // The regex match outranks the substring match, so there is no tie
+        let windows = vec![
+            make_window("1", "calc a+b", "Calc", "calc"),
+            make_window("2", "aab", "Editor", "editor"),
+        ];
+        let selector = WindowSelector {
+            ambiguity: AmbiguityPolicy::Error,
+            ..WindowSelector::by_title("a+b")
+        };
+
+        assert_eq!(matcher.select_match(&selector, &windows).unwrap(), "2");
+    }
+
+    #[test]
+    fn test_select_match_index_policy() {
+        let matcher = WindowMatcher::new();
+        let windows = github_windows();
+        let by_index = |index| WindowSelector {
+            ambiguity: AmbiguityPolicy::Index(index),
+            ..WindowSelector::by_title("GitHub")
+        };
+
+        assert_eq!(matcher.select_match(&by_index(0), &windows).unwrap(), "a");
+        assert_eq!(matcher.select_match(&by_index(1), &windows).unwrap(), "b");
+        assert!(matches!(
+            matcher.select_match(&by_index(2), &windows),
+            Err(CaptureError::WindowNotFound { .. })
+        ));
+    }
+
+    // The sticky cache is global, so each sticky test uses its own class to
+    // keep its selector keys apart from tests running in parallel
+
+    #[test]
+    fn test_select_match_sticky_prefers_remembered_window() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector {
+            class: Some("StickyPrefer".to_string()),
+            ..WindowSelector::by_title("Terminal")
+        };
+        let mut windows = vec![
+            make_window("t2", "Terminal - zsh", "StickyPrefer", "alacritty"),
+            make_window("t3", "Terminal - vim", "StickyPrefer", "alacritty"),
+        ];
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "t2");
+
+        // A new window that would win the tie-break does not steal the target
+        windows.push(make_window("t1", "Terminal - htop", "StickyPrefer", "alacritty"));
+        assert_eq!(matcher.select_match(&selector, &windows).unwrap(), "t1");
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "t2");
+
+        // Case and width differences share the same entry
+        let shouted = WindowSelector {
+            class: Some("STICKYPREFER".to_string()),
+            ..WindowSelector::by_title("ＴＥＲＭＩＮＡＬ")
+        };
+        assert_eq!(matcher.select_match_sticky(&shouted, &windows).unwrap(), "t2");
+
+        // Once the remembered window stops matching, the selector re-resolves
+        windows[0].title = "Settings".to_string();
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "t1");
+        windows[0].title = "Terminal - zsh".to_string();
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "t1");
+    }
+
+    #[test]
+    fn test_select_match_sticky_forgets_destroyed_windows() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector::by_class("StickyForget");
+        let mut windows = vec![make_window("f2", "Editor", "StickyForget", "editor")];
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "f2");
+
+        windows.push(make_window("f1", "Editor", "StickyForget", "editor"));
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "f2");
+
+        forget_sticky_window("f2");
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "f1");
+
+        // A remembered window that disappears from the list is dropped too
+        windows.retain(|w| w.id != "f1");
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "f2");
+        assert!(matches!(
+            matcher.select_match_sticky(&selector, &[]),
+            Err(CaptureError::WindowNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_select_match_sticky_index_policy_bypasses_cache() {
+        let matcher = WindowMatcher::new();
+        let windows = vec![
+            make_window("i1", "Shell", "StickyIndex", "sh"),
+            make_window("i2", "Shell", "StickyIndex", "sh"),
+        ];
+        let selector = WindowSelector {
+            ambiguity: AmbiguityPolicy::Index(1),
+            ..WindowSelector::by_class("StickyIndex")
+        };
+        assert_eq!(matcher.select_match_sticky(&selector, &windows).unwrap(), "i2");
+
+        let first = WindowSelector::by_class("StickyIndex");
+        assert_eq!(matcher.select_match_sticky(&first, &windows).unwrap(), "i1");
+    }
+
+    #[test]
+    fn test_select_match_sticky_error_policy_reports_ties() {
+        let matcher = WindowMatcher::new();
+        let windows = vec![
+            make_window("e1", "Shell", "StickyError", "sh"),
+            make_window("e2", "Shell", "StickyError", "sh"),
+        ];
+        let selector = WindowSelector {
+            ambiguity: AmbiguityPolicy::Error,
+            ..WindowSelector::by_class("StickyError")
+        };
+        assert_eq!(
+            matcher
+                .select_match_sticky(&selector, &windows[..1])
+                .unwrap(),
+            "e1"
+        );
+        // A second match appearing is a tie, even though e1 resolved before
+        assert!(matches!(
+            matcher.select_match_sticky(&selector, &windows),
+            Err(CaptureError::AmbiguousWindow { .. })
+        ));
+    }
+
+    fn firefox_windows() -> Vec<WindowInfo> {
+        let mut private = make_window(
+            "60817415",
+            "GitHub - Mozilla Firefox Private Browsing",
+            "Navigator",
+            "firefox",
+        );
+        private.pid = 4242;
+        vec![
+            private,
+            make_window("60817416", "GitHub - Mozilla Firefox", "Navigator", "firefox"),
+            make_window("60817417", "Visual Studio Code", "Code", "code"),
+        ]
+    }
+
+    #[test]
+    fn test_window_matcher_pid() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector {
+            pid: Some(4242),
+            ..Default::default()
+        };
+
+        assert_eq!(matcher.find_match(&selector, &firefox_windows()).as_deref(), Some("60817415"));
+    }
+
+    #[test]
+    fn test_window_matcher_window_id_hex_and_decimal() {
+        let matcher = WindowMatcher::new();
+        let windows = firefox_windows();
+
+        for id in ["60817416", "0x3a00008", "0X3A00008"] {
+            let selector = WindowSelector {
+                window_id: Some(id.to_string()),
+                ..Default::default()
+            };
+            assert_eq!(
+                matcher.find_match(&selector, &windows).as_deref(),
+                Some("60817416"),
+                "id '{}'",
+                id
+            );
+        }
+
+        // Non-numeric handles compare as strings
+        let windows = vec![make_window("mock-0x1", "Firefox", "Navigator", "firefox")];
+        let selector = WindowSelector {
+            window_id: Some("MOCK-0X1".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(matcher.find_match(&selector, &windows).as_deref(), Some("mock-0x1"));
+    }
+
+    #[test]
+    fn test_window_matcher_exclude() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector {
+            exclude: vec!["private browsing".to_string()],
+            ..WindowSelector::by_exe("firefox")
+        };
+
+        assert_eq!(matcher.find_match(&selector, &firefox_windows()).as_deref(), Some("60817416"));
+
+        // Exclusion alone selects nothing
+        let exclude_only = WindowSelector {
+            exclude: vec!["Code".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(matcher.find_match(&exclude_only, &firefox_windows()), None);
+    }
+
+    #[test]
+    fn test_window_matcher_any_of() {
+        let matcher = WindowMatcher::new();
+        let windows = firefox_windows();
+        let selector = WindowSelector {
+            any_of: vec![
+                WindowSelector::by_class("Code"),
+                WindowSelector::by_exe("zed"),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(matcher.find_match(&selector, &windows).as_deref(), Some("60817417"));
+
+        // any_of combines with top-level criteria using AND
+        let selector = WindowSelector {
+            any_of: vec![WindowSelector::by_class("Code")],
+            ..WindowSelector::by_exe("firefox")
+        };
+        assert_eq!(matcher.find_match(&selector, &windows), None);
+
+        // An empty alternative matches nothing
+        let selector = WindowSelector {
+            any_of: vec![WindowSelector::default()],
+            ..Default::default()
+        };
+        assert_eq!(matcher.find_match(&selector, &windows), None);
+    }
+
+    #[test]
+    fn test_window_matcher_any_of_title_ranks_candidates() {
+        let matcher = WindowMatcher::new();
+        // The regex alternative matches only "aab", which must outrank the
+        // substring-only match on "calc a+b"
+        let windows = vec![
+            make_window("1", "calc a+b", "Calc", "calc"),
+            make_window("2", "aab", "Editor", "editor"),
+        ];
+        let selector = WindowSelector {
+            any_of: vec![WindowSelector::by_title("a+b")],
+            ..Default::default()
+        };
+
+        assert_eq!(matcher.find_match(&selector, &windows).as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_explain_selector_reports_extended_criteria() {
+        let matcher = WindowMatcher::new();
+        let selector = WindowSelector {
+            pid: Some(1234),
+            exclude: vec!["Private".to_string()],
+            any_of: vec![WindowSelector::by_exe("firefox")],
+            ..Default::default()
+        };
+
+        let explanation = matcher.explain_selector(&selector, &firefox_windows());
+        assert_eq!(explanation.selected.as_deref(), Some("60817416"));
+        assert_eq!(explanation.match_count, 1);
+
+        let private = explanation
+            .candidates
+            .iter()
+            .find(|c| c.window.id == "60817415")
+            .unwrap();
+        assert_eq!(private.criteria.pid, Some(false));
+        assert_eq!(private.criteria.exclude, Some(false));
+        assert_eq!(private.criteria.any_of, Some(true));
+        assert_eq!(private.criteria.window_id, None);
+
+        let code = explanation
+            .candidates
+            .iter()
+            .find(|c| c.window.id == "60817417")
+            .unwrap();
+        assert_eq!(code.criteria.any_of, Some(false));
+        assert!(!code.matched);
+    }
+
+    fn multilingual_windows() -> Vec<WindowInfo> {
+        vec![
+            make_window("1", "Résumé — Café.docx - LibreOffice Writer", "libreoffice", "soffice"),
+            make_window("2", "Ｆｉｒｅｆｏｘ Ｎｉｇｈｔｌｙ", "Navigator", "firefox"),
+            make_window("3", "Straße der Pariser Kommune - Karte", "Maps", "maps"),
+            make_window("4", "\u{0422}\u{0435}rminal - zsh", "Alacritty", "alacritty"),
+            make_window("5", "🎵 Spotify \u{2764}\u{FE0F} Playlist", "Spotify", "spotify"),
+            make_window("6", "設定 - システム環境", "Settings", "gnome-control-center"),
+        ]
+    }
+
+    #[test]
+    fn test_window_matcher_folds_diacritics_and_width() {
+        let matcher = WindowMatcher::new();
+        let windows = multilingual_windows();
+
+        for (pattern, expected) in [
+            ("resume", "1"),
+            ("CAFÉ.DOCX", "1"),
+            ("firefox nightly", "2"),
+            ("STRASSE", "3"),
+            ("strasse der pariser", "3"),
+        ] {
+            let result = matcher.find_match(&WindowSelector::by_title(pattern), &windows);
+            assert_eq!(result.as_deref(), Some(expected), "pattern {pattern:?}");
+        }
+    }
+
+    #[test]
+    fn test_window_matcher_folds_homoglyphs_emoji_and_cjk() {
+        let matcher = WindowMatcher::new();
+        let windows = multilingual_windows();
+
+        // Cyrillic Т and е in the title still match a Latin pattern
+        let result = matcher.find_match(&WindowSelector::by_title("Terminal - zsh"), &windows);
+        assert_eq!(result.as_deref(), Some("4"));
+
+        // Emoji variation selectors do not break substring matches
+        let result = matcher.find_match(&WindowSelector::by_title("spotify ❤ playlist"), &windows);
+        assert_eq!(result.as_deref(), Some("5"));
+
+        let result = matcher.find_match(&WindowSelector::by_title("システム"), &windows);
+        assert_eq!(result.as_deref(), Some("6"));
+    }
+
+    #[test]
+    fn test_window_matcher_strict_keeps_diacritics_and_homoglyphs() {
+        let matcher = WindowMatcher::new();
+        let windows = multilingual_windows();
+        let strict = |pattern: &str| WindowSelector {
+            strict: Some(true),
+            ..WindowSelector::by_title(pattern)
+        };
+
+        // Width and case are still folded
+        assert_eq!(
+            matcher
+                .find_match(&strict("FIREFOX NIGHTLY"), &windows)
+                .as_deref(),
+            Some("2")
+        );
+        assert_eq!(matcher.find_match(&strict("straße"), &windows).as_deref(), Some("3"));
+        assert_eq!(
+            matcher
+                .find_match(&strict("café.docx"), &windows)
+                .as_deref(),
+            Some("1")
+        );
+
+        // Accents and lookalike letters are significant
+        assert_eq!(matcher.find_match(&strict("resume —"), &windows), None);
+        assert_eq!(matcher.find_match(&strict("Terminal - zsh"), &windows), None);
+
+        // Alternatives inherit strictness unless they set their own
+        let any_of = |alt: WindowSelector| WindowSelector {
+            strict: Some(true),
+            any_of: vec![alt],
+            ..Default::default()
+        };
+        assert_eq!(
+            matcher.find_match(&any_of(WindowSelector::by_title("resume —")), &windows),
+            None
+        );
+        let lenient = WindowSelector {
+            strict: Some(false),
+            ..WindowSelector::by_title("resume —")
+        };
+        assert!(matcher.find_match(&any_of(lenient), &windows).is_some());
+    }
+
+    #[test]
+    fn test_window_matcher_exclude_is_folded() {
+        let matcher = WindowMatcher::new();
+        let windows = multilingual_windows();
+        let selector = WindowSelector {
+            exclude: vec!["RESUME".to_string()],
+            ..WindowSelector::by_class("libreoffice")
+        };
+
+        assert_eq!(matcher.find_match(&selector, &windows), None);
+    }
+
+    #[test]
+    fn test_substring_and_fuzzy_helpers_fold_text() {
+        let windows = multilingual_windows();
+        assert_eq!(try_substring_match("ｃａｆｅ", &windows).as_deref(), Some("1"));
+        assert_eq!(try_fuzzy_match("firefx nightly", &windows).as_deref(), Some("2"));
+    }
 }
@@ -14,6 +14,12 @@
 //!   Terminal)
 //! - **Fuzzy Matching:** Supports window selector matching by title (case-
 //!   insensitive), class, and executable name
+//! - **Pointer Simulation:** Reports a configurable pointer position and
+//!   resolves the topmost mock window under it
+//! - **Event Injection:** Push synthetic window lifecycle events to
+//!   subscribers via `emit_event()`
+//! - **Window State:** Reports mock window geometry; windows can be marked
+//!   unmapped via `with_unmapped_window()`
 //! - **Configurable Delay:** Simulate async operation delays for testing
 //! - **Error Injection:** Inject errors to test error handling paths
 //! - **Full Capabilities:** Supports all capture features (cursor, region,
@@ -94,17 +100,24 @@
 use std::time::Duration;
 
 use async_trait::async_trait;
-use tokio::time::sleep;
+use tokio::{sync::broadcast, time::sleep};
 
 use super::{
-    BackendCapabilities, ImageBuffer, ScreenCapture, WindowEnumerator, WindowMatcher,
-    WindowResolver,
+    BackendCapabilities, ImageBuffer, PointerResolver, ScreenCapture, WindowEnumerator,
+    WindowEventSource, WindowEventStream, WindowInspector, WindowMatcher, WindowResolver,
+    matching::forget_sticky_window,
 };
 use crate::{
     error::{CaptureError, CaptureResult},
-    model::{BackendType, CaptureOptions, WindowHandle, WindowInfo, WindowSelector},
+    model::{
+        BackendType, CaptureOptions, PointerInfo, Region, WindowEvent, WindowEventKind,
+        WindowHandle, WindowInfo, WindowSelector, WindowState,
+    },
 };
 
+/// Capacity of the mock event broadcast channel
+const MOCK_EVENT_CHANNEL_CAPACITY: usize = 64;
+
 /// Mock capture backend for testing and development
 ///
 /// Implements the capability traits (`WindowEnumerator`, `WindowResolver`,
@@ -127,15 +140,30 @@ pub struct MockBackend {
     error_injection: Option<CaptureError>,
     /// Predefined mock windows
     windows: Vec<WindowInfo>,
+    /// Screen geometry of each mock window, in stacking order (bottom first)
+    geometry: Vec<(WindowHandle, Region)>,
+    /// Windows reported as unmapped by `window_state()`
+    unmapped: Vec<WindowHandle>,
+    /// Simulated pointer state
+    pointer: PointerInfo,
+    /// Broadcast channel feeding window event subscribers
+    events: broadcast::Sender<WindowEvent>,
+    /// Whether `resolve` prefers each selector's previously resolved window
+    sticky: bool,
+    /// Screen position of the captured display
+    display_origin: (i32, i32),
 }
 
 impl MockBackend {
     /// Creates a new MockBackend with default mock windows
     ///
-    /// The backend includes 3 predefined windows:
-    /// - Firefox (Navigator class, pid 1000)
-    /// - VSCode (Code class, pid 2000)
-    /// - Terminal (Alacritty class, pid 3000)
+    /// The backend includes 3 predefined windows, stacked bottom to top:
+    /// - Firefox (Navigator class, pid 1000) at 1280x1080+0+0
+    /// - VSCode (Code class, pid 2000) at 1280x1080+640+0
+    /// - Terminal (Alacritty class, pid 3000) at 640x540+1280+540
+    ///
+    /// The simulated pointer rests at (960, 540) with the `left_ptr` cursor,
+    /// which places it over the VSCode window.
     ///
     /// # Examples
     ///
@@ -149,7 +177,107 @@ impl MockBackend {
             delay: None,
             error_injection: None,
             windows: Self::create_mock_windows(),
+            geometry: Self::create_mock_geometry(),
+            unmapped: Vec::new(),
+            pointer: PointerInfo::new(960, 540, Some("left_ptr".to_string())),
+            events: broadcast::channel(MOCK_EVENT_CHANNEL_CAPACITY).0,
+            sticky: false,
+            display_origin: (0, 0),
+        }
+    }
+
+    /// Pushes a window event to all current subscribers
+    ///
+    /// Returns the number of subscribers that received the event. Events
+    /// emitted while nobody is subscribed are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::{
+    ///     capture::mock::MockBackend,
+    ///     model::{WindowEvent, WindowEventKind},
+    /// };
+    ///
+    /// let backend = MockBackend::new();
+    /// let delivered = backend.emit_event(WindowEvent::new(WindowEventKind::Created, "mock-0x9", None));
+    /// assert_eq!(delivered, 0); // no subscribers yet
+    /// ```
+    pub fn emit_event(&self, event: WindowEvent) -> usize {
+        if event.kind == WindowEventKind::Destroyed {
+            forget_sticky_window(&event.window);
         }
+        self.events.send(event).unwrap_or(0)
+    }
+
+    /// Sets the simulated pointer position and cursor shape
+    ///
+    /// # Arguments
+    ///
+    /// * `x` - Pointer X coordinate
+    /// * `y` - Pointer Y coordinate
+    /// * `cursor_name` - Cursor shape name reported by `pointer_info()`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::mock::MockBackend;
+    ///
+    /// let backend = MockBackend::new().with_pointer(100, 200, Some("hand2"));
+    /// ```
+    pub fn with_pointer(mut self, x: i32, y: i32, cursor_name: Option<&str>) -> Self {
+        self.pointer = PointerInfo::new(x, y, cursor_name.map(str::to_string));
+        self
+    }
+
+    /// Marks a mock window as unmapped (not viewable)
+    ///
+    /// The window is still listed and resolvable, but `window_state()`
+    /// reports `mapped: false` for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::mock::MockBackend;
+    ///
+    /// let backend = MockBackend::new().with_unmapped_window("mock-0x3");
+    /// ```
+    pub fn with_unmapped_window(mut self, handle: &str) -> Self {
+        self.unmapped.push(handle.to_string());
+        self
+    }
+
+    /// Places the captured display at `(x, y)` on the screen
+    ///
+    /// The display is at the origin by default.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::mock::MockBackend;
+    ///
+    /// let backend = MockBackend::new().with_display_origin(1920, 0);
+    /// ```
+    pub fn with_display_origin(mut self, x: i32, y: i32) -> Self {
+        self.display_origin = (x, y);
+        self
+    }
+
+    /// Makes `resolve` prefer the window a selector resolved to last time
+    ///
+    /// See [`WindowMatcher::select_match_sticky`]. Off by default; a
+    /// selector's `sticky` field overrides it either way.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::mock::MockBackend;
+    ///
+    /// let backend = MockBackend::new().with_sticky_resolution(true);
+    /// ```
+    pub fn with_sticky_resolution(mut self, enabled: bool) -> Self {
+        self.sticky = enabled;
+        self
     }
 
     /// Sets a configurable delay for all async operations
@@ -234,6 +362,15 @@ impl MockBackend {
         ]
     }
 
+    /// Creates the screen geometry for the predefined mock windows
+    fn create_mock_geometry() -> Vec<(WindowHandle, Region)> {
+        vec![
+            ("mock-0x1".to_string(), Region::new(0, 0, 1280, 1080)),
+            ("mock-0x2".to_string(), Region::new(640, 0, 1280, 1080)),
+            ("mock-0x3".to_string(), Region::new(1280, 540, 640, 540)),
+        ]
+    }
+
     /// Applies configured delay if set
     async fn apply_delay(&self) {
         if let Some(duration) = self.delay {
@@ -305,32 +442,34 @@ impl MockBackend {
                     feature: feature.clone(),
                     backend: *backend,
                 },
+                CaptureError::AmbiguousWindow {
+                    selector,
+                    candidates,
+                } => CaptureError::AmbiguousWindow {
+                    selector: selector.clone(),
+                    candidates: candidates.clone(),
+                },
+                CaptureError::PolicyDenied { target, reason } => CaptureError::PolicyDenied {
+                    target: target.clone(),
+                    reason: reason.clone(),
+                },
             });
         }
         Ok(())
     }
 
-    /// Finds a window matching the selector using WindowMatcher
-    ///
-    /// Uses `WindowMatcher` with AND semantics for consistency with other backends.
-    fn find_matching_window(&self, selector: &WindowSelector) -> Option<&WindowInfo> {
-        let matcher = WindowMatcher::new();
-        matcher
-            .find_match(selector, &self.windows)
-            .and_then(|handle| self.windows.iter().find(|w| w.id == handle))
-    }
-
     /// Validates that a window handle exists in the mock window list
     fn validate_handle(&self, handle: &WindowHandle) -> CaptureResult<()> {
         if self.windows.iter().any(|w| w.id == *handle) {
             Ok(())
         } else {
             Err(CaptureError::WindowNotFound {
-                selector: WindowSelector {
+                selector: Box::new(WindowSelector {
                     title_substring_or_regex: Some(format!("handle:{}", handle)),
                     class: None,
                     exe: None,
-                },
+                    ..Default::default()
+                }),
             })
         }
     }
@@ -380,11 +519,13 @@ impl WindowResolver for MockBackend {
         self.apply_delay().await;
         self.check_error_injection()?;
 
-        self.find_matching_window(selector)
-            .map(|window| window.id.clone())
-            .ok_or_else(|| CaptureError::WindowNotFound {
-                selector: selector.clone(),
-            })
+        // Uses `WindowMatcher` with AND semantics for consistency with other backends
+        let matcher = WindowMatcher::new();
+        if selector.sticky.unwrap_or(self.sticky) {
+            matcher.select_match_sticky(selector, &self.windows)
+        } else {
+            matcher.select_match(selector, &self.windows)
+        }
     }
 }
 
@@ -420,6 +561,80 @@ impl ScreenCapture for MockBackend {
         // Apply transformations
         self.apply_transformations(image, opts)
     }
+
+    async fn display_origin(&self, _display_id: Option<u32>) -> Option<(i32, i32)> {
+        Some(self.display_origin)
+    }
+}
+
+#[async_trait]
+impl PointerResolver for MockBackend {
+    async fn pointer_info(&self) -> CaptureResult<PointerInfo> {
+        self.apply_delay().await;
+        self.check_error_injection()?;
+        Ok(self.pointer.clone())
+    }
+
+    async fn window_at(&self, x: i32, y: i32) -> CaptureResult<WindowHandle> {
+        self.apply_delay().await;
+        self.check_error_injection()?;
+
+        // Topmost window wins: walk the stacking order from the top
+        self.geometry
+            .iter()
+            .rev()
+            .find(|(_, region)| region.contains(x, y))
+            .map(|(handle, _)| handle.clone())
+            .ok_or_else(|| CaptureError::WindowNotFound {
+                selector: Box::new(WindowSelector::by_title(format!("point:{},{}", x, y))),
+            })
+    }
+}
+
+#[async_trait]
+impl WindowInspector for MockBackend {
+    async fn window_state(&self, handle: &WindowHandle) -> CaptureResult<WindowState> {
+        self.apply_delay().await;
+        self.check_error_injection()?;
+        self.validate_handle(handle)?;
+
+        let geometry = self
+            .geometry
+            .iter()
+            .find(|(id, _)| id == handle)
+            .map(|(_, region)| *region)
+            .unwrap_or_else(|| Region::new(0, 0, 0, 0));
+        let mapped = !self.unmapped.contains(handle);
+
+        Ok(WindowState::new(mapped, geometry))
+    }
+
+    async fn stacking_order(&self) -> CaptureResult<Vec<WindowHandle>> {
+        self.apply_delay().await;
+        self.check_error_injection()?;
+
+        Ok(self.geometry.iter().map(|(id, _)| id.clone()).collect())
+    }
+}
+
+impl WindowEventSource for MockBackend {
+    fn subscribe(&self) -> CaptureResult<WindowEventStream> {
+        self.check_error_injection()?;
+
+        let receiver = self.events.subscribe();
+        let stream = futures::stream::unfold(receiver, |mut receiver| async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => return Some((event, receiver)),
+                    // A slow subscriber skips missed events rather than ending
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                }
+            }
+        });
+
+        Ok(Box::pin(stream))
+    }
 }
 
 impl BackendCapabilities for MockBackend {
@@ -577,6 +792,7 @@ mod tests {
             title_substring_or_regex: Some("Code".to_string()),
             class: Some("Code".to_string()),
             exe: None,
+            ..Default::default()
         };
         let handle = backend.resolve(&selector).await.unwrap();
         assert_eq!(handle, "mock-0x2");
@@ -725,6 +941,94 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_pointer_info_default() {
+        let backend = MockBackend::new();
+        let pointer = backend.pointer_info().await.unwrap();
+        assert_eq!((pointer.x, pointer.y), (960, 540));
+        assert_eq!(pointer.cursor_name.as_deref(), Some("left_ptr"));
+    }
+
+    #[tokio::test]
+    async fn test_window_at_respects_stacking_order() {
+        let backend = MockBackend::new();
+
+        // Only Firefox covers the far left
+        assert_eq!(backend.window_at(100, 100).await.unwrap(), "mock-0x1");
+        // Firefox and VSCode overlap; VSCode is above
+        assert_eq!(backend.window_at(700, 100).await.unwrap(), "mock-0x2");
+        // All three overlap; Terminal is topmost
+        assert_eq!(backend.window_at(1500, 800).await.unwrap(), "mock-0x3");
+    }
+
+    #[tokio::test]
+    async fn test_window_at_outside_all_windows() {
+        let backend = MockBackend::new();
+        let result = backend.window_at(5000, 5000).await;
+        assert!(matches!(result.unwrap_err(), CaptureError::WindowNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_window_under_pointer() {
+        let backend = MockBackend::new();
+        assert_eq!(backend.window_under_pointer().await.unwrap(), "mock-0x2");
+
+        let backend = MockBackend::new().with_pointer(1800, 1000, Some("xterm"));
+        assert_eq!(backend.window_under_pointer().await.unwrap(), "mock-0x3");
+    }
+
+    #[tokio::test]
+    async fn test_window_state_reports_geometry() {
+        let backend = MockBackend::new().with_unmapped_window("mock-0x3");
+
+        let state = backend.window_state(&"mock-0x2".to_string()).await.unwrap();
+        assert!(state.is_visible());
+        assert_eq!(state.geometry, Region::new(640, 0, 1280, 1080));
+
+        let state = backend.window_state(&"mock-0x3".to_string()).await.unwrap();
+        assert!(!state.mapped);
+    }
+
+    #[tokio::test]
+    async fn test_stacking_order_is_bottom_first() {
+        let backend = MockBackend::new();
+        assert_eq!(
+            backend.stacking_order().await.unwrap(),
+            vec!["mock-0x1", "mock-0x2", "mock-0x3"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_window_state_unknown_handle() {
+        let backend = MockBackend::new();
+        let result = backend.window_state(&"mock-0x99".to_string()).await;
+        assert!(matches!(result.unwrap_err(), CaptureError::WindowNotFound { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_emitted_events() {
+        use futures::StreamExt;
+
+        use crate::model::WindowEventKind;
+
+        let backend = MockBackend::new();
+        let mut first = backend.subscribe().unwrap();
+        let mut second = backend.subscribe().unwrap();
+
+        let delivered =
+            backend.emit_event(WindowEvent::new(WindowEventKind::Created, "mock-0x9", None));
+        assert_eq!(delivered, 2);
+
+        assert_eq!(first.next().await.unwrap().window, "mock-0x9");
+        assert_eq!(second.next().await.unwrap().kind, WindowEventKind::Created);
+    }
+
+    #[test]
+    fn test_subscribe_with_error_injection() {
+        let backend = MockBackend::new().with_error(CaptureError::WindowClosed);
+        assert!(backend.subscribe().is_err());
+    }
+
     #[test]
     fn test_capabilities() {
         let backend = MockBackend::new();
@@ -10,6 +10,9 @@
 //!
 //! - [`WindowEnumerator`] - List all capturable windows (X11, Windows)
 //! - [`WindowResolver`] - Resolve window selectors to handles (all backends)
+//! - [`PointerResolver`] - Pointer position and window-under-point (X11, Mock)
+//! - [`WindowEventSource`] - Window lifecycle event streams (X11, Mock)
+//! - [`WindowInspector`] - Window mapping state and geometry (X11, Mock)
 //! - [`ScreenCapture`] - Capture screenshots (all backends)
 //! - [`WaylandRestoreCapable`] - Wayland restore token workflow (Wayland only)
 //!
@@ -34,12 +37,12 @@
 //!
 //! ## Backend Capabilities
 //!
-//! | Backend | WindowEnumerator | WindowResolver | ScreenCapture | WaylandRestore |
-//! |---------|------------------|----------------|---------------|----------------|
-//! | Windows | ✓ | ✓ | ✓ | - |
-//! | X11     | ✓ | ✓ | ✓ | - |
-//! | Wayland | - | ✓ | ✓ | ✓ |
-//! | Mock    | ✓ | ✓ | ✓ | - |
+//! | Backend | WindowEnumerator | WindowResolver | PointerResolver | ScreenCapture | WaylandRestore |
+//! |---------|------------------|----------------|-----------------|---------------|----------------|
+//! | Windows | ✓ | ✓ | - | ✓ | - |
+//! | X11     | ✓ | ✓ | ✓ | ✓ | - |
+//! | Wayland | - | ✓ | - | ✓ | ✓ |
+//! | Mock    | ✓ | ✓ | ✓ | ✓ | - |
 //!
 //! # Core Types
 //!
@@ -51,12 +54,29 @@ use std::sync::Arc;
 
 use crate::error::CaptureResult;
 
+pub mod annotate;
+pub mod baseline;
+pub mod compare;
 pub mod composite;
 pub mod constants;
+pub mod delta;
+pub mod grid;
 pub mod image_buffer;
+pub mod locate;
+pub mod marks;
 pub mod matching;
 pub mod mock;
+pub mod normalize;
+pub mod overview;
+pub mod policy;
+pub mod query;
+pub mod record;
+pub mod redact;
+pub mod settle;
+pub mod targets;
+pub mod timelapse;
 pub mod traits;
+pub mod wait;
 
 #[cfg(target_os = "linux")]
 pub mod wayland_backend;
@@ -64,22 +84,35 @@ pub mod wayland_backend;
 #[cfg(target_os = "linux")]
 pub mod x11_backend;
 
+#[cfg(target_os = "linux")]
+mod x11_events;
+
 #[cfg(target_os = "windows")]
 pub mod windows_backend;
 
-pub use composite::CompositeBackend;
+pub use baseline::BaselineStore;
+pub use compare::{compare_images, measure_changes};
 pub use composite::composite_from_mock;
 #[cfg(target_os = "windows")]
 pub use composite::composite_from_windows;
+pub use composite::{CompositeBackend, SettledCapture};
 #[cfg(target_os = "linux")]
 pub use composite::{composite_from_wayland, composite_from_x11};
+pub use delta::FrameCache;
 pub use image_buffer::ImageBuffer;
+pub use locate::locate_image;
+pub use marks::{compute_window_marks, draw_window_marks};
 pub use matching::WindowMatcher;
 pub use mock::MockBackend;
+pub use policy::{CapturePolicy, Permission, PolicyResolver};
+pub use query::parse_selector_query;
+pub use redact::RedactionPolicy;
+pub use targets::{TargetAlias, TargetRegistry};
 pub use traits::{
-    BackendCapabilities, PrimeConsentResult, ScreenCapture, WaylandRestoreCapable,
-    WindowEnumerator, WindowResolver,
+    BackendCapabilities, PointerResolver, PrimeConsentResult, ScreenCapture, WaylandRestoreCapable,
+    WindowEnumerator, WindowEventSource, WindowEventStream, WindowInspector, WindowResolver,
 };
+pub use wait::{WaitForWindowOptions, WaitOutcome, wait_for_window};
 #[cfg(target_os = "linux")]
 pub use wayland_backend::WaylandBackend;
 #[cfg(target_os = "windows")]
@@ -97,11 +130,16 @@ pub use x11_backend::X11Backend;
 /// - **Linux/Wayland**: Uses `WaylandBackend` (no window enumeration, has restore tokens)
 /// - **Linux/X11**: Uses `X11Backend` (full window enumeration)
 /// - **macOS/Other**: Returns a structured `BackendNotAvailable` error
+///
+/// Selectors resolve afresh on every capture unless they set `sticky`.
+///
+/// The redaction and capture policies are loaded from the privacy file
+/// (see [`RedactionPolicy::default_path`]); a malformed file is an error.
 pub fn create_default_backend() -> CaptureResult<Arc<CompositeBackend>> {
     #[cfg(target_os = "windows")]
     {
         let backend = Arc::new(WindowsBackend::new()?);
-        Ok(Arc::new(composite_from_windows(backend)))
+        with_privacy_settings(composite_from_windows(backend))
     }
 
     #[cfg(target_os = "linux")]
@@ -114,11 +152,11 @@ pub fn create_default_backend() -> CaptureResult<Arc<CompositeBackend>> {
             BackendType::Wayland => {
                 let key_store = Arc::new(crate::util::key_store::KeyStore::new());
                 let backend = Arc::new(WaylandBackend::new(key_store));
-                Ok(Arc::new(composite_from_wayland(backend)))
+                with_privacy_settings(composite_from_wayland(backend))
             }
             BackendType::X11 => {
                 let backend = Arc::new(X11Backend::new()?);
-                Ok(Arc::new(composite_from_x11(backend)))
+                with_privacy_settings(composite_from_x11(backend))
             }
             BackendType::None | BackendType::Windows | BackendType::MacOS => {
                 Err(CaptureError::BackendNotAvailable {
@@ -142,3 +180,13 @@ pub fn create_default_backend() -> CaptureResult<Arc<CompositeBackend>> {
         })
     }
 }
+
+/// Attaches the redaction and capture policies from the privacy file
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn with_privacy_settings(backend: CompositeBackend) -> CaptureResult<Arc<CompositeBackend>> {
+    Ok(Arc::new(
+        backend
+            .with_redaction(RedactionPolicy::load_default()?)
+            .with_policy(CapturePolicy::load_default()?),
+    ))
+}
@@ -0,0 +1,179 @@
+//! Unicode-aware text folding for title matching
+//!
+//! Window titles come from every script and input method, so plain
+//! `to_lowercase` misses matches users expect: full-width `Ｆｉｒｅｆｏｘ`,
+//! `Straße` typed as `strasse`, `Résumé` typed as `resume`, or a title that
+//! uses a Cyrillic `Т` in place of a Latin `T`.
+//!
+//! [`fold_for_matching`] maps text to a canonical form so substring and fuzzy
+//! matching can compare the pattern and the title as equals. Both sides must
+//! be folded with the same `strict` flag.
+//!
+//! # Folding Steps
+//!
+//! Always applied:
+//! 1. **NFKC normalization** - compatibility forms (full-width, ligatures,
+//!    half-width kana) become their canonical characters
+//! 2. **Full case folding** - `ß` → `ss`, `Σ`/`ς` → `σ`, etc.
+//! 3. **Invisible characters removed** - zero-width spaces/joiners, soft
+//!    hyphens and emoji variation selectors
+//!
+//! Skipped in strict mode:
+//! 4. **Diacritic stripping** - combining accents are removed (`é` → `e`)
+//! 5. **Homoglyph transliteration** - Cyrillic and Greek letters that look
+//!    like Latin letters are mapped to them (`о` → `o`)
+//!
+//! Regex matching is unaffected; it always runs on the raw title.
+
+use caseless::Caseless;
+use unicode_normalization::UnicodeNormalization;
+
+/// Folds text into the canonical form used for substring and fuzzy matching
+///
+/// # Arguments
+///
+/// * `text` - Title or pattern to fold
+/// * `strict` - Keep diacritics and homoglyphs significant
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::capture::normalize::fold_for_matching;
+///
+/// assert_eq!(fold_for_matching("Ｃａｆé Straße", false), "cafe strasse");
+/// assert_eq!(fold_for_matching("Ｃａｆé Straße", true), "café strasse");
+/// ```
+pub fn fold_for_matching(text: &str, strict: bool) -> String {
+    // Case folding can denormalize, so fold twice around a compatibility
+    // decomposition as in the Unicode compatibility caseless match
+    let decomposed = text
+        .nfd()
+        .default_case_fold()
+        .nfkd()
+        .default_case_fold()
+        .nfkd()
+        .filter(|&c| !is_invisible(c));
+
+    if strict {
+        decomposed.nfc().collect()
+    } else {
+        decomposed
+            .filter(|&c| !is_diacritic(c))
+            .map(transliterate_homoglyph)
+            .nfc()
+            .collect()
+    }
+}
+
+/// Format and variation characters that change nothing visible
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' // soft hyphen
+            | '\u{200B}'..='\u{200D}' // zero-width space / non-joiner / joiner
+            | '\u{2060}' // word joiner
+            | '\u{FE00}'..='\u{FE0F}' // variation selectors (emoji presentation)
+            | '\u{FEFF}' // zero-width no-break space
+    )
+}
+
+/// Combining marks used as diacritics on Latin, Greek and Cyrillic letters
+///
+/// Deliberately excludes marks that are part of other scripts' letters (such
+/// as Japanese dakuten or Indic vowel signs), which carry meaning.
+fn is_diacritic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036F}' // combining diacritical marks
+            | '\u{1AB0}'..='\u{1AFF}' // combining diacritical marks extended
+            | '\u{1DC0}'..='\u{1DFF}' // combining diacritical marks supplement
+            | '\u{20D0}'..='\u{20FF}' // combining marks for symbols
+            | '\u{FE20}'..='\u{FE2F}' // combining half marks
+    )
+}
+
+/// Maps case-folded Cyrillic and Greek lookalikes to the Latin letter they
+/// imitate
+fn transliterate_homoglyph(c: char) -> char {
+    match c {
+        // Cyrillic
+        'а' => 'a',
+        'в' => 'b',
+        'с' => 'c',
+        'ԁ' => 'd',
+        'е' => 'e',
+        'һ' => 'h',
+        'н' => 'h',
+        'і' => 'i',
+        'ј' => 'j',
+        'к' => 'k',
+        'ӏ' => 'l',
+        'м' => 'm',
+        'о' => 'o',
+        'р' => 'p',
+        'ԛ' => 'q',
+        'ѕ' => 's',
+        'т' => 't',
+        'у' => 'y',
+        'ԝ' => 'w',
+        'х' => 'x',
+        // Greek
+        'α' => 'a',
+        'β' => 'b',
+        'ε' => 'e',
+        'η' => 'n',
+        'ι' => 'i',
+        'κ' => 'k',
+        'ν' => 'v',
+        'ο' => 'o',
+        'ρ' => 'p',
+        'τ' => 't',
+        'υ' => 'u',
+        'χ' => 'x',
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nfkc_full_width_and_ligatures() {
+        assert_eq!(fold_for_matching("Ｆｉｒｅｆｏｘ", true), "firefox");
+        assert_eq!(fold_for_matching("ﬁle", true), "file");
+        // Half-width katakana compose into full-width
+        assert_eq!(fold_for_matching("ﾌｧｲﾙ", true), "ファイル");
+    }
+
+    #[test]
+    fn test_full_case_folding() {
+        assert_eq!(fold_for_matching("STRASSE", true), fold_for_matching("Straße", true));
+        assert_eq!(fold_for_matching("ΣΊΣΥΦΟΣ", true), fold_for_matching("σίσυφος", true));
+        assert_eq!(fold_for_matching("İstanbul", true), "i\u{307}stanbul");
+    }
+
+    #[test]
+    fn test_diacritics_stripped_unless_strict() {
+        assert_eq!(fold_for_matching("Résumé — Café.docx", false), "resume — cafe.docx");
+        assert_eq!(fold_for_matching("Résumé", true), "résumé");
+        // Japanese voiced marks are part of the letter and are kept
+        assert_eq!(fold_for_matching("ガ", false), "ガ");
+    }
+
+    #[test]
+    fn test_homoglyphs_transliterated_unless_strict() {
+        // Cyrillic Т and е in an otherwise Latin title
+        let spoofed = "\u{0422}\u{0435}rminal";
+        assert_eq!(fold_for_matching(spoofed, false), "terminal");
+        assert_ne!(fold_for_matching(spoofed, true), "terminal");
+        // Cyrillic Ѕ, е, і, ѕ
+        assert_eq!(fold_for_matching("\u{0405}\u{0435}tt\u{0456}ng\u{0455}", false), "settings");
+    }
+
+    #[test]
+    fn test_invisible_characters_removed() {
+        assert_eq!(fold_for_matching("Fire\u{200B}fox", true), "firefox");
+        assert_eq!(fold_for_matching("❤\u{FE0F} Music", true), "❤ music");
+    }
+}
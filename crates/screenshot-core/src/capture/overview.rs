@@ -0,0 +1,389 @@
+//! Overviews: every window as a thumbnail in one labelled grid image
+//!
+//! Getting a feel for the desktop otherwise takes one capture per window.
+//! [`capture_thumbnails`] captures a list of windows a few at a time within
+//! a shared time budget and scales each capture to fit a thumbnail box;
+//! [`compose_overview`] lays the thumbnails out in a grid with a numbered
+//! caption above each and returns which window is in which cell.
+//!
+//! Windows whose capture fails or does not finish in time are reported as
+//! skipped rather than failing the whole overview.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{
+//!         ImageBuffer,
+//!         overview::{capture_thumbnails, compose_overview},
+//!     },
+//!     model::{BackendType, OverviewOptions, WindowInfo},
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let window = |id: &str| {
+//!     WindowInfo::new(id.into(), id.into(), "Demo".into(), "demo".into(), 1, BackendType::None)
+//! };
+//! let options = OverviewOptions {
+//!     columns: Some(2),
+//!     ..Default::default()
+//! };
+//! let thumbnails = capture_thumbnails(vec![window("a"), window("b"), window("c")], &options, |_| {
+//!     async { Ok(ImageBuffer::from_test_pattern(1280, 800)) }
+//! })
+//! .await;
+//! assert_eq!(thumbnails.captured.len(), 3);
+//!
+//! let (image, cells) = compose_overview(&thumbnails.captured, &options).unwrap();
+//! assert_eq!((cells[2].row, cells[2].column), (1, 0));
+//! assert_eq!(cells[0].thumbnail.width, 320);
+//! assert!(image.width() > 640);
+//! # }
+//! ```
+
+use std::{future::Future, time::Duration};
+
+use futures::{StreamExt, stream};
+use image::{DynamicImage, RgbaImage};
+use tokio::time::Instant;
+
+use super::{
+    ImageBuffer,
+    annotate::{MAX_ANNOTATIONS, label_size},
+};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{
+        Annotation, AnnotationShape, OverviewCell, OverviewOptions, OverviewSkip, Region,
+        WindowInfo,
+    },
+};
+
+/// Space around and between cells in pixels
+const MARGIN: u32 = 8;
+
+/// Caption font size in pixels
+const CAPTION_SIZE: f32 = 14.0;
+
+/// Grid background
+const BACKGROUND: [u8; 4] = [32, 32, 32, 255];
+
+/// Caption box colour
+const CAPTION_COLOR: &str = "#e0e0e0";
+
+/// Thumbnails captured for an overview
+#[derive(Debug, Clone)]
+pub struct Thumbnails {
+    /// Captured windows in the order given, each fitted to the thumbnail box
+    pub captured: Vec<(WindowInfo, ImageBuffer)>,
+    /// Windows whose capture failed or ran out of time
+    pub skipped: Vec<OverviewSkip>,
+    /// Whether the time budget ran out before every capture finished
+    pub timed_out: bool,
+}
+
+/// Captures `windows`, `options.concurrency` at a time
+///
+/// Every capture must finish within `options.timeout_ms` of the call;
+/// captures still running then, and windows not yet started, are skipped.
+/// Each capture is scaled down to fit the thumbnail box as soon as it
+/// arrives.
+pub async fn capture_thumbnails<F, Fut>(
+    windows: Vec<WindowInfo>,
+    options: &OverviewOptions,
+    capture: F,
+) -> Thumbnails
+where
+    F: Fn(WindowInfo) -> Fut,
+    Fut: Future<Output = CaptureResult<ImageBuffer>>,
+{
+    let deadline = Instant::now() + Duration::from_millis(options.timeout_ms);
+    let (width, height) = (options.thumbnail_width, options.thumbnail_height);
+    let results: Vec<_> = stream::iter(windows)
+        .map(|window| {
+            let shot = capture(window.clone());
+            async move {
+                let result = tokio::time::timeout_at(deadline, shot)
+                    .await
+                    .map(|shot| shot.map(|image| fit_thumbnail(image, width, height)));
+                (window, result)
+            }
+        })
+        .buffered(options.concurrency.max(1) as usize)
+        .collect()
+        .await;
+
+    let mut thumbnails = Thumbnails {
+        captured: Vec::new(),
+        skipped: Vec::new(),
+        timed_out: false,
+    };
+    for (window, result) in results {
+        let error = match result {
+            Ok(Ok(image)) => {
+                thumbnails.captured.push((window, image));
+                continue;
+            }
+            Ok(Err(e)) => e,
+            Err(_) => {
+                thumbnails.timed_out = true;
+                CaptureError::CaptureTimeout {
+                    duration_ms: options.timeout_ms,
+                }
+            }
+        };
+        tracing::debug!("Skipping window {} in overview: {}", window.id, error);
+        thumbnails.skipped.push(OverviewSkip {
+            window,
+            reason: error.to_string(),
+        });
+    }
+    thumbnails
+}
+
+/// Scales `image` down, keeping its aspect ratio, to fit `width` x `height`
+fn fit_thumbnail(image: ImageBuffer, width: u32, height: u32) -> ImageBuffer {
+    let (w, h) = image.dimensions();
+    if w <= width && h <= height {
+        return image;
+    }
+    ImageBuffer::new(
+        image
+            .inner()
+            .resize(width, height, image::imageops::FilterType::Triangle),
+    )
+}
+
+/// Caption for a cell: its label and the window title, cut to `width`
+fn caption(label: u32, window: &WindowInfo, width: u32) -> String {
+    let name = if window.title.trim().is_empty() {
+        &window.class
+    } else {
+        &window.title
+    };
+    let full = format!("{} {}", label, name);
+    if label_size(&full, CAPTION_SIZE).0 <= width as f32 {
+        return full;
+    }
+
+    let mut chars: Vec<char> = full.chars().collect();
+    let min = label.to_string().len();
+    while chars.len() > min {
+        chars.pop();
+        let cut = format!("{}...", chars.iter().collect::<String>().trim_end());
+        if label_size(&cut, CAPTION_SIZE).0 <= width as f32 {
+            return cut;
+        }
+    }
+    label.to_string()
+}
+
+/// Lays thumbnails out in a captioned grid, in the order given
+///
+/// Cells are filled row by row, `options.columns` wide (or about square by
+/// default). Each thumbnail is centred in its box below a caption with its
+/// label and window title.
+///
+/// # Errors
+///
+/// `CaptureError::InvalidParameter` if `thumbnails` is empty.
+pub fn compose_overview(
+    thumbnails: &[(WindowInfo, ImageBuffer)],
+    options: &OverviewOptions,
+) -> CaptureResult<(ImageBuffer, Vec<OverviewCell>)> {
+    if thumbnails.is_empty() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "windows".to_string(),
+            reason: "an overview needs at least one window".to_string(),
+        });
+    }
+
+    let count = thumbnails.len() as u32;
+    let columns = options
+        .columns
+        .unwrap_or_else(|| (count as f64).sqrt().ceil() as u32)
+        .clamp(1, count);
+    let rows = count.div_ceil(columns);
+    let (box_width, box_height) = (options.thumbnail_width, options.thumbnail_height);
+    let caption_height = label_size("0", CAPTION_SIZE).1.ceil() as u32;
+    let cell_height = caption_height + box_height;
+
+    let mut canvas = RgbaImage::from_pixel(
+        columns * box_width + (columns + 1) * MARGIN,
+        rows * cell_height + (rows + 1) * MARGIN,
+        image::Rgba(BACKGROUND),
+    );
+    let mut cells = Vec::with_capacity(thumbnails.len());
+    let mut captions = Vec::with_capacity(thumbnails.len());
+    for (index, (window, image)) in thumbnails.iter().enumerate() {
+        let index = index as u32;
+        let (row, column) = (index / columns, index % columns);
+        let left = MARGIN + column * (box_width + MARGIN);
+        let top = MARGIN + row * (cell_height + MARGIN);
+
+        let image = fit_thumbnail(image.clone(), box_width, box_height);
+        let (width, height) = image.dimensions();
+        let x = left + (box_width - width) / 2;
+        let y = top + caption_height + (box_height - height) / 2;
+        image::imageops::replace(&mut canvas, &image.to_rgba8(), x.into(), y.into());
+
+        let label = index + 1;
+        captions.push(Annotation {
+            color: CAPTION_COLOR.to_string(),
+            ..Annotation::new(AnnotationShape::Text {
+                x: left as f32,
+                y: top as f32,
+                text: caption(label, window, box_width),
+                size: CAPTION_SIZE,
+            })
+        });
+        cells.push(OverviewCell {
+            label,
+            row,
+            column,
+            thumbnail: Region::new(x, y, width, height),
+            window: window.clone(),
+        });
+    }
+
+    let mut overview = ImageBuffer::new(DynamicImage::ImageRgba8(canvas));
+    for chunk in captions.chunks(MAX_ANNOTATIONS) {
+        overview = overview.annotate(chunk)?;
+    }
+    Ok((overview, cells))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::BackendType;
+
+    fn window(id: &str, title: &str) -> WindowInfo {
+        WindowInfo::new(
+            id.to_string(),
+            title.to_string(),
+            "Demo".to_string(),
+            "demo".to_string(),
+            1,
+            BackendType::None,
+        )
+    }
+
+    fn windows(count: usize) -> Vec<WindowInfo> {
+        (0..count)
+            .map(|i| window(&format!("w{}", i), &format!("Window {}", i)))
+            .collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_and_slow_captures_are_skipped() {
+        let options = OverviewOptions {
+            timeout_ms: 1000,
+            ..Default::default()
+        };
+
+        let thumbnails = capture_thumbnails(windows(3), &options, |window| async move {
+            match window.id.as_str() {
+                "w1" => Err(CaptureError::WindowClosed),
+                "w2" => {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    Ok(ImageBuffer::from_test_pattern(10, 10))
+                }
+                _ => Ok(ImageBuffer::from_test_pattern(1600, 400)),
+            }
+        })
+        .await;
+
+        assert_eq!(thumbnails.captured.len(), 1);
+        assert_eq!(thumbnails.captured[0].0.id, "w0");
+        // Fitted to the 320x200 box, aspect ratio kept
+        assert_eq!(thumbnails.captured[0].1.dimensions(), (320, 80));
+        let skipped: Vec<&str> = thumbnails
+            .skipped
+            .iter()
+            .map(|s| s.window.id.as_str())
+            .collect();
+        assert_eq!(skipped, vec!["w1", "w2"]);
+        assert!(thumbnails.skipped[1].reason.contains("timed out"));
+        assert!(thumbnails.timed_out);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_captures_run_concurrently() {
+        let options = OverviewOptions {
+            concurrency: 4,
+            timeout_ms: 1500,
+            ..Default::default()
+        };
+
+        // Four one-second captures only fit the budget if run together
+        let thumbnails = capture_thumbnails(windows(4), &options, |_| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Ok(ImageBuffer::from_test_pattern(64, 48))
+        })
+        .await;
+
+        assert_eq!(thumbnails.captured.len(), 4);
+        assert!(!thumbnails.timed_out);
+    }
+
+    #[test]
+    fn test_compose_overview_layout() {
+        let options = OverviewOptions {
+            thumbnail_width: 100,
+            thumbnail_height: 80,
+            ..Default::default()
+        };
+        let thumbnails: Vec<_> = windows(5)
+            .into_iter()
+            .map(|w| (w, ImageBuffer::from_test_pattern(100, 40)))
+            .collect();
+
+        let (image, cells) = compose_overview(&thumbnails, &options).unwrap();
+
+        // Five cells make a 3x2 grid
+        let positions: Vec<(u32, u32, u32)> =
+            cells.iter().map(|c| (c.label, c.row, c.column)).collect();
+        assert_eq!(positions, vec![(1, 0, 0), (2, 0, 1), (3, 0, 2), (4, 1, 0), (5, 1, 1)]);
+        assert_eq!(image.width(), 3 * 100 + 4 * MARGIN);
+        assert_eq!(cells[4].window.id, "w4");
+
+        // Thumbnails are centred vertically in their box, below the caption
+        let caption_height = label_size("0", CAPTION_SIZE).1.ceil() as u32;
+        assert_eq!(
+            cells[1].thumbnail,
+            Region::new(2 * MARGIN + 100, MARGIN + caption_height + 20, 100, 40)
+        );
+        let pixels = image.to_rgba8();
+        let t = cells[1].thumbnail;
+        assert_ne!(*pixels.get_pixel(t.x + 50, t.y + 20), image::Rgba(BACKGROUND));
+        assert_eq!(*pixels.get_pixel(t.x + 50, t.y - 2), image::Rgba(BACKGROUND));
+        // The empty sixth cell stays blank
+        assert_eq!(
+            *pixels.get_pixel(image.width() - MARGIN - 50, image.height() - MARGIN - 40),
+            image::Rgba(BACKGROUND)
+        );
+    }
+
+    #[test]
+    fn test_compose_overview_rejects_no_windows() {
+        assert!(matches!(
+            compose_overview(&[], &OverviewOptions::default()),
+            Err(CaptureError::InvalidParameter { .. })
+        ));
+    }
+
+    #[test]
+    fn test_caption_is_cut_to_fit() {
+        let long = window("w", &"Very long title ".repeat(10));
+        let cut = caption(3, &long, 160);
+        assert!(cut.starts_with("3 Very long"));
+        assert!(cut.ends_with("..."));
+        assert!(label_size(&cut, CAPTION_SIZE).0 <= 160.0);
+
+        assert_eq!(caption(1, &window("w", "Short"), 160), "1 Short");
+        // Untitled windows show their class
+        assert_eq!(caption(2, &window("w", ""), 160), "2 Demo");
+    }
+}
@@ -0,0 +1,556 @@
+//! Capture privacy policy: which windows and tools may be used at all
+//!
+//! Where [redaction](super::redact) hides parts of an image, the policy
+//! refuses the capture outright. It is configured with:
+//!
+//! - `deny` selector queries: matching windows can never be captured
+//! - `allow` selector queries: if any are given, only matching windows can
+//!   be captured
+//! - `ask` selector queries: matching windows need the user's consent
+//! - `display`: permission for full-display captures, `ask` unless set
+//! - `tools`: per-tool permissions for the MCP server
+//!
+//! Consent (`"ask"`) is obtained by the MCP server, which asks the client's
+//! user; the core only reports which captures need it.
+//!
+//! [`CompositeBackend`](super::CompositeBackend) consults the policy when a
+//! selector is resolved (see [`PolicyResolver`]) and again before each
+//! window capture, failing with [`CaptureError::PolicyDenied`]. Display
+//! captures black out every window the policy denies.
+//!
+//! Window rules need window enumeration. On backends without it (Wayland)
+//! only tool permissions apply; the portal's consent dialog is the gate
+//! for individual windows there.
+//!
+//! # File Format
+//!
+//! The policy lives in the `[policy]` table of the privacy file, next to
+//! `[redaction]`:
+//!
+//! ```toml
+//! [policy]
+//! deny = ["class:KeePassXC", "title~/- Signal$/"]
+//! allow = ["class:code", "class:firefox", "class:thunderbird"]
+//! ask = ["class:thunderbird"]
+//! display = "allow"  # ask (default), allow or deny
+//!
+//! [policy.tools]
+//! mark_windows = "deny"
+//! ```
+//!
+//! The file is found as described for
+//! [`RedactionPolicy::default_path`](super::RedactionPolicy::default_path).
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    RedactionPolicy, WindowEnumerator, WindowMatcher, WindowResolver, query::parse_selector_query,
+};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{WindowHandle, WindowInfo, WindowSelector},
+};
+
+/// Whether a window, tool or display capture may be used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    /// Allowed without asking
+    #[default]
+    Allow,
+    /// Allowed once the user consents
+    Ask,
+    /// Always refused with `PolicyDenied`
+    Deny,
+}
+
+/// A window rule of the policy
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolicyRule {
+    /// Selector query as written in the config file
+    pub query: String,
+    /// Selector compiled from `query`
+    pub selector: WindowSelector,
+}
+
+impl PolicyRule {
+    /// Compiles a selector query into a rule
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` if the query does not parse.
+    pub fn parse(query: &str) -> CaptureResult<Self> {
+        Ok(Self {
+            query: query.to_string(),
+            selector: parse_selector_query(query)?,
+        })
+    }
+
+    fn matches(&self, window: &WindowInfo) -> bool {
+        !WindowMatcher::new()
+            .all_matches(&self.selector, std::slice::from_ref(window))
+            .is_empty()
+    }
+}
+
+/// Allow and deny rules for windows, and permissions for tools
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapturePolicy {
+    /// If non-empty, only windows matching one of these can be captured
+    pub allow: Vec<PolicyRule>,
+    /// Windows that can never be captured; wins over `allow`
+    pub deny: Vec<PolicyRule>,
+    /// Windows that need the user's consent
+    pub ask: Vec<PolicyRule>,
+    /// Permission for full-display captures
+    ///
+    /// Defaults to `Ask`: a display capture shows every window at once.
+    pub display: Permission,
+    /// Permissions by tool name; unlisted tools are allowed
+    pub tools: BTreeMap<String, Permission>,
+}
+
+impl Default for CapturePolicy {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            deny: Vec::new(),
+            ask: Vec::new(),
+            display: Permission::Ask,
+            tools: BTreeMap::new(),
+        }
+    }
+}
+
+/// The `[policy]` table as written in the file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyTable {
+    #[serde(default)]
+    allow: Vec<String>,
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    ask: Vec<String>,
+    #[serde(default = "default_display")]
+    display: Permission,
+    #[serde(default)]
+    tools: BTreeMap<String, Permission>,
+}
+
+fn default_display() -> Permission {
+    Permission::Ask
+}
+
+impl CapturePolicy {
+    /// Creates a policy without rules: every window and tool is allowed, and
+    /// full-display captures ask
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the `[policy]` table of a TOML document
+    ///
+    /// Other tables are ignored.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `policy` if the TOML is
+    /// malformed, the table has unknown keys or permissions, or a query
+    /// does not parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::policy::{CapturePolicy, Permission};
+    ///
+    /// let policy = CapturePolicy::from_toml_str(
+    ///     r#"
+    ///     [policy]
+    ///     deny = ["class:KeePassXC"]
+    ///     tools = { mark_windows = "deny" }
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(policy.deny[0].query, "class:KeePassXC");
+    /// assert_eq!(policy.tool_permission("mark_windows"), Permission::Deny);
+    /// assert!(policy.check_tool("capture_window").is_ok());
+    /// ```
+    pub fn from_toml_str(text: &str) -> CaptureResult<Self> {
+        let mut document: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| config_error(e.message()))?;
+
+        let Some(table) = document.remove("policy") else {
+            return Ok(Self::new());
+        };
+        let table: PolicyTable = table
+            .try_into()
+            .map_err(|e: toml::de::Error| config_error(e.message()))?;
+
+        let rules = |queries: &[String], list: &str| {
+            queries
+                .iter()
+                .map(|query| {
+                    PolicyRule::parse(query).map_err(|e| match e {
+                        CaptureError::InvalidParameter { reason, .. } => {
+                            config_error(format!("{} query '{}': {}", list, query, reason))
+                        }
+                        other => other,
+                    })
+                })
+                .collect::<CaptureResult<Vec<_>>>()
+        };
+
+        Ok(Self {
+            allow: rules(&table.allow, "allow")?,
+            deny: rules(&table.deny, "deny")?,
+            ask: rules(&table.ask, "ask")?,
+            display: table.display,
+            tools: table.tools,
+        })
+    }
+
+    /// Loads the policy from a TOML file
+    ///
+    /// # Errors
+    ///
+    /// - `CaptureError::IoError` - The file cannot be read
+    /// - `CaptureError::InvalidParameter` - As for
+    ///   [`from_toml_str`](Self::from_toml_str), with the file path in the
+    ///   reason
+    pub fn load(path: &Path) -> CaptureResult<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_toml_str(&text).map_err(|e| match e {
+            CaptureError::InvalidParameter { parameter, reason } => {
+                CaptureError::InvalidParameter {
+                    parameter,
+                    reason: format!("{}: {}", path.display(), reason),
+                }
+            }
+            other => other,
+        })
+    }
+
+    /// Loads the policy from the privacy file
+    ///
+    /// Returns an empty policy if the file does not exist.
+    pub fn load_default() -> CaptureResult<Self> {
+        let Some(path) = RedactionPolicy::default_path() else {
+            return Ok(Self::new());
+        };
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let policy = Self::load(&path)?;
+        tracing::debug!(
+            "Loaded capture policy from {}: {} allow, {} deny, {} tool rules",
+            path.display(),
+            policy.allow.len(),
+            policy.deny.len(),
+            policy.tools.len()
+        );
+        Ok(policy)
+    }
+
+    /// Returns true if the policy has no rules beyond the defaults
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Returns true if the policy refuses any windows
+    pub fn has_window_rules(&self) -> bool {
+        !self.allow.is_empty() || !self.deny.is_empty()
+    }
+
+    /// Checks whether a window may be captured
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::PolicyDenied` if the window matches a deny rule, or
+    /// there are allow rules and it matches none of them.
+    pub fn check_window(&self, window: &WindowInfo) -> CaptureResult<()> {
+        if let Some(rule) = self.deny.iter().find(|rule| rule.matches(window)) {
+            return Err(window_denied(window, format!("matches deny rule '{}'", rule.query)));
+        }
+        if !self.allow.is_empty() && !self.allow.iter().any(|rule| rule.matches(window)) {
+            return Err(window_denied(window, "matches no allow rule".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Returns whether a window may be captured, and if so whether the user
+    /// must be asked first
+    pub fn window_permission(&self, window: &WindowInfo) -> Permission {
+        if self.check_window(window).is_err() {
+            Permission::Deny
+        } else if self.ask.iter().any(|rule| rule.matches(window)) {
+            Permission::Ask
+        } else {
+            Permission::Allow
+        }
+    }
+
+    /// Checks whether the window `handle` may be captured
+    ///
+    /// The window is looked up with `enumerator`. Without an enumerator the
+    /// window cannot be checked and is allowed. A window the enumerator does
+    /// not list is allowed unless there are allow rules.
+    pub async fn check_handle(
+        &self,
+        enumerator: Option<&dyn WindowEnumerator>,
+        handle: &WindowHandle,
+    ) -> CaptureResult<()> {
+        if !self.has_window_rules() {
+            return Ok(());
+        }
+        let Some(enumerator) = enumerator else {
+            tracing::debug!("Window rules not checked for {}: cannot list windows", handle);
+            return Ok(());
+        };
+        let windows = enumerator.list_windows().await?;
+        match windows.iter().find(|w| w.id == *handle) {
+            Some(window) => self.check_window(window),
+            None if self.allow.is_empty() => Ok(()),
+            None => Err(CaptureError::PolicyDenied {
+                target: format!("window {}", handle),
+                reason: "window is not listed, so no allow rule can match".to_string(),
+            }),
+        }
+    }
+
+    /// Returns the windows the policy denies, in list order
+    pub fn denied_windows(&self, windows: &[WindowInfo]) -> Vec<WindowHandle> {
+        if !self.has_window_rules() {
+            return Vec::new();
+        }
+        windows
+            .iter()
+            .filter(|w| self.check_window(w).is_err())
+            .map(|w| w.id.clone())
+            .collect()
+    }
+
+    /// Returns the permission for a tool
+    pub fn tool_permission(&self, tool: &str) -> Permission {
+        self.tools.get(tool).copied().unwrap_or_default()
+    }
+
+    /// Checks whether a tool may be used
+    ///
+    /// Returns `Allow`, or `Ask` if the user must consent first.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::PolicyDenied` if the tool's permission is `deny`.
+    pub fn check_tool(&self, tool: &str) -> CaptureResult<Permission> {
+        check_permission(self.tool_permission(tool), || format!("tool '{}'", tool))
+    }
+
+    /// Checks whether the whole display may be captured
+    ///
+    /// Returns `Allow`, or `Ask` if the user must consent first.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::PolicyDenied` if the display permission is `deny`.
+    pub fn check_display(&self) -> CaptureResult<Permission> {
+        check_permission(self.display, || "full-display capture".to_string())
+    }
+}
+
+fn check_permission(
+    permission: Permission,
+    target: impl FnOnce() -> String,
+) -> CaptureResult<Permission> {
+    match permission {
+        Permission::Deny => Err(CaptureError::PolicyDenied {
+            target: target(),
+            reason: "permission is 'deny'".to_string(),
+        }),
+        allowed => Ok(allowed),
+    }
+}
+
+/// Refusal naming the window by handle only: the title of a denied window
+/// is exactly what must not reach the client
+fn window_denied(window: &WindowInfo, reason: String) -> CaptureError {
+    CaptureError::PolicyDenied {
+        target: format!("window {}", window.id),
+        reason,
+    }
+}
+
+fn config_error(reason: impl std::fmt::Display) -> CaptureError {
+    CaptureError::InvalidParameter {
+        parameter: "policy".to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Resolver that refuses windows the policy denies
+///
+/// Wraps a backend's resolver; [`CompositeBackend::with_policy`] installs
+/// it.
+///
+/// [`CompositeBackend::with_policy`]: super::CompositeBackend::with_policy
+pub struct PolicyResolver {
+    inner: Arc<dyn WindowResolver>,
+    enumerator: Option<Arc<dyn WindowEnumerator>>,
+    policy: Arc<CapturePolicy>,
+}
+
+impl PolicyResolver {
+    /// Wraps `inner`, looking windows up with `enumerator`
+    pub fn new(
+        inner: Arc<dyn WindowResolver>,
+        enumerator: Option<Arc<dyn WindowEnumerator>>,
+        policy: Arc<CapturePolicy>,
+    ) -> Self {
+        Self {
+            inner,
+            enumerator,
+            policy,
+        }
+    }
+}
+
+#[async_trait]
+impl WindowResolver for PolicyResolver {
+    async fn resolve(&self, selector: &WindowSelector) -> CaptureResult<WindowHandle> {
+        let handle = self.inner.resolve(selector).await?;
+        self.policy
+            .check_handle(self.enumerator.as_deref(), &handle)
+            .await?;
+        Ok(handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::MockBackend;
+
+    fn policy(text: &str) -> CapturePolicy {
+        CapturePolicy::from_toml_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_policy_from_toml() {
+        let parsed = policy(
+            r#"
+            [redaction]
+            deny = ["class:Signal"]
+
+            [policy]
+            allow = ["class:Code", "exe:firefox"]
+            deny = ["title~/Private/"]
+            ask = ["class:Code"]
+            display = "ask"
+            tools = { mark_windows = "deny", capture_window = "allow", locate_image = "ask" }
+            "#,
+        );
+        assert_eq!(parsed.allow.len(), 2);
+        assert_eq!(parsed.deny[0].query, "title~/Private/");
+        assert_eq!(parsed.tool_permission("mark_windows"), Permission::Deny);
+        assert_eq!(parsed.tool_permission("capture_window"), Permission::Allow);
+        assert_eq!(parsed.tool_permission("locate_image"), Permission::Ask);
+        assert_eq!(parsed.tool_permission("list_windows"), Permission::Allow);
+        assert_eq!(parsed.ask[0].query, "class:Code");
+        assert_eq!(parsed.check_display().unwrap(), Permission::Ask);
+        assert!(policy("").is_empty());
+
+        for bad in [
+            "[policy]\ndeny = [\"class:\"]",
+            "[policy]\nblock = []",
+            "[policy.tools]\nmark_windows = \"maybe\"",
+        ] {
+            assert!(matches!(
+                CapturePolicy::from_toml_str(bad),
+                Err(CaptureError::InvalidParameter { parameter, .. }) if parameter == "policy"
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deny_and_allow_rules() {
+        let windows = MockBackend::new().list_windows().await.unwrap();
+
+        let deny = policy("[policy]\ndeny = [\"class:Alacritty\"]");
+        assert_eq!(deny.denied_windows(&windows), vec!["mock-0x3"]);
+        let Err(CaptureError::PolicyDenied { target, reason }) = deny.check_window(&windows[2])
+        else {
+            panic!("terminal should be denied");
+        };
+        assert_eq!(target, "window mock-0x3");
+        assert_eq!(reason, "matches deny rule 'class:Alacritty'");
+
+        // Deny wins over allow, and an allow list shuts out everything else
+        let allow = policy(
+            "[policy]\nallow = [\"class:Code\", \"class:Alacritty\"]\ndeny = [\"class:Alacritty\"]",
+        );
+        assert_eq!(allow.denied_windows(&windows), vec!["mock-0x1", "mock-0x3"]);
+        assert!(allow.check_window(&windows[1]).is_ok());
+
+        // Asking never overrides a denial
+        let ask = policy(
+            "[policy]\nask = [\"class:Code\", \"class:Alacritty\"]\ndeny = [\"class:Alacritty\"]",
+        );
+        assert_eq!(ask.window_permission(&windows[0]), Permission::Allow);
+        assert_eq!(ask.window_permission(&windows[1]), Permission::Ask);
+        assert_eq!(ask.window_permission(&windows[2]), Permission::Deny);
+        assert_eq!(ask.denied_windows(&windows), vec!["mock-0x3"]);
+    }
+
+    #[tokio::test]
+    async fn test_policy_resolver() {
+        let mock = Arc::new(MockBackend::new());
+        let policy = Arc::new(policy("[policy]\ndeny = [\"class:Alacritty\"]"));
+        let resolver = PolicyResolver::new(mock.clone(), Some(mock.clone()), policy.clone());
+
+        assert_eq!(
+            resolver
+                .resolve(&WindowSelector::by_title("Firefox"))
+                .await
+                .unwrap(),
+            "mock-0x1"
+        );
+        assert!(matches!(
+            resolver
+                .resolve(&WindowSelector::by_title("Terminal"))
+                .await,
+            Err(CaptureError::PolicyDenied { .. })
+        ));
+
+        // Without enumeration only tool rules apply
+        assert!(
+            policy
+                .check_handle(None, &"mock-0x3".to_string())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_check_tool() {
+        let parsed = policy("[policy.tools]\nmark_windows = \"deny\"");
+        assert!(!parsed.is_empty());
+        assert!(!parsed.has_window_rules());
+        assert_eq!(parsed.check_tool("capture_window").unwrap(), Permission::Allow);
+        // Display captures ask unless the policy allows them
+        assert_eq!(parsed.check_display().unwrap(), Permission::Ask);
+        assert_eq!(
+            policy("[policy]\ndisplay = \"allow\"")
+                .check_display()
+                .unwrap(),
+            Permission::Allow
+        );
+        assert!(matches!(
+            parsed.check_tool("mark_windows"),
+            Err(CaptureError::PolicyDenied { target, .. }) if target == "tool 'mark_windows'"
+        ));
+    }
+}
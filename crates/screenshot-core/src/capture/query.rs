@@ -0,0 +1,556 @@
+//! Compact selector query language
+//!
+//! Parses a one-line query such as
+//! `class:firefox title~/PR #\d+/ !title:Private pid:1234` into a
+//! [`WindowSelector`], so selectors can be typed on the command line or in a
+//! prompt instead of written as JSON.
+//!
+//! # Syntax
+//!
+//! A query is a whitespace-separated list of terms, combined with AND:
+//!
+//! | Term | Selector field |
+//! |------|----------------|
+//! | `title:Firefox` | `title_substring_or_regex` |
+//! | `title~/PR #\d+/` | `title_substring_or_regex` (must be a valid regex) |
+//! | `class:Navigator` | `class` |
+//! | `exe:firefox` | `exe` |
+//! | `pid:1234` | `pid` |
+//! | `id:0x3a00007` | `window_id` |
+//! | `!title:Private` | `exclude` (also `!title~/re/`) |
+//! | `ambiguity:error` | `ambiguity` (`first`, `error` or an index) |
+//! | `strict:true` | `strict` (`true` or `false`) |
+//! | `sticky:true` | `sticky` (`true` or `false`) |
+//! | `(class:code \| exe:zed)` | `any_of` |
+//!
+//! Values containing spaces can be quoted (`title:"Pull Request"`). Inside
+//! quotes `\"` is a literal quote; inside `/.../` `\/` is a literal slash and
+//! other escapes are passed to the regex unchanged.
+//!
+//! A query may contain one parenthesised any-of group. Each alternative is a
+//! list of terms; negation, `ambiguity`, `strict` and `sticky` are only allowed
+//! at the top level.
+//!
+//! # Errors
+//!
+//! Malformed queries fail with `CaptureError::InvalidParameter` for the
+//! `query` parameter. The reason names the 1-based column of the problem.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::capture::query::parse_selector_query;
+//!
+//! let selector = parse_selector_query(r"class:firefox title~/PR #\d+/ !title:Private").unwrap();
+//! assert_eq!(selector.class.as_deref(), Some("firefox"));
+//! assert_eq!(selector.title_substring_or_regex.as_deref(), Some(r"PR #\d+"));
+//! assert_eq!(selector.exclude, vec!["Private".to_string()]);
+//!
+//! assert!(parse_selector_query("class:").is_err());
+//! ```
+
+use std::str::FromStr;
+
+use regex::RegexBuilder;
+
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{AmbiguityPolicy, WindowSelector},
+};
+
+/// How a term compares its value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    /// `field:value`
+    Match,
+    /// `field~/regex/`
+    Regex,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    OpenGroup,
+    CloseGroup,
+    Or,
+    Term {
+        negated: bool,
+        field: String,
+        operator: Operator,
+        value: String,
+    },
+}
+
+/// Parses a selector query into a [`WindowSelector`]
+///
+/// # Arguments
+///
+/// * `query` - Query text, e.g. `class:firefox !title:Private`
+///
+/// # Returns
+///
+/// - `Ok(WindowSelector)` - The compiled selector (never empty)
+/// - `Err(CaptureError::InvalidParameter)` - Syntax error, unknown field,
+///   duplicate field, invalid value, or a query with no positive criteria
+pub fn parse_selector_query(query: &str) -> CaptureResult<WindowSelector> {
+    let tokens = tokenize(query)?;
+
+    let mut selector = WindowSelector::default();
+    let mut tokens = tokens.into_iter();
+    let mut seen_group = false;
+
+    while let Some((column, token)) = tokens.next() {
+        match token {
+            Token::Term {
+                negated,
+                field,
+                operator,
+                value,
+            } => apply_term(&mut selector, column, negated, &field, operator, value, true)?,
+            Token::OpenGroup => {
+                if seen_group {
+                    return Err(query_error(column, "only one any-of group is supported"));
+                }
+                seen_group = true;
+
+                let mut alternative = WindowSelector::default();
+                loop {
+                    let Some((column, token)) = tokens.next() else {
+                        return Err(query_error(query.chars().count() + 1, "unclosed '('"));
+                    };
+                    match token {
+                        Token::Term {
+                            negated,
+                            field,
+                            operator,
+                            value,
+                        } => apply_term(
+                            &mut alternative,
+                            column,
+                            negated,
+                            &field,
+                            operator,
+                            value,
+                            false,
+                        )?,
+                        Token::Or | Token::CloseGroup => {
+                            if alternative.is_empty() {
+                                return Err(query_error(
+                                    column,
+                                    "empty alternative in any-of group",
+                                ));
+                            }
+                            selector.any_of.push(std::mem::take(&mut alternative));
+                            if token == Token::CloseGroup {
+                                break;
+                            }
+                        }
+                        Token::OpenGroup => {
+                            return Err(query_error(column, "any-of groups cannot be nested"));
+                        }
+                    }
+                }
+            }
+            Token::CloseGroup => return Err(query_error(column, "unexpected ')'")),
+            Token::Or => return Err(query_error(column, "'|' is only allowed inside ( ... )")),
+        }
+    }
+
+    if selector.is_empty() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "query".to_string(),
+            reason: "query must contain at least one of title, class, exe, pid, id or an any-of \
+                     group"
+                .to_string(),
+        });
+    }
+
+    Ok(selector)
+}
+
+impl FromStr for WindowSelector {
+    type Err = CaptureError;
+
+    /// Parses a selector query; see [`parse_selector_query`]
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        parse_selector_query(query)
+    }
+}
+
+fn query_error(column: usize, reason: impl std::fmt::Display) -> CaptureError {
+    CaptureError::InvalidParameter {
+        parameter: "query".to_string(),
+        reason: format!("at column {}: {}", column, reason),
+    }
+}
+
+/// Splits a query into tokens, each paired with its 1-based start column
+fn tokenize(query: &str) -> CaptureResult<Vec<(usize, Token)>> {
+    let chars: Vec<char> = query.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push((column, Token::OpenGroup));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((column, Token::CloseGroup));
+                i += 1;
+            }
+            '|' => {
+                tokens.push((column, Token::Or));
+                i += 1;
+            }
+            _ => {
+                let (token, next) = read_term(&chars, i)?;
+                tokens.push((column, token));
+                i = next;
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Reads one `[!]field(:|~)value` term starting at `start`
+fn read_term(chars: &[char], start: usize) -> CaptureResult<(Token, usize)> {
+    let mut i = start;
+    let negated = chars[i] == '!';
+    if negated {
+        i += 1;
+    }
+
+    let field_start = i;
+    while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+        i += 1;
+    }
+    let field: String = chars[field_start..i].iter().collect();
+
+    let operator = match chars.get(i) {
+        Some(':') => Operator::Match,
+        Some('~') => Operator::Regex,
+        _ if field.is_empty() => {
+            let found = chars
+                .get(i)
+                .map_or_else(|| "end of query".to_string(), |c| format!("'{}'", c));
+            return Err(query_error(i + 1, format!("expected a field name, found {}", found)));
+        }
+        _ => {
+            return Err(query_error(
+                field_start + 1,
+                format!("expected ':' or '~' after '{}' (terms look like field:value)", field),
+            ));
+        }
+    };
+    if field.is_empty() {
+        return Err(query_error(i + 1, "missing field name before operator"));
+    }
+    i += 1;
+
+    let value_start = i;
+    let value = match chars.get(i) {
+        Some('"') => {
+            let (value, next) = read_delimited(chars, i, '"', false)?;
+            i = next;
+            value
+        }
+        Some('/') => {
+            let (value, next) = read_delimited(chars, i, '/', true)?;
+            i = next;
+            value
+        }
+        _ => {
+            while i < chars.len()
+                && !chars[i].is_whitespace()
+                && !matches!(chars[i], '(' | ')' | '|')
+            {
+                i += 1;
+            }
+            chars[value_start..i].iter().collect()
+        }
+    };
+
+    if value.is_empty() {
+        return Err(query_error(value_start + 1, format!("missing value for '{}'", field)));
+    }
+
+    Ok((
+        Token::Term {
+            negated,
+            field,
+            operator,
+            value,
+        },
+        i,
+    ))
+}
+
+/// Reads a value between `delimiter`s starting at `start` (the opening one)
+///
+/// An escaped delimiter becomes a literal delimiter. Other escapes are kept
+/// verbatim when `keep_escapes` is set (regex bodies) and unescaped otherwise.
+fn read_delimited(
+    chars: &[char],
+    start: usize,
+    delimiter: char,
+    keep_escapes: bool,
+) -> CaptureResult<(String, usize)> {
+    let mut value = String::new();
+    let mut i = start + 1;
+
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                let escaped = chars[i + 1];
+                if escaped != delimiter && keep_escapes {
+                    value.push('\\');
+                }
+                value.push(escaped);
+                i += 2;
+            }
+            c if c == delimiter => return Ok((value, i + 1)),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    Err(query_error(start + 1, format!("unterminated {}...{}", delimiter, delimiter)))
+}
+
+/// Applies one parsed term to `selector`
+fn apply_term(
+    selector: &mut WindowSelector,
+    column: usize,
+    negated: bool,
+    field: &str,
+    operator: Operator,
+    value: String,
+    top_level: bool,
+) -> CaptureResult<()> {
+    if operator == Operator::Regex {
+        if field != "title" {
+            return Err(query_error(
+                column,
+                format!("'~' is only supported for title, not '{}'", field),
+            ));
+        }
+        if let Err(e) = RegexBuilder::new(&value).case_insensitive(true).build() {
+            return Err(query_error(column, format!("invalid regex '{}': {}", value, e)));
+        }
+    }
+
+    if negated {
+        if field != "title" {
+            return Err(query_error(column, format!("only title can be negated, not '{}'", field)));
+        }
+        if !top_level {
+            return Err(query_error(
+                column,
+                "negated terms are not allowed inside an any-of group",
+            ));
+        }
+        selector.exclude.push(value);
+        return Ok(());
+    }
+
+    let duplicate = || query_error(column, format!("'{}' is specified more than once", field));
+    match field {
+        "title" => set_once(&mut selector.title_substring_or_regex, value, duplicate),
+        "class" => set_once(&mut selector.class, value, duplicate),
+        "exe" => set_once(&mut selector.exe, value, duplicate),
+        "id" => set_once(&mut selector.window_id, value, duplicate),
+        "pid" => {
+            let pid = value.parse::<u32>().map_err(|_| {
+                query_error(column, format!("pid must be a non-negative integer, got '{}'", value))
+            })?;
+            set_once(&mut selector.pid, pid, duplicate)
+        }
+        "ambiguity" => {
+            if !top_level {
+                return Err(query_error(column, "ambiguity is not allowed inside an any-of group"));
+            }
+            selector.ambiguity = match value.as_str() {
+                "first" => AmbiguityPolicy::First,
+                "error" => AmbiguityPolicy::Error,
+                index => AmbiguityPolicy::Index(index.parse().map_err(|_| {
+                    query_error(
+                        column,
+                        format!("ambiguity must be 'first', 'error' or an index, got '{}'", value),
+                    )
+                })?),
+            };
+            Ok(())
+        }
+        "strict" => {
+            if !top_level {
+                return Err(query_error(column, "strict is not allowed inside an any-of group"));
+            }
+            selector.strict = Some(value.parse().map_err(|_| {
+                query_error(column, format!("strict must be 'true' or 'false', got '{}'", value))
+            })?);
+            Ok(())
+        }
+        "sticky" => {
+            if !top_level {
+                return Err(query_error(column, "sticky is not allowed inside an any-of group"));
+            }
+            selector.sticky = Some(value.parse().map_err(|_| {
+                query_error(column, format!("sticky must be 'true' or 'false', got '{}'", value))
+            })?);
+            Ok(())
+        }
+        _ => Err(query_error(
+            column,
+            format!(
+                "unknown field '{}' (expected title, class, exe, pid, id, ambiguity, strict or \
+                 sticky)",
+                field
+            ),
+        )),
+    }
+}
+
+fn set_once<T>(
+    slot: &mut Option<T>,
+    value: T,
+    duplicate: impl FnOnce() -> CaptureError,
+) -> CaptureResult<()> {
+    if slot.is_some() {
+        return Err(duplicate());
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reason(query: &str) -> String {
+        match parse_selector_query(query) {
+            Err(CaptureError::InvalidParameter { parameter, reason }) => {
+                assert_eq!(parameter, "query");
+                reason
+            }
+            other => panic!("expected InvalidParameter for '{}', got {:?}", query, other),
+        }
+    }
+
+    #[test]
+    fn test_parse_full_example() {
+        let selector =
+            parse_selector_query(r"class:firefox title~/PR #\d+/ !title:Private pid:1234").unwrap();
+
+        assert_eq!(selector.class.as_deref(), Some("firefox"));
+        assert_eq!(selector.title_substring_or_regex.as_deref(), Some(r"PR #\d+"));
+        assert_eq!(selector.exclude, vec!["Private".to_string()]);
+        assert_eq!(selector.pid, Some(1234));
+        assert_eq!(selector.ambiguity, AmbiguityPolicy::First);
+    }
+
+    #[test]
+    fn test_parse_quoted_and_escaped_values() {
+        let selector =
+            parse_selector_query(r#"title:"Pull \"Request\"" !title~/a\/b/ id:0x3a00007"#).unwrap();
+
+        assert_eq!(selector.title_substring_or_regex.as_deref(), Some(r#"Pull "Request""#));
+        assert_eq!(selector.exclude, vec!["a/b".to_string()]);
+        assert_eq!(selector.window_id.as_deref(), Some("0x3a00007"));
+    }
+
+    #[test]
+    fn test_parse_any_of_group() {
+        let selector =
+            parse_selector_query("(class:code | exe:zed title:main) ambiguity:error").unwrap();
+
+        assert_eq!(selector.any_of.len(), 2);
+        assert_eq!(selector.any_of[0], WindowSelector::by_class("code"));
+        assert_eq!(selector.any_of[1].exe.as_deref(), Some("zed"));
+        assert_eq!(selector.any_of[1].title_substring_or_regex.as_deref(), Some("main"));
+        assert_eq!(selector.ambiguity, AmbiguityPolicy::Error);
+    }
+
+    #[test]
+    fn test_parse_ambiguity_index() {
+        let selector = parse_selector_query("exe:firefox ambiguity:2").unwrap();
+        assert_eq!(selector.ambiguity, AmbiguityPolicy::Index(2));
+    }
+
+    #[test]
+    fn test_parse_strict() {
+        let selector = parse_selector_query("title:Résumé strict:true").unwrap();
+        assert_eq!(selector.strict, Some(true));
+        assert_eq!(parse_selector_query("title:Résumé").unwrap().strict, None);
+        assert!(reason("title:a strict:yes").contains("strict must be 'true' or 'false'"));
+        assert!(reason("(title:a strict:true)").contains("not allowed inside an any-of group"));
+    }
+
+    #[test]
+    fn test_parse_sticky() {
+        assert_eq!(
+            parse_selector_query("class:term sticky:true")
+                .unwrap()
+                .sticky,
+            Some(true)
+        );
+        assert_eq!(parse_selector_query("class:term").unwrap().sticky, None);
+        assert!(reason("(class:a sticky:true)").contains("not allowed inside an any-of group"));
+    }
+
+    #[test]
+    fn test_from_str() {
+        let selector: WindowSelector = "exe:code".parse().unwrap();
+        assert_eq!(selector, WindowSelector::by_exe("code"));
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_column() {
+        assert_eq!(
+            reason("class:code foo"),
+            "at column 12: expected ':' or '~' after 'foo' (terms look like field:value)"
+        );
+        assert_eq!(reason("class:"), "at column 7: missing value for 'class'");
+        assert_eq!(reason(r#"title:"open"#), "at column 7: unterminated \"...\"");
+        assert_eq!(
+            reason("size:10"),
+            "at column 1: unknown field 'size' (expected title, class, exe, pid, id, ambiguity, \
+             strict or sticky)"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_values() {
+        assert!(reason("pid:-1").contains("pid must be a non-negative integer"));
+        assert!(reason("title~/(unclosed/").contains("invalid regex"));
+        assert!(reason("class~/Nav.*/").contains("only supported for title"));
+        assert!(reason("!class:Code").contains("only title can be negated"));
+        assert!(reason("ambiguity:random exe:x").contains("ambiguity must be"));
+        assert!(reason("exe:a exe:b").contains("'exe' is specified more than once"));
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_groups() {
+        assert!(reason("(class:a").contains("unclosed '('"));
+        assert!(reason("class:a)").contains("unexpected ')'"));
+        assert!(reason("class:a | class:b").contains("only allowed inside"));
+        assert!(reason("(class:a | )").contains("empty alternative"));
+        assert!(reason("(class:a) (class:b)").contains("only one any-of group"));
+        assert!(reason("((class:a))").contains("cannot be nested"));
+        assert!(reason("(!title:a)").contains("not allowed inside an any-of group"));
+    }
+
+    #[test]
+    fn test_parse_rejects_empty_queries() {
+        assert!(reason("").contains("at least one of"));
+        assert_eq!(reason("!"), "at column 2: expected a field name, found end of query");
+        assert!(reason(":code").contains("missing field name"));
+        assert!(reason("!title:Private").contains("at least one of"));
+    }
+}
@@ -0,0 +1,442 @@
+//! Window recording: short animations of changing content
+//!
+//! [`record_frames`] captures `fps` frames a second for the configured
+//! duration. A frame identical to the previous one only extends how long
+//! the previous frame is shown, so static stretches cost nothing; once
+//! `max_frames` distinct frames are kept, or the kept frames reach
+//! [`MAX_RECORDING_BYTES`] of pixels, recording stops early and the
+//! recording is marked truncated.
+//!
+//! [`encode_recording`] turns the frames into an animated WebP, APNG or GIF
+//! (see [`encode_animation`]). If the result is larger than `max_bytes`,
+//! the frames are downscaled and encoded again until it fits.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{
+//!         ImageBuffer, SettledCapture,
+//!         record::{encode_recording, record_frames},
+//!     },
+//!     model::RecordOptions,
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let options = RecordOptions {
+//!     fps: 20,
+//!     duration_ms: 200,
+//!     ..Default::default()
+//! };
+//! let recording = record_frames(&options, || async {
+//!     Ok(SettledCapture {
+//!         image: ImageBuffer::from_test_pattern(64, 48),
+//!         settle: None,
+//!         redaction: None,
+//!     })
+//! })
+//! .await
+//! .unwrap();
+//!
+//! // Four identical frames become one frame shown for 200ms
+//! assert_eq!(recording.captured, 4);
+//! assert_eq!(recording.frames.len(), 1);
+//! assert_eq!(recording.frames[0].duration_ms, 200);
+//!
+//! let (data, report) = encode_recording(&recording, &options).unwrap();
+//! assert_eq!(report.bytes, data.len() as u64);
+//! # }
+//! ```
+
+use std::{future::Future, time::Duration};
+
+use tokio::time::Instant;
+
+use super::{ImageBuffer, SettledCapture};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{RecordOptions, RecordReport, RedactionReport},
+    util::animation::{AnimationFrame, encode_animation},
+};
+
+/// Smallest frame width a recording is downscaled to before giving up
+const MIN_RECORDING_WIDTH: f32 = 16.0;
+
+/// Most decoded pixel data a recording keeps in memory (256 MiB)
+pub const MAX_RECORDING_BYTES: usize = 256 * 1024 * 1024;
+
+/// Frames of a recording, before encoding
+#[derive(Debug, Clone)]
+pub struct Recording {
+    /// Distinct consecutive frames with how long each is shown
+    pub frames: Vec<AnimationFrame>,
+    /// Number of frames captured, including merged duplicates
+    pub captured: u32,
+    /// Whether recording stopped early because `max_frames` or
+    /// [`MAX_RECORDING_BYTES`] was reached
+    pub truncated: bool,
+    /// What was redacted in any frame (see [`RedactionReport::merge`])
+    pub redaction: Option<RedactionReport>,
+}
+
+/// Captures frames at `options.fps` for `options.duration_ms`
+///
+/// Frames are captured on a fixed schedule; a capture that takes longer
+/// than the interval delays the next one rather than being skipped. Each
+/// frame is shown for one interval, and identical consecutive frames are
+/// merged. Frames whose size differs from the first (e.g. after a window
+/// resize) are resized to match it. Callers should hand in frames already
+/// fitted to the size they will be encoded at, since up to
+/// [`MAX_RECORDING_BYTES`] of them are held at once.
+///
+/// # Arguments
+///
+/// * `options` - Rate, duration and frame cap
+/// * `capture` - Produces one frame per call
+///
+/// # Returns
+///
+/// - `Ok(recording)` - At least one frame
+/// - `Err(_)` - The first capture error, returned as-is
+pub async fn record_frames<F, Fut>(options: &RecordOptions, capture: F) -> CaptureResult<Recording>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = CaptureResult<SettledCapture>>,
+{
+    record_frames_within(options, MAX_RECORDING_BYTES, capture).await
+}
+
+/// [`record_frames`] with the memory bound as a parameter
+async fn record_frames_within<F, Fut>(
+    options: &RecordOptions,
+    max_bytes: usize,
+    mut capture: F,
+) -> CaptureResult<Recording>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = CaptureResult<SettledCapture>>,
+{
+    let fps = options.fps.max(1);
+    let interval_ms = 1000 / fps;
+    let interval = Duration::from_millis(interval_ms as u64);
+    let planned = (options.duration_ms * fps as u64 / 1000).max(1);
+
+    let start = Instant::now();
+    let mut recording = Recording {
+        frames: Vec::new(),
+        captured: 0,
+        truncated: false,
+        redaction: None,
+    };
+    let mut bytes = 0;
+
+    for index in 0..planned {
+        tokio::time::sleep_until(start + interval * index as u32).await;
+        let shot = capture().await?;
+        recording.captured += 1;
+        if let Some(report) = shot.redaction {
+            match &mut recording.redaction {
+                Some(merged) => merged.merge(report),
+                None => recording.redaction = Some(report),
+            }
+        }
+
+        let image = match recording.frames.first() {
+            Some(first) if first.image.dimensions() != shot.image.dimensions() => {
+                let (width, height) = first.image.dimensions();
+                ImageBuffer::new(shot.image.inner().resize_exact(
+                    width,
+                    height,
+                    image::imageops::FilterType::Triangle,
+                ))
+            }
+            _ => shot.image,
+        };
+
+        let kept = recording.frames.len() as u32;
+        match recording.frames.last_mut() {
+            Some(last) if last.image.as_bytes() == image.as_bytes() => {
+                last.duration_ms += interval_ms;
+            }
+            _ if kept >= options.max_frames || bytes + image.as_bytes().len() > max_bytes => {
+                recording.truncated = true;
+                break;
+            }
+            _ => {
+                bytes += image.as_bytes().len();
+                recording
+                    .frames
+                    .push(AnimationFrame::new(image, interval_ms));
+            }
+        }
+    }
+
+    tracing::debug!(
+        "Recorded {} frames ({} distinct) in {}ms",
+        recording.captured,
+        recording.frames.len(),
+        start.elapsed().as_millis()
+    );
+    Ok(recording)
+}
+
+/// Encodes a recording, downscaling it until it fits `options.max_bytes`
+///
+/// # Errors
+///
+/// - `CaptureError::InvalidParameter` if the recording has no frames
+/// - `CaptureError::EncodingFailed` if encoding fails, or the animation is
+///   still too large once frames are 10% of their size or 16 pixels wide
+pub fn encode_recording(
+    recording: &Recording,
+    options: &RecordOptions,
+) -> CaptureResult<(Vec<u8>, RecordReport)> {
+    let mut scale = 1.0f32;
+    let mut data = encode_animation(&recording.frames, options.format)?;
+    let mut scaled = None;
+
+    while data.len() as u64 > options.max_bytes {
+        // Encoded size grows roughly with the pixel count
+        let ratio = (options.max_bytes as f32 / data.len() as f32).sqrt();
+        let next = scale * (ratio * 0.9).min(0.9);
+        let width = recording.frames[0].image.width() as f32;
+        if next < 0.1 || width * next < MIN_RECORDING_WIDTH {
+            return Err(CaptureError::EncodingFailed {
+                format: options.format.to_string(),
+                reason: format!(
+                    "animation is {} bytes at {:.0}% scale, over the {} byte limit",
+                    data.len(),
+                    scale * 100.0,
+                    options.max_bytes
+                ),
+            });
+        }
+
+        let frames = recording
+            .frames
+            .iter()
+            .map(|frame| Ok(AnimationFrame::new(frame.image.scale(next)?, frame.duration_ms)))
+            .collect::<CaptureResult<Vec<_>>>()?;
+        data = encode_animation(&frames, options.format)?;
+        scaled = Some(frames);
+        scale = next;
+    }
+
+    let frames = scaled.as_deref().unwrap_or(&recording.frames);
+    let (width, height) = frames[0].image.dimensions();
+    let report = RecordReport {
+        format: options.format,
+        captured: recording.captured,
+        frames: frames.len() as u32,
+        duration_ms: frames.iter().map(|f| f.duration_ms as u64).sum(),
+        truncated: recording.truncated,
+        width,
+        height,
+        scale,
+        bytes: data.len() as u64,
+    };
+    Ok((data, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::model::AnimationFormat;
+
+    fn shot(image: ImageBuffer) -> CaptureResult<SettledCapture> {
+        Ok(SettledCapture {
+            image,
+            settle: None,
+            redaction: None,
+        })
+    }
+
+    fn solid(value: u8) -> ImageBuffer {
+        ImageBuffer::new(image::DynamicImage::ImageLuma8(image::GrayImage::from_pixel(
+            32,
+            32,
+            image::Luma([value]),
+        )))
+    }
+
+    fn fast_record() -> RecordOptions {
+        RecordOptions {
+            fps: 20,
+            duration_ms: 300,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_identical_frames_are_merged() {
+        let calls = AtomicU32::new(0);
+
+        // Two frames of each shade
+        let recording = record_frames(&fast_record(), || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move { shot(solid((n / 2 * 40) as u8)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(recording.captured, 6);
+        assert!(!recording.truncated);
+        let durations: Vec<u32> = recording.frames.iter().map(|f| f.duration_ms).collect();
+        assert_eq!(durations, vec![100, 100, 100]);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_max_frames() {
+        let calls = AtomicU32::new(0);
+        let options = RecordOptions {
+            max_frames: 2,
+            ..fast_record()
+        };
+
+        let recording = record_frames(&options, || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move { shot(solid((n * 40) as u8)) }
+        })
+        .await
+        .unwrap();
+
+        assert!(recording.truncated);
+        assert_eq!(recording.frames.len(), 2);
+        assert_eq!(recording.captured, 3);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_memory_bound() {
+        let calls = AtomicU32::new(0);
+
+        // Each 32x32 grey frame is 1 KiB
+        let recording = record_frames_within(&fast_record(), 2500, || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move { shot(solid((n * 40) as u8)) }
+        })
+        .await
+        .unwrap();
+
+        assert!(recording.truncated);
+        assert_eq!(recording.frames.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_redaction_reports_are_merged() {
+        let calls = AtomicU32::new(0);
+
+        let recording = record_frames(&fast_record(), || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let redaction = match n {
+                    0 => Some(RedactionReport {
+                        windows: vec!["w1".to_string()],
+                        ..Default::default()
+                    }),
+                    1 => Some(RedactionReport {
+                        regions: 2,
+                        windows: vec!["w1".to_string(), "w2".to_string()],
+                        ..Default::default()
+                    }),
+                    _ => None,
+                };
+                Ok(SettledCapture {
+                    image: solid(0),
+                    settle: None,
+                    redaction,
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        let report = recording.redaction.unwrap();
+        assert_eq!(report.regions, 2);
+        assert_eq!(report.windows, vec!["w1".to_string(), "w2".to_string()]);
+        assert!(!report.full);
+    }
+
+    #[tokio::test]
+    async fn test_resized_frames_match_the_first() {
+        let calls = AtomicU32::new(0);
+
+        let recording = record_frames(&fast_record(), || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            let size = if n == 0 { 64 } else { 48 };
+            async move { shot(ImageBuffer::from_test_pattern(size, size)) }
+        })
+        .await
+        .unwrap();
+
+        assert!(
+            recording
+                .frames
+                .iter()
+                .all(|f| f.image.dimensions() == (64, 64))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capture_error_is_returned() {
+        let result =
+            record_frames(&fast_record(), || async { Err(CaptureError::WindowClosed) }).await;
+
+        assert!(matches!(result, Err(CaptureError::WindowClosed)));
+    }
+
+    /// Frames of random noise, which compresses poorly
+    fn noisy_recording(frames: u32) -> Recording {
+        let mut seed = 0x2545_f491u32;
+        let frames = (0..frames)
+            .map(|_| {
+                let image = image::RgbaImage::from_fn(256, 256, |_, _| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 17;
+                    seed ^= seed << 5;
+                    image::Rgba(seed.to_le_bytes())
+                });
+                AnimationFrame::new(ImageBuffer::new(image::DynamicImage::ImageRgba8(image)), 100)
+            })
+            .collect();
+        Recording {
+            frames,
+            captured: 4,
+            truncated: false,
+            redaction: None,
+        }
+    }
+
+    #[test]
+    fn test_encode_recording_downscales_to_fit() {
+        let options = RecordOptions {
+            max_bytes: 256 * 1024,
+            format: AnimationFormat::Apng,
+            ..Default::default()
+        };
+        let recording = noisy_recording(4);
+
+        let (data, report) = encode_recording(&recording, &options).unwrap();
+        assert!(data.len() as u64 <= options.max_bytes);
+        assert!(report.scale < 1.0);
+        assert!(report.width < 256);
+        assert_eq!(report.frames, 4);
+        assert_eq!(report.duration_ms, 400);
+        assert_eq!(report.bytes, data.len() as u64);
+    }
+
+    #[test]
+    fn test_encode_recording_fails_when_unable_to_fit() {
+        let options = RecordOptions {
+            max_bytes: 1,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            encode_recording(&noisy_recording(2), &options),
+            Err(CaptureError::EncodingFailed { .. })
+        ));
+    }
+}
@@ -0,0 +1,534 @@
+//! Redaction of sensitive regions and windows before encoding
+//!
+//! Captures leave the machine, usually for a model provider. Redaction
+//! hides pixels after capture and before the image is encoded, so they are
+//! never part of the output. Two things are redacted:
+//!
+//! - Rectangles the caller lists in [`CaptureOptions::redact`], in screen
+//!   coordinates
+//! - The on-screen rectangles of windows matching a deny selector of the
+//!   [`RedactionPolicy`], such as password managers and banking sites
+//!
+//! [`CompositeBackend`](super::CompositeBackend) applies both to every
+//! window and display capture it makes. A capture of a denied window is
+//! hidden entirely.
+//!
+//! # File Format
+//!
+//! The policy lives in the `[redaction]` table of a TOML file. Deny
+//! selectors use the [query syntax](super::query):
+//!
+//! ```toml
+//! [redaction]
+//! mode = "pixelate"   # fill (default), pixelate or blur
+//! deny = ["class:KeePassXC", "title~/Online Banking/"]
+//! ```
+//!
+//! # Location
+//!
+//! [`RedactionPolicy::load_default`] reads `$SCREENSHOT_PRIVACY_FILE` if
+//! set, otherwise `$XDG_CONFIG_HOME/screenshot-mcp/privacy.toml` (falling
+//! back to `~/.config`). A missing file yields an empty policy, and so does
+//! having none of these set: a shared directory such as `/tmp` is never
+//! used, since anyone could plant a policy there.
+//!
+//! [`CaptureOptions::redact`]: crate::model::CaptureOptions::redact
+
+use std::path::{Path, PathBuf};
+
+use image::{DynamicImage, Rgba, RgbaImage};
+use serde::Deserialize;
+
+use super::{ImageBuffer, WindowMatcher, query::parse_selector_query};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{RedactionMode, Region, WindowHandle, WindowInfo, WindowSelector},
+};
+
+/// Environment variable that overrides the privacy file location
+pub const PRIVACY_FILE_ENV: &str = "SCREENSHOT_PRIVACY_FILE";
+
+/// Side of the blocks `pixelate` and `blur` average over, in output pixels
+pub const REDACTION_BLOCK: u32 = 16;
+
+/// Windows to hide from every capture
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RedactionPolicy {
+    /// How denied windows are hidden
+    pub mode: RedactionMode,
+    /// Selectors of windows to hide
+    pub deny: Vec<WindowSelector>,
+}
+
+/// The `[redaction]` table as written in the file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RedactionTable {
+    #[serde(default)]
+    mode: RedactionMode,
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+impl RedactionPolicy {
+    /// Creates an empty policy that hides nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses the `[redaction]` table of a TOML document
+    ///
+    /// Other tables are ignored, so the policy can share a file with other
+    /// settings.
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `redaction` if the TOML is
+    /// malformed, the table has unknown keys or an unknown mode, or a deny
+    /// query does not parse.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::{capture::redact::RedactionPolicy, model::RedactionMode};
+    ///
+    /// let policy = RedactionPolicy::from_toml_str(
+    ///     r#"
+    ///     [redaction]
+    ///     mode = "blur"
+    ///     deny = ["class:KeePassXC"]
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(policy.mode, RedactionMode::Blur);
+    /// assert_eq!(policy.deny[0].class.as_deref(), Some("KeePassXC"));
+    /// ```
+    pub fn from_toml_str(text: &str) -> CaptureResult<Self> {
+        let mut document: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| config_error(e.message()))?;
+
+        let Some(table) = document.remove("redaction") else {
+            return Ok(Self::new());
+        };
+        let table: RedactionTable = table
+            .try_into()
+            .map_err(|e: toml::de::Error| config_error(e.message()))?;
+
+        let deny = table
+            .deny
+            .iter()
+            .map(|query| {
+                parse_selector_query(query).map_err(|e| match e {
+                    CaptureError::InvalidParameter { reason, .. } => {
+                        config_error(format!("deny query '{}': {}", query, reason))
+                    }
+                    other => other,
+                })
+            })
+            .collect::<CaptureResult<_>>()?;
+
+        Ok(Self {
+            mode: table.mode,
+            deny,
+        })
+    }
+
+    /// Loads the policy from a TOML file
+    ///
+    /// # Errors
+    ///
+    /// - `CaptureError::IoError` - The file cannot be read
+    /// - `CaptureError::InvalidParameter` - As for
+    ///   [`from_toml_str`](Self::from_toml_str), with the file path in the
+    ///   reason
+    pub fn load(path: &Path) -> CaptureResult<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_toml_str(&text).map_err(|e| match e {
+            CaptureError::InvalidParameter { parameter, reason } => {
+                CaptureError::InvalidParameter {
+                    parameter,
+                    reason: format!("{}: {}", path.display(), reason),
+                }
+            }
+            other => other,
+        })
+    }
+
+    /// Loads the policy from [`default_path`](Self::default_path)
+    ///
+    /// Returns an empty policy if the file does not exist.
+    pub fn load_default() -> CaptureResult<Self> {
+        let Some(path) = Self::default_path() else {
+            tracing::debug!("No config directory, so no privacy file");
+            return Ok(Self::new());
+        };
+        if !path.exists() {
+            tracing::debug!("No privacy file at {}", path.display());
+            return Ok(Self::new());
+        }
+        let policy = Self::load(&path)?;
+        tracing::debug!(
+            "Loaded {} redaction deny selectors from {}",
+            policy.deny.len(),
+            path.display()
+        );
+        Ok(policy)
+    }
+
+    /// Gets the path of the privacy file
+    ///
+    /// None if neither the override nor a per-user config directory is set.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(PRIVACY_FILE_ENV) {
+            return Some(PathBuf::from(path));
+        }
+
+        let config_dir = if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+            PathBuf::from(dir)
+        } else if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".config")
+        } else {
+            return None;
+        };
+
+        Some(config_dir.join("screenshot-mcp").join("privacy.toml"))
+    }
+
+    /// Returns true if the policy denies no windows
+    pub fn is_empty(&self) -> bool {
+        self.deny.is_empty()
+    }
+
+    /// Returns the windows matching any deny selector, in list order
+    pub fn denied_windows(&self, windows: &[WindowInfo]) -> Vec<WindowHandle> {
+        let matcher = WindowMatcher::new();
+        let denied: Vec<WindowHandle> = self
+            .deny
+            .iter()
+            .flat_map(|selector| matcher.all_matches(selector, windows))
+            .collect();
+        windows
+            .iter()
+            .filter(|w| denied.contains(&w.id))
+            .map(|w| w.id.clone())
+            .collect()
+    }
+}
+
+fn config_error(reason: impl std::fmt::Display) -> CaptureError {
+    CaptureError::InvalidParameter {
+        parameter: "redaction".to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Maps a screen rectangle into the pixels of an image showing `area`
+///
+/// The result covers every pixel the rectangle touches, clipped to the
+/// image, or is None if the rectangle misses the image.
+pub fn screen_to_image(
+    region: Region,
+    area: Region,
+    (width, height): (u32, u32),
+) -> Option<Region> {
+    if area.width == 0 || area.height == 0 {
+        return None;
+    }
+    let scale_x = f64::from(width) / f64::from(area.width);
+    let scale_y = f64::from(height) / f64::from(area.height);
+    let map = |start: u32, length: u32, origin: u32, scale: f64, limit: u32| {
+        let from = (f64::from(start) - f64::from(origin)) * scale;
+        let to = (f64::from(start) + f64::from(length) - f64::from(origin)) * scale;
+        let from = from.floor().clamp(0.0, f64::from(limit)) as u32;
+        let to = to.ceil().clamp(0.0, f64::from(limit)) as u32;
+        (to > from).then_some((from, to - from))
+    };
+    let (x, w) = map(region.x, region.width, area.x, scale_x, width)?;
+    let (y, h) = map(region.y, region.height, area.y, scale_y, height)?;
+    Some(Region::new(x, y, w, h))
+}
+
+/// Mean colour of each block of a rectangle, row by row
+fn block_means(image: &RgbaImage, r: Region) -> (Vec<[f32; 4]>, u32, u32) {
+    let columns = r.width.div_ceil(REDACTION_BLOCK);
+    let rows = r.height.div_ceil(REDACTION_BLOCK);
+    let mut means = Vec::with_capacity((columns * rows) as usize);
+    for row in 0..rows {
+        for column in 0..columns {
+            let x0 = r.x + column * REDACTION_BLOCK;
+            let y0 = r.y + row * REDACTION_BLOCK;
+            let x1 = (x0 + REDACTION_BLOCK).min(r.x + r.width);
+            let y1 = (y0 + REDACTION_BLOCK).min(r.y + r.height);
+            let mut sum = [0.0f32; 4];
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    for (total, channel) in sum.iter_mut().zip(image.get_pixel(x, y).0) {
+                        *total += f32::from(channel);
+                    }
+                }
+            }
+            let count = ((x1 - x0) * (y1 - y0)) as f32;
+            means.push(sum.map(|total| total / count));
+        }
+    }
+    (means, columns, rows)
+}
+
+fn to_pixel(color: [f32; 4]) -> Rgba<u8> {
+    Rgba(color.map(|c| c.round().clamp(0.0, 255.0) as u8))
+}
+
+/// Hides one rectangle, which must lie inside the image
+fn redact_rect(image: &mut RgbaImage, r: Region, mode: RedactionMode) {
+    match mode {
+        RedactionMode::Fill => {
+            for y in r.y..r.y + r.height {
+                for x in r.x..r.x + r.width {
+                    image.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+                }
+            }
+        }
+        RedactionMode::Pixelate => {
+            let (means, columns, _) = block_means(image, r);
+            for y in r.y..r.y + r.height {
+                for x in r.x..r.x + r.width {
+                    let column = (x - r.x) / REDACTION_BLOCK;
+                    let row = (y - r.y) / REDACTION_BLOCK;
+                    image.put_pixel(x, y, to_pixel(means[(row * columns + column) as usize]));
+                }
+            }
+        }
+        RedactionMode::Blur => {
+            // Interpolate between block centres; the output depends only on
+            // the block means, like pixelate, so detail is gone for good
+            let (means, columns, rows) = block_means(image, r);
+            let block = REDACTION_BLOCK as f32;
+            let axis = |offset: u32, count: u32| {
+                let position = ((offset as f32 + 0.5) / block - 0.5).clamp(0.0, (count - 1) as f32);
+                let low = position.floor() as u32;
+                let high = (low + 1).min(count - 1);
+                (low, high, position - low as f32)
+            };
+            for y in r.y..r.y + r.height {
+                let (top, bottom, fy) = axis(y - r.y, rows);
+                for x in r.x..r.x + r.width {
+                    let (left, right, fx) = axis(x - r.x, columns);
+                    let mean = |row: u32, column: u32| means[(row * columns + column) as usize];
+                    let mut color = [0.0f32; 4];
+                    for (i, c) in color.iter_mut().enumerate() {
+                        let upper = mean(top, left)[i] * (1.0 - fx) + mean(top, right)[i] * fx;
+                        let lower =
+                            mean(bottom, left)[i] * (1.0 - fx) + mean(bottom, right)[i] * fx;
+                        *c = upper * (1.0 - fy) + lower * fy;
+                    }
+                    image.put_pixel(x, y, to_pixel(color));
+                }
+            }
+        }
+    }
+}
+
+impl ImageBuffer {
+    /// Returns a copy of the image with `regions` hidden
+    ///
+    /// Regions are image pixels and are clipped to the image; regions
+    /// outside it are ignored. Overlapping regions are hidden one after
+    /// another.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::{
+    ///     capture::ImageBuffer,
+    ///     model::{RedactionMode, Region},
+    /// };
+    ///
+    /// let capture = ImageBuffer::from_test_pattern(320, 200);
+    /// let redacted = capture.redact(&[Region::new(10, 10, 100, 40)], RedactionMode::Fill);
+    /// assert_eq!(redacted.to_rgba8().get_pixel(50, 30).0, [0, 0, 0, 255]);
+    /// ```
+    pub fn redact(&self, regions: &[Region], mode: RedactionMode) -> Self {
+        let (width, height) = self.dimensions();
+        let whole = Region::new(0, 0, width, height);
+        let mut image = self.to_rgba8();
+        for region in regions {
+            if let Some(clipped) = screen_to_image(*region, whole, (width, height)) {
+                redact_rect(&mut image, clipped, mode);
+            }
+        }
+        Self::new(DynamicImage::ImageRgba8(image))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::BackendType;
+
+    /// Deterministic noise, standing in for text and other fine detail
+    fn noise(width: u32, height: u32, seed: u32) -> RgbaImage {
+        RgbaImage::from_fn(width, height, |x, y| {
+            let v = (x.wrapping_mul(73_856_093) ^ y.wrapping_mul(19_349_663) ^ seed)
+                .wrapping_mul(2_654_435_761);
+            Rgba([(v >> 24) as u8, (v >> 16) as u8, (v >> 8) as u8, 255])
+        })
+    }
+
+    /// Same pixels as `image`, mirrored within each redaction block
+    fn shuffle_blocks(image: &RgbaImage) -> RgbaImage {
+        RgbaImage::from_fn(image.width(), image.height(), |x, y| {
+            let bx = x / REDACTION_BLOCK * REDACTION_BLOCK;
+            let by = y / REDACTION_BLOCK * REDACTION_BLOCK;
+            let mx = bx + (REDACTION_BLOCK - 1 - (x - bx));
+            let my = by + (REDACTION_BLOCK - 1 - (y - by));
+            *image.get_pixel(mx, my)
+        })
+    }
+
+    fn buffer(image: RgbaImage) -> ImageBuffer {
+        ImageBuffer::new(DynamicImage::ImageRgba8(image))
+    }
+
+    #[test]
+    fn test_default_path_ignores_shared_directories() {
+        temp_env::with_vars_unset([PRIVACY_FILE_ENV, "XDG_CONFIG_HOME", "HOME"], || {
+            assert_eq!(RedactionPolicy::default_path(), None);
+            assert!(RedactionPolicy::load_default().unwrap().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_fill_blacks_out_region_only() {
+        let original = noise(64, 64, 1);
+        let redacted = buffer(original.clone())
+            .redact(&[Region::new(16, 16, 32, 32)], RedactionMode::Fill)
+            .to_rgba8();
+
+        for (x, y, pixel) in redacted.enumerate_pixels() {
+            let inside = (16..48).contains(&x) && (16..48).contains(&y);
+            if inside {
+                assert_eq!(pixel.0, [0, 0, 0, 255]);
+            } else {
+                assert_eq!(pixel, original.get_pixel(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pixelate_and_blur_cannot_be_recovered() {
+        // Two images with different detail but the same block means must
+        // redact to exactly the same pixels
+        let a = noise(64, 48, 7);
+        let b = shuffle_blocks(&a);
+        assert_ne!(a, b);
+
+        let region = [Region::new(0, 0, 64, 48)];
+        for mode in [RedactionMode::Pixelate, RedactionMode::Blur] {
+            let ra = buffer(a.clone()).redact(&region, mode).to_rgba8();
+            let rb = buffer(b.clone()).redact(&region, mode).to_rgba8();
+            assert_eq!(ra, rb, "{:?} output depends on more than block means", mode);
+
+            // And no original pixel survives in place
+            let unchanged = ra
+                .pixels()
+                .zip(a.pixels())
+                .filter(|(redacted, original)| redacted == original)
+                .count();
+            assert!(unchanged < 16, "{:?} kept {} original pixels", mode, unchanged);
+        }
+    }
+
+    #[test]
+    fn test_pixelate_blocks_are_uniform() {
+        let redacted = buffer(noise(40, 40, 3))
+            .redact(&[Region::new(4, 4, 32, 32)], RedactionMode::Pixelate)
+            .to_rgba8();
+        let first = redacted.get_pixel(4, 4);
+        for y in 4..20 {
+            for x in 4..20 {
+                assert_eq!(redacted.get_pixel(x, y), first);
+            }
+        }
+        assert_ne!(redacted.get_pixel(20, 4), first);
+    }
+
+    #[test]
+    fn test_regions_are_clipped() {
+        let redacted = buffer(noise(20, 20, 5))
+            .redact(
+                &[Region::new(15, 15, 100, 100), Region::new(50, 50, 5, 5)],
+                RedactionMode::Fill,
+            )
+            .to_rgba8();
+        assert_eq!(redacted.get_pixel(19, 19).0, [0, 0, 0, 255]);
+        assert_ne!(redacted.get_pixel(14, 14).0, [0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_screen_to_image_covers_touched_pixels() {
+        // Screen area at (100, 50) shown at half scale
+        let area = Region::new(100, 50, 400, 200);
+        assert_eq!(
+            screen_to_image(Region::new(101, 51, 3, 3), area, (200, 100)),
+            Some(Region::new(0, 0, 2, 2))
+        );
+        assert_eq!(
+            screen_to_image(Region::new(0, 0, 200, 100), area, (200, 100)),
+            Some(Region::new(0, 0, 50, 25))
+        );
+        assert_eq!(screen_to_image(Region::new(0, 0, 100, 50), area, (200, 100)), None);
+    }
+
+    #[test]
+    fn test_policy_from_toml() {
+        let policy = RedactionPolicy::from_toml_str(
+            r#"
+            [targets]
+            ide = "class:code"
+
+            [redaction]
+            deny = ["class:KeePassXC", "title:Bank exe:firefox"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.mode, RedactionMode::Fill);
+        assert_eq!(policy.deny.len(), 2);
+        assert!(RedactionPolicy::from_toml_str("").unwrap().is_empty());
+
+        for bad in [
+            "[redaction]\nmode = \"smudge\"",
+            "[redaction]\ndenied = []",
+            "[redaction]\ndeny = [\"title:\"]",
+        ] {
+            assert!(matches!(
+                RedactionPolicy::from_toml_str(bad),
+                Err(CaptureError::InvalidParameter { parameter, .. }) if parameter == "redaction"
+            ));
+        }
+    }
+
+    #[test]
+    fn test_denied_windows() {
+        let window = |id: &str, title: &str, class: &str| {
+            WindowInfo::new(
+                id.to_string(),
+                title.to_string(),
+                class.to_string(),
+                "app".to_string(),
+                1,
+                BackendType::None,
+            )
+        };
+        let windows = vec![
+            window("1", "Passwords.kdbx", "KeePassXC"),
+            window("2", "Editor", "Code"),
+            window("3", "My Bank - Online Banking", "Navigator"),
+        ];
+        let policy = RedactionPolicy::from_toml_str(
+            "[redaction]\ndeny = [\"title~/Online Banking/\", \"class:KeePassXC\"]",
+        )
+        .unwrap();
+        assert_eq!(policy.denied_windows(&windows), vec!["1", "3"]);
+    }
+}
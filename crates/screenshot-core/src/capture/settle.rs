@@ -0,0 +1,193 @@
+//! Visual settling: capture once the content stops changing
+//!
+//! Screenshots taken mid-animation or while content is still loading are
+//! rarely useful. [`capture_until_settled`] repeats a capture at a fixed
+//! interval and returns the first frame whose perceptual difference from the
+//! previous frame is at most the configured threshold.
+//!
+//! If the content never settles within `max_wait_ms`, the most recent frame
+//! is returned and the report has `settled: false`; this is not an error,
+//! since a busy window is still worth capturing.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{ImageBuffer, settle::capture_until_settled},
+//!     model::SettleOptions,
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let settle = SettleOptions {
+//!     interval_ms: 10,
+//!     ..Default::default()
+//! };
+//! let (image, report) =
+//!     capture_until_settled(&settle, || async { Ok(ImageBuffer::from_test_pattern(64, 64)) })
+//!         .await
+//!         .unwrap();
+//!
+//! assert_eq!(image.dimensions(), (64, 64));
+//! assert!(report.settled);
+//! assert_eq!(report.frames, 2);
+//! # }
+//! ```
+
+use std::{future::Future, time::Duration};
+
+use tokio::time::Instant;
+
+use super::ImageBuffer;
+use crate::{
+    error::CaptureResult,
+    model::{SettleOptions, SettleReport},
+};
+
+/// Captures repeatedly until two consecutive frames are perceptually equal
+///
+/// # Arguments
+///
+/// * `settle` - Interval, threshold and maximum wait
+/// * `capture` - Produces one frame per call
+///
+/// # Returns
+///
+/// - `Ok((frame, report))` - The settled frame (or the last frame on
+///   timeout) and how many frames / how long it took
+/// - `Err(_)` - The first capture error, returned as-is
+pub async fn capture_until_settled<F, Fut>(
+    settle: &SettleOptions,
+    mut capture: F,
+) -> CaptureResult<(ImageBuffer, SettleReport)>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = CaptureResult<ImageBuffer>>,
+{
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(settle.max_wait_ms);
+    let interval = Duration::from_millis(settle.interval_ms);
+
+    let mut previous = capture().await?;
+    let mut frames = 1u32;
+    let mut last_diff = None;
+
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        tokio::time::sleep(interval.min(deadline - now)).await;
+
+        let current = capture().await?;
+        frames += 1;
+
+        let diff = previous.perceptual_diff(&current);
+        last_diff = Some(diff);
+        previous = current;
+
+        if diff <= settle.threshold {
+            let report = SettleReport {
+                frames,
+                waited_ms: start.elapsed().as_millis() as u64,
+                settled: true,
+                last_diff,
+            };
+            tracing::debug!("Capture settled: {:?}", report);
+            return Ok((previous, report));
+        }
+    }
+
+    let report = SettleReport {
+        frames,
+        waited_ms: start.elapsed().as_millis() as u64,
+        settled: false,
+        last_diff,
+    };
+    tracing::debug!("Capture did not settle within {}ms: {:?}", settle.max_wait_ms, report);
+    Ok((previous, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::error::CaptureError;
+
+    fn solid(value: u8) -> ImageBuffer {
+        ImageBuffer::new(image::DynamicImage::ImageLuma8(image::GrayImage::from_pixel(
+            32,
+            32,
+            image::Luma([value]),
+        )))
+    }
+
+    fn fast_settle() -> SettleOptions {
+        SettleOptions {
+            interval_ms: 10,
+            threshold: 0.01,
+            max_wait_ms: 200,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_settles_after_animation_stops() {
+        let calls = AtomicU32::new(0);
+
+        // Frames 1-3 change, then the content is static
+        let (image, report) = capture_until_settled(&fast_settle(), || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(solid((n.min(3) * 60) as u8)) }
+        })
+        .await
+        .unwrap();
+
+        assert!(report.settled);
+        assert_eq!(report.frames, 5);
+        assert_eq!(report.last_diff, Some(0.0));
+        assert_eq!(image.perceptual_diff(&solid(180)), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_returns_last_frame_when_never_settling() {
+        let calls = AtomicU32::new(0);
+
+        let (_, report) = capture_until_settled(&fast_settle(), || {
+            let n = calls.fetch_add(1, Ordering::SeqCst);
+            async move { Ok(solid(if n % 2 == 0 { 0 } else { 255 })) }
+        })
+        .await
+        .unwrap();
+
+        assert!(!report.settled);
+        assert!(report.frames > 2);
+        assert!(report.waited_ms >= 200);
+        assert_eq!(report.last_diff, Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn test_zero_max_wait_returns_first_frame() {
+        let settle = SettleOptions {
+            max_wait_ms: 0,
+            ..fast_settle()
+        };
+
+        let (_, report) = capture_until_settled(&settle, || async { Ok(solid(0)) })
+            .await
+            .unwrap();
+
+        assert_eq!(report.frames, 1);
+        assert!(!report.settled);
+        assert_eq!(report.last_diff, None);
+    }
+
+    #[tokio::test]
+    async fn test_capture_error_is_returned() {
+        let result =
+            capture_until_settled(&fast_settle(), || async { Err(CaptureError::WindowClosed) })
+                .await;
+
+        assert!(matches!(result, Err(CaptureError::WindowClosed)));
+    }
+}
@@ -0,0 +1,447 @@
+//! Named capture targets loaded from a config file
+//!
+//! A target gives a short name to a selector query, so prompts and scripts
+//! can say `target: "ide"` instead of repeating
+//! `{class: "jetbrains-idea", title: "backend"}`. Each target may also carry
+//! default [`CaptureOptions`] that apply whenever it is captured.
+//!
+//! # File Format
+//!
+//! Targets live in the `[targets]` table of a TOML file. A target is either
+//! a selector query (see [`query`](super::query)) or a table with a `query`
+//! key plus any `CaptureOptions` fields:
+//!
+//! ```toml
+//! [targets]
+//! ide = "class:jetbrains-idea title:backend"
+//!
+//! [targets.dashboard]
+//! query = "class:firefox title:Grafana !title:Private"
+//! format = "png"
+//! scale = 0.5
+//! region = { x = 0, y = 80, width = 1280, height = 720 }
+//! ```
+//!
+//! # Location
+//!
+//! [`TargetRegistry::load_default`] reads `$SCREENSHOT_TARGETS_FILE` if set,
+//! otherwise `$XDG_CONFIG_HOME/screenshot-mcp/targets.toml` (falling back to
+//! `~/.config`). A missing file yields an empty registry.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use super::{WindowMatcher, query::parse_selector_query};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{CaptureOptions, WindowInfo, WindowSelector},
+};
+
+/// Environment variable that overrides the targets file location
+pub const TARGETS_FILE_ENV: &str = "SCREENSHOT_TARGETS_FILE";
+
+/// A named selector with its default capture options
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetAlias {
+    /// Alias name, e.g. `ide`
+    pub name: String,
+    /// Selector query as written in the config file
+    pub query: String,
+    /// Selector compiled from `query`
+    pub selector: WindowSelector,
+    /// Capture options used unless the caller overrides them
+    pub options: CaptureOptions,
+}
+
+/// Whether a target currently resolves to a window
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TargetStatus {
+    /// Alias name
+    pub name: String,
+    /// Selector query of the alias
+    pub query: String,
+    /// The window the alias resolves to, if any
+    pub window: Option<WindowInfo>,
+    /// Why the alias does not resolve (not found, ambiguous, ...)
+    pub error: Option<String>,
+}
+
+/// Set of named targets, ordered by name
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TargetRegistry {
+    targets: BTreeMap<String, TargetAlias>,
+}
+
+impl TargetRegistry {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses targets from TOML text
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `targets` if the TOML is
+    /// malformed, a name is invalid, a query does not parse, or an option
+    /// has the wrong type. The reason names the offending target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::targets::TargetRegistry;
+    ///
+    /// let registry = TargetRegistry::from_toml_str(
+    ///     r#"
+    ///     [targets]
+    ///     ide = "class:jetbrains-idea title:backend"
+    ///     "#,
+    /// )
+    /// .unwrap();
+    /// let ide = registry.get("ide").unwrap();
+    /// assert_eq!(ide.selector.class.as_deref(), Some("jetbrains-idea"));
+    /// ```
+    pub fn from_toml_str(text: &str) -> CaptureResult<Self> {
+        let mut document: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| config_error(e.message()))?;
+
+        let Some(entries) = document.remove("targets") else {
+            return Ok(Self::new());
+        };
+        let toml::Value::Table(entries) = entries else {
+            return Err(config_error("'targets' must be a table"));
+        };
+
+        let mut registry = Self::new();
+        for (name, entry) in entries {
+            let alias = parse_alias(&name, entry)?;
+            registry.targets.insert(name, alias);
+        }
+        Ok(registry)
+    }
+
+    /// Loads targets from a TOML file
+    ///
+    /// # Errors
+    ///
+    /// - `CaptureError::IoError` - The file cannot be read
+    /// - `CaptureError::InvalidParameter` - As for [`from_toml_str`](Self::from_toml_str),
+    ///   with the file path in the reason
+    pub fn load(path: &Path) -> CaptureResult<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_toml_str(&text).map_err(|e| match e {
+            CaptureError::InvalidParameter { parameter, reason } => {
+                CaptureError::InvalidParameter {
+                    parameter,
+                    reason: format!("{}: {}", path.display(), reason),
+                }
+            }
+            other => other,
+        })
+    }
+
+    /// Loads targets from [`default_path`](Self::default_path)
+    ///
+    /// Returns an empty registry if the file does not exist.
+    pub fn load_default() -> CaptureResult<Self> {
+        let path = Self::default_path();
+        if !path.exists() {
+            tracing::debug!("No targets file at {}", path.display());
+            return Ok(Self::new());
+        }
+        let registry = Self::load(&path)?;
+        tracing::debug!("Loaded {} targets from {}", registry.len(), path.display());
+        Ok(registry)
+    }
+
+    /// Gets the path of the targets file
+    pub fn default_path() -> PathBuf {
+        if let Ok(path) = std::env::var(TARGETS_FILE_ENV) {
+            return PathBuf::from(path);
+        }
+
+        let config_dir = if let Ok(dir) = std::env::var("XDG_CONFIG_HOME") {
+            PathBuf::from(dir)
+        } else if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".config")
+        } else {
+            PathBuf::from("/tmp")
+        };
+
+        config_dir.join("screenshot-mcp").join("targets.toml")
+    }
+
+    /// Looks up a target by name
+    pub fn get(&self, name: &str) -> Option<&TargetAlias> {
+        self.targets.get(name)
+    }
+
+    /// Looks up a target by name, failing with the known names if absent
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `target` if no target has this
+    /// name.
+    pub fn require(&self, name: &str) -> CaptureResult<&TargetAlias> {
+        self.get(name).ok_or_else(|| {
+            let known = if self.is_empty() {
+                format!("no targets are defined in {}", Self::default_path().display())
+            } else {
+                format!(
+                    "known targets: {}",
+                    self.targets.keys().cloned().collect::<Vec<_>>().join(", ")
+                )
+            };
+            CaptureError::InvalidParameter {
+                parameter: "target".to_string(),
+                reason: format!("unknown target '{}' ({})", name, known),
+            }
+        })
+    }
+
+    /// Iterates over targets in name order
+    pub fn iter(&self) -> impl Iterator<Item = &TargetAlias> {
+        self.targets.values()
+    }
+
+    /// Returns the number of targets
+    pub fn len(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Returns true if no targets are defined
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Resolves every target against a window list
+    ///
+    /// Uses the same matching as capture, including each selector's
+    /// ambiguity policy.
+    pub fn statuses(&self, windows: &[WindowInfo]) -> Vec<TargetStatus> {
+        let matcher = WindowMatcher::new();
+        self.iter()
+            .map(|alias| {
+                let resolved = matcher.select_match(&alias.selector, windows);
+                let (window, error) = match resolved {
+                    Ok(handle) => (windows.iter().find(|w| w.id == handle).cloned(), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                TargetStatus {
+                    name: alias.name.clone(),
+                    query: alias.query.clone(),
+                    window,
+                    error,
+                }
+            })
+            .collect()
+    }
+}
+
+fn config_error(reason: impl std::fmt::Display) -> CaptureError {
+    CaptureError::InvalidParameter {
+        parameter: "targets".to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+/// Builds one alias from its `[targets]` entry
+fn parse_alias(name: &str, entry: toml::Value) -> CaptureResult<TargetAlias> {
+    let valid_name = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+        return Err(config_error(format!(
+            "invalid target name '{}' (use letters, digits, '-' and '_')",
+            name
+        )));
+    }
+
+    let (query, options) = match entry {
+        toml::Value::String(query) => (query, CaptureOptions::default()),
+        toml::Value::Table(mut table) => {
+            let query = match table.remove("query") {
+                Some(toml::Value::String(query)) => query,
+                Some(_) => {
+                    return Err(config_error(format!("target '{}': query must be a string", name)));
+                }
+                None => {
+                    return Err(config_error(format!("target '{}': missing query", name)));
+                }
+            };
+            let mut options: CaptureOptions =
+                toml::Value::Table(table)
+                    .try_into()
+                    .map_err(|e: toml::de::Error| {
+                        config_error(format!("target '{}': {}", name, e.message()))
+                    })?;
+            options.validate();
+            (query, options)
+        }
+        _ => {
+            return Err(config_error(format!(
+                "target '{}' must be a query string or a table",
+                name
+            )));
+        }
+    };
+
+    let selector = parse_selector_query(&query).map_err(|e| match e {
+        CaptureError::InvalidParameter { reason, .. } => {
+            config_error(format!("target '{}': {}", name, reason))
+        }
+        other => other,
+    })?;
+
+    Ok(TargetAlias {
+        name: name.to_string(),
+        query,
+        selector,
+        options,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BackendType, ImageFormat, Region};
+
+    const SAMPLE: &str = r#"
+        [targets]
+        ide = "class:jetbrains-idea title:backend"
+        term = "exe:alacritty"
+
+        [targets.dashboard]
+        query = "class:firefox title:Grafana !title:Private"
+        format = "png"
+        scale = 0.5
+        region = { x = 0, y = 80, width = 1280, height = 720 }
+    "#;
+
+    fn reason(text: &str) -> String {
+        match TargetRegistry::from_toml_str(text) {
+            Err(CaptureError::InvalidParameter { parameter, reason }) => {
+                assert_eq!(parameter, "targets");
+                reason
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
+    }
+
+    fn window(id: &str, title: &str, class: &str, owner: &str) -> WindowInfo {
+        WindowInfo::new(
+            id.to_string(),
+            title.to_string(),
+            class.to_string(),
+            owner.to_string(),
+            1,
+            BackendType::None,
+        )
+    }
+
+    #[test]
+    fn test_parse_string_and_table_targets() {
+        let registry = TargetRegistry::from_toml_str(SAMPLE).unwrap();
+        assert_eq!(registry.len(), 3);
+        let names: Vec<_> = registry.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["dashboard", "ide", "term"]);
+
+        let ide = registry.get("ide").unwrap();
+        assert_eq!(ide.selector.class.as_deref(), Some("jetbrains-idea"));
+        assert_eq!(ide.selector.title_substring_or_regex.as_deref(), Some("backend"));
+        assert_eq!(ide.options, CaptureOptions::default());
+
+        let dashboard = registry.get("dashboard").unwrap();
+        assert_eq!(dashboard.selector.exclude, vec!["Private".to_string()]);
+        assert_eq!(dashboard.options.format, ImageFormat::Png);
+        assert_eq!(dashboard.options.scale, 0.5);
+        assert_eq!(dashboard.options.quality, 80);
+        assert_eq!(dashboard.options.region, Some(Region::new(0, 80, 1280, 720)));
+    }
+
+    #[test]
+    fn test_empty_and_missing_table() {
+        assert!(TargetRegistry::from_toml_str("").unwrap().is_empty());
+        assert!(
+            TargetRegistry::from_toml_str("[other]\nx = 1")
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_target() {
+        assert!(reason("[targets]\nide = \"class:\"").contains("target 'ide': at column 7"));
+        assert!(reason("[targets.ide]\nscale = 0.5").contains("target 'ide': missing query"));
+        assert!(reason("[targets.ide]\nquery = \"exe:x\"\nformat = \"gif\"").contains("'ide'"));
+        assert!(reason("[targets]\n\"my ide\" = \"exe:x\"").contains("invalid target name"));
+        assert!(reason("[targets]\nide = 3").contains("query string or a table"));
+        assert!(!reason("[targets").is_empty());
+    }
+
+    #[test]
+    fn test_require_lists_known_targets() {
+        let registry = TargetRegistry::from_toml_str(SAMPLE).unwrap();
+        assert!(registry.require("ide").is_ok());
+        match registry.require("editor") {
+            Err(CaptureError::InvalidParameter { parameter, reason }) => {
+                assert_eq!(parameter, "target");
+                assert!(reason.contains("known targets: dashboard, ide, term"));
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_statuses_report_resolution() {
+        let registry = TargetRegistry::from_toml_str(SAMPLE).unwrap();
+        let windows = vec![
+            window("1", "backend – IntelliJ IDEA", "jetbrains-idea", "idea"),
+            window("2", "zsh", "Alacritty", "alacritty"),
+        ];
+
+        let statuses = registry.statuses(&windows);
+        assert_eq!(statuses.len(), 3);
+        assert!(statuses[0].window.is_none());
+        assert!(statuses[0].error.as_deref().unwrap().contains("not found"));
+        assert_eq!(statuses[1].window.as_ref().unwrap().id, "1");
+        assert_eq!(statuses[2].window.as_ref().unwrap().id, "2");
+        assert!(statuses[2].error.is_none());
+    }
+
+    #[test]
+    fn test_load_reports_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("targets.toml");
+        std::fs::write(&path, "[targets]\nide = \"pid:x\"").unwrap();
+
+        match TargetRegistry::load(&path) {
+            Err(CaptureError::InvalidParameter { reason, .. }) => {
+                assert!(reason.starts_with(&path.display().to_string()));
+            }
+            other => panic!("expected InvalidParameter, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_default_path_env_override() {
+        temp_env::with_var(TARGETS_FILE_ENV, Some("/etc/screenshot/targets.toml"), || {
+            assert_eq!(
+                TargetRegistry::default_path(),
+                PathBuf::from("/etc/screenshot/targets.toml")
+            );
+        });
+        temp_env::with_vars([(TARGETS_FILE_ENV, None), ("XDG_CONFIG_HOME", Some("/xdg"))], || {
+            assert_eq!(
+                TargetRegistry::default_path(),
+                PathBuf::from("/xdg/screenshot-mcp/targets.toml")
+            );
+        });
+    }
+}
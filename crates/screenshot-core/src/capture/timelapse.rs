@@ -0,0 +1,686 @@
+//! Time-lapse: captures of one window at a fixed interval
+//!
+//! [`run_timelapse`] captures a window every `interval_secs` and writes each
+//! frame to a directory, named from a template (see
+//! [`render_timelapse_filename`]). After every tick it rewrites
+//! [`TIMELAPSE_MANIFEST`], a JSON [`TimelapseManifest`] listing the frames,
+//! so the directory describes itself even while the run is going on.
+//!
+//! A run ends when `max_frames` frames were written, `max_duration_secs`
+//! elapsed, or the caller's cancellation future completes. With `keep`, only
+//! the newest frame files are kept; older ones are deleted and dropped from
+//! the manifest, which keeps counting them, so long runs don't make every
+//! manifest rewrite slower.
+//!
+//! The window may disappear for a while, e.g. while an installer switches
+//! pages: ticks where it cannot be found are recorded as missing, and the
+//! run goes on until the window has been gone for `max_missing_secs`. Any
+//! other capture error ends the run as failed.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::{ImageBuffer, timelapse::run_timelapse},
+//!     model::{CaptureOptions, ImageFormat, TimelapseOptions, TimelapseStop},
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let dir = tempfile::tempdir().unwrap();
+//! let options = TimelapseOptions {
+//!     interval_secs: 1,
+//!     max_frames: Some(1),
+//!     ..Default::default()
+//! };
+//! let opts = CaptureOptions::builder().format(ImageFormat::Png).build();
+//!
+//! let manifest = run_timelapse(
+//!     dir.path(),
+//!     "render",
+//!     &options,
+//!     &opts,
+//!     || async { Ok(("0x1".to_string(), ImageBuffer::from_test_pattern(32, 32))) },
+//!     std::future::pending(),
+//! )
+//! .await
+//! .unwrap();
+//!
+//! assert_eq!(manifest.stopped, Some(TimelapseStop::FrameLimit));
+//! let file = manifest.frames[0].file.as_ref().unwrap();
+//! assert!(file.starts_with("render-00001-"));
+//! assert!(dir.path().join(file).exists());
+//! # }
+//! ```
+
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use tokio::time::Instant;
+
+use super::ImageBuffer;
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{
+        CaptureOptions, ImageFormat, TimelapseFrame, TimelapseManifest, TimelapseOptions,
+        TimelapseStop, WindowHandle,
+    },
+    util::encode::encode_image,
+};
+
+/// File name of the manifest within a time-lapse directory
+pub const TIMELAPSE_MANIFEST: &str = "manifest.json";
+
+/// Builds a frame file name from a time-lapse `filename` template
+///
+/// `target` is reduced to letters, digits, `-`, `_` and `.` (other
+/// characters become `_`). `{index}` is zero-padded to five digits.
+///
+/// # Errors
+///
+/// `CaptureError::InvalidParameter` for `filename` if the template has an
+/// unknown or unclosed placeholder, uses neither `{index}` nor
+/// `{timestamp}`, or would name a path rather than a file.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::{TimeZone, Utc};
+/// use screenshot_core::capture::timelapse::render_timelapse_filename;
+///
+/// let at = Utc.with_ymd_and_hms(2025, 3, 1, 12, 30, 0).unwrap();
+/// let name =
+///     render_timelapse_filename("{target}-{index}-{timestamp}.{ext}", "My App", 7, at, "png")
+///         .unwrap();
+/// assert_eq!(name, "My_App-00007-20250301T123000Z.png");
+/// ```
+pub fn render_timelapse_filename(
+    template: &str,
+    target: &str,
+    index: u32,
+    timestamp: DateTime<Utc>,
+    ext: &str,
+) -> CaptureResult<String> {
+    let invalid = |reason: String| CaptureError::InvalidParameter {
+        parameter: "filename".to_string(),
+        reason,
+    };
+
+    let mut name = String::new();
+    let mut unique = false;
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        name.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let close = after
+            .find('}')
+            .ok_or_else(|| invalid(format!("unclosed placeholder in '{}'", template)))?;
+        match &after[..close] {
+            "target" => name.push_str(&sanitize_label(target)),
+            "index" => {
+                name.push_str(&format!("{:05}", index));
+                unique = true;
+            }
+            "timestamp" => {
+                name.push_str(&timestamp.format("%Y%m%dT%H%M%SZ").to_string());
+                unique = true;
+            }
+            "ext" => name.push_str(ext),
+            other => {
+                return Err(invalid(format!(
+                    "unknown placeholder '{{{}}}'; use {{target}}, {{index}}, {{timestamp}} or {{ext}}",
+                    other
+                )));
+            }
+        }
+        rest = &after[close + 1..];
+    }
+    name.push_str(rest);
+
+    if !unique {
+        return Err(invalid(
+            "must contain {index} or {timestamp} so frames get distinct names".to_string(),
+        ));
+    }
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name == TIMELAPSE_MANIFEST
+        || name.contains(['/', '\\'])
+    {
+        return Err(invalid(format!("'{}' is not a valid file name", name)));
+    }
+    Ok(name)
+}
+
+/// Reduces a target label to characters that are safe in file names
+fn sanitize_label(label: &str) -> String {
+    let sanitized: String = label
+        .chars()
+        .take(64)
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let sanitized = sanitized.trim_matches('.');
+    if sanitized.is_empty() {
+        "window".to_string()
+    } else {
+        sanitized.to_string()
+    }
+}
+
+/// Checks that a time-lapse can start with `options`
+///
+/// # Errors
+///
+/// `CaptureError::InvalidParameter` if neither `max_frames` nor
+/// `max_duration_secs` is set, or the `filename` template is invalid.
+pub fn check_timelapse_options(
+    options: &TimelapseOptions,
+    target: &str,
+    format: ImageFormat,
+) -> CaptureResult<()> {
+    if options.max_frames.is_none() && options.max_duration_secs.is_none() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "maxFrames".to_string(),
+            reason: "a time-lapse needs maxFrames or maxDurationSecs".to_string(),
+        });
+    }
+    render_timelapse_filename(&options.filename, target, 1, Utc::now(), format.extension())?;
+    Ok(())
+}
+
+/// Reads the manifest of a time-lapse directory
+///
+/// # Errors
+///
+/// `CaptureError::IoError` if the manifest cannot be read, or
+/// `CaptureError::InvalidParameter` if it is not a valid manifest.
+pub fn read_timelapse_manifest(dir: &Path) -> CaptureResult<TimelapseManifest> {
+    let path = dir.join(TIMELAPSE_MANIFEST);
+    let text = fs::read_to_string(&path)?;
+    serde_json::from_str(&text).map_err(|e| CaptureError::InvalidParameter {
+        parameter: "manifest".to_string(),
+        reason: format!("{}: {}", path.display(), e),
+    })
+}
+
+/// Writes the manifest atomically, so readers never see half of it
+fn write_manifest(dir: &Path, manifest: &TimelapseManifest) -> CaptureResult<()> {
+    let json = serde_json::to_vec_pretty(manifest).map_err(|e| CaptureError::InvalidParameter {
+        parameter: "manifest".to_string(),
+        reason: e.to_string(),
+    })?;
+    let partial = dir.join(format!("{}.partial", TIMELAPSE_MANIFEST));
+    fs::write(&partial, json)?;
+    fs::rename(&partial, dir.join(TIMELAPSE_MANIFEST))?;
+    Ok(())
+}
+
+fn now_rfc3339() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
+/// Runs a time-lapse, writing frames and the manifest into `dir`
+///
+/// # Arguments
+///
+/// * `dir` - Output directory, created if needed
+/// * `target` - Label of the captured target, used for `{target}`
+/// * `options` - Interval, limits, file names and retention
+/// * `opts` - Image format and quality of the frames
+/// * `capture` - Resolves the window and captures it, once per tick
+/// * `cancel` - Ends the run when it completes
+///
+/// # Returns
+///
+/// - `Ok(manifest)` - The final manifest; `stopped` says why the run ended
+/// - `Err(_)` - The run could not start: no frame or time limit, an invalid
+///   file name template, or the directory could not be written
+pub async fn run_timelapse<F, Fut, C>(
+    dir: &Path,
+    target: &str,
+    options: &TimelapseOptions,
+    opts: &CaptureOptions,
+    mut capture: F,
+    cancel: C,
+) -> CaptureResult<TimelapseManifest>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = CaptureResult<(WindowHandle, ImageBuffer)>>,
+    C: Future<Output = ()>,
+{
+    let mut options = options.clone();
+    options.validate();
+    check_timelapse_options(&options, target, opts.format)?;
+
+    fs::create_dir_all(dir)?;
+    let mut manifest = TimelapseManifest {
+        target: target.to_string(),
+        options: options.clone(),
+        format: opts.format,
+        started: now_rfc3339(),
+        finished: None,
+        stopped: None,
+        error: None,
+        captured: 0,
+        missed: 0,
+        frames: Vec::new(),
+    };
+    write_manifest(dir, &manifest)?;
+    tracing::info!(
+        "Started time-lapse of {} into {} every {}s",
+        target,
+        dir.display(),
+        options.interval_secs
+    );
+
+    let start = Instant::now();
+    let interval = Duration::from_secs(options.interval_secs);
+    let max_missing = Duration::from_secs(options.max_missing_secs);
+    let deadline = options
+        .max_duration_secs
+        .map(|secs| start + Duration::from_secs(secs));
+    let mut missing_since: Option<Instant> = None;
+    tokio::pin!(cancel);
+
+    let mut tick = 0u32;
+    let stop = loop {
+        tick += 1;
+        let now = Utc::now();
+        let mut frame = TimelapseFrame {
+            index: tick,
+            timestamp: now.to_rfc3339_opts(SecondsFormat::Millis, true),
+            file: None,
+            window: None,
+            width: None,
+            height: None,
+            bytes: None,
+            missing: None,
+        };
+
+        match capture().await {
+            Ok((window, image)) => {
+                missing_since = None;
+                match write_frame(dir, &options, target, tick, now, &image, opts) {
+                    Ok((file, bytes)) => {
+                        let (width, height) = image.dimensions();
+                        frame.file = Some(file);
+                        frame.window = Some(window);
+                        frame.width = Some(width);
+                        frame.height = Some(height);
+                        frame.bytes = Some(bytes);
+                        manifest.frames.push(frame);
+                        manifest.captured += 1;
+                    }
+                    Err(e) => {
+                        manifest.error = Some(e.to_string());
+                        break TimelapseStop::Failed;
+                    }
+                }
+                apply_retention(dir, &mut manifest.frames, options.keep);
+            }
+            Err(e @ (CaptureError::WindowNotFound { .. } | CaptureError::WindowClosed)) => {
+                tracing::debug!("Time-lapse tick {}: window missing: {}", tick, e);
+                frame.missing = Some(e.to_string());
+                manifest.frames.push(frame);
+                manifest.missed += 1;
+                let since = *missing_since.get_or_insert_with(Instant::now);
+                if since.elapsed() >= max_missing {
+                    break TimelapseStop::TargetLost;
+                }
+            }
+            Err(e) => {
+                manifest.error = Some(e.to_string());
+                break TimelapseStop::Failed;
+            }
+        }
+
+        if let Err(e) = write_manifest(dir, &manifest) {
+            tracing::warn!("Failed to update time-lapse manifest: {}", e);
+        }
+        if options
+            .max_frames
+            .is_some_and(|max| manifest.captured >= max)
+        {
+            break TimelapseStop::FrameLimit;
+        }
+
+        let next = start + interval * tick;
+        if deadline.is_some_and(|deadline| next >= deadline) {
+            break TimelapseStop::TimeLimit;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep_until(next) => {}
+            _ = &mut cancel => break TimelapseStop::Cancelled,
+        }
+    };
+
+    manifest.stopped = Some(stop);
+    manifest.finished = Some(now_rfc3339());
+    write_manifest(dir, &manifest)?;
+    tracing::info!(
+        "Time-lapse of {} ended ({:?}) after {} frames",
+        target,
+        stop,
+        manifest.captured
+    );
+    Ok(manifest)
+}
+
+/// Encodes and writes one frame, returning its file name and size
+fn write_frame(
+    dir: &Path,
+    options: &TimelapseOptions,
+    target: &str,
+    index: u32,
+    timestamp: DateTime<Utc>,
+    image: &ImageBuffer,
+    opts: &CaptureOptions,
+) -> CaptureResult<(String, u64)> {
+    let name = render_timelapse_filename(
+        &options.filename,
+        target,
+        index,
+        timestamp,
+        opts.format.extension(),
+    )?;
+    let data = encode_image(image, opts)?;
+    fs::write(dir.join(&name), &data)?;
+    Ok((name, data.len() as u64))
+}
+
+/// Deletes the oldest frame files beyond `keep`
+///
+/// Ticks before the oldest frame still on disk are dropped from `frames`. A
+/// file that cannot be deleted stays listed and is retried after the next
+/// tick.
+fn apply_retention(dir: &Path, frames: &mut Vec<TimelapseFrame>, keep: Option<u32>) {
+    let Some(keep) = keep else {
+        return;
+    };
+    let files = frames.iter().filter(|f| f.file.is_some()).count();
+    let excess = files.saturating_sub(keep as usize);
+
+    let mut deleted = vec![false; frames.len()];
+    for (i, file) in frames
+        .iter()
+        .enumerate()
+        .filter_map(|(i, f)| Some((i, f.file.as_deref()?)))
+        .take(excess)
+    {
+        let path: PathBuf = dir.join(file);
+        match fs::remove_file(&path) {
+            Ok(()) => deleted[i] = true,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => deleted[i] = true,
+            Err(e) => tracing::warn!("Failed to remove time-lapse frame {:?}: {}", path, e),
+        }
+    }
+
+    let oldest_kept = (0..frames.len())
+        .find(|&i| frames[i].file.is_some() && !deleted[i])
+        .unwrap_or(frames.len());
+    let mut index = 0;
+    frames.retain(|_| {
+        let keep = index >= oldest_kept && !deleted[index];
+        index += 1;
+        keep
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    fn png() -> CaptureOptions {
+        CaptureOptions::builder().format(ImageFormat::Png).build()
+    }
+
+    fn frame() -> CaptureResult<(WindowHandle, ImageBuffer)> {
+        Ok(("0x1".to_string(), ImageBuffer::from_test_pattern(16, 16)))
+    }
+
+    fn not_found() -> CaptureError {
+        CaptureError::WindowNotFound {
+            selector: Box::default(),
+        }
+    }
+
+    #[test]
+    fn test_render_filename_rejects_bad_templates() {
+        let now = Utc::now();
+        for template in [
+            "{target}.{ext}",
+            "{index",
+            "{size}-{index}",
+            "../{index}.png",
+        ] {
+            assert!(
+                matches!(
+                    render_timelapse_filename(template, "app", 1, now, "png"),
+                    Err(CaptureError::InvalidParameter { .. })
+                ),
+                "{} should be rejected",
+                template
+            );
+        }
+        assert_eq!(
+            render_timelapse_filename("{target}_{index}.{ext}", "..", 2, now, "jpg").unwrap(),
+            "window_00002.jpg"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stops_at_frame_limit_with_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = TimelapseOptions {
+            interval_secs: 5,
+            max_frames: Some(4),
+            keep: Some(2),
+            filename: "frame-{index}.{ext}".to_string(),
+            ..Default::default()
+        };
+
+        let manifest = run_timelapse(
+            dir.path(),
+            "app",
+            &options,
+            &png(),
+            || async { frame() },
+            std::future::pending(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(manifest.stopped, Some(TimelapseStop::FrameLimit));
+        assert_eq!(manifest.captured, 4);
+        let listed: Vec<u32> = manifest.frames.iter().map(|f| f.index).collect();
+        assert_eq!(listed, vec![3, 4]);
+        assert!(!dir.path().join("frame-00001.png").exists());
+        assert!(dir.path().join("frame-00004.png").exists());
+
+        // The manifest on disk is the final one, in snake_case throughout
+        assert_eq!(read_timelapse_manifest(dir.path()).unwrap(), manifest);
+        let json: serde_json::Value =
+            serde_json::from_slice(&fs::read(dir.path().join(TIMELAPSE_MANIFEST)).unwrap())
+                .unwrap();
+        assert_eq!(json["options"]["max_frames"], 4);
+        assert_eq!(json["options"]["interval_secs"], 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_survives_missing_window() {
+        let dir = tempfile::tempdir().unwrap();
+        let calls = AtomicU32::new(0);
+        let options = TimelapseOptions {
+            interval_secs: 10,
+            max_duration_secs: Some(60),
+            ..Default::default()
+        };
+
+        // The window is gone for ticks 2 and 3
+        let manifest = run_timelapse(
+            dir.path(),
+            "installer",
+            &options,
+            &png(),
+            || {
+                let n = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match n {
+                        1 | 2 => Err(not_found()),
+                        _ => frame(),
+                    }
+                }
+            },
+            std::future::pending(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(manifest.stopped, Some(TimelapseStop::TimeLimit));
+        assert_eq!(manifest.frames.len(), 6);
+        assert_eq!((manifest.captured, manifest.missed), (4, 2));
+        assert!(manifest.frames[1].missing.is_some());
+        assert!(manifest.frames[3].file.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retention_keeps_the_manifest_bounded() {
+        let dir = tempfile::tempdir().unwrap();
+        let calls = AtomicU32::new(0);
+        let options = TimelapseOptions {
+            interval_secs: 1,
+            max_frames: Some(50),
+            keep: Some(3),
+            filename: "frame-{index}.{ext}".to_string(),
+            ..Default::default()
+        };
+
+        // Every fifth tick the window is missing
+        let manifest = run_timelapse(
+            dir.path(),
+            "app",
+            &options,
+            &png(),
+            || {
+                let n = calls.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    if n % 5 == 4 {
+                        Err(not_found())
+                    } else {
+                        frame()
+                    }
+                }
+            },
+            std::future::pending(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!((manifest.captured, manifest.missed), (50, 12));
+        // Only the ticks from the oldest kept frame on are listed
+        let listed: Vec<u32> = manifest.frames.iter().map(|f| f.index).collect();
+        assert_eq!(listed, vec![59, 60, 61, 62]);
+        assert!(manifest.frames[1].missing.is_some());
+        let files = fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(files, 3 + 1, "three frames and the manifest");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_gives_up_when_target_is_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = TimelapseOptions {
+            interval_secs: 10,
+            max_frames: Some(10),
+            max_missing_secs: 25,
+            ..Default::default()
+        };
+
+        let manifest = run_timelapse(
+            dir.path(),
+            "app",
+            &options,
+            &png(),
+            || async { Err(not_found()) },
+            std::future::pending(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(manifest.stopped, Some(TimelapseStop::TargetLost));
+        // Missing at 0s, 10s, 20s and 30s
+        assert_eq!(manifest.frames.len(), 4);
+        assert_eq!((manifest.captured, manifest.missed), (0, 4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancel_and_failure_end_the_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = TimelapseOptions {
+            interval_secs: 10,
+            max_frames: Some(10),
+            ..Default::default()
+        };
+
+        let manifest = run_timelapse(
+            dir.path(),
+            "app",
+            &options,
+            &png(),
+            || async { frame() },
+            tokio::time::sleep(Duration::from_secs(15)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(manifest.stopped, Some(TimelapseStop::Cancelled));
+        assert_eq!(manifest.captured, 2);
+
+        let manifest = run_timelapse(
+            dir.path(),
+            "app",
+            &options,
+            &png(),
+            || async {
+                Err(CaptureError::PolicyDenied {
+                    target: "window 0x1".to_string(),
+                    reason: "denied".to_string(),
+                })
+            },
+            std::future::pending(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(manifest.stopped, Some(TimelapseStop::Failed));
+        assert!(manifest.error.unwrap().contains("denied"));
+    }
+
+    #[tokio::test]
+    async fn test_requires_a_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = run_timelapse(
+            dir.path(),
+            "app",
+            &TimelapseOptions::default(),
+            &png(),
+            || async { frame() },
+            std::future::pending(),
+        )
+        .await;
+
+        assert!(matches!(result, Err(CaptureError::InvalidParameter { .. })));
+        assert!(!dir.path().join(TIMELAPSE_MANIFEST).exists());
+    }
+}
@@ -8,14 +8,23 @@
 //!
 //! - [`WindowEnumerator`]: List capturable windows (X11, Windows, not Wayland)
 //! - [`WindowResolver`]: Resolve window selectors to handles
+//! - [`PointerResolver`]: Resolve windows by pointer position / screen point
+//! - [`WindowEventSource`]: Subscribe to window lifecycle events
+//! - [`WindowInspector`]: Query window mapping state and geometry
 //! - [`ScreenCapture`]: Capture screenshots from windows/displays
 //! - [`WaylandRestoreCapable`]: Wayland-specific restore token workflow
 //! - [`BackendCapabilities`]: Query backend feature support
 
+use std::pin::Pin;
+
 use async_trait::async_trait;
+use futures::Stream;
 
 use crate::error::CaptureResult;
-use crate::model::{CaptureOptions, SourceType, WindowHandle, WindowInfo, WindowSelector};
+use crate::model::{
+    CaptureOptions, PointerInfo, SourceType, WindowEvent, WindowHandle, WindowInfo, WindowSelector,
+    WindowState,
+};
 
 use super::ImageBuffer;
 
@@ -63,9 +72,102 @@ pub trait WindowResolver: Send + Sync {
     /// - `exe`: Process executable name
     ///
     /// All non-None criteria must match (AND semantics).
+    ///
+    /// Enumerating backends resolve stickily (see
+    /// [`WindowMatcher::select_match_sticky`](super::WindowMatcher::select_match_sticky))
+    /// when the selector sets `sticky`, or when built to by default, in which
+    /// case a selector keeps resolving to the same window while that window
+    /// still exists and matches.
     async fn resolve(&self, selector: &WindowSelector) -> CaptureResult<WindowHandle>;
 }
 
+/// Capability: Backend can locate the pointer and the window beneath a point.
+///
+/// This extends [`WindowResolver`] with geometric resolution: instead of
+/// matching on title/class/exe, windows are found by screen position using
+/// the window manager's stacking order (topmost first).
+///
+/// # Platform Support
+///
+/// - **X11**: Full support via `QueryPointer`, `_NET_CLIENT_LIST_STACKING`
+///   and XFixes cursor names
+/// - **Windows**: Not yet implemented
+/// - **Wayland**: Not supported (clients cannot query global pointer position)
+#[async_trait]
+pub trait PointerResolver: Send + Sync {
+    /// Returns the current pointer position and cursor shape name.
+    async fn pointer_info(&self) -> CaptureResult<PointerInfo>;
+
+    /// Resolves the topmost visible window containing the point `(x, y)`.
+    ///
+    /// Coordinates are relative to the root window / virtual desktop.
+    /// Returns [`CaptureError::WindowNotFound`](crate::error::CaptureError::WindowNotFound)
+    /// if no managed window covers the point.
+    async fn window_at(&self, x: i32, y: i32) -> CaptureResult<WindowHandle>;
+
+    /// Resolves the topmost visible window under the pointer.
+    ///
+    /// The default implementation combines [`pointer_info`](Self::pointer_info)
+    /// and [`window_at`](Self::window_at).
+    async fn window_under_pointer(&self) -> CaptureResult<WindowHandle> {
+        let pointer = self.pointer_info().await?;
+        self.window_at(pointer.x, pointer.y).await
+    }
+}
+
+/// Capability: Backend can inspect a window's mapping state and geometry.
+///
+/// Used to wait for windows to become visible and settle to a stable size
+/// before capturing them.
+///
+/// # Platform Support
+///
+/// - **X11**: `GetWindowAttributes` map state and `GetGeometry` translated
+///   into root coordinates
+/// - **Windows**: Not yet implemented
+/// - **Wayland**: Not supported (no global window geometry)
+#[async_trait]
+pub trait WindowInspector: Send + Sync {
+    /// Returns the current mapping state and root-relative geometry.
+    ///
+    /// Returns [`CaptureError::WindowNotFound`](crate::error::CaptureError::WindowNotFound)
+    /// if the handle no longer refers to a window.
+    async fn window_state(&self, handle: &WindowHandle) -> CaptureResult<WindowState>;
+
+    /// Returns the managed windows in stacking order, bottom first.
+    ///
+    /// Used together with [`window_state`](Self::window_state) geometry to
+    /// work out which parts of each window are covered by others. The default
+    /// implementation returns an empty list, which callers treat as an
+    /// unknown order.
+    async fn stacking_order(&self) -> CaptureResult<Vec<WindowHandle>> {
+        Ok(Vec::new())
+    }
+}
+
+/// Stream of window lifecycle events returned by [`WindowEventSource::subscribe`].
+pub type WindowEventStream = Pin<Box<dyn Stream<Item = WindowEvent> + Send>>;
+
+/// Capability: Backend can push window lifecycle events.
+///
+/// Lets consumers react to windows appearing, disappearing, being retitled
+/// or focused without polling [`WindowEnumerator::list_windows`].
+///
+/// # Platform Support
+///
+/// - **X11**: `SubstructureNotify` / `PropertyNotify` on the root window and
+///   `PropertyNotify` on client windows
+/// - **Windows**: Not yet implemented
+/// - **Wayland**: Not supported (no global window visibility)
+pub trait WindowEventSource: Send + Sync {
+    /// Subscribes to window lifecycle events.
+    ///
+    /// Each call returns an independent stream. The stream ends when the
+    /// underlying event connection closes; dropping it releases the
+    /// subscription.
+    fn subscribe(&self) -> CaptureResult<WindowEventStream>;
+}
+
 /// Capability: Backend can capture screenshots.
 ///
 /// This is the core capture trait that all backends must implement.
@@ -100,6 +202,16 @@ pub trait ScreenCapture: Send + Sync {
         display_id: Option<u32>,
         opts: &CaptureOptions,
     ) -> CaptureResult<ImageBuffer>;
+
+    /// Returns the screen position of the display `capture_display` captures
+    ///
+    /// This is the top-left corner of the uncropped capture in screen (root
+    /// window) coordinates. The default returns `None`: the position is
+    /// unknown, so screen coordinates cannot be placed on the capture.
+    async fn display_origin(&self, display_id: Option<u32>) -> Option<(i32, i32)> {
+        let _ = display_id;
+        None
+    }
 }
 
 // ============================================================================
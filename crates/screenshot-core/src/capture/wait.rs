@@ -0,0 +1,527 @@
+//! Waiting for windows to appear and settle
+//!
+//! Test automation often launches an application and needs to capture its
+//! window as soon as it exists and has finished laying out. This module
+//! provides [`wait_for_window`], which repeatedly resolves a
+//! [`WindowSelector`] through [`WindowMatcher`] until a set of conditions
+//! holds or a deadline passes.
+//!
+//! # Conditions
+//!
+//! All configured conditions must hold at the same poll:
+//!
+//! | Option | Condition | Requires |
+//! |--------|-----------|----------|
+//! | (always) | A window matches the selector | `enumerator` or `resolver` |
+//! | `title_regex` | Window title matches the regex (case-insensitive) | `enumerator` |
+//! | `require_visible` | Window is mapped, viewable and non-empty | `inspector` |
+//! | `stable_for_ms` | Window size unchanged for at least N ms | `inspector` |
+//!
+//! # Examples
+//!
+//! ```
+//! use std::sync::Arc;
+//!
+//! use screenshot_core::{
+//!     capture::{MockBackend, WaitForWindowOptions, composite_from_mock, wait_for_window},
+//!     model::WindowSelector,
+//! };
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let backend = composite_from_mock(Arc::new(MockBackend::new()));
+//! let options = WaitForWindowOptions {
+//!     require_visible: true,
+//!     ..Default::default()
+//! };
+//!
+//! let outcome = wait_for_window(&backend, &WindowSelector::by_title("Firefox"), &options)
+//!     .await
+//!     .unwrap();
+//! assert_eq!(outcome.handle, "mock-0x1");
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::{
+    CompositeBackend, WindowMatcher,
+    constants::{
+        DEFAULT_WAIT_POLL_INTERVAL_MS, DEFAULT_WAIT_TIMEOUT_MS, MAX_WAIT_TIMEOUT_MS,
+        MIN_WAIT_POLL_INTERVAL_MS,
+    },
+    matching::get_or_compile_regex,
+};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{WindowHandle, WindowInfo, WindowSelector, WindowState},
+};
+
+/// Options for [`wait_for_window`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitForWindowOptions {
+    /// Deadline in milliseconds (1 to [`MAX_WAIT_TIMEOUT_MS`])
+    pub timeout_ms: u64,
+    /// Interval between polls in milliseconds (at least
+    /// [`MIN_WAIT_POLL_INTERVAL_MS`])
+    pub poll_interval_ms: u64,
+    /// Regex the window title must match (case-insensitive)
+    pub title_regex: Option<String>,
+    /// Require the window to be mapped, viewable and non-empty
+    pub require_visible: bool,
+    /// Require the window size to be unchanged for this many milliseconds
+    pub stable_for_ms: Option<u64>,
+}
+
+impl Default for WaitForWindowOptions {
+    fn default() -> Self {
+        Self {
+            timeout_ms: DEFAULT_WAIT_TIMEOUT_MS,
+            poll_interval_ms: DEFAULT_WAIT_POLL_INTERVAL_MS,
+            title_regex: None,
+            require_visible: false,
+            stable_for_ms: None,
+        }
+    }
+}
+
+impl WaitForWindowOptions {
+    /// Validates the deadline, poll interval and title regex
+    ///
+    /// # Errors
+    ///
+    /// Returns `InvalidParameter` naming the offending option.
+    pub fn validate(&self) -> CaptureResult<()> {
+        if self.timeout_ms == 0 || self.timeout_ms > MAX_WAIT_TIMEOUT_MS {
+            return Err(CaptureError::InvalidParameter {
+                parameter: "timeout_ms".to_string(),
+                reason: format!("must be between 1 and {}", MAX_WAIT_TIMEOUT_MS),
+            });
+        }
+
+        if self.poll_interval_ms < MIN_WAIT_POLL_INTERVAL_MS {
+            return Err(CaptureError::InvalidParameter {
+                parameter: "poll_interval_ms".to_string(),
+                reason: format!("must be at least {}", MIN_WAIT_POLL_INTERVAL_MS),
+            });
+        }
+
+        if let Some(ref pattern) = self.title_regex
+            && get_or_compile_regex(pattern).is_none()
+        {
+            return Err(CaptureError::InvalidParameter {
+                parameter: "title_regex".to_string(),
+                reason: format!("'{}' is not a valid regular expression", pattern),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns true if any condition needs window state inspection
+    fn needs_state(&self) -> bool {
+        self.require_visible || self.stable_for_ms.is_some()
+    }
+}
+
+/// Result of a successful [`wait_for_window`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WaitOutcome {
+    /// Handle of the window that satisfied all conditions
+    pub handle: WindowHandle,
+    /// Window metadata, when the backend can enumerate windows
+    pub info: Option<WindowInfo>,
+    /// Window state at the final poll, when a state condition was requested
+    pub state: Option<WindowState>,
+    /// Time spent waiting in milliseconds
+    pub waited_ms: u64,
+    /// Number of polls performed
+    pub polls: u32,
+}
+
+/// Tracks how long a window has kept the same size
+#[derive(Debug)]
+struct SizeTracker {
+    handle: WindowHandle,
+    size: (u32, u32),
+    since: Instant,
+}
+
+impl SizeTracker {
+    /// Records an observation and returns how long the size has been stable
+    fn observe(tracker: &mut Option<Self>, handle: &WindowHandle, state: &WindowState) -> Duration {
+        let size = (state.geometry.width, state.geometry.height);
+        let now = Instant::now();
+
+        match tracker {
+            Some(t) if t.handle == *handle && t.size == size => now.duration_since(t.since),
+            _ => {
+                *tracker = Some(Self {
+                    handle: handle.clone(),
+                    size,
+                    since: now,
+                });
+                Duration::ZERO
+            }
+        }
+    }
+}
+
+/// Waits until a window matching `selector` satisfies all conditions
+///
+/// Polls every `poll_interval_ms` until the selector resolves to a window
+/// for which every condition in `options` holds, then returns immediately.
+///
+/// When the backend can enumerate windows, matching uses [`WindowMatcher`]
+/// over the windows whose title matches `title_regex` (if given). If the
+/// selector is empty, `title_regex` alone selects the window. Backends
+/// without enumeration fall back to their [`WindowResolver`](super::WindowResolver).
+///
+/// # Arguments
+///
+/// * `backend` - Composite backend providing resolution and inspection
+/// * `selector` - Window selector (may be empty if `title_regex` is set)
+/// * `options` - Deadline, poll interval and conditions
+///
+/// # Returns
+///
+/// - `Ok(WaitOutcome)` - The matching window and how long it took
+/// - `Err(CaptureTimeout)` - Conditions not met before the deadline
+/// - `Err(InvalidParameter)` - Invalid options, empty selector, or a
+///   condition the backend cannot evaluate
+/// - Other errors from the backend are returned as-is
+pub async fn wait_for_window(
+    backend: &CompositeBackend,
+    selector: &WindowSelector,
+    options: &WaitForWindowOptions,
+) -> CaptureResult<WaitOutcome> {
+    options.validate()?;
+    check_backend_support(backend, selector, options)?;
+
+    let start = Instant::now();
+    let deadline = start + Duration::from_millis(options.timeout_ms);
+    let poll_interval = Duration::from_millis(options.poll_interval_ms);
+    let mut tracker: Option<SizeTracker> = None;
+    let mut polls = 0u32;
+
+    loop {
+        polls += 1;
+
+        if let Some((handle, info, state)) =
+            poll_once(backend, selector, options, &mut tracker).await?
+        {
+            let waited_ms = start.elapsed().as_millis() as u64;
+            tracing::info!(
+                "wait_for_window: window {} ready after {}ms ({} polls)",
+                handle,
+                waited_ms,
+                polls
+            );
+            return Ok(WaitOutcome {
+                handle,
+                info,
+                state,
+                waited_ms,
+                polls,
+            });
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            tracing::debug!("wait_for_window: conditions not met after {} polls", polls);
+            return Err(CaptureError::CaptureTimeout {
+                duration_ms: options.timeout_ms,
+            });
+        }
+        tokio::time::sleep(poll_interval.min(deadline - now)).await;
+    }
+}
+
+/// Rejects selectors and conditions the backend cannot evaluate
+fn check_backend_support(
+    backend: &CompositeBackend,
+    selector: &WindowSelector,
+    options: &WaitForWindowOptions,
+) -> CaptureResult<()> {
+    let selector_empty = selector.is_empty();
+    if selector_empty && options.title_regex.is_none() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "selector".to_string(),
+            reason: "at least one of title, class, exe or title_regex is required".to_string(),
+        });
+    }
+
+    if options.title_regex.is_some() && backend.enumerator.is_none() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "title_regex".to_string(),
+            reason: format!("backend '{}' cannot enumerate window titles", backend.name),
+        });
+    }
+
+    if backend.enumerator.is_none() && backend.resolver.is_none() {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "selector".to_string(),
+            reason: format!("backend '{}' cannot resolve windows", backend.name),
+        });
+    }
+
+    if options.needs_state() && backend.inspector.is_none() {
+        let parameter = if options.require_visible {
+            "require_visible"
+        } else {
+            "stable_for_ms"
+        };
+        return Err(CaptureError::InvalidParameter {
+            parameter: parameter.to_string(),
+            reason: format!("backend '{}' cannot inspect window state", backend.name),
+        });
+    }
+
+    Ok(())
+}
+
+/// Returns true for errors that mean "not there yet" rather than failure
+fn is_transient(error: &CaptureError) -> bool {
+    matches!(error, CaptureError::WindowNotFound { .. } | CaptureError::WindowClosed)
+}
+
+/// Resolves the selector once, returning the handle and metadata if found
+async fn find_window(
+    backend: &CompositeBackend,
+    selector: &WindowSelector,
+    options: &WaitForWindowOptions,
+) -> CaptureResult<Option<(WindowHandle, Option<WindowInfo>)>> {
+    let Some(ref enumerator) = backend.enumerator else {
+        // check_backend_support guarantees one of the two is present
+        let Some(ref resolver) = backend.resolver else {
+            return Ok(None);
+        };
+        return match resolver.resolve(selector).await {
+            Ok(handle) => Ok(Some((handle, None))),
+            Err(e) if is_transient(&e) => Ok(None),
+            Err(e) => Err(e),
+        };
+    };
+
+    let mut windows = enumerator.list_windows().await?;
+
+    if let Some(regex) = options
+        .title_regex
+        .as_deref()
+        .and_then(get_or_compile_regex)
+    {
+        windows.retain(|w| regex.is_match(&w.title));
+    }
+
+    let handle = if selector.is_empty() {
+        // title_regex alone: first remaining window in enumeration order
+        windows.first().map(|w| w.id.clone())
+    } else {
+        // An ambiguous selector fails the wait rather than being retried
+        match WindowMatcher::new().select_match(selector, &windows) {
+            Ok(handle) => Some(handle),
+            Err(e) if is_transient(&e) => None,
+            Err(e) => return Err(e),
+        }
+    };
+
+    Ok(handle.map(|handle| {
+        let info = windows.into_iter().find(|w| w.id == handle);
+        (handle, info)
+    }))
+}
+
+/// Performs one poll, returning the window if every condition holds
+async fn poll_once(
+    backend: &CompositeBackend,
+    selector: &WindowSelector,
+    options: &WaitForWindowOptions,
+    tracker: &mut Option<SizeTracker>,
+) -> CaptureResult<Option<(WindowHandle, Option<WindowInfo>, Option<WindowState>)>> {
+    let Some((handle, info)) = find_window(backend, selector, options).await? else {
+        tracing::trace!("wait_for_window: no matching window yet");
+        *tracker = None;
+        return Ok(None);
+    };
+
+    if !options.needs_state() {
+        return Ok(Some((handle, info, None)));
+    }
+
+    let Some(ref inspector) = backend.inspector else {
+        return Ok(None);
+    };
+    let state = match inspector.window_state(&handle).await {
+        Ok(state) => state,
+        Err(e) if is_transient(&e) => {
+            *tracker = None;
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    if options.require_visible && !state.is_visible() {
+        tracing::trace!("wait_for_window: window {} not visible yet", handle);
+        *tracker = None;
+        return Ok(None);
+    }
+
+    if let Some(stable_for_ms) = options.stable_for_ms {
+        let stable = SizeTracker::observe(tracker, &handle, &state);
+        if stable < Duration::from_millis(stable_for_ms) {
+            tracing::trace!(
+                "wait_for_window: window {} size stable for {}ms of {}ms",
+                handle,
+                stable.as_millis(),
+                stable_for_ms
+            );
+            return Ok(None);
+        }
+    }
+
+    Ok(Some((handle, info, Some(state))))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::capture::{MockBackend, composite_from_mock};
+
+    fn mock_backend(mock: MockBackend) -> CompositeBackend {
+        composite_from_mock(Arc::new(mock))
+    }
+
+    fn fast_options() -> WaitForWindowOptions {
+        WaitForWindowOptions {
+            timeout_ms: 200,
+            poll_interval_ms: 10,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_wait_returns_existing_window_immediately() {
+        let backend = mock_backend(MockBackend::new());
+        let outcome =
+            wait_for_window(&backend, &WindowSelector::by_title("Firefox"), &fast_options())
+                .await
+                .unwrap();
+
+        assert_eq!(outcome.handle, "mock-0x1");
+        assert_eq!(outcome.polls, 1);
+        assert_eq!(outcome.info.unwrap().class, "Navigator");
+        assert!(outcome.state.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out_when_no_window_matches() {
+        let backend = mock_backend(MockBackend::new());
+        let result =
+            wait_for_window(&backend, &WindowSelector::by_title("Nonexistent"), &fast_options())
+                .await;
+
+        assert!(matches!(result.unwrap_err(), CaptureError::CaptureTimeout { duration_ms: 200 }));
+    }
+
+    #[tokio::test]
+    async fn test_wait_title_regex_narrows_match() {
+        let backend = mock_backend(MockBackend::new());
+        let options = WaitForWindowOptions {
+            title_regex: Some("^terminal".to_string()),
+            ..fast_options()
+        };
+
+        // Regex alone selects the window
+        let outcome = wait_for_window(&backend, &WindowSelector::default(), &options)
+            .await
+            .unwrap();
+        assert_eq!(outcome.handle, "mock-0x3");
+
+        // Selector and regex must both hold
+        let result =
+            wait_for_window(&backend, &WindowSelector::by_class("Navigator"), &options).await;
+        assert!(matches!(result.unwrap_err(), CaptureError::CaptureTimeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_wait_require_visible() {
+        let backend = mock_backend(MockBackend::new().with_unmapped_window("mock-0x3"));
+        let options = WaitForWindowOptions {
+            require_visible: true,
+            ..fast_options()
+        };
+
+        let outcome = wait_for_window(&backend, &WindowSelector::by_title("Code"), &options)
+            .await
+            .unwrap();
+        assert!(outcome.state.unwrap().is_visible());
+
+        let result =
+            wait_for_window(&backend, &WindowSelector::by_title("Alacritty"), &options).await;
+        assert!(matches!(result.unwrap_err(), CaptureError::CaptureTimeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_wait_stable_for_requires_elapsed_time() {
+        let backend = mock_backend(MockBackend::new());
+        let options = WaitForWindowOptions {
+            stable_for_ms: Some(50),
+            ..fast_options()
+        };
+
+        let outcome = wait_for_window(&backend, &WindowSelector::by_title("Firefox"), &options)
+            .await
+            .unwrap();
+        assert!(outcome.waited_ms >= 50, "waited only {}ms", outcome.waited_ms);
+        assert!(outcome.polls > 1);
+    }
+
+    #[tokio::test]
+    async fn test_wait_empty_selector_rejected() {
+        let backend = mock_backend(MockBackend::new());
+        let result = wait_for_window(&backend, &WindowSelector::default(), &fast_options()).await;
+        assert!(matches!(result.unwrap_err(), CaptureError::InvalidParameter { .. }));
+    }
+
+    #[test]
+    fn test_options_validate() {
+        assert!(WaitForWindowOptions::default().validate().is_ok());
+
+        let zero_timeout = WaitForWindowOptions {
+            timeout_ms: 0,
+            ..Default::default()
+        };
+        assert!(zero_timeout.validate().is_err());
+
+        let fast_poll = WaitForWindowOptions {
+            poll_interval_ms: 1,
+            ..Default::default()
+        };
+        assert!(fast_poll.validate().is_err());
+
+        let bad_regex = WaitForWindowOptions {
+            title_regex: Some("(unclosed".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            bad_regex.validate().unwrap_err(),
+            CaptureError::InvalidParameter { parameter, .. } if parameter == "title_regex"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_wait_propagates_backend_errors() {
+        let backend = mock_backend(MockBackend::new().with_error(CaptureError::PermissionDenied {
+            platform: "test".to_string(),
+            backend: crate::model::BackendType::None,
+        }));
+        let result =
+            wait_for_window(&backend, &WindowSelector::by_title("Firefox"), &fast_options()).await;
+        assert!(matches!(result.unwrap_err(), CaptureError::PermissionDenied { .. }));
+    }
+}
@@ -715,10 +715,7 @@ impl WaylandBackend {
     /// restore token from a previous `prime_consent()` call.
     pub async fn resolve_target(&self, selector: &WindowSelector) -> CaptureResult<WindowHandle> {
         // Check if selector has any criteria
-        if selector.title_substring_or_regex.is_none()
-            && selector.class.is_none()
-            && selector.exe.is_none()
-        {
+        if selector.is_empty() {
             return Err(CaptureError::InvalidParameter {
                 parameter: "selector".to_string(),
                 reason: "At least one selector field must be specified".to_string(),
@@ -752,7 +749,7 @@ impl WaylandBackend {
 
         // If we get here, selector doesn't match Wayland pattern
         Err(CaptureError::WindowNotFound {
-            selector: selector.clone(),
+            selector: Box::new(selector.clone()),
         })
     }
 
@@ -1279,6 +1276,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: None,
+            ..Default::default()
         };
         let result = backend.resolve_target(&selector).await;
         assert!(result.is_err());
@@ -1295,6 +1293,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: Some("wayland:".to_string()),
+            ..Default::default()
         };
         let result = backend.resolve_target(&selector).await;
         assert!(result.is_err());
@@ -1311,6 +1310,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: Some("wayland:test-source".to_string()),
+            ..Default::default()
         };
         let result = backend.resolve_target(&selector).await;
         assert!(result.is_err());
@@ -1332,6 +1332,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: Some("wayland:test-source".to_string()),
+            ..Default::default()
         };
         let result = backend.resolve_target(&selector).await;
         assert!(result.is_ok());
@@ -1485,6 +1486,7 @@ mod tests {
                 title_substring_or_regex: None,
                 class: None,
                 exe: Some(exe.to_string()),
+                ..Default::default()
             };
 
             let result = backend.resolve_target(&selector).await;
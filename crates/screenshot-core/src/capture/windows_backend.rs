@@ -101,9 +101,8 @@ const MINIMUM_WGC_BUILD: u32 = 17134;
 /// across tasks using `Arc`.
 #[derive(Debug)]
 pub struct WindowsBackend {
-    // Stateless - no fields needed for now
-    // Future: could cache monitor info, etc.
-    _private: (),
+    /// Whether `resolve` prefers each selector's previously resolved window
+    sticky: bool,
 }
 
 impl WindowsBackend {
@@ -124,7 +123,24 @@ impl WindowsBackend {
     pub fn new() -> CaptureResult<Self> {
         // On Windows, we can always create the backend
         // WGC availability will be checked at capture time
-        Ok(Self { _private: () })
+        Ok(Self { sticky: false })
+    }
+
+    /// Makes `resolve` prefer the window a selector resolved to last time
+    ///
+    /// See [`WindowMatcher::select_match_sticky`]. Off by default; a
+    /// selector's `sticky` field overrides it either way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use screenshot_core::capture::windows_backend::WindowsBackend;
+    ///
+    /// let backend = WindowsBackend::new().unwrap().with_sticky_resolution(true);
+    /// ```
+    pub fn with_sticky_resolution(mut self, enabled: bool) -> Self {
+        self.sticky = enabled;
+        self
     }
 
     /// Wraps async operation with timeout
@@ -792,11 +808,11 @@ impl WindowResolver for WindowsBackend {
         let windows = self.list_windows_impl().await?;
         let matcher = WindowMatcher::new();
 
-        matcher
-            .find_match(selector, &windows)
-            .ok_or_else(|| CaptureError::WindowNotFound {
-                selector: selector.clone(),
-            })
+        if selector.sticky.unwrap_or(self.sticky) {
+            matcher.select_match_sticky(selector, &windows)
+        } else {
+            matcher.select_match(selector, &windows)
+        }
     }
 }
 
@@ -910,6 +926,11 @@ impl ScreenCapture for WindowsBackend {
 
         Ok(buffer)
     }
+
+    async fn display_origin(&self, display_id: Option<u32>) -> Option<(i32, i32)> {
+        // The primary monitor defines the virtual screen origin
+        display_id.is_none().then_some((0, 0))
+    }
 }
 
 impl BackendCapabilities for WindowsBackend {
@@ -1027,6 +1048,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: None,
+            ..Default::default()
         };
         let result = backend.resolve(&selector).await;
         // Empty selector returns WindowNotFound (no windows match empty criteria)
@@ -1218,6 +1240,7 @@ mod tests {
             title_substring_or_regex: None,
             class: Some("Shell_TrayWnd".to_string()), // Windows taskbar
             exe: None,
+            ..Default::default()
         };
         let result = backend.resolve(&selector).await;
         // Taskbar should exist on any Windows system
@@ -1235,6 +1258,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: Some("explorer.exe".to_string()),
+            ..Default::default()
         };
         let result = backend.resolve(&selector).await;
         // Explorer should exist on any Windows system (File Explorer or shell)
@@ -1622,6 +1646,7 @@ mod tests {
             title_substring_or_regex: None,
             class: None,
             exe: None,
+            ..Default::default()
         };
         let result = backend.resolve(&selector).await;
         // Empty selector returns WindowNotFound (no windows match empty criteria)
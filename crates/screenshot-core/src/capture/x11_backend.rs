@@ -5,6 +5,9 @@
 //!
 //! - **Window Enumeration**: Query _NET_CLIENT_LIST for window metadata
 //! - **Fuzzy Matching**: Regex, substring, and fuzzy-match window selection
+//! - **Pointer Resolution**: Window under the pointer via `QueryPointer` and
+//!   `_NET_CLIENT_LIST_STACKING`, cursor names via XFixes
+//! - **Window Events**: Lifecycle event streams (see `x11_events`)
 //! - **Direct Capture**: Fast window capture via xcap library
 //! - **Connection Management**: Lazy shared connection with reconnect-on-error
 //!
@@ -67,13 +70,16 @@ use x11rb::{
 };
 
 use super::{
-    BackendCapabilities, ImageBuffer, ScreenCapture, WindowEnumerator, WindowMatcher,
-    WindowResolver,
+    BackendCapabilities, ImageBuffer, PointerResolver, ScreenCapture, WindowEnumerator,
+    WindowEventSource, WindowEventStream, WindowInspector, WindowMatcher, WindowResolver,
     constants::{LIST_WINDOWS_TIMEOUT_MS, X11_CAPTURE_TIMEOUT_MS},
 };
 use crate::{
     error::{CaptureError, CaptureResult},
-    model::{BackendType, Capabilities, CaptureOptions, WindowHandle, WindowInfo, WindowSelector},
+    model::{
+        BackendType, Capabilities, CaptureOptions, PointerInfo, Region, WindowHandle, WindowInfo,
+        WindowSelector, WindowState,
+    },
 };
 
 /// X11 screenshot backend using x11rb + xcap
@@ -102,6 +108,8 @@ pub struct X11Backend {
     screen_idx: usize,
     /// Cached EWMH atoms (initialized once on first use)
     atoms: OnceLock<X11Atoms>,
+    /// Whether `resolve` prefers each selector's previously resolved window
+    sticky: bool,
 }
 
 /// Cached EWMH atoms for efficient property queries
@@ -112,6 +120,8 @@ pub struct X11Backend {
 struct X11Atoms {
     /// _NET_CLIENT_LIST: list of all managed windows
     net_client_list: Atom,
+    /// _NET_CLIENT_LIST_STACKING: managed windows in stacking order (bottom first)
+    net_client_list_stacking: Atom,
     /// _NET_WM_NAME: UTF-8 encoded window title
     net_wm_name: Atom,
     /// WM_NAME: Latin-1 encoded window title (fallback)
@@ -193,9 +203,27 @@ impl X11Backend {
             conn: Arc::new(Mutex::new(None)),
             screen_idx: 0,
             atoms: OnceLock::new(),
+            sticky: false,
         })
     }
 
+    /// Makes `resolve` prefer the window a selector resolved to last time
+    ///
+    /// See [`WindowMatcher::select_match_sticky`]. Off by default; a
+    /// selector's `sticky` field overrides it either way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use screenshot_core::capture::x11_backend::X11Backend;
+    ///
+    /// let backend = X11Backend::new().unwrap().with_sticky_resolution(true);
+    /// ```
+    pub fn with_sticky_resolution(mut self, enabled: bool) -> Self {
+        self.sticky = enabled;
+        self
+    }
+
     /// Maps xcap errors to CaptureError with appropriate error types.
     ///
     /// Provides context-specific error mapping:
@@ -359,6 +387,15 @@ impl X11Backend {
             }
         })?;
 
+        let net_client_list_stacking = conn
+            .intern_atom(false, b"_NET_CLIENT_LIST_STACKING")
+            .map_err(|e| {
+                tracing::error!("Failed to intern _NET_CLIENT_LIST_STACKING: {}", e);
+                CaptureError::BackendNotAvailable {
+                    backend: BackendType::X11,
+                }
+            })?;
+
         let net_wm_name = conn.intern_atom(false, b"_NET_WM_NAME").map_err(|e| {
             tracing::error!("Failed to intern _NET_WM_NAME: {}", e);
             CaptureError::BackendNotAvailable {
@@ -405,6 +442,15 @@ impl X11Backend {
                     }
                 })?
                 .atom,
+            net_client_list_stacking: net_client_list_stacking
+                .reply()
+                .map_err(|e| {
+                    tracing::error!("Failed to get _NET_CLIENT_LIST_STACKING reply: {}", e);
+                    CaptureError::BackendNotAvailable {
+                        backend: BackendType::X11,
+                    }
+                })?
+                .atom,
             net_wm_name: net_wm_name
                 .reply()
                 .map_err(|e| {
@@ -938,13 +984,13 @@ impl X11Backend {
         tracing::debug!("Resolving window target: {:?}", selector);
 
         // Validate selector is not empty
-        if selector.title_substring_or_regex.is_none()
-            && selector.class.is_none()
-            && selector.exe.is_none()
-        {
+        if selector.is_empty() {
             return Err(CaptureError::InvalidParameter {
                 parameter: "selector".to_string(),
-                reason: "At least one field (title, class, or exe) must be specified".to_string(),
+                reason:
+                    "At least one selector field (title, class, exe, pid, window ID or any_of) \
+                         must be specified"
+                        .to_string(),
             });
         }
 
@@ -952,17 +998,19 @@ impl X11Backend {
         let windows = self.list_windows().await?;
         if windows.is_empty() {
             return Err(CaptureError::WindowNotFound {
-                selector: selector.clone(),
+                selector: Box::new(selector.clone()),
             });
         }
 
         // Use WindowMatcher with AND semantics
         let matcher = WindowMatcher::new();
-        matcher.find_match(selector, &windows).ok_or_else(|| {
-            tracing::debug!("No window matched selector: {:?}", selector);
-            CaptureError::WindowNotFound {
-                selector: selector.clone(),
-            }
+        let resolved = if selector.sticky.unwrap_or(self.sticky) {
+            matcher.select_match_sticky(selector, &windows)
+        } else {
+            matcher.select_match(selector, &windows)
+        };
+        resolved.inspect_err(|e| {
+            tracing::debug!("Selector {:?} did not resolve: {}", selector, e);
         })
     }
 
@@ -973,6 +1021,226 @@ impl X11Backend {
         })
     }
 
+    /// Queries the current pointer position and cursor shape name
+    ///
+    /// The position comes from a core `QueryPointer` request on the root
+    /// window. The cursor name is best-effort via XFixes and is `None` if the
+    /// extension is missing or the current cursor is unnamed.
+    pub async fn pointer_info(&self) -> CaptureResult<PointerInfo> {
+        Self::with_timeout(
+            async {
+                let (conn, screen_idx) = self.get_or_create_connection()?;
+                let root = conn.setup().roots[screen_idx].root;
+
+                let reply = conn
+                    .query_pointer(root)
+                    .map_err(|e| {
+                        tracing::error!("Failed to query pointer: {}", e);
+                        CaptureError::BackendNotAvailable {
+                            backend: BackendType::X11,
+                        }
+                    })?
+                    .reply()
+                    .map_err(|e| {
+                        tracing::error!("Failed to get QueryPointer reply: {}", e);
+                        CaptureError::BackendNotAvailable {
+                            backend: BackendType::X11,
+                        }
+                    })?;
+
+                let cursor_name = Self::query_cursor_name(&conn);
+                tracing::debug!(
+                    "Pointer at ({}, {}), cursor={:?}",
+                    reply.root_x,
+                    reply.root_y,
+                    cursor_name
+                );
+
+                Ok(PointerInfo::new(reply.root_x.into(), reply.root_y.into(), cursor_name))
+            },
+            LIST_WINDOWS_TIMEOUT_MS,
+        )
+        .await
+    }
+
+    /// Queries the current cursor shape name via XFixes
+    ///
+    /// Returns `None` if XFixes is unavailable or the cursor has no name
+    /// (e.g. an application-defined pixmap cursor).
+    fn query_cursor_name(conn: &RustConnection) -> Option<String> {
+        use x11rb::protocol::xfixes::ConnectionExt as _;
+
+        // XFixes requires a version handshake before any other request
+        conn.xfixes_query_version(4, 0).ok()?.reply().ok()?;
+
+        let reply = conn.xfixes_get_cursor_image_and_name().ok()?.reply().ok()?;
+        let name = String::from_utf8_lossy(&reply.name).into_owned();
+        (!name.is_empty()).then_some(name)
+    }
+
+    /// Reads a window's viewable flag and signed root-relative frame
+    ///
+    /// The origin is translated into root coordinates, so it works for
+    /// reparented (decorated) client windows, and stays negative for windows
+    /// that start left of or above the root. Returns `None` if the window no
+    /// longer exists.
+    fn query_window_frame(
+        conn: &RustConnection,
+        root: Window,
+        window: Window,
+    ) -> Option<(bool, i16, i16, u16, u16)> {
+        use x11rb::protocol::xproto::MapState;
+
+        let attrs = conn.get_window_attributes(window).ok()?.reply().ok()?;
+        let geometry = conn.get_geometry(window).ok()?.reply().ok()?;
+        let origin = conn
+            .translate_coordinates(window, root, 0, 0)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        Some((
+            attrs.map_state == MapState::VIEWABLE,
+            origin.dst_x,
+            origin.dst_y,
+            geometry.width,
+            geometry.height,
+        ))
+    }
+
+    /// Reads a window's viewable state and root-relative geometry
+    ///
+    /// The geometry is the part of the window inside the root's positive
+    /// quadrant (see [`clip_to_root`](Self::clip_to_root)). Returns `None` if
+    /// the window no longer exists.
+    fn query_window_state(
+        conn: &RustConnection,
+        root: Window,
+        window: Window,
+    ) -> Option<WindowState> {
+        let (mapped, x, y, width, height) = Self::query_window_frame(conn, root, window)?;
+        Some(WindowState::new(mapped, Self::clip_to_root(x, y, width, height)))
+    }
+
+    /// Clips a signed window frame to the root's positive quadrant
+    ///
+    /// A window hanging off the left or top edge keeps only its visible part,
+    /// rather than being shifted right or down by the overhang.
+    fn clip_to_root(x: i16, y: i16, width: u16, height: u16) -> Region {
+        let clip = |origin: i16, size: u16| {
+            let overhang = u32::from(origin.min(0).unsigned_abs());
+            (origin.max(0) as u32, u32::from(size).saturating_sub(overhang))
+        };
+        let (x, width) = clip(x, width);
+        let (y, height) = clip(y, height);
+        Region::new(x, y, width, height)
+    }
+
+    /// Checks whether a viewable window covers a root-relative point
+    ///
+    /// Compares against the signed frame, so negative origins hit-test where
+    /// the window actually is.
+    fn window_contains_point(
+        conn: &RustConnection,
+        root: Window,
+        window: Window,
+        x: i32,
+        y: i32,
+    ) -> bool {
+        Self::query_window_frame(conn, root, window).is_some_and(
+            |(mapped, left, top, width, height)| {
+                let (left, top) = (i32::from(left), i32::from(top));
+                mapped
+                    && x >= left
+                    && y >= top
+                    && x < left + i32::from(width)
+                    && y < top + i32::from(height)
+            },
+        )
+    }
+
+    /// Returns a window's viewable state and root-relative geometry
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidParameter` - Handle is not a numeric X11 window ID
+    /// - `WindowNotFound` - Window no longer exists
+    pub async fn window_state(&self, handle: &WindowHandle) -> CaptureResult<WindowState> {
+        let window = handle
+            .parse::<u32>()
+            .map_err(|_| CaptureError::InvalidParameter {
+                parameter: "window_handle".to_string(),
+                reason: format!("Expected numeric X11 window ID, got '{}'", handle),
+            })?;
+
+        Self::with_timeout(
+            async {
+                let (conn, screen_idx) = self.get_or_create_connection()?;
+                let root = conn.setup().roots[screen_idx].root;
+
+                Self::query_window_state(&conn, root, window).ok_or_else(|| {
+                    CaptureError::WindowNotFound {
+                        selector: Box::new(WindowSelector::by_title(format!("handle:{}", handle))),
+                    }
+                })
+            },
+            LIST_WINDOWS_TIMEOUT_MS,
+        )
+        .await
+    }
+
+    /// Returns the managed windows in stacking order, bottom first
+    ///
+    /// Reads `_NET_CLIENT_LIST_STACKING`. Untitled windows are included, since
+    /// they can still cover titled ones.
+    pub async fn stacking_order(&self) -> CaptureResult<Vec<WindowHandle>> {
+        Self::with_timeout(
+            async {
+                let (conn, screen_idx) = self.get_or_create_connection()?;
+                let atoms = self.get_atoms().await?;
+                let stacking =
+                    self.get_client_list(&conn, screen_idx, atoms.net_client_list_stacking)?;
+                Ok(stacking.iter().map(|window| window.to_string()).collect())
+            },
+            LIST_WINDOWS_TIMEOUT_MS,
+        )
+        .await
+    }
+
+    /// Resolves the topmost titled, viewable window containing a point
+    ///
+    /// Walks `_NET_CLIENT_LIST_STACKING` from the top of the stack down and
+    /// returns the first window whose root-relative geometry contains
+    /// `(x, y)`. Untitled windows are skipped, matching [`list_windows`](Self::list_windows).
+    pub async fn window_at(&self, x: i32, y: i32) -> CaptureResult<WindowHandle> {
+        Self::with_timeout(
+            async {
+                let (conn, screen_idx) = self.get_or_create_connection()?;
+                let atoms = self.get_atoms().await?;
+                let root = conn.setup().roots[screen_idx].root;
+
+                let stacking =
+                    self.get_client_list(&conn, screen_idx, atoms.net_client_list_stacking)?;
+
+                for &window in stacking.iter().rev() {
+                    if Self::window_contains_point(&conn, root, window, x, y)
+                        && self.fetch_window_info(&conn, window, &atoms).is_some()
+                    {
+                        tracing::debug!("Window {} is topmost at ({}, {})", window, x, y);
+                        return Ok(window.to_string());
+                    }
+                }
+
+                tracing::debug!("No window found at ({}, {})", x, y);
+                Err(CaptureError::WindowNotFound {
+                    selector: Box::new(WindowSelector::by_title(format!("point:{},{}", x, y))),
+                })
+            },
+            LIST_WINDOWS_TIMEOUT_MS,
+        )
+        .await
+    }
+
     /// Captures a screenshot of a specific window (implementation)
     #[cfg(target_os = "linux")]
     pub async fn capture_window_impl(
@@ -1010,11 +1278,12 @@ impl X11Backend {
                 .ok_or_else(|| {
                     tracing::error!("Window {} not found in xcap enumeration", win_id);
                     CaptureError::WindowNotFound {
-                        selector: crate::model::WindowSelector {
+                        selector: Box::new(crate::model::WindowSelector {
                             title_substring_or_regex: Some(format!("window_id:{}", win_id)),
                             class: None,
                             exe: None,
-                        },
+                            ..Default::default()
+                        }),
                     }
                 })?;
 
@@ -1029,11 +1298,12 @@ impl X11Backend {
                 let err_str = e.to_string().to_lowercase();
                 if err_str.contains("not found") || err_str.contains("destroyed") {
                     CaptureError::WindowNotFound {
-                        selector: crate::model::WindowSelector {
+                        selector: Box::new(crate::model::WindowSelector {
                             title_substring_or_regex: Some(format!("window_id:{}", win_id)),
                             class: None,
                             exe: None,
-                        },
+                            ..Default::default()
+                        }),
                     }
                 } else {
                     CaptureError::BackendNotAvailable {
@@ -1165,6 +1435,22 @@ impl X11Backend {
         Ok(buffer)
     }
 
+    /// Screen position of the monitor `capture_display_impl` captures
+    #[cfg(target_os = "linux")]
+    pub async fn display_origin_impl(&self, _display_id: Option<u32>) -> Option<(i32, i32)> {
+        let origin = tokio::task::spawn_blocking(|| {
+            let monitors = xcap::Monitor::all().ok()?;
+            let monitor = monitors.first()?;
+            Some((monitor.x().ok()?, monitor.y().ok()?))
+        });
+        origin.await.ok().flatten()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub async fn display_origin_impl(&self, _display_id: Option<u32>) -> Option<(i32, i32)> {
+        None
+    }
+
     #[cfg(not(target_os = "linux"))]
     pub async fn capture_display_impl(
         &self,
@@ -1224,6 +1510,34 @@ impl WindowResolver for X11Backend {
     }
 }
 
+#[async_trait]
+impl PointerResolver for X11Backend {
+    async fn pointer_info(&self) -> CaptureResult<PointerInfo> {
+        X11Backend::pointer_info(self).await
+    }
+
+    async fn window_at(&self, x: i32, y: i32) -> CaptureResult<WindowHandle> {
+        X11Backend::window_at(self, x, y).await
+    }
+}
+
+#[async_trait]
+impl WindowInspector for X11Backend {
+    async fn window_state(&self, handle: &WindowHandle) -> CaptureResult<WindowState> {
+        X11Backend::window_state(self, handle).await
+    }
+
+    async fn stacking_order(&self) -> CaptureResult<Vec<WindowHandle>> {
+        X11Backend::stacking_order(self).await
+    }
+}
+
+impl WindowEventSource for X11Backend {
+    fn subscribe(&self) -> CaptureResult<WindowEventStream> {
+        super::x11_events::subscribe()
+    }
+}
+
 #[async_trait]
 impl ScreenCapture for X11Backend {
     async fn capture_window(
@@ -1241,6 +1555,10 @@ impl ScreenCapture for X11Backend {
     ) -> CaptureResult<ImageBuffer> {
         self.capture_display_impl(display_id, opts).await
     }
+
+    async fn display_origin(&self, display_id: Option<u32>) -> Option<(i32, i32)> {
+        self.display_origin_impl(display_id).await
+    }
 }
 
 // Note: X11Backend does NOT implement WaylandRestoreCapable
@@ -1349,6 +1667,37 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_pointer_info_and_window_under_pointer() {
+        if std::env::var("DISPLAY").is_ok() {
+            let backend = X11Backend::new().unwrap();
+            let Ok(pointer) = backend.pointer_info().await else {
+                return;
+            };
+            tracing::debug!("Pointer: {:?}", pointer);
+
+            // The pointer may rest on the bare desktop, so not-found is acceptable
+            match PointerResolver::window_under_pointer(&backend).await {
+                Ok(handle) => assert!(handle.parse::<u32>().is_ok()),
+                Err(e) => assert!(matches!(
+                    e,
+                    CaptureError::WindowNotFound { .. }
+                        | CaptureError::CaptureTimeout { .. }
+                        | CaptureError::BackendNotAvailable { .. }
+                )),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_window_state_rejects_non_numeric_handle() {
+        if std::env::var("DISPLAY").is_ok() {
+            let backend = X11Backend::new().unwrap();
+            let result = backend.window_state(&"not-a-window".to_string()).await;
+            assert!(matches!(result, Err(CaptureError::InvalidParameter { .. })));
+        }
+    }
+
     #[tokio::test]
     async fn test_with_timeout_helper() {
         // Test timeout helper with fast operation
@@ -1586,6 +1935,7 @@ mod tests {
                 title_substring_or_regex: None,
                 class: None,
                 exe: None,
+                ..Default::default()
             };
             let result = backend.resolve_target(&selector).await;
             assert!(result.is_err());
@@ -1959,6 +2309,14 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_clip_to_root_keeps_visible_part() {
+        assert_eq!(X11Backend::clip_to_root(10, 20, 300, 200), Region::new(10, 20, 300, 200));
+        // 100px hang off the left edge: the visible part starts at 0
+        assert_eq!(X11Backend::clip_to_root(-100, 0, 300, 200), Region::new(0, 0, 200, 200));
+        assert_eq!(X11Backend::clip_to_root(0, -250, 300, 200), Region::new(0, 0, 300, 0));
+    }
+
     #[test]
     fn test_constants_values() {
         // Verify timeout constants are reasonable
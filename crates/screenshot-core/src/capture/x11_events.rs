@@ -0,0 +1,262 @@
+//! X11 window lifecycle event watcher
+//!
+//! This module turns raw X11 notifications into [`WindowEvent`]s for
+//! [`WindowEventSource`](super::WindowEventSource) subscribers.
+//!
+//! # Event Sources
+//!
+//! | X11 notification | Selected on | Emitted event |
+//! |------------------|-------------|---------------|
+//! | `CreateNotify` | root (`SubstructureNotify`) | `created` |
+//! | `DestroyNotify` | root (`SubstructureNotify`) | `destroyed` |
+//! | `MapNotify` / `UnmapNotify` | root (`SubstructureNotify`) | `mapped` / `unmapped` |
+//! | `PropertyNotify` `_NET_ACTIVE_WINDOW` | root (`PropertyChange`) | `focus_changed` |
+//! | `PropertyNotify` `_NET_WM_NAME` / `WM_NAME` | clients (`PropertyChange`) | `title_changed` |
+//!
+//! Client windows are tracked via `_NET_CLIENT_LIST`: every window that
+//! appears in the list gets `PropertyChange` selected so title changes are
+//! observed even after a reparenting window manager has moved it into a
+//! frame.
+//!
+//! # Threading
+//!
+//! Each subscription owns a dedicated X11 connection polled on a background
+//! thread. The thread exits once the returned stream is dropped.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use x11rb::{
+    connection::Connection as _,
+    protocol::{
+        Event,
+        xproto::{AtomEnum, ChangeWindowAttributesAux, ConnectionExt as _, EventMask, Window},
+    },
+    rust_connection::RustConnection,
+};
+
+use super::{
+    WindowEventStream, constants::X11_EVENT_POLL_INTERVAL_MS, matching::forget_sticky_window,
+};
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{BackendType, WindowEvent, WindowEventKind},
+};
+
+/// Atoms needed to interpret property notifications
+#[derive(Debug, Clone, Copy)]
+struct EventAtoms {
+    net_client_list: u32,
+    net_active_window: u32,
+    net_wm_name: u32,
+    wm_name: u32,
+}
+
+/// Mutable watcher state owned by the polling thread
+#[derive(Debug)]
+struct WatcherState {
+    root: Window,
+    atoms: EventAtoms,
+    /// Clients currently in `_NET_CLIENT_LIST` with `PropertyChange` selected
+    clients: HashSet<Window>,
+    /// Last reported title per window, to suppress duplicate notifications
+    titles: HashMap<Window, String>,
+}
+
+/// Maps any X11 request failure to `BackendNotAvailable`
+fn backend_unavailable(context: &str, e: impl std::fmt::Display) -> CaptureError {
+    tracing::error!("X11 event watcher: {} failed: {}", context, e);
+    CaptureError::BackendNotAvailable {
+        backend: BackendType::X11,
+    }
+}
+
+/// Opens a connection, selects events and spawns the watcher thread
+///
+/// # Returns
+///
+/// - `Ok(WindowEventStream)` - Stream of events for this subscription
+/// - `Err(BackendNotAvailable)` - Connection or event selection failed
+pub(crate) fn subscribe() -> CaptureResult<WindowEventStream> {
+    let (conn, screen_idx) = x11rb::connect(None).map_err(|e| backend_unavailable("connect", e))?;
+    let root = conn.setup().roots[screen_idx].root;
+    let atoms = intern_atoms(&conn)?;
+
+    conn.change_window_attributes(
+        root,
+        &ChangeWindowAttributesAux::new()
+            .event_mask(EventMask::SUBSTRUCTURE_NOTIFY | EventMask::PROPERTY_CHANGE),
+    )
+    .map_err(|e| backend_unavailable("select root events", e))?
+    .check()
+    .map_err(|e| backend_unavailable("select root events", e))?;
+
+    let mut state = WatcherState {
+        root,
+        atoms,
+        clients: HashSet::new(),
+        titles: HashMap::new(),
+    };
+    track_new_clients(&conn, &mut state);
+
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+    std::thread::Builder::new()
+        .name("x11-window-events".to_string())
+        .spawn(move || {
+            tracing::debug!("X11 event watcher started");
+            let poll_interval = Duration::from_millis(X11_EVENT_POLL_INTERVAL_MS);
+
+            while !tx.is_closed() {
+                match conn.poll_for_event() {
+                    Ok(Some(event)) => {
+                        if let Some(window_event) = translate_event(&conn, &mut state, event)
+                            && tx.send(window_event).is_err()
+                        {
+                            break;
+                        }
+                    }
+                    Ok(None) => std::thread::sleep(poll_interval),
+                    Err(e) => {
+                        tracing::warn!("X11 event connection closed: {}", e);
+                        break;
+                    }
+                }
+            }
+
+            tracing::debug!("X11 event watcher stopped");
+        })
+        .map_err(CaptureError::IoError)?;
+
+    let stream =
+        futures::stream::unfold(
+            rx,
+            |mut rx| async move { rx.recv().await.map(|event| (event, rx)) },
+        );
+    Ok(Box::pin(stream))
+}
+
+/// Interns the atoms used to classify property notifications
+fn intern_atoms(conn: &RustConnection) -> CaptureResult<EventAtoms> {
+    let intern = |name: &[u8]| -> CaptureResult<u32> {
+        Ok(conn
+            .intern_atom(false, name)
+            .map_err(|e| backend_unavailable("intern atom", e))?
+            .reply()
+            .map_err(|e| backend_unavailable("intern atom reply", e))?
+            .atom)
+    };
+
+    Ok(EventAtoms {
+        net_client_list: intern(b"_NET_CLIENT_LIST")?,
+        net_active_window: intern(b"_NET_ACTIVE_WINDOW")?,
+        net_wm_name: intern(b"_NET_WM_NAME")?,
+        wm_name: intern(b"WM_NAME")?,
+    })
+}
+
+/// Reads a single-window property (e.g. `_NET_ACTIVE_WINDOW`) from the root
+fn read_window_property(conn: &RustConnection, root: Window, property: u32) -> Option<Window> {
+    let reply = conn
+        .get_property(false, root, property, AtomEnum::WINDOW, 0, 1)
+        .ok()?
+        .reply()
+        .ok()?;
+    reply.value32()?.next()
+}
+
+/// Reads `_NET_CLIENT_LIST` from the root window
+fn read_client_list(conn: &RustConnection, root: Window, atoms: &EventAtoms) -> Vec<Window> {
+    conn.get_property(false, root, atoms.net_client_list, AtomEnum::WINDOW, 0, 4096)
+        .ok()
+        .and_then(|cookie| cookie.reply().ok())
+        .and_then(|reply| reply.value32().map(|values| values.collect()))
+        .unwrap_or_default()
+}
+
+/// Reads a window title, preferring `_NET_WM_NAME` over `WM_NAME`
+fn read_title(conn: &RustConnection, window: Window, atoms: &EventAtoms) -> Option<String> {
+    [atoms.net_wm_name, atoms.wm_name]
+        .into_iter()
+        .filter_map(|property| {
+            conn.get_property(false, window, property, AtomEnum::ANY, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()
+        })
+        .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+        .find(|title| !title.is_empty())
+}
+
+/// Selects `PropertyChange` on clients that are not yet tracked
+fn track_new_clients(conn: &RustConnection, state: &mut WatcherState) {
+    let current: HashSet<Window> = read_client_list(conn, state.root, &state.atoms)
+        .into_iter()
+        .collect();
+
+    for &window in current.difference(&state.clients) {
+        // The window may already be gone; that's fine
+        let _ = conn.change_window_attributes(
+            window,
+            &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+        );
+    }
+    let _ = conn.flush();
+
+    state.clients = current;
+}
+
+/// Converts a raw X11 event into a window event, if it is one we report
+fn translate_event(
+    conn: &RustConnection,
+    state: &mut WatcherState,
+    event: Event,
+) -> Option<WindowEvent> {
+    let root = state.root;
+    let atoms = state.atoms;
+
+    let event = match event {
+        Event::CreateNotify(e) if e.parent == root => {
+            WindowEvent::new(WindowEventKind::Created, e.window.to_string(), None)
+        }
+        Event::DestroyNotify(e) => {
+            state.clients.remove(&e.window);
+            state.titles.remove(&e.window);
+            forget_sticky_window(&e.window.to_string());
+            WindowEvent::new(WindowEventKind::Destroyed, e.window.to_string(), None)
+        }
+        Event::MapNotify(e) if e.event == root => {
+            WindowEvent::new(WindowEventKind::Mapped, e.window.to_string(), None)
+        }
+        Event::UnmapNotify(e) if e.event == root => {
+            WindowEvent::new(WindowEventKind::Unmapped, e.window.to_string(), None)
+        }
+        Event::PropertyNotify(e) if e.window == root && e.atom == atoms.net_client_list => {
+            track_new_clients(conn, state);
+            return None;
+        }
+        Event::PropertyNotify(e) if e.window == root && e.atom == atoms.net_active_window => {
+            let active = read_window_property(conn, root, atoms.net_active_window)
+                .filter(|&window| window != x11rb::NONE)?;
+            let title = read_title(conn, active, &atoms);
+            WindowEvent::new(WindowEventKind::FocusChanged, active.to_string(), title.as_deref())
+        }
+        Event::PropertyNotify(e)
+            if e.window != root && (e.atom == atoms.net_wm_name || e.atom == atoms.wm_name) =>
+        {
+            let title = read_title(conn, e.window, &atoms)?;
+            // Many clients set both _NET_WM_NAME and WM_NAME; report once
+            if state.titles.get(&e.window) == Some(&title) {
+                return None;
+            }
+            state.titles.insert(e.window, title.clone());
+            WindowEvent::new(WindowEventKind::TitleChanged, e.window.to_string(), Some(&title))
+        }
+        _ => return None,
+    };
+
+    tracing::trace!("X11 window event: {:?}", event);
+    Some(event)
+}
@@ -11,7 +11,7 @@
 //! to automatically attempt recovery actions without parsing prose.
 //!
 //! ```rust,ignore
-//! let error = CaptureError::WindowNotFound { selector };
+//! let error = CaptureError::WindowNotFound { selector: Box::new(selector) };
 //! let hint = error.structured_hint();
 //!
 //! if let Some(tool) = hint.suggested_tool {
@@ -20,7 +20,7 @@
 //! }
 //! ```
 
-use crate::model::{BackendType, WindowSelector};
+use crate::model::{BackendType, WindowInfo, WindowSelector};
 use serde::{Deserialize, Serialize};
 
 /// Result type alias for capture operations
@@ -97,7 +97,7 @@ pub enum CaptureError {
     #[error("Window not found: {selector:?}")]
     WindowNotFound {
         /// The selector that failed to match any window
-        selector: WindowSelector,
+        selector: Box<WindowSelector>,
     },
 
     /// Required desktop portal is unavailable
@@ -210,6 +210,25 @@ pub enum CaptureError {
         /// Backend that doesn't support the feature
         backend: BackendType,
     },
+
+    /// Several windows matched the selector equally well
+    #[error("Selector {selector:?} is ambiguous: {} windows match equally well", candidates.len())]
+    AmbiguousWindow {
+        /// The selector that matched more than one window
+        selector: Box<WindowSelector>,
+        /// The tied windows, in match rank order
+        candidates: Vec<WindowInfo>,
+    },
+
+    /// The capture privacy policy forbids the request
+    #[error("Privacy policy denies {target}: {reason}")]
+    PolicyDenied {
+        /// What was refused, e.g. `window 'KeePassXC' (0x3a00007)` or
+        /// `tool 'mark_windows'`
+        target: String,
+        /// The rule that refused it
+        reason: String,
+    },
 }
 
 impl CaptureError {
@@ -227,7 +246,7 @@ impl CaptureError {
     /// };
     ///
     /// let error = CaptureError::WindowNotFound {
-    ///     selector: WindowSelector::by_title("Firefox"),
+    ///     selector: Box::new(WindowSelector::by_title("Firefox")),
     /// };
     ///
     /// let hint = error.remediation_hint();
@@ -363,6 +382,16 @@ impl CaptureError {
                      capabilities to see which features are available."
                 }
             },
+            CaptureError::AmbiguousWindow { .. } => {
+                "More than one window matches the selector equally well. Use the find_windows tool \
+                 to see the ranked candidates, then narrow the selector (add class or exe) or set \
+                 the ambiguity policy to an index."
+            }
+            CaptureError::PolicyDenied { .. } => {
+                "The capture privacy policy forbids this. Capture a different window, or ask the \
+                 user to change the [policy] table of the privacy file ($SCREENSHOT_PRIVACY_FILE or \
+                 ~/.config/screenshot-mcp/privacy.toml). Do not retry the same request."
+            }
         }
     }
 
@@ -381,7 +410,7 @@ impl CaptureError {
     /// };
     ///
     /// let error = CaptureError::WindowNotFound {
-    ///     selector: WindowSelector::by_title("Firefox"),
+    ///     selector: Box::new(WindowSelector::by_title("Firefox")),
     /// };
     ///
     /// let hint = error.structured_hint();
@@ -579,6 +608,41 @@ impl CaptureError {
                 is_transient: false,
                 category: ErrorCategory::Unavailable,
             },
+            CaptureError::AmbiguousWindow {
+                selector,
+                candidates,
+            } => ErrorHint {
+                message: self.remediation_hint().to_string(),
+                recovery_action: RecoveryAction::CallTool,
+                suggested_tool: Some("find_windows".to_string()),
+                tool_params: Some(serde_json::json!({
+                    "titleSubstringOrRegex": selector.title_substring_or_regex,
+                    "class": selector.class,
+                    "exe": selector.exe,
+                    "candidates": candidates
+                        .iter()
+                        .map(|w| serde_json::json!({
+                            "windowId": w.id,
+                            "title": w.title,
+                            "class": w.class,
+                            "exe": w.owner,
+                        }))
+                        .collect::<Vec<_>>(),
+                })),
+                is_transient: false,
+                category: ErrorCategory::InvalidInput,
+            },
+            CaptureError::PolicyDenied { target, reason } => ErrorHint {
+                message: self.remediation_hint().to_string(),
+                recovery_action: RecoveryAction::RequireUser,
+                suggested_tool: None,
+                tool_params: Some(serde_json::json!({
+                    "denied": target,
+                    "reason": reason,
+                })),
+                is_transient: false,
+                category: ErrorCategory::PermissionDenied,
+            },
         }
     }
 }
@@ -590,7 +654,7 @@ mod tests {
     #[test]
     fn test_window_not_found_error_message() {
         let error = CaptureError::WindowNotFound {
-            selector: WindowSelector::by_title("Firefox"),
+            selector: Box::new(WindowSelector::by_title("Firefox")),
         };
 
         let msg = error.to_string();
@@ -601,7 +665,7 @@ mod tests {
     #[test]
     fn test_window_not_found_remediation() {
         let error = CaptureError::WindowNotFound {
-            selector: WindowSelector::by_title("Firefox"),
+            selector: Box::new(WindowSelector::by_title("Firefox")),
         };
 
         let hint = error.remediation_hint();
@@ -803,7 +867,7 @@ mod tests {
     #[test]
     fn test_error_debug_format() {
         let error = CaptureError::WindowNotFound {
-            selector: WindowSelector::by_title("Test"),
+            selector: Box::new(WindowSelector::by_title("Test")),
         };
 
         let debug = format!("{:?}", error);
@@ -845,7 +909,7 @@ mod tests {
     #[test]
     fn test_structured_hint_window_not_found() {
         let error = CaptureError::WindowNotFound {
-            selector: WindowSelector::by_title("Firefox"),
+            selector: Box::new(WindowSelector::by_title("Firefox")),
         };
 
         let hint = error.structured_hint();
@@ -937,10 +1001,84 @@ mod tests {
         assert_eq!(hint.category, ErrorCategory::NotFound);
     }
 
+    #[test]
+    fn test_ambiguous_window_error_message() {
+        let error = CaptureError::AmbiguousWindow {
+            selector: Box::new(WindowSelector::by_title("GitHub")),
+            candidates: vec![
+                WindowInfo::new(
+                    "0x1".to_string(),
+                    "GitHub - Firefox".to_string(),
+                    "Navigator".to_string(),
+                    "firefox".to_string(),
+                    1,
+                    BackendType::X11,
+                ),
+                WindowInfo::new(
+                    "0x2".to_string(),
+                    "GitHub - Firefox".to_string(),
+                    "Navigator".to_string(),
+                    "firefox".to_string(),
+                    1,
+                    BackendType::X11,
+                ),
+            ],
+        };
+
+        let msg = error.to_string();
+        assert!(msg.contains("ambiguous"));
+        assert!(msg.contains("2 windows"));
+        assert!(error.remediation_hint().contains("find_windows"));
+    }
+
+    #[test]
+    fn test_structured_hint_ambiguous_window() {
+        let error = CaptureError::AmbiguousWindow {
+            selector: Box::new(WindowSelector::by_title("GitHub")),
+            candidates: vec![WindowInfo::new(
+                "0x1".to_string(),
+                "GitHub - Firefox".to_string(),
+                "Navigator".to_string(),
+                "firefox".to_string(),
+                1,
+                BackendType::X11,
+            )],
+        };
+
+        let hint = error.structured_hint();
+        assert_eq!(hint.recovery_action, RecoveryAction::CallTool);
+        assert_eq!(hint.suggested_tool.as_deref(), Some("find_windows"));
+        assert!(!hint.is_transient);
+        assert_eq!(hint.category, ErrorCategory::InvalidInput);
+
+        let params = hint.tool_params.unwrap();
+        assert_eq!(params["titleSubstringOrRegex"], "GitHub");
+        assert_eq!(params["candidates"][0]["windowId"], "0x1");
+    }
+
+    #[test]
+    fn test_structured_hint_policy_denied() {
+        let error = CaptureError::PolicyDenied {
+            target: "window 'Passwords' (0x1)".to_string(),
+            reason: "matches deny rule 'class:KeePassXC'".to_string(),
+        };
+        assert_eq!(
+            error.to_string(),
+            "Privacy policy denies window 'Passwords' (0x1): matches deny rule 'class:KeePassXC'"
+        );
+        assert!(error.remediation_hint().contains("[policy]"));
+
+        let hint = error.structured_hint();
+        assert_eq!(hint.recovery_action, RecoveryAction::RequireUser);
+        assert_eq!(hint.category, ErrorCategory::PermissionDenied);
+        assert!(!hint.is_transient);
+        assert_eq!(hint.tool_params.unwrap()["reason"], "matches deny rule 'class:KeePassXC'");
+    }
+
     #[test]
     fn test_structured_hint_serialization() {
         let error = CaptureError::WindowNotFound {
-            selector: WindowSelector::by_title("Test"),
+            selector: Box::new(WindowSelector::by_title("Test")),
         };
 
         let hint = error.structured_hint();
@@ -426,13 +426,131 @@ impl Region {
             height,
         }
     }
+
+    /// Creates a region of `width`x`height` centred on a point
+    ///
+    /// The region is shifted (never shrunk unless it exceeds the bounds) so
+    /// that it lies entirely within a `bounds.0`x`bounds.1` surface. This is
+    /// used for pointer-centred captures where the pointer may sit near a
+    /// screen edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::model::Region;
+    ///
+    /// // Centred in the middle of the screen
+    /// let region = Region::centered_on(960, 540, 200, 100, (1920, 1080));
+    /// assert_eq!(region, Region::new(860, 490, 200, 100));
+    ///
+    /// // Pointer in the top-left corner: region is clamped to the screen
+    /// let region = Region::centered_on(10, 10, 200, 100, (1920, 1080));
+    /// assert_eq!(region, Region::new(0, 0, 200, 100));
+    /// ```
+    pub fn centered_on(x: i32, y: i32, width: u32, height: u32, bounds: (u32, u32)) -> Self {
+        let width = width.min(bounds.0);
+        let height = height.min(bounds.1);
+
+        let clamp_axis = |center: i32, size: u32, limit: u32| -> u32 {
+            let origin = i64::from(center) - i64::from(size / 2);
+            origin.clamp(0, i64::from(limit - size)) as u32
+        };
+
+        Self {
+            x: clamp_axis(x, width, bounds.0),
+            y: clamp_axis(y, height, bounds.1),
+            width,
+            height,
+        }
+    }
+
+    /// Returns true if the point lies inside this region
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        let (x, y) = (i64::from(x), i64::from(y));
+        x >= i64::from(self.x)
+            && y >= i64::from(self.y)
+            && x < i64::from(self.x) + i64::from(self.width)
+            && y < i64::from(self.y) + i64::from(self.height)
+    }
+}
+
+/// Pointer (mouse cursor) state at the time of a query
+///
+/// Coordinates are in pixels relative to the top-left corner of the root
+/// window / virtual desktop. The cursor shape name is backend-specific (for
+/// example `left_ptr`, `xterm` or `hand2` on X11) and is `None` when the
+/// backend cannot report it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PointerInfo {
+    /// X coordinate of the pointer hotspot
+    pub x: i32,
+    /// Y coordinate of the pointer hotspot
+    pub y: i32,
+    /// Cursor shape name (e.g. "left_ptr", "xterm"), if known
+    pub cursor_name: Option<String>,
+}
+
+impl PointerInfo {
+    /// Creates a new PointerInfo
+    pub fn new(x: i32, y: i32, cursor_name: Option<String>) -> Self {
+        Self { x, y, cursor_name }
+    }
+}
+
+/// Mapping state and on-screen geometry of a window
+///
+/// Geometry is in pixels relative to the root window / virtual desktop.
+/// `mapped` is true only when the window is actually viewable (mapped and
+/// all of its ancestors mapped).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct WindowState {
+    /// Whether the window is mapped and viewable
+    pub mapped: bool,
+    /// Root-relative window geometry
+    pub geometry: Region,
+}
+
+impl WindowState {
+    /// Creates a new WindowState
+    pub fn new(mapped: bool, geometry: Region) -> Self {
+        Self { mapped, geometry }
+    }
+
+    /// Returns true if the window is viewable and has a non-empty area
+    pub fn is_visible(&self) -> bool {
+        self.mapped && self.geometry.width > 0 && self.geometry.height > 0
+    }
+}
+
+/// What to do when several windows match a selector equally well
+///
+/// Windows "tie" when they match with the same title strategy and fuzzy
+/// score, so only the window ID would separate them.
+///
+/// Serialized as `"first"`, `"error"` or `{"index": n}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmbiguityPolicy {
+    /// Pick the best-ranked window, breaking ties by window ID
+    #[default]
+    First,
+    /// Fail with `CaptureError::AmbiguousWindow` when the best match is tied
+    Error,
+    /// Pick the n-th (0-based) window in match rank order
+    Index(usize),
 }
 
 /// Selector for identifying target windows
 ///
-/// Used to locate windows by title, class, or executable name.
-/// At least one field must be specified. If multiple fields are provided,
-/// they are combined with AND logic.
+/// Used to locate windows by title, class, executable name, process ID or
+/// window ID. At least one of those fields (or an `any_of` group) must be
+/// specified. If multiple fields are provided, they are combined with AND
+/// logic:
+///
+/// - `exclude` rejects windows whose title matches any of its patterns
+/// - `any_of` requires at least one of its selectors to match as well
+///
+/// `ambiguity` controls what happens when more than one window matches.
 ///
 /// # Examples
 ///
@@ -444,6 +562,7 @@ impl Region {
 ///     title_substring_or_regex: Some("Firefox".to_string()),
 ///     class: None,
 ///     exe: None,
+///     ..Default::default()
 /// };
 ///
 /// // Select by class and exe
@@ -451,9 +570,22 @@ impl Region {
 ///     title_substring_or_regex: None,
 ///     class: Some("Alacritty".to_string()),
 ///     exe: Some("alacritty".to_string()),
+///     ..Default::default()
+/// };
+///
+/// // Firefox, but not a private browsing window
+/// let selector = WindowSelector {
+///     exclude: vec!["Private Browsing".to_string()],
+///     ..WindowSelector::by_exe("firefox")
+/// };
+///
+/// // Either editor
+/// let selector = WindowSelector {
+///     any_of: vec![WindowSelector::by_class("Code"), WindowSelector::by_exe("zed")],
+///     ..Default::default()
 /// };
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub struct WindowSelector {
     /// Window title substring or regex pattern
     pub title_substring_or_regex: Option<String>,
@@ -461,15 +593,104 @@ pub struct WindowSelector {
     pub class: Option<String>,
     /// Executable name
     pub exe: Option<String>,
+    /// Process ID of the window owner
+    #[serde(default)]
+    pub pid: Option<u32>,
+    /// Exact window ID; numeric IDs may be given in decimal or `0x` hex
+    #[serde(default)]
+    pub window_id: Option<WindowHandle>,
+    /// Title patterns (regex or substring) that rule a window out
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Alternative selectors, at least one of which must also match
+    #[serde(default)]
+    pub any_of: Vec<WindowSelector>,
+    /// Keep diacritics and lookalike letters significant in title matching
+    ///
+    /// By default titles and patterns are NFKC-normalized, case-folded,
+    /// stripped of diacritics and have Cyrillic/Greek homoglyphs mapped to
+    /// Latin. Strict mode still normalizes and case-folds but compares
+    /// accented and lookalike letters as distinct.
+    ///
+    /// Unset means not strict, except in an `any_of` alternative, which
+    /// inherits from its parent.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    /// Keep resolving to the window this selector matched last time
+    ///
+    /// See [`crate::capture::WindowMatcher::select_match_sticky`]. Unset
+    /// leaves it to the backend, which resolves afresh by default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sticky: Option<bool>,
+    /// How to choose between equally good matches
+    #[serde(default)]
+    pub ambiguity: AmbiguityPolicy,
 }
 
 impl WindowSelector {
+    /// Returns true when the selector has no positive criteria
+    ///
+    /// `exclude` patterns alone select nothing, so they do not count.
+    pub fn is_empty(&self) -> bool {
+        self.title_substring_or_regex.is_none()
+            && self.class.is_none()
+            && self.exe.is_none()
+            && self.pid.is_none()
+            && self.window_id.is_none()
+            && self.any_of.is_empty()
+    }
+
+    /// Fills criteria this selector leaves unset from `base`
+    ///
+    /// Fields set here take precedence; `exclude` patterns from both are
+    /// kept (base first), `any_of` is replaced only when this selector has
+    /// alternatives, and a non-default ambiguity policy wins.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::model::WindowSelector;
+    ///
+    /// let base = WindowSelector {
+    ///     exe: Some("idea".to_string()),
+    ///     ..WindowSelector::by_class("jetbrains-idea")
+    /// };
+    /// let selector = WindowSelector::by_exe("clion").with_fallback(base);
+    /// assert_eq!(selector.class.as_deref(), Some("jetbrains-idea"));
+    /// assert_eq!(selector.exe.as_deref(), Some("clion"));
+    /// ```
+    pub fn with_fallback(self, base: WindowSelector) -> WindowSelector {
+        WindowSelector {
+            title_substring_or_regex: self
+                .title_substring_or_regex
+                .or(base.title_substring_or_regex),
+            class: self.class.or(base.class),
+            exe: self.exe.or(base.exe),
+            pid: self.pid.or(base.pid),
+            window_id: self.window_id.or(base.window_id),
+            exclude: base.exclude.into_iter().chain(self.exclude).collect(),
+            any_of: if self.any_of.is_empty() {
+                base.any_of
+            } else {
+                self.any_of
+            },
+            strict: self.strict.or(base.strict),
+            sticky: self.sticky.or(base.sticky),
+            ambiguity: if self.ambiguity == AmbiguityPolicy::default() {
+                base.ambiguity
+            } else {
+                self.ambiguity
+            },
+        }
+    }
+
     /// Creates a WindowSelector that matches by title
     pub fn by_title(title: impl Into<String>) -> Self {
         Self {
             title_substring_or_regex: Some(title.into()),
             class: None,
             exe: None,
+            ..Default::default()
         }
     }
 
@@ -479,6 +700,7 @@ impl WindowSelector {
             title_substring_or_regex: None,
             class: Some(class.into()),
             exe: None,
+            ..Default::default()
         }
     }
 
@@ -488,6 +710,7 @@ impl WindowSelector {
             title_substring_or_regex: None,
             class: None,
             exe: Some(exe.into()),
+            ..Default::default()
         }
     }
 }
@@ -532,6 +755,178 @@ impl WindowInfo {
     }
 }
 
+/// Title matching strategy that accepted a window
+///
+/// Strategies are tried in order and ranked the same way: regex beats
+/// substring, which beats fuzzy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchStrategy {
+    /// Case-insensitive regex on the title
+    Regex,
+    /// Case-insensitive substring of the title
+    Substring,
+    /// Fuzzy match at or above the score threshold
+    Fuzzy,
+}
+
+/// Rule that ordered a candidate behind the one ranked just above it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreakRule {
+    /// Weaker title strategy (regex > substring > fuzzy)
+    TitleStrategy,
+    /// Lower fuzzy score (fuzzy matches only)
+    FuzzyScore,
+    /// Lexicographically larger window ID
+    WindowId,
+}
+
+/// Per-criterion match results for one window
+///
+/// Each field is `None` when the selector does not specify that criterion.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CriteriaMatch {
+    /// Whether the title matched by any strategy
+    pub title: Option<bool>,
+    /// Whether the class matched (case-insensitive exact)
+    pub class: Option<bool>,
+    /// Whether the executable matched (case-insensitive exact)
+    pub exe: Option<bool>,
+    /// Whether the process ID matched
+    pub pid: Option<bool>,
+    /// Whether the window ID matched
+    pub window_id: Option<bool>,
+    /// Whether the title avoided every exclusion pattern
+    pub exclude: Option<bool>,
+    /// Whether at least one `any_of` selector matched
+    pub any_of: Option<bool>,
+}
+
+/// One window evaluated against a selector
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct MatchCandidate {
+    /// The evaluated window
+    pub window: WindowInfo,
+    /// Whether every specified criterion matched
+    pub matched: bool,
+    /// 1-based position in the tie-break order (matching windows only)
+    pub rank: Option<u32>,
+    /// Whether this window is the one `find_match` selects
+    pub selected: bool,
+    /// Title strategy that matched, if any
+    pub title_strategy: Option<MatchStrategy>,
+    /// Raw fuzzy score of the title against the pattern, if any. Only used
+    /// for ranking when `title_strategy` is `fuzzy`.
+    pub fuzzy_score: Option<i64>,
+    /// Per-criterion results
+    pub criteria: CriteriaMatch,
+    /// Rule that ranked this window below the previous one (rank > 1 only)
+    pub decided_by: Option<TieBreakRule>,
+}
+
+/// Full explanation of how a selector resolves against a window list
+///
+/// Candidates are ordered as `find_match` ranks them: matching windows in
+/// tie-break order, then non-matching windows by ID.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SelectorExplanation {
+    /// The evaluated selector
+    pub selector: WindowSelector,
+    /// Handle of the selected window, if any matched
+    pub selected: Option<WindowHandle>,
+    /// Number of matching windows
+    pub match_count: usize,
+    /// Tie-break rules, in the order they are applied
+    pub tie_break_order: Vec<TieBreakRule>,
+    /// Every evaluated window
+    pub candidates: Vec<MatchCandidate>,
+}
+
+/// Kind of window lifecycle event
+///
+/// Serialized in `snake_case` (e.g. `"title_changed"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEventKind {
+    /// A new top-level window was created
+    Created,
+    /// A window was destroyed
+    Destroyed,
+    /// A window became mapped (shown)
+    Mapped,
+    /// A window became unmapped (hidden or minimized)
+    Unmapped,
+    /// A window's title changed
+    TitleChanged,
+    /// The active (focused) window changed
+    FocusChanged,
+}
+
+impl WindowEventKind {
+    /// Returns the string representation of the event kind
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WindowEventKind::Created => "created",
+            WindowEventKind::Destroyed => "destroyed",
+            WindowEventKind::Mapped => "mapped",
+            WindowEventKind::Unmapped => "unmapped",
+            WindowEventKind::TitleChanged => "title_changed",
+            WindowEventKind::FocusChanged => "focus_changed",
+        }
+    }
+}
+
+impl std::fmt::Display for WindowEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A window lifecycle event
+///
+/// Emitted by backends implementing
+/// [`WindowEventSource`](crate::capture::traits::WindowEventSource).
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::model::{WindowEvent, WindowEventKind};
+///
+/// let event = WindowEvent::new(WindowEventKind::TitleChanged, "12345", Some("Save As"));
+/// assert_eq!(event.kind, WindowEventKind::TitleChanged);
+/// assert_eq!(event.window, "12345");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct WindowEvent {
+    /// What happened
+    pub kind: WindowEventKind,
+    /// Handle of the affected window (the newly active window for
+    /// `focus_changed`)
+    pub window: WindowHandle,
+    /// Window title, when known (set for `title_changed` and `focus_changed`)
+    pub title: Option<String>,
+    /// RFC 3339 timestamp (UTC, millisecond precision) of when the event was
+    /// observed
+    pub timestamp: String,
+}
+
+impl WindowEvent {
+    /// Creates a new WindowEvent timestamped with the current time
+    pub fn new(
+        kind: WindowEventKind,
+        window: impl Into<WindowHandle>,
+        title: Option<&str>,
+    ) -> Self {
+        Self {
+            kind,
+            window: window.into(),
+            title: title.map(str::to_string),
+            timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        }
+    }
+}
+
 /// Backend capabilities for screenshot capture
 ///
 /// Different backends support different features. This struct describes
@@ -601,6 +996,9 @@ impl Default for Capabilities {
 ///     region:         None,
 ///     wayland_source: None,
 ///     max_dimension:  Some(1920),
+///     settle:         None,
+///     grid:           None,
+///     redact:         None,
 /// };
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -630,115 +1028,1216 @@ pub struct CaptureOptions {
     /// Set to 0 or None to disable auto-scaling.
     #[serde(default = "default_max_dimension")]
     pub max_dimension: Option<u32>,
+    /// Wait for the content to stop changing before returning a frame.
+    /// None = return the first frame.
+    #[serde(default)]
+    pub settle: Option<SettleOptions>,
+    /// Coordinate grid drawn over the output image after scaling.
+    /// None = no grid.
+    #[serde(default)]
+    pub grid: Option<GridOptions>,
+    /// Screen rectangles to redact before the image is returned.
+    /// None = redact only windows denied by the redaction policy.
+    #[serde(default)]
+    pub redact: Option<RedactOptions>,
+}
+
+fn default_quality() -> u8 {
+    80
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_max_dimension() -> Option<u32> {
+    Some(1920)
+}
+
+impl CaptureOptions {
+    /// Creates CaptureOptions with default values and validates parameters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates and clamps quality and scale parameters
+    pub fn validate(&mut self) {
+        self.quality = self.quality.clamp(0, 100);
+        self.scale = self.scale.clamp(0.1, 2.0);
+        if let Some(ref mut settle) = self.settle {
+            settle.validate();
+        }
+        if let Some(ref mut grid) = self.grid {
+            grid.validate();
+        }
+    }
+
+    /// Creates a builder for CaptureOptions
+    pub fn builder() -> CaptureOptionsBuilder {
+        CaptureOptionsBuilder::default()
+    }
+}
+
+impl Default for CaptureOptions {
+    fn default() -> Self {
+        Self {
+            format: ImageFormat::default(),
+            quality: default_quality(),
+            scale: default_scale(),
+            include_cursor: false,
+            region: None,
+            wayland_source: None,
+            max_dimension: default_max_dimension(),
+            settle: None,
+            grid: None,
+            redact: None,
+        }
+    }
+}
+
+/// Builder for CaptureOptions
+#[derive(Debug, Clone, Default)]
+pub struct CaptureOptionsBuilder {
+    options: CaptureOptions,
+}
+
+impl CaptureOptionsBuilder {
+    /// Sets the image format
+    pub fn format(mut self, format: ImageFormat) -> Self {
+        self.options.format = format;
+        self
+    }
+
+    /// Sets the quality (0-100)
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.options.quality = quality;
+        self
+    }
+
+    /// Sets the scale factor (0.1-2.0)
+    pub fn scale(mut self, scale: f32) -> Self {
+        self.options.scale = scale;
+        self
+    }
+
+    /// Sets whether to include cursor
+    pub fn include_cursor(mut self, include: bool) -> Self {
+        self.options.include_cursor = include;
+        self
+    }
+
+    /// Sets the capture region
+    pub fn region(mut self, region: Region) -> Self {
+        self.options.region = Some(region);
+        self
+    }
+
+    /// Sets the Wayland source
+    pub fn wayland_source(mut self, source: WaylandSource) -> Self {
+        self.options.wayland_source = Some(source);
+        self
+    }
+
+    /// Sets the maximum dimension for auto-scaling
+    ///
+    /// Images larger than this (in either width or height) will be
+    /// proportionally downscaled. Use `None` to disable auto-scaling.
+    pub fn max_dimension(mut self, max: Option<u32>) -> Self {
+        self.options.max_dimension = max;
+        self
+    }
+
+    /// Enables visual settling with the given options
+    pub fn settle(mut self, settle: SettleOptions) -> Self {
+        self.options.settle = Some(settle);
+        self
+    }
+
+    /// Overlays a labelled coordinate grid with the given options
+    pub fn grid(mut self, grid: GridOptions) -> Self {
+        self.options.grid = Some(grid);
+        self
+    }
+
+    /// Redacts the given screen rectangles
+    pub fn redact(mut self, redact: RedactOptions) -> Self {
+        self.options.redact = Some(redact);
+        self
+    }
+
+    /// Disables auto-scaling (returns full resolution image)
+    pub fn full_resolution(mut self) -> Self {
+        self.options.max_dimension = None;
+        self
+    }
+
+    /// Builds CaptureOptions and validates parameters
+    pub fn build(mut self) -> CaptureOptions {
+        self.options.validate();
+        self.options
+    }
+}
+
+/// Options for waiting until captured content stops changing
+///
+/// When set on [`CaptureOptions::settle`], the capture is repeated every
+/// `interval_ms` until the perceptual difference between two consecutive
+/// frames is at most `threshold`, or `max_wait_ms` has elapsed.
+///
+/// The difference is the mean absolute luminance difference of downsampled
+/// frames, from 0.0 (identical) to 1.0 (black vs. white).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SettleOptions {
+    /// Delay between frames in milliseconds (10-5000, default: 100)
+    #[serde(default = "default_settle_interval_ms")]
+    pub interval_ms: u64,
+    /// Maximum perceptual difference for a frame to count as settled
+    /// (0.0-1.0, default: 0.005)
+    #[serde(default = "default_settle_threshold")]
+    pub threshold: f32,
+    /// Maximum total wait in milliseconds (0-30000, default: 3000)
+    #[serde(default = "default_settle_max_wait_ms")]
+    pub max_wait_ms: u64,
+}
+
+fn default_settle_interval_ms() -> u64 {
+    100
+}
+
+fn default_settle_threshold() -> f32 {
+    0.005
+}
+
+fn default_settle_max_wait_ms() -> u64 {
+    3000
+}
+
+impl Default for SettleOptions {
+    fn default() -> Self {
+        Self {
+            interval_ms: default_settle_interval_ms(),
+            threshold: default_settle_threshold(),
+            max_wait_ms: default_settle_max_wait_ms(),
+        }
+    }
+}
+
+impl SettleOptions {
+    /// Clamps interval, threshold and maximum wait to their valid ranges
+    pub fn validate(&mut self) {
+        self.interval_ms = self.interval_ms.clamp(10, 5000);
+        self.threshold = if self.threshold.is_nan() {
+            default_settle_threshold()
+        } else {
+            self.threshold.clamp(0.0, 1.0)
+        };
+        self.max_wait_ms = self.max_wait_ms.min(30_000);
+    }
+}
+
+/// Options for the coordinate grid overlay
+///
+/// When set on [`CaptureOptions::grid`], grid lines are drawn every
+/// `spacing` screen pixels and labelled with screen coordinates, so a
+/// point in the image can be read off as a screen position even when the
+/// image is scaled. Lines are coarsened to a multiple of `spacing` if they
+/// would be too close together in the output image.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct GridOptions {
+    /// Distance between grid lines in screen pixels (10-5000, default: 100)
+    #[serde(default = "default_grid_spacing")]
+    pub spacing: u32,
+    /// Whether to draw tick rulers along the top and left edges
+    /// (default: true)
+    #[serde(default = "default_grid_rulers")]
+    pub rulers: bool,
+}
+
+fn default_grid_spacing() -> u32 {
+    100
+}
+
+fn default_grid_rulers() -> bool {
+    true
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        Self {
+            spacing: default_grid_spacing(),
+            rulers: default_grid_rulers(),
+        }
+    }
+}
+
+impl GridOptions {
+    /// Clamps the spacing to its valid range
+    pub fn validate(&mut self) {
+        self.spacing = self.spacing.clamp(10, 5000);
+    }
+}
+
+/// How redacted pixels are hidden
+///
+/// Every mode discards the original pixels: `pixelate` and `blur` keep only
+/// the mean colour of coarse blocks, so detail such as text cannot be
+/// recovered from the output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    /// Solid black
+    #[default]
+    Fill,
+    /// Coarse blocks of the mean colour
+    Pixelate,
+    /// Smooth gradient between coarse block means
+    Blur,
+}
+
+/// Caller-specified redaction for one capture
+///
+/// Regions are in screen coordinates, like grid labels. When the backend
+/// does not know where a captured window is, they are relative to the
+/// window instead.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RedactOptions {
+    /// Rectangles to hide
+    #[serde(default)]
+    pub regions: Vec<Region>,
+    /// How to hide them (default: fill)
+    #[serde(default)]
+    pub mode: RedactionMode,
+}
+
+/// What was redacted from a capture, reported in capture metadata
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RedactionReport {
+    /// Caller-specified rectangles that overlapped the capture
+    pub regions: usize,
+    /// Windows hidden because the redaction policy denies them
+    pub windows: Vec<WindowHandle>,
+    /// Windows blacked out because the capture policy denies them
+    #[serde(default)]
+    pub blocked: Vec<WindowHandle>,
+    /// Whether the whole image was hidden (the captured window itself is
+    /// denied, or a denied window could not be located)
+    pub full: bool,
+}
+
+impl RedactionReport {
+    /// Returns true if nothing was redacted
+    pub fn is_empty(&self) -> bool {
+        self.regions == 0 && self.windows.is_empty() && self.blocked.is_empty() && !self.full
+    }
+
+    /// Folds in the report of another frame of the same capture
+    ///
+    /// The result covers what was redacted in either: the larger region
+    /// count, every hidden or blocked window once, and `full` if either
+    /// frame was hidden entirely.
+    pub fn merge(&mut self, other: RedactionReport) {
+        self.regions = self.regions.max(other.regions);
+        for window in other.windows {
+            if !self.windows.contains(&window) {
+                self.windows.push(window);
+            }
+        }
+        for window in other.blocked {
+            if !self.blocked.contains(&window) {
+                self.blocked.push(window);
+            }
+        }
+        self.full |= other.full;
+    }
+}
+
+/// Outcome of a settled capture, reported in capture metadata
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct SettleReport {
+    /// Number of frames captured
+    pub frames: u32,
+    /// Time spent capturing and waiting, in milliseconds
+    pub waited_ms: u64,
+    /// Whether the content settled before `max_wait_ms` elapsed
+    pub settled: bool,
+    /// Perceptual difference between the last two frames (None if only one
+    /// frame was captured)
+    pub last_diff: Option<f32>,
+}
+
+/// Container format of a recorded animation
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationFormat {
+    /// Animated WebP with lossless frames (default)
+    #[default]
+    Webp,
+    /// Animated PNG, lossless
+    Apng,
+    /// GIF, quantized to 256 colours per frame
+    Gif,
+}
+
+impl AnimationFormat {
+    /// Returns the file extension for this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            AnimationFormat::Webp => "webp",
+            AnimationFormat::Apng => "png",
+            AnimationFormat::Gif => "gif",
+        }
+    }
+
+    /// Returns the MIME type for this format
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            AnimationFormat::Webp => "image/webp",
+            AnimationFormat::Apng => "image/apng",
+            AnimationFormat::Gif => "image/gif",
+        }
+    }
+}
+
+impl std::fmt::Display for AnimationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnimationFormat::Webp => write!(f, "webp"),
+            AnimationFormat::Apng => write!(f, "apng"),
+            AnimationFormat::Gif => write!(f, "gif"),
+        }
+    }
+}
+
+/// Options for recording a window as a short animation
+///
+/// Frames are captured `fps` times a second for `duration_ms`. Consecutive
+/// identical frames are merged into one longer frame, and recording stops
+/// early once `max_frames` distinct frames have been kept. If the encoded
+/// animation is larger than `max_bytes`, the frames are downscaled until it
+/// fits.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordOptions {
+    /// Frames captured per second (1-30, default: 5)
+    #[serde(default = "default_record_fps")]
+    pub fps: u32,
+    /// Recording length in milliseconds (100-30000, default: 3000)
+    #[serde(default = "default_record_duration_ms")]
+    pub duration_ms: u64,
+    /// Most distinct frames kept (1-300, default: 60)
+    #[serde(default = "default_record_max_frames")]
+    pub max_frames: u32,
+    /// Largest encoded animation in bytes (64 KiB-50 MiB, default: 5 MiB)
+    #[serde(default = "default_record_max_bytes")]
+    pub max_bytes: u64,
+    /// Container format (default: webp)
+    #[serde(default)]
+    pub format: AnimationFormat,
+}
+
+fn default_record_fps() -> u32 {
+    5
+}
+
+fn default_record_duration_ms() -> u64 {
+    3000
+}
+
+fn default_record_max_frames() -> u32 {
+    60
+}
+
+fn default_record_max_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+impl Default for RecordOptions {
+    fn default() -> Self {
+        Self {
+            fps: default_record_fps(),
+            duration_ms: default_record_duration_ms(),
+            max_frames: default_record_max_frames(),
+            max_bytes: default_record_max_bytes(),
+            format: AnimationFormat::default(),
+        }
+    }
+}
+
+impl RecordOptions {
+    /// Clamps the rate, length and caps to their valid ranges
+    pub fn validate(&mut self) {
+        self.fps = self.fps.clamp(1, 30);
+        self.duration_ms = self.duration_ms.clamp(100, 30_000);
+        self.max_frames = self.max_frames.clamp(1, 300);
+        self.max_bytes = self.max_bytes.clamp(64 * 1024, 50 * 1024 * 1024);
+    }
+}
+
+/// Outcome of a recording, reported in capture metadata
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct RecordReport {
+    /// Container format of the animation
+    pub format: AnimationFormat,
+    /// Number of frames captured
+    pub captured: u32,
+    /// Number of distinct frames in the animation
+    pub frames: u32,
+    /// Total playback time in milliseconds
+    pub duration_ms: u64,
+    /// Whether recording stopped early because `max_frames` was reached
+    pub truncated: bool,
+    /// Frame width in the animation
+    pub width: u32,
+    /// Frame height in the animation
+    pub height: u32,
+    /// Factor the frames were downscaled by to fit `max_bytes` (1.0 if not)
+    pub scale: f32,
+    /// Size of the encoded animation in bytes
+    pub bytes: u64,
+}
+
+/// Options for a time-lapse: captures of one window at a fixed interval
+///
+/// Frames are written to a directory under names built from `filename`,
+/// which may use `{target}`, `{index}` (zero-padded tick number),
+/// `{timestamp}` (UTC, `YYYYMMDDTHHMMSSZ`) and `{ext}`, and must use
+/// `{index}` or `{timestamp}`. A run needs `max_frames` or `max_duration_secs`
+/// to end on its own.
+///
+/// Serialized in snake_case, as part of the [`TimelapseManifest`]; the MCP
+/// tool takes its arguments separately.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TimelapseOptions {
+    /// Seconds between captures (1-86400, default: 60)
+    #[serde(default = "default_timelapse_interval_secs")]
+    pub interval_secs: u64,
+    /// Stop after this many frames were written
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_frames: Option<u32>,
+    /// Stop after this many seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+    /// File name template (default: "{target}-{index}-{timestamp}.{ext}")
+    #[serde(default = "default_timelapse_filename")]
+    pub filename: String,
+    /// Keep only the newest this many frame files, deleting older ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<u32>,
+    /// Stop once the window has been missing this many seconds (default:
+    /// 600)
+    #[serde(default = "default_timelapse_max_missing_secs")]
+    pub max_missing_secs: u64,
+}
+
+fn default_timelapse_interval_secs() -> u64 {
+    60
+}
+
+fn default_timelapse_filename() -> String {
+    "{target}-{index}-{timestamp}.{ext}".to_string()
+}
+
+fn default_timelapse_max_missing_secs() -> u64 {
+    600
+}
+
+impl Default for TimelapseOptions {
+    fn default() -> Self {
+        Self {
+            interval_secs: default_timelapse_interval_secs(),
+            max_frames: None,
+            max_duration_secs: None,
+            filename: default_timelapse_filename(),
+            keep: None,
+            max_missing_secs: default_timelapse_max_missing_secs(),
+        }
+    }
+}
+
+impl TimelapseOptions {
+    /// Clamps the interval to its valid range and the limits to at least 1
+    pub fn validate(&mut self) {
+        self.interval_secs = self.interval_secs.clamp(1, 86_400);
+        self.max_frames = self.max_frames.map(|n| n.max(1));
+        self.max_duration_secs = self.max_duration_secs.map(|n| n.max(1));
+        self.keep = self.keep.map(|n| n.max(1));
+    }
+}
+
+/// One tick of a time-lapse, as listed in its manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TimelapseFrame {
+    /// Tick number, counting from 1 (missed ticks included)
+    pub index: u32,
+    /// When the tick happened (RFC 3339, UTC)
+    pub timestamp: String,
+    /// Frame file name within the time-lapse directory (None if the window
+    /// was missing)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Captured window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowHandle>,
+    /// Frame width in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Frame height in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Encoded frame size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bytes: Option<u64>,
+    /// Why nothing was captured at this tick
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub missing: Option<String>,
+}
+
+/// Why a time-lapse ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelapseStop {
+    /// `max_frames` frames were written
+    FrameLimit,
+    /// `max_duration_secs` elapsed
+    TimeLimit,
+    /// The window was missing for longer than `max_missing_secs`
+    TargetLost,
+    /// The run was stopped by its owner
+    Cancelled,
+    /// A capture or write failed for a reason other than a missing window
+    Failed,
+}
+
+impl TimelapseStop {
+    /// Returns the snake_case name used in the manifest
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimelapseStop::FrameLimit => "frame_limit",
+            TimelapseStop::TimeLimit => "time_limit",
+            TimelapseStop::TargetLost => "target_lost",
+            TimelapseStop::Cancelled => "cancelled",
+            TimelapseStop::Failed => "failed",
+        }
+    }
+}
+
+impl std::fmt::Display for TimelapseStop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Manifest of a time-lapse, kept as `manifest.json` in its directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TimelapseManifest {
+    /// Label of the captured target, as used for `{target}`
+    pub target: String,
+    /// Options of the run
+    pub options: TimelapseOptions,
+    /// Image format of the frames
+    pub format: ImageFormat,
+    /// When the run started (RFC 3339, UTC)
+    pub started: String,
+    /// When the run ended (None while running)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished: Option<String>,
+    /// Why the run ended (None while running)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stopped: Option<TimelapseStop>,
+    /// Error that ended a failed run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Number of frames written, including ones deleted by retention
+    pub captured: u32,
+    /// Number of ticks where the window was missing
+    pub missed: u32,
+    /// Ticks so far, oldest first
+    ///
+    /// With `keep`, ticks before the oldest kept frame are dropped along
+    /// with their files, so the list stays bounded; `captured` and `missed`
+    /// still count them.
+    pub frames: Vec<TimelapseFrame>,
+}
+
+/// Tolerances for comparing two images
+///
+/// Screenshots of the same UI rarely match bit for bit: lossy encoding
+/// shifts colours slightly and re-rendered text and edges move by a pixel.
+/// These options keep such noise out of the changed-pixel count.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CompareOptions {
+    /// Largest per-channel difference still treated as unchanged (0-255,
+    /// default: 16)
+    #[serde(default = "default_compare_pixel_tolerance")]
+    pub pixel_tolerance: u8,
+    /// Ignore changed pixels that look like re-rendered anti-aliased edges
+    /// (default: true)
+    #[serde(default = "default_compare_ignore_antialiasing")]
+    pub ignore_antialiasing: bool,
+    /// Changed pixels about this close together (in pixels) are reported as
+    /// one region (1-256, default: 8)
+    #[serde(default = "default_compare_merge_distance")]
+    pub merge_distance: u32,
+    /// Regions with fewer changed pixels than this are dropped as noise
+    /// (default: 4)
+    #[serde(default = "default_compare_min_region_pixels")]
+    pub min_region_pixels: u32,
+}
+
+fn default_compare_pixel_tolerance() -> u8 {
+    16
+}
+
+fn default_compare_ignore_antialiasing() -> bool {
+    true
+}
+
+fn default_compare_merge_distance() -> u32 {
+    8
+}
+
+fn default_compare_min_region_pixels() -> u32 {
+    4
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            pixel_tolerance: default_compare_pixel_tolerance(),
+            ignore_antialiasing: default_compare_ignore_antialiasing(),
+            merge_distance: default_compare_merge_distance(),
+            min_region_pixels: default_compare_min_region_pixels(),
+        }
+    }
+}
+
+impl CompareOptions {
+    /// Clamps the merge distance to its valid range
+    pub fn validate(&mut self) {
+        self.merge_distance = self.merge_distance.clamp(1, 256);
+    }
+}
+
+/// Statistics describing how two images differ
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct ImageComparison {
+    /// Width and height shared by both images
+    pub dimensions: (u32, u32),
+    /// Whether no pixel changed beyond the tolerances
+    pub identical: bool,
+    /// Pixels that changed beyond the tolerances
+    pub changed_pixels: u64,
+    /// Pixels that differ but were classified as anti-aliasing noise
+    pub antialiased_pixels: u64,
+    /// `changed_pixels` as a fraction of all pixels (0.0-1.0)
+    pub changed_ratio: f64,
+    /// Mean absolute per-channel difference over all pixels (0.0-1.0)
+    pub mean_abs_diff: f64,
+    /// Largest per-channel difference of any pixel (0-255)
+    pub max_channel_diff: u8,
+    /// Structural similarity of the grayscale images (1.0 = identical)
+    pub ssim: f64,
+    /// Bounding boxes of changed areas, top to bottom
+    pub regions: Vec<Region>,
+}
+
+/// Options for delta captures, which return only what changed
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct DeltaOptions {
+    /// Return the full frame when changed regions cover more than this
+    /// fraction of it (0.0-1.0, default: 0.5)
+    #[serde(default = "default_delta_max_changed_ratio")]
+    pub max_changed_ratio: f64,
+    /// How changed pixels are detected and grouped into regions
+    #[serde(default)]
+    pub compare: CompareOptions,
+}
+
+fn default_delta_max_changed_ratio() -> f64 {
+    0.5
+}
+
+impl Default for DeltaOptions {
+    fn default() -> Self {
+        Self {
+            max_changed_ratio: default_delta_max_changed_ratio(),
+            compare: CompareOptions::default(),
+        }
+    }
+}
+
+impl DeltaOptions {
+    /// Clamps all values to their valid ranges
+    pub fn validate(&mut self) {
+        self.max_changed_ratio = if self.max_changed_ratio.is_nan() {
+            default_delta_max_changed_ratio()
+        } else {
+            self.max_changed_ratio.clamp(0.0, 1.0)
+        };
+        self.compare.validate();
+    }
+}
+
+/// Options for checking a capture against a stored baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineCheckOptions {
+    /// How changed pixels are detected and grouped into regions
+    #[serde(default)]
+    pub compare: CompareOptions,
+    /// Largest fraction of changed pixels that still passes (0.0-1.0,
+    /// default: 0.0)
+    #[serde(default)]
+    pub max_changed_ratio: f64,
+    /// Areas excluded from the comparison, e.g. clocks or cursors
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_regions: Vec<Region>,
+}
+
+impl Default for BaselineCheckOptions {
+    fn default() -> Self {
+        Self {
+            compare: CompareOptions::default(),
+            max_changed_ratio: 0.0,
+            ignore_regions: Vec::new(),
+        }
+    }
+}
+
+impl BaselineCheckOptions {
+    /// Clamps all values to their valid ranges
+    pub fn validate(&mut self) {
+        self.max_changed_ratio = if self.max_changed_ratio.is_nan() {
+            0.0
+        } else {
+            self.max_changed_ratio.clamp(0.0, 1.0)
+        };
+        self.compare.validate();
+    }
+}
+
+/// Why a delta capture returned the full frame
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FullFrameReason {
+    /// No earlier frame is remembered for this window
+    NoPreviousFrame,
+    /// The frame size differs from the remembered frame
+    DimensionsChanged,
+    /// The changed regions cover more than `max_changed_ratio`
+    TooManyChanges,
+}
+
+/// Result of a delta capture, reported in capture metadata
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum FrameDelta {
+    /// The full frame is returned
+    Full {
+        /// Why regions alone were not enough
+        reason: FullFrameReason,
+        /// Fraction of the frame covered by changed regions, if compared
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        changed_ratio: Option<f64>,
+    },
+    /// Nothing changed since the last frame
+    Unchanged,
+    /// Only the changed regions are returned, in frame coordinates
+    Regions {
+        /// Bounding boxes of the changed areas, top to bottom
+        regions: Vec<Region>,
+        /// Fraction of the frame covered by `regions`
+        changed_ratio: f64,
+    },
+}
+
+/// Options for locating a template image inside a capture
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LocateOptions {
+    /// Lowest normalized cross-correlation score reported as a match
+    /// (0.0-1.0, default: 0.8)
+    #[serde(default = "default_min_confidence")]
+    pub min_confidence: f32,
+    /// Most matches returned, best first (1-100, default: 5)
+    #[serde(default = "default_max_matches")]
+    pub max_matches: usize,
+    /// Template scale factors to search, e.g. `[0.5, 1.0, 2.0]` when the UI
+    /// may be rendered at a different DPI (each 0.1-4.0, default: `[1.0]`)
+    #[serde(default = "default_locate_scales")]
+    pub scales: Vec<f32>,
+}
+
+fn default_min_confidence() -> f32 {
+    0.8
+}
+
+fn default_max_matches() -> usize {
+    5
+}
+
+fn default_locate_scales() -> Vec<f32> {
+    vec![1.0]
+}
+
+impl Default for LocateOptions {
+    fn default() -> Self {
+        Self {
+            min_confidence: default_min_confidence(),
+            max_matches: default_max_matches(),
+            scales: default_locate_scales(),
+        }
+    }
+}
+
+impl LocateOptions {
+    /// Clamps all values to their valid ranges
+    ///
+    /// Non-finite scales are dropped and duplicates removed; an empty scale
+    /// list falls back to `[1.0]`.
+    pub fn validate(&mut self) {
+        self.min_confidence = if self.min_confidence.is_nan() {
+            default_min_confidence()
+        } else {
+            self.min_confidence.clamp(0.0, 1.0)
+        };
+        self.max_matches = self.max_matches.clamp(1, 100);
+
+        self.scales.retain(|s| s.is_finite());
+        for scale in &mut self.scales {
+            *scale = scale.clamp(0.1, 4.0);
+        }
+        self.scales.sort_by(f32::total_cmp);
+        self.scales.dedup();
+        if self.scales.is_empty() {
+            self.scales = default_locate_scales();
+        }
+    }
+}
+
+/// Where a template was found in a capture
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct TemplateMatch {
+    /// Matched rectangle in image pixels
+    pub region: Region,
+    /// Matched rectangle in screen (root window) coordinates, if the
+    /// capture's position on screen is known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_region: Option<Region>,
+    /// Normalized cross-correlation score (0.0-1.0)
+    pub confidence: f32,
+    /// Template scale factor that produced the match
+    pub scale: f32,
+}
+
+impl TemplateMatch {
+    /// Fills in `screen_region` for a capture of the screen area `area`
+    ///
+    /// `image_dimensions` are the capture's dimensions, so captures scaled
+    /// down from the screen map back to full-size screen coordinates.
+    pub fn map_to_screen(&mut self, area: Region, image_dimensions: (u32, u32)) {
+        let (width, height) = image_dimensions;
+        if width == 0 || height == 0 {
+            return;
+        }
+        let sx = f64::from(area.width) / f64::from(width);
+        let sy = f64::from(area.height) / f64::from(height);
+        let r = self.region;
+        self.screen_region = Some(Region::new(
+            area.x + (f64::from(r.x) * sx).round() as u32,
+            area.y + (f64::from(r.y) * sy).round() as u32,
+            ((f64::from(r.width) * sx).round() as u32).max(1),
+            ((f64::from(r.height) * sy).round() as u32).max(1),
+        ));
+    }
 }
 
-fn default_quality() -> u8 {
-    80
+/// Coordinate space that annotation shapes are given in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateSpace {
+    /// Pixels of the annotated image (default)
+    #[default]
+    Image,
+    /// Screen (root window) coordinates, mapped through the capture's
+    /// position on screen
+    Screen,
 }
 
-fn default_scale() -> f32 {
-    1.0
+/// Shape drawn by an [`Annotation`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(
+    tag = "type",
+    rename_all = "snake_case",
+    rename_all_fields = "camelCase"
+)]
+pub enum AnnotationShape {
+    /// Rectangle outline
+    Rectangle {
+        /// Left edge
+        x: f32,
+        /// Top edge
+        y: f32,
+        /// Width
+        width: f32,
+        /// Height
+        height: f32,
+    },
+    /// Ellipse outline inscribed in a bounding box
+    Ellipse {
+        /// Left edge of the bounding box
+        x: f32,
+        /// Top edge of the bounding box
+        y: f32,
+        /// Width of the bounding box
+        width: f32,
+        /// Height of the bounding box
+        height: f32,
+    },
+    /// Line with an arrowhead at `to`
+    Arrow {
+        /// Tail point `[x, y]`
+        from: [f32; 2],
+        /// Head point `[x, y]`
+        to: [f32; 2],
+    },
+    /// Freehand line through at least two points
+    Polyline {
+        /// Points `[x, y]` in drawing order
+        points: Vec<[f32; 2]>,
+    },
+    /// Text label on a filled box
+    Text {
+        /// Left edge of the label box
+        x: f32,
+        /// Top edge of the label box
+        y: f32,
+        /// Label text; `\n` starts a new line
+        text: String,
+        /// Font size in pixels (6-200, default: 16)
+        #[serde(default = "default_text_size")]
+        size: f32,
+    },
 }
 
-fn default_max_dimension() -> Option<u32> {
-    Some(1920)
+fn default_text_size() -> f32 {
+    16.0
 }
 
-impl CaptureOptions {
-    /// Creates CaptureOptions with default values and validates parameters
-    pub fn new() -> Self {
-        Self::default()
-    }
+/// A shape drawn on a capture, e.g. to point out a bug
+///
+/// Serialized flat, e.g.
+/// `{"type": "rectangle", "x": 10, "y": 20, "width": 80, "height": 30,
+/// "color": "#ff8800"}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotation {
+    /// What to draw
+    #[serde(flatten)]
+    pub shape: AnnotationShape,
+    /// Colour as "#rgb", "#rrggbb", "#rrggbbaa" or a basic colour name such
+    /// as "red" (default: "red"); text labels use it as the box fill
+    #[serde(default = "default_annotation_color")]
+    pub color: String,
+    /// Line width in pixels (1-64, default: 3)
+    #[serde(default = "default_stroke_width")]
+    pub stroke_width: f32,
+}
 
-    /// Validates and clamps quality and scale parameters
-    pub fn validate(&mut self) {
-        self.quality = self.quality.clamp(0, 100);
-        self.scale = self.scale.clamp(0.1, 2.0);
-    }
+fn default_annotation_color() -> String {
+    "red".to_string()
+}
 
-    /// Creates a builder for CaptureOptions
-    pub fn builder() -> CaptureOptionsBuilder {
-        CaptureOptionsBuilder::default()
-    }
+fn default_stroke_width() -> f32 {
+    3.0
 }
 
-impl Default for CaptureOptions {
-    fn default() -> Self {
+impl Annotation {
+    /// Creates an annotation in the default colour and line width
+    pub fn new(shape: AnnotationShape) -> Self {
         Self {
-            format: ImageFormat::default(),
-            quality: default_quality(),
-            scale: default_scale(),
-            include_cursor: false,
-            region: None,
-            wayland_source: None,
-            max_dimension: default_max_dimension(),
+            shape,
+            color: default_annotation_color(),
+            stroke_width: default_stroke_width(),
+        }
+    }
+
+    /// Converts the shape from screen coordinates to image pixels
+    ///
+    /// `area` is the screen area the image shows and `image_dimensions` the
+    /// image's size, as for [`TemplateMatch::map_to_screen`]. Line widths
+    /// and text sizes are left in image pixels.
+    pub fn map_from_screen(&mut self, area: Region, image_dimensions: (u32, u32)) {
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let sx = image_dimensions.0 as f32 / area.width as f32;
+        let sy = image_dimensions.1 as f32 / area.height as f32;
+        let (ox, oy) = (area.x as f32, area.y as f32);
+        let point = |[x, y]: [f32; 2]| [(x - ox) * sx, (y - oy) * sy];
+
+        match &mut self.shape {
+            AnnotationShape::Rectangle {
+                x,
+                y,
+                width,
+                height,
+            }
+            | AnnotationShape::Ellipse {
+                x,
+                y,
+                width,
+                height,
+            } => {
+                [*x, *y] = point([*x, *y]);
+                *width *= sx;
+                *height *= sy;
+            }
+            AnnotationShape::Arrow { from, to } => {
+                *from = point(*from);
+                *to = point(*to);
+            }
+            AnnotationShape::Polyline { points } => {
+                for p in points {
+                    *p = point(*p);
+                }
+            }
+            AnnotationShape::Text { x, y, .. } => [*x, *y] = point([*x, *y]),
         }
     }
 }
 
-/// Builder for CaptureOptions
-#[derive(Debug, Clone, Default)]
-pub struct CaptureOptionsBuilder {
-    options: CaptureOptions,
+/// A numbered window on a set-of-marks display capture
+///
+/// Rectangles are in screen (root window) coordinates.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct WindowMark {
+    /// Number drawn on the capture, starting at 1 for the topmost window
+    pub label: u32,
+    /// Window to pass to capture tools
+    pub handle: WindowHandle,
+    /// Window title
+    pub title: String,
+    /// Window class name
+    pub class: String,
+    /// Full window geometry, including covered parts
+    pub bounds: Region,
+    /// Largest rectangle of the window not covered by windows above it; this
+    /// is what gets outlined
+    pub visible: Region,
+    /// Fraction of the window's on-screen area that is not covered
+    pub visible_ratio: f64,
 }
 
-impl CaptureOptionsBuilder {
-    /// Sets the image format
-    pub fn format(mut self, format: ImageFormat) -> Self {
-        self.options.format = format;
-        self
-    }
+/// Options for an overview (contact sheet) of several windows
+///
+/// Each window is captured and scaled to fit a `thumbnail_width` x
+/// `thumbnail_height` box; the boxes are laid out in a grid with a caption
+/// above each. Captures run `concurrency` at a time and must all finish
+/// within `timeout_ms`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OverviewOptions {
+    /// Grid columns (1-16, default: about square)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<u32>,
+    /// Thumbnail box width in pixels (64-1024, default: 320)
+    #[serde(default = "default_overview_thumbnail_width")]
+    pub thumbnail_width: u32,
+    /// Thumbnail box height in pixels (48-1024, default: 200)
+    #[serde(default = "default_overview_thumbnail_height")]
+    pub thumbnail_height: u32,
+    /// Most windows shown (1-64, default: 24)
+    #[serde(default = "default_overview_max_windows")]
+    pub max_windows: u32,
+    /// Captures running at once (1-16, default: 4)
+    #[serde(default = "default_overview_concurrency")]
+    pub concurrency: u32,
+    /// Time budget for all captures in milliseconds (100-60000, default:
+    /// 5000)
+    #[serde(default = "default_overview_timeout_ms")]
+    pub timeout_ms: u64,
+}
 
-    /// Sets the quality (0-100)
-    pub fn quality(mut self, quality: u8) -> Self {
-        self.options.quality = quality;
-        self
-    }
+fn default_overview_thumbnail_width() -> u32 {
+    320
+}
 
-    /// Sets the scale factor (0.1-2.0)
-    pub fn scale(mut self, scale: f32) -> Self {
-        self.options.scale = scale;
-        self
-    }
+fn default_overview_thumbnail_height() -> u32 {
+    200
+}
 
-    /// Sets whether to include cursor
-    pub fn include_cursor(mut self, include: bool) -> Self {
-        self.options.include_cursor = include;
-        self
-    }
+fn default_overview_max_windows() -> u32 {
+    24
+}
 
-    /// Sets the capture region
-    pub fn region(mut self, region: Region) -> Self {
-        self.options.region = Some(region);
-        self
-    }
+fn default_overview_concurrency() -> u32 {
+    4
+}
 
-    /// Sets the Wayland source
-    pub fn wayland_source(mut self, source: WaylandSource) -> Self {
-        self.options.wayland_source = Some(source);
-        self
-    }
+fn default_overview_timeout_ms() -> u64 {
+    5000
+}
 
-    /// Sets the maximum dimension for auto-scaling
-    ///
-    /// Images larger than this (in either width or height) will be
-    /// proportionally downscaled. Use `None` to disable auto-scaling.
-    pub fn max_dimension(mut self, max: Option<u32>) -> Self {
-        self.options.max_dimension = max;
-        self
+impl Default for OverviewOptions {
+    fn default() -> Self {
+        Self {
+            columns: None,
+            thumbnail_width: default_overview_thumbnail_width(),
+            thumbnail_height: default_overview_thumbnail_height(),
+            max_windows: default_overview_max_windows(),
+            concurrency: default_overview_concurrency(),
+            timeout_ms: default_overview_timeout_ms(),
+        }
     }
+}
 
-    /// Disables auto-scaling (returns full resolution image)
-    pub fn full_resolution(mut self) -> Self {
-        self.options.max_dimension = None;
-        self
+impl OverviewOptions {
+    /// Clamps the layout, caps and budget to their valid ranges
+    pub fn validate(&mut self) {
+        self.columns = self.columns.map(|columns| columns.clamp(1, 16));
+        self.thumbnail_width = self.thumbnail_width.clamp(64, 1024);
+        self.thumbnail_height = self.thumbnail_height.clamp(48, 1024);
+        self.max_windows = self.max_windows.clamp(1, 64);
+        self.concurrency = self.concurrency.clamp(1, 16);
+        self.timeout_ms = self.timeout_ms.clamp(100, 60_000);
     }
+}
 
-    /// Builds CaptureOptions and validates parameters
-    pub fn build(mut self) -> CaptureOptions {
-        self.options.validate();
-        self.options
-    }
+/// A window shown in one cell of an overview grid
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct OverviewCell {
+    /// Number in the cell's caption, starting at 1 in the top-left cell
+    pub label: u32,
+    /// Grid row, from 0
+    pub row: u32,
+    /// Grid column, from 0
+    pub column: u32,
+    /// Where the thumbnail is in the overview image, in image pixels
+    pub thumbnail: Region,
+    /// The captured window
+    pub window: WindowInfo,
+}
+
+/// A window left out of an overview because its capture failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct OverviewSkip {
+    /// The window
+    pub window: WindowInfo,
+    /// Why it was not captured
+    pub reason: String,
+}
+
+/// What an overview shows, reported in capture metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct OverviewReport {
+    /// Grid cells in label order
+    pub cells: Vec<OverviewCell>,
+    /// Windows whose capture failed or ran out of time
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub skipped: Vec<OverviewSkip>,
+    /// Windows not attempted because of `max_windows`
+    pub omitted: u32,
+    /// Whether the time budget ran out before every capture finished
+    pub timed_out: bool,
 }
 
 /// Platform information including OS and display backend
@@ -1180,6 +2679,66 @@ mod tests {
         assert_eq!(region.height, 600);
     }
 
+    #[test]
+    fn test_region_centered_on_clamps_to_bounds() {
+        let bounds = (1920, 1080);
+
+        // Bottom-right corner: shifted back inside
+        let region = Region::centered_on(1910, 1075, 400, 300, bounds);
+        assert_eq!(region, Region::new(1520, 780, 400, 300));
+
+        // Negative coordinates clamp to origin
+        let region = Region::centered_on(-50, -50, 400, 300, bounds);
+        assert_eq!(region, Region::new(0, 0, 400, 300));
+
+        // Larger than bounds: shrunk to fit
+        let region = Region::centered_on(100, 100, 4000, 3000, bounds);
+        assert_eq!(region, Region::new(0, 0, 1920, 1080));
+    }
+
+    #[test]
+    fn test_region_contains() {
+        let region = Region::new(10, 20, 100, 50);
+        assert!(region.contains(10, 20));
+        assert!(region.contains(109, 69));
+        assert!(!region.contains(110, 20));
+        assert!(!region.contains(10, 70));
+        assert!(!region.contains(-1, 30));
+    }
+
+    #[test]
+    fn test_pointer_info_serialization() {
+        let pointer = PointerInfo::new(640, 480, Some("left_ptr".to_string()));
+        let json = serde_json::to_value(&pointer).unwrap();
+        assert_eq!(json["x"], 640);
+        assert_eq!(json["y"], 480);
+        assert_eq!(json["cursor_name"], "left_ptr");
+
+        let parsed: PointerInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, pointer);
+    }
+
+    #[test]
+    fn test_window_event_kind_serialization() {
+        let json = serde_json::to_string(&WindowEventKind::TitleChanged).unwrap();
+        assert_eq!(json, "\"title_changed\"");
+        assert_eq!(WindowEventKind::FocusChanged.to_string(), "focus_changed");
+
+        let parsed: WindowEventKind = serde_json::from_str("\"destroyed\"").unwrap();
+        assert_eq!(parsed, WindowEventKind::Destroyed);
+    }
+
+    #[test]
+    fn test_window_event_new() {
+        let event = WindowEvent::new(WindowEventKind::Created, "42", None);
+        assert_eq!(event.window, "42");
+        assert!(event.title.is_none());
+        assert!(event.timestamp.ends_with('Z'));
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "created");
+    }
+
     #[test]
     fn test_region_serialization() {
         let region = Region::new(0, 0, 1920, 1080);
@@ -1233,6 +2792,82 @@ mod tests {
         assert_eq!(json["exe"], serde_json::Value::Null);
     }
 
+    #[test]
+    fn test_window_selector_is_empty() {
+        assert!(WindowSelector::default().is_empty());
+        assert!(
+            WindowSelector {
+                exclude: vec!["Private".to_string()],
+                ..Default::default()
+            }
+            .is_empty()
+        );
+        assert!(
+            !WindowSelector {
+                pid: Some(4242),
+                ..Default::default()
+            }
+            .is_empty()
+        );
+        assert!(
+            !WindowSelector {
+                any_of: vec![WindowSelector::by_class("Code")],
+                ..Default::default()
+            }
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_window_selector_with_fallback() {
+        let base = WindowSelector {
+            exclude: vec!["Private".to_string()],
+            ambiguity: AmbiguityPolicy::Error,
+            strict: Some(true),
+            sticky: Some(true),
+            ..WindowSelector::by_class("Navigator")
+        };
+        let selector = WindowSelector {
+            exclude: vec!["Draft".to_string()],
+            sticky: Some(false),
+            ..WindowSelector::by_title("GitHub")
+        }
+        .with_fallback(base);
+
+        assert_eq!(selector.title_substring_or_regex.as_deref(), Some("GitHub"));
+        assert_eq!(selector.class.as_deref(), Some("Navigator"));
+        assert_eq!(selector.exclude, vec!["Private".to_string(), "Draft".to_string()]);
+        assert_eq!(selector.ambiguity, AmbiguityPolicy::Error);
+        assert_eq!(selector.strict, Some(true));
+        assert_eq!(selector.sticky, Some(false));
+
+        // An explicit false is kept rather than overridden by the base
+        let selector = WindowSelector {
+            strict: Some(false),
+            ..Default::default()
+        }
+        .with_fallback(WindowSelector {
+            strict: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(selector.strict, Some(false));
+    }
+
+    #[test]
+    fn test_window_selector_deserializes_extended_fields() {
+        let selector: WindowSelector = serde_json::from_value(serde_json::json!({
+            "exe": "firefox",
+            "exclude": ["Private Browsing"],
+            "any_of": [{"pid": 4242}, {"window_id": "0x3a00007"}],
+        }))
+        .unwrap();
+
+        assert_eq!(selector.exclude, vec!["Private Browsing".to_string()]);
+        assert_eq!(selector.any_of[0].pid, Some(4242));
+        assert_eq!(selector.any_of[1].window_id.as_deref(), Some("0x3a00007"));
+        assert_eq!(selector.ambiguity, AmbiguityPolicy::First);
+    }
+
     #[test]
     fn test_window_info_creation() {
         let info = WindowInfo::new(
@@ -1329,6 +2964,9 @@ mod tests {
             region: None,
             wayland_source: None,
             max_dimension: None,
+            settle: None,
+            grid: None,
+            redact: None,
         };
         opts.validate();
         assert_eq!(opts.quality, 100);
@@ -1348,6 +2986,9 @@ mod tests {
             region: None,
             wayland_source: None,
             max_dimension: None,
+            settle: None,
+            grid: None,
+            redact: None,
         };
         opts.validate();
         assert_eq!(opts.scale, 2.0);
@@ -1382,6 +3023,244 @@ mod tests {
         assert_eq!(opts.scale, 2.0);
     }
 
+    #[test]
+    fn test_settle_options_validation() {
+        let mut opts = CaptureOptions::builder()
+            .settle(SettleOptions {
+                interval_ms: 1,
+                threshold: 2.0,
+                max_wait_ms: 600_000,
+            })
+            .build();
+        opts.validate();
+
+        let settle = opts.settle.unwrap();
+        assert_eq!(settle.interval_ms, 10);
+        assert_eq!(settle.threshold, 1.0);
+        assert_eq!(settle.max_wait_ms, 30_000);
+    }
+
+    #[test]
+    fn test_settle_options_deserialize_defaults() {
+        let opts: CaptureOptions = serde_json::from_str(r#"{"settle": {}}"#).unwrap();
+        assert_eq!(opts.settle, Some(SettleOptions::default()));
+
+        let opts: CaptureOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(opts.settle, None);
+    }
+
+    #[test]
+    fn test_grid_options_defaults_and_validation() {
+        let opts: CaptureOptions = serde_json::from_str(r#"{"grid": {}}"#).unwrap();
+        assert_eq!(opts.grid, Some(GridOptions::default()));
+        assert_eq!(opts.grid.unwrap().spacing, 100);
+        assert!(opts.grid.unwrap().rulers);
+        assert_eq!(CaptureOptions::default().grid, None);
+
+        let opts = CaptureOptions::builder()
+            .grid(GridOptions {
+                spacing: 1,
+                rulers: false,
+            })
+            .build();
+        assert_eq!(opts.grid.unwrap().spacing, 10);
+
+        let mut grid = GridOptions {
+            spacing: 100_000,
+            rulers: true,
+        };
+        grid.validate();
+        assert_eq!(grid.spacing, 5000);
+    }
+
+    #[test]
+    fn test_redact_options_deserialize() {
+        let opts: CaptureOptions = serde_json::from_str(
+            r#"{"redact": {"regions": [{"x": 1, "y": 2, "width": 3, "height": 4}], "mode": "pixelate"}}"#,
+        )
+        .unwrap();
+        let redact = opts.redact.unwrap();
+        assert_eq!(redact.regions, vec![Region::new(1, 2, 3, 4)]);
+        assert_eq!(redact.mode, RedactionMode::Pixelate);
+
+        let redact: RedactOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(redact, RedactOptions::default());
+        assert_eq!(redact.mode, RedactionMode::Fill);
+        assert!(RedactionReport::default().is_empty());
+    }
+
+    #[test]
+    fn test_compare_options_defaults_and_validation() {
+        let opts: CompareOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(opts, CompareOptions::default());
+        assert_eq!(opts.pixel_tolerance, 16);
+        assert!(opts.ignore_antialiasing);
+
+        let mut opts = CompareOptions {
+            merge_distance: 0,
+            ..Default::default()
+        };
+        opts.validate();
+        assert_eq!(opts.merge_distance, 1);
+
+        opts.merge_distance = 10_000;
+        opts.validate();
+        assert_eq!(opts.merge_distance, 256);
+    }
+
+    #[test]
+    fn test_delta_options_validation() {
+        let mut opts: DeltaOptions = serde_json::from_str(r#"{"max_changed_ratio": 3.0}"#).unwrap();
+        opts.validate();
+        assert_eq!(opts.max_changed_ratio, 1.0);
+        assert_eq!(opts.compare, CompareOptions::default());
+
+        opts.max_changed_ratio = f64::NAN;
+        opts.validate();
+        assert_eq!(opts.max_changed_ratio, 0.5);
+    }
+
+    #[test]
+    fn test_baseline_check_options_defaults_and_validation() {
+        let mut opts: BaselineCheckOptions = serde_json::from_str(
+            r#"{"max_changed_ratio": -1.0, "ignore_regions": [{"x": 0, "y": 0, "width": 10, "height": 5}]}"#,
+        )
+        .unwrap();
+        opts.validate();
+        assert_eq!(opts.max_changed_ratio, 0.0);
+        assert_eq!(opts.ignore_regions, vec![Region::new(0, 0, 10, 5)]);
+        assert_eq!(opts.compare, CompareOptions::default());
+
+        let json = serde_json::to_value(BaselineCheckOptions::default()).unwrap();
+        assert!(json.get("ignore_regions").is_none());
+    }
+
+    #[test]
+    fn test_locate_options_defaults_and_validation() {
+        let opts: LocateOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(opts, LocateOptions::default());
+        assert_eq!(opts.scales, vec![1.0]);
+
+        let mut opts = LocateOptions {
+            min_confidence: 1.5,
+            max_matches: 0,
+            scales: vec![2.0, f32::NAN, 0.01, 2.0, 9.0],
+        };
+        opts.validate();
+        assert_eq!(opts.min_confidence, 1.0);
+        assert_eq!(opts.max_matches, 1);
+        assert_eq!(opts.scales, vec![0.1, 2.0, 4.0]);
+
+        opts.scales.clear();
+        opts.validate();
+        assert_eq!(opts.scales, vec![1.0]);
+    }
+
+    #[test]
+    fn test_template_match_maps_to_screen() {
+        let mut m = TemplateMatch {
+            region: Region::new(100, 50, 40, 20),
+            screen_region: None,
+            confidence: 0.95,
+            scale: 1.0,
+        };
+        // Capture of a window at (640, 0) scaled down to half size
+        m.map_to_screen(Region::new(640, 0, 1280, 1080), (640, 540));
+        assert_eq!(m.screen_region, Some(Region::new(840, 100, 80, 40)));
+
+        let json = serde_json::to_value(m).unwrap();
+        assert_eq!(json["screen_region"]["x"], 840);
+        assert!(json.get("region").is_some());
+    }
+
+    #[test]
+    fn test_annotation_serialization() {
+        let annotation: Annotation = serde_json::from_str(
+            r##"{"type": "arrow", "from": [10, 20], "to": [50, 60], "color": "#00ff00"}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            annotation.shape,
+            AnnotationShape::Arrow {
+                from: [10.0, 20.0],
+                to: [50.0, 60.0]
+            }
+        );
+        assert_eq!(annotation.color, "#00ff00");
+        assert_eq!(annotation.stroke_width, 3.0);
+
+        let text: Annotation =
+            serde_json::from_str(r#"{"type": "text", "x": 1, "y": 2, "text": "Bug"}"#).unwrap();
+        assert_eq!(text.color, "red");
+        assert!(matches!(text.shape, AnnotationShape::Text { size, .. } if size == 16.0));
+
+        let json = serde_json::to_value(Annotation::new(AnnotationShape::Polyline {
+            points: vec![[0.0, 0.0], [1.0, 1.0]],
+        }))
+        .unwrap();
+        assert_eq!(json["type"], "polyline");
+        assert_eq!(json["strokeWidth"], 3.0);
+    }
+
+    #[test]
+    fn test_annotation_maps_from_screen() {
+        let mut annotation = Annotation::new(AnnotationShape::Rectangle {
+            x: 840.0,
+            y: 100.0,
+            width: 80.0,
+            height: 40.0,
+        });
+        // Capture of a window at (640, 0) scaled down to half size
+        annotation.map_from_screen(Region::new(640, 0, 1280, 1080), (640, 540));
+        assert_eq!(
+            annotation.shape,
+            AnnotationShape::Rectangle {
+                x: 100.0,
+                y: 50.0,
+                width: 40.0,
+                height: 20.0
+            }
+        );
+
+        let mut arrow = Annotation::new(AnnotationShape::Arrow {
+            from: [650.0, 10.0],
+            to: [660.0, 30.0],
+        });
+        arrow.map_from_screen(Region::new(640, 0, 100, 100), (100, 100));
+        assert_eq!(
+            arrow.shape,
+            AnnotationShape::Arrow {
+                from: [10.0, 10.0],
+                to: [20.0, 30.0]
+            }
+        );
+    }
+
+    #[test]
+    fn test_frame_delta_serialization() {
+        let full = FrameDelta::Full {
+            reason: FullFrameReason::TooManyChanges,
+            changed_ratio: Some(0.75),
+        };
+        assert_eq!(
+            serde_json::to_value(&full).unwrap(),
+            serde_json::json!({"mode": "full", "reason": "too_many_changes", "changed_ratio": 0.75})
+        );
+
+        let regions = FrameDelta::Regions {
+            regions: vec![Region::new(1, 2, 3, 4)],
+            changed_ratio: 0.1,
+        };
+        let json = serde_json::to_value(&regions).unwrap();
+        assert_eq!(json["mode"], "regions");
+        assert_eq!(json["regions"][0]["width"], 3);
+
+        assert_eq!(
+            serde_json::to_value(FrameDelta::Unchanged).unwrap(),
+            serde_json::json!({"mode": "unchanged"})
+        );
+    }
+
     #[test]
     fn test_capture_options_serialization() {
         let opts = CaptureOptions {
@@ -1392,6 +3271,9 @@ mod tests {
             region: Some(Region::new(10, 20, 640, 480)),
             wayland_source: None,
             max_dimension: Some(1920),
+            settle: None,
+            grid: None,
+            redact: None,
         };
 
         let json = serde_json::to_value(&opts).unwrap();
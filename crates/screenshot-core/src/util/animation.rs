@@ -0,0 +1,320 @@
+//! Animated image encoding for window recordings
+//!
+//! Encodes a sequence of equally sized frames, each shown for its own
+//! duration, as an animation that loops forever:
+//!
+//! - **WebP**: Lossless frames in an animated WebP container (`ANMF`
+//!   chunks wrapping the still encoder's `VP8L` bitstreams)
+//! - **APNG**: Lossless, via the `png` crate's animation support
+//! - **GIF**: Quantized to 256 colours per frame; delays are rounded to
+//!   centiseconds
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     capture::ImageBuffer,
+//!     model::AnimationFormat,
+//!     util::animation::{AnimationFrame, encode_animation},
+//! };
+//!
+//! let frames = vec![
+//!     AnimationFrame::new(ImageBuffer::from_test_pattern(64, 48), 200),
+//!     AnimationFrame::new(ImageBuffer::from_test_pattern(64, 48), 300),
+//! ];
+//! let data = encode_animation(&frames, AnimationFormat::Webp).unwrap();
+//! assert_eq!(&data[8..12], b"WEBP");
+//! ```
+
+use image::{
+    Delay, Frame,
+    codecs::gif::{GifEncoder, Repeat},
+};
+
+use super::encode::encode_webp;
+use crate::{
+    capture::ImageBuffer,
+    error::{CaptureError, CaptureResult},
+    model::AnimationFormat,
+};
+
+/// One frame of an animation
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    /// The frame image
+    pub image: ImageBuffer,
+    /// How long the frame is shown, in milliseconds
+    pub duration_ms: u32,
+}
+
+impl AnimationFrame {
+    /// Creates a frame shown for `duration_ms`
+    pub fn new(image: ImageBuffer, duration_ms: u32) -> Self {
+        Self { image, duration_ms }
+    }
+}
+
+/// Encodes `frames` as a looping animation in `format`
+///
+/// # Errors
+///
+/// - `CaptureError::InvalidParameter` for `frames` if there are none or
+///   their dimensions differ
+/// - `CaptureError::EncodingFailed` if the encoder fails
+pub fn encode_animation(
+    frames: &[AnimationFrame],
+    format: AnimationFormat,
+) -> CaptureResult<Vec<u8>> {
+    let Some(first) = frames.first() else {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "frames".to_string(),
+            reason: "an animation needs at least one frame".to_string(),
+        });
+    };
+    let dimensions = first.image.dimensions();
+    if let Some(frame) = frames.iter().find(|f| f.image.dimensions() != dimensions) {
+        return Err(CaptureError::InvalidParameter {
+            parameter: "frames".to_string(),
+            reason: format!(
+                "frame size {:?} differs from the first frame's {:?}",
+                frame.image.dimensions(),
+                dimensions
+            ),
+        });
+    }
+
+    match format {
+        AnimationFormat::Webp => encode_animated_webp(frames, dimensions),
+        AnimationFormat::Apng => encode_apng(frames, dimensions),
+        AnimationFormat::Gif => encode_gif(frames),
+    }
+}
+
+fn encoding_failed(format: AnimationFormat, reason: impl ToString) -> CaptureError {
+    CaptureError::EncodingFailed {
+        format: format.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+fn encode_animated_webp(
+    frames: &[AnimationFrame],
+    (width, height): (u32, u32),
+) -> CaptureResult<Vec<u8>> {
+    // Canvas flags: animation (0x02) and alpha (0x10)
+    let mut vp8x = vec![0x12, 0, 0, 0];
+    push_u24(&mut vp8x, width - 1);
+    push_u24(&mut vp8x, height - 1);
+
+    let mut body = Vec::new();
+    push_chunk(&mut body, b"VP8X", &vp8x);
+    // Transparent background, loop forever
+    push_chunk(&mut body, b"ANIM", &[0, 0, 0, 0, 0, 0]);
+
+    for frame in frames {
+        let still = encode_webp(&frame.image, 100)?;
+        let bitstream = webp_image_chunk(&still).ok_or_else(|| {
+            encoding_failed(AnimationFormat::Webp, "unexpected still WebP layout")
+        })?;
+
+        let mut anmf = Vec::with_capacity(16 + bitstream.len());
+        push_u24(&mut anmf, 0);
+        push_u24(&mut anmf, 0);
+        push_u24(&mut anmf, width - 1);
+        push_u24(&mut anmf, height - 1);
+        push_u24(&mut anmf, frame.duration_ms.min(0xFF_FFFF));
+        // Frames cover the canvas: overwrite rather than alpha-blend
+        anmf.push(0x02);
+        anmf.extend_from_slice(bitstream);
+        push_chunk(&mut body, b"ANMF", &anmf);
+    }
+
+    let mut output = Vec::with_capacity(12 + body.len());
+    output.extend_from_slice(b"RIFF");
+    output.extend_from_slice(&(4 + body.len() as u32).to_le_bytes());
+    output.extend_from_slice(b"WEBP");
+    output.extend_from_slice(&body);
+    Ok(output)
+}
+
+/// Finds the image chunk (`VP8L` or `VP8 `, with header and padding) of a
+/// still WebP file
+fn webp_image_chunk(data: &[u8]) -> Option<&[u8]> {
+    if data.get(0..4)? != b"RIFF" || data.get(8..12)? != b"WEBP" {
+        return None;
+    }
+    let mut position = 12;
+    while position + 8 <= data.len() {
+        let fourcc = &data[position..position + 4];
+        let size = u32::from_le_bytes(data[position + 4..position + 8].try_into().ok()?) as usize;
+        let end = (position + 8 + size + size % 2).min(data.len());
+        if fourcc == b"VP8L" || fourcc == b"VP8 " {
+            return data.get(position..end);
+        }
+        position = end;
+    }
+    None
+}
+
+fn push_u24(output: &mut Vec<u8>, value: u32) {
+    output.extend_from_slice(&value.to_le_bytes()[..3]);
+}
+
+fn push_chunk(output: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+    output.extend_from_slice(fourcc);
+    output.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    output.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        output.push(0);
+    }
+}
+
+fn encode_apng(frames: &[AnimationFrame], (width, height): (u32, u32)) -> CaptureResult<Vec<u8>> {
+    let failed = |e: png::EncodingError| encoding_failed(AnimationFormat::Apng, e);
+
+    let mut output = Vec::new();
+    let mut encoder = png::Encoder::new(&mut output, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_compression(png::Compression::Fast);
+    encoder
+        .set_animated(frames.len() as u32, 0)
+        .map_err(failed)?;
+
+    let mut writer = encoder.write_header().map_err(failed)?;
+    for frame in frames {
+        writer
+            .set_frame_delay(frame.duration_ms.min(u16::MAX as u32) as u16, 1000)
+            .map_err(failed)?;
+        writer
+            .write_image_data(frame.image.to_rgba8().as_raw())
+            .map_err(failed)?;
+    }
+    writer.finish().map_err(failed)?;
+    Ok(output)
+}
+
+fn encode_gif(frames: &[AnimationFrame]) -> CaptureResult<Vec<u8>> {
+    let failed = |e: image::ImageError| encoding_failed(AnimationFormat::Gif, e);
+
+    let mut output = Vec::new();
+    {
+        // Speed 10 is the quantizer's usual trade-off of quality for time
+        let mut encoder = GifEncoder::new_with_speed(&mut output, 10);
+        encoder.set_repeat(Repeat::Infinite).map_err(failed)?;
+        encoder
+            .encode_frames(frames.iter().map(|frame| {
+                Frame::from_parts(
+                    frame.image.to_rgba8(),
+                    0,
+                    0,
+                    Delay::from_numer_denom_ms(frame.duration_ms, 1),
+                )
+            }))
+            .map_err(failed)?;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use image::{
+        AnimationDecoder,
+        codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    };
+
+    use super::*;
+
+    fn solid(value: u8, duration_ms: u32) -> AnimationFrame {
+        AnimationFrame::new(
+            ImageBuffer::new(image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+                40,
+                30,
+                image::Rgba([value, 255 - value, 0, 255]),
+            ))),
+            duration_ms,
+        )
+    }
+
+    fn frames() -> Vec<AnimationFrame> {
+        vec![solid(0, 200), solid(128, 300), solid(255, 500)]
+    }
+
+    /// Decoded frames as (first pixel, delay in ms)
+    fn summarize(frames: Vec<image::Frame>) -> Vec<([u8; 4], u32)> {
+        frames
+            .into_iter()
+            .map(|frame| {
+                let (numerator, denominator) = frame.delay().numer_denom_ms();
+                (frame.buffer().get_pixel(0, 0).0, numerator / denominator)
+            })
+            .collect()
+    }
+
+    fn expected() -> Vec<([u8; 4], u32)> {
+        vec![
+            ([0, 255, 0, 255], 200),
+            ([128, 127, 0, 255], 300),
+            ([255, 0, 0, 255], 500),
+        ]
+    }
+
+    #[test]
+    fn test_encode_webp_animation() {
+        let data = encode_animation(&frames(), AnimationFormat::Webp).unwrap();
+        let decoder = WebPDecoder::new(Cursor::new(data)).unwrap();
+        assert!(decoder.has_animation());
+        let decoded = decoder.into_frames().collect_frames().unwrap();
+        assert_eq!(summarize(decoded), expected());
+    }
+
+    #[test]
+    fn test_encode_apng_animation() {
+        let data = encode_animation(&frames(), AnimationFormat::Apng).unwrap();
+        let decoder = PngDecoder::new(Cursor::new(data)).unwrap();
+        assert!(decoder.is_apng().unwrap());
+        let decoded = decoder
+            .apng()
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        assert_eq!(summarize(decoded), expected());
+    }
+
+    #[test]
+    fn test_encode_gif_animation() {
+        let data = encode_animation(&frames(), AnimationFormat::Gif).unwrap();
+        let decoded = GifDecoder::new(Cursor::new(data))
+            .unwrap()
+            .into_frames()
+            .collect_frames()
+            .unwrap();
+        let summary = summarize(decoded);
+        assert_eq!(summary.len(), 3);
+        // Quantization may shift colours slightly
+        for ((pixel, delay), (expected_pixel, expected_delay)) in summary.iter().zip(expected()) {
+            assert_eq!(*delay, expected_delay);
+            for (channel, expected_channel) in pixel.iter().zip(expected_pixel) {
+                assert!(channel.abs_diff(expected_channel) <= 8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_animation_rejects_bad_frames() {
+        assert!(matches!(
+            encode_animation(&[], AnimationFormat::Webp),
+            Err(CaptureError::InvalidParameter { .. })
+        ));
+
+        let mut frames = frames();
+        frames.push(AnimationFrame::new(ImageBuffer::from_test_pattern(20, 20), 100));
+        assert!(matches!(
+            encode_animation(&frames, AnimationFormat::Gif),
+            Err(CaptureError::InvalidParameter { .. })
+        ));
+    }
+}
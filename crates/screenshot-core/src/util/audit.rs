@@ -0,0 +1,570 @@
+//! Append-only audit log of what was captured
+//!
+//! The MCP server appends one JSON line per capture, and per call the
+//! privacy policy refuses, so a person can later review what an agent looked
+//! at. Each [`AuditEntry`] records the tool, the selector, the resolved
+//! window, the backend, the image dimensions, where the image went and
+//! whether redaction or the capture policy applied.
+//!
+//! # Rotation
+//!
+//! When appending would grow the log past its size limit, the log is renamed
+//! to `audit.jsonl.1` (older rotations move up to `.2`, `.3`, ...) and a new
+//! log is started. The oldest rotation beyond the configured count is
+//! deleted. [`AuditLog::read`] reads the rotations oldest first.
+//!
+//! # Location
+//!
+//! [`AuditLog::open_default`] uses `$SCREENSHOT_AUDIT_FILE` if set,
+//! otherwise `$XDG_STATE_HOME/screenshot-mcp/audit.jsonl` (falling back to
+//! `~/.local/state`). With none of these set it refuses rather than fall
+//! back to a shared directory. On Unix the log is created readable by its
+//! owner only.
+//!
+//! # Examples
+//!
+//! ```
+//! use screenshot_core::{
+//!     model::BackendType,
+//!     util::audit::{AuditEntry, AuditFilter, AuditLog},
+//! };
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! let log = AuditLog::new(dir.path().join("audit.jsonl"));
+//!
+//! let mut entry = AuditEntry::new("capture_window", BackendType::X11);
+//! entry.output.push("inline".to_string());
+//! log.append(&entry).unwrap();
+//!
+//! let entries = log.read(&AuditFilter::default()).unwrap();
+//! assert_eq!(entries, vec![entry]);
+//! ```
+
+use std::{
+    ffi::OsString,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::{DateTime, NaiveDate, SecondsFormat, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{CaptureError, CaptureResult},
+    model::{BackendType, RedactionReport, WindowHandle, WindowInfo, WindowSelector},
+};
+
+/// Environment variable that overrides the audit log path
+pub const AUDIT_FILE_ENV: &str = "SCREENSHOT_AUDIT_FILE";
+
+/// Default size at which the audit log is rotated (10 MiB)
+pub const DEFAULT_AUDIT_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated logs kept next to the current one
+pub const DEFAULT_AUDIT_ROTATIONS: usize = 5;
+
+/// The window a capture resolved to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditWindow {
+    /// Window handle
+    pub id: WindowHandle,
+    /// Window title at capture time
+    pub title: String,
+    /// Window class
+    pub class: String,
+    /// Owning application
+    pub owner: String,
+    /// Process ID of the owner
+    pub pid: u32,
+}
+
+impl From<&WindowInfo> for AuditWindow {
+    fn from(window: &WindowInfo) -> Self {
+        Self {
+            id: window.id.clone(),
+            title: window.title.clone(),
+            class: window.class.clone(),
+            owner: window.owner.clone(),
+            pid: window.pid,
+        }
+    }
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the capture happened (RFC 3339, UTC)
+    pub timestamp: String,
+    /// Tool that captured, e.g. `capture_window`
+    pub tool: String,
+    /// Selector the window was resolved from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selector: Option<WindowSelector>,
+    /// Window the capture resolved to; absent for display captures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<AuditWindow>,
+    /// Capture backend
+    pub backend: BackendType,
+    /// Width of the captured image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Height of the captured image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Files the image was written to, or `"inline"` if it was only
+    /// returned inline; empty if the image was not returned
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<String>,
+    /// What redaction and the capture policy hid from the image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redaction: Option<RedactionReport>,
+    /// Why the capture policy refused the call, if it did
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denied: Option<String>,
+}
+
+impl AuditEntry {
+    /// Creates an entry for `tool` timestamped now
+    pub fn new(tool: impl Into<String>, backend: BackendType) -> Self {
+        Self {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            tool: tool.into(),
+            selector: None,
+            window: None,
+            backend,
+            width: None,
+            height: None,
+            output: Vec::new(),
+            redaction: None,
+            denied: None,
+        }
+    }
+
+    /// Parses the entry's timestamp
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    }
+
+    /// Returns true if anything was hidden from the image
+    pub fn redacted(&self) -> bool {
+        self.redaction.as_ref().is_some_and(|r| !r.is_empty())
+    }
+
+    /// Returns true if the capture policy refused the call or blacked out
+    /// windows in the image
+    pub fn policy_applied(&self) -> bool {
+        self.denied.is_some()
+            || self
+                .redaction
+                .as_ref()
+                .is_some_and(|r| !r.blocked.is_empty())
+    }
+}
+
+/// Which entries [`AuditLog::read`] returns
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditFilter {
+    /// Only entries at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only entries before this time
+    pub until: Option<DateTime<Utc>>,
+    /// Only entries whose window class or owner contains this text
+    /// (case-insensitive)
+    pub app: Option<String>,
+}
+
+impl AuditFilter {
+    /// Returns true if `entry` passes the filter
+    ///
+    /// Entries with an unreadable timestamp fail any time bound.
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        if self.since.is_some() || self.until.is_some() {
+            let Some(time) = entry.time() else {
+                return false;
+            };
+            if self.since.is_some_and(|since| time < since)
+                || self.until.is_some_and(|until| time >= until)
+            {
+                return false;
+            }
+        }
+
+        match &self.app {
+            Some(app) => {
+                let app = app.to_lowercase();
+                entry.window.as_ref().is_some_and(|window| {
+                    window.class.to_lowercase().contains(&app)
+                        || window.owner.to_lowercase().contains(&app)
+                })
+            }
+            None => true,
+        }
+    }
+}
+
+/// Parses a time bound for an [`AuditFilter`]
+///
+/// Accepts an RFC 3339 timestamp (`2026-03-01T09:30:00Z`), a date
+/// (`2026-03-01`, midnight UTC), or a duration before `now` with an `s`,
+/// `m`, `h` or `d` suffix (`90m`, `7d`).
+///
+/// # Errors
+///
+/// `CaptureError::InvalidParameter` for `time` if `value` is none of these.
+pub fn parse_audit_time(value: &str, now: DateTime<Utc>) -> CaptureResult<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_time(Default::default()).and_utc());
+    }
+
+    let invalid = || CaptureError::InvalidParameter {
+        parameter: "time".to_string(),
+        reason: format!(
+            "'{}' is not an RFC 3339 time, a date (YYYY-MM-DD) or a duration such as 90m or 7d",
+            value
+        ),
+    };
+    let split = value.len().saturating_sub(1);
+    let (amount, unit) = (value.get(..split), value.get(split..));
+    let (Some(amount), Some(unit)) = (amount, unit) else {
+        return Err(invalid());
+    };
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+    let delta = match unit {
+        "s" => TimeDelta::try_seconds(amount),
+        "m" => TimeDelta::try_minutes(amount),
+        "h" => TimeDelta::try_hours(amount),
+        "d" => TimeDelta::try_days(amount),
+        _ => None,
+    }
+    .ok_or_else(invalid)?;
+    now.checked_sub_signed(delta).ok_or_else(invalid)
+}
+
+/// Size-rotated JSONL audit log
+#[derive(Debug)]
+pub struct AuditLog {
+    path: PathBuf,
+    max_bytes: u64,
+    rotations: usize,
+    /// Serializes appends and rotation within this process
+    lock: Mutex<()>,
+}
+
+impl AuditLog {
+    /// Creates a log at `path` (created on first append) with the default
+    /// rotation settings
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_bytes: DEFAULT_AUDIT_MAX_BYTES,
+            rotations: DEFAULT_AUDIT_ROTATIONS,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Creates a log at the default path (see the module docs)
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::InvalidParameter` for `audit` if there is no per-user
+    /// state directory to put the log in.
+    pub fn open_default() -> CaptureResult<Self> {
+        Self::default_path()
+            .map(Self::new)
+            .ok_or_else(|| CaptureError::InvalidParameter {
+                parameter: "audit".to_string(),
+                reason: format!(
+                    "no private directory for the audit log; set {}, XDG_STATE_HOME or HOME",
+                    AUDIT_FILE_ENV
+                ),
+            })
+    }
+
+    /// Gets the default audit log path
+    ///
+    /// None if neither the override nor a per-user state directory is set.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(AUDIT_FILE_ENV) {
+            return Some(PathBuf::from(path));
+        }
+
+        let state_dir = if let Ok(dir) = std::env::var("XDG_STATE_HOME") {
+            PathBuf::from(dir)
+        } else if let Ok(home) = std::env::var("HOME") {
+            PathBuf::from(home).join(".local").join("state")
+        } else {
+            return None;
+        };
+
+        Some(state_dir.join("screenshot-mcp").join("audit.jsonl"))
+    }
+
+    /// Sets the size at which the log is rotated and how many rotated logs
+    /// are kept (at least one)
+    pub fn with_rotation(mut self, max_bytes: u64, rotations: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self.rotations = rotations.max(1);
+        self
+    }
+
+    /// The path of the current log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the path of the `index`th rotated log (1 is the newest)
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{}", index));
+        PathBuf::from(path)
+    }
+
+    /// Appends `entry` as one line, rotating the log first if it would
+    /// grow past its size limit
+    pub fn append(&self, entry: &AuditEntry) -> CaptureResult<()> {
+        let mut line =
+            serde_json::to_string(entry).map_err(|e| CaptureError::InvalidParameter {
+                parameter: "entry".to_string(),
+                reason: format!("cannot serialize audit entry: {}", e),
+            })?;
+        line.push('\n');
+
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parent) = self.path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            let mut builder = fs::DirBuilder::new();
+            builder.recursive(true);
+            #[cfg(unix)]
+            std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+            builder.create(parent)?;
+        }
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+
+        // The log names every window an agent looked at: owner-only
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&self.path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Shifts the rotated logs up by one and moves the current log to `.1`
+    fn rotate(&self) -> CaptureResult<()> {
+        let oldest = self.rotated_path(self.rotations);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for index in (1..self.rotations).rev() {
+            let path = self.rotated_path(index);
+            if path.exists() {
+                fs::rename(&path, self.rotated_path(index + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated_path(1))?;
+        tracing::debug!("Rotated audit log {}", self.path.display());
+        Ok(())
+    }
+
+    /// Reads the entries that pass `filter`, oldest first
+    ///
+    /// Includes the rotated logs. Lines that cannot be parsed are skipped
+    /// with a warning.
+    pub fn read(&self, filter: &AuditFilter) -> CaptureResult<Vec<AuditEntry>> {
+        let mut files: Vec<PathBuf> = (1..=self.rotations)
+            .rev()
+            .map(|index| self.rotated_path(index))
+            .collect();
+        files.push(self.path.clone());
+
+        let mut entries = Vec::new();
+        for path in files {
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            for (number, line) in text.lines().enumerate() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<AuditEntry>(line) {
+                    Ok(entry) if filter.matches(&entry) => entries.push(entry),
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!(
+                        "Skipping malformed audit entry {}:{}: {}",
+                        path.display(),
+                        number + 1,
+                        e
+                    ),
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tool: &str, timestamp: &str, class: &str) -> AuditEntry {
+        let mut entry = AuditEntry::new(tool, BackendType::X11);
+        entry.timestamp = timestamp.to_string();
+        entry.window = Some(AuditWindow {
+            id: "0x1".to_string(),
+            title: format!("{} window", class),
+            class: class.to_string(),
+            owner: class.to_lowercase(),
+            pid: 42,
+        });
+        entry
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_append_creates_owner_only_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        log.append(&entry("capture_window", "2026-03-01T09:00:00.000Z", "Code"))
+            .unwrap();
+
+        let mode = fs::metadata(log.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_default_path_needs_a_private_directory() {
+        temp_env::with_vars_unset([AUDIT_FILE_ENV, "XDG_STATE_HOME", "HOME"], || {
+            assert_eq!(AuditLog::default_path(), None);
+            assert!(AuditLog::open_default().is_err());
+        });
+        temp_env::with_vars(
+            [
+                (AUDIT_FILE_ENV, None),
+                ("XDG_STATE_HOME", None),
+                ("HOME", Some("/home/u")),
+            ],
+            || {
+                assert_eq!(
+                    AuditLog::default_path(),
+                    Some(PathBuf::from("/home/u/.local/state/screenshot-mcp/audit.jsonl"))
+                );
+            },
+        );
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("logs").join("audit.jsonl"));
+
+        let mut denied = entry("capture_window", "2026-03-01T09:00:00.000Z", "KeePassXC");
+        denied.denied = Some("matches deny rule 'class:KeePassXC'".to_string());
+        let mut redacted = entry("capture_window", "2026-03-01T10:00:00.000Z", "Firefox");
+        redacted.output = vec!["/tmp/screenshot-mcp/a.png".to_string()];
+        redacted.redaction = Some(RedactionReport {
+            windows: vec!["0x2".to_string()],
+            ..Default::default()
+        });
+        log.append(&denied).unwrap();
+        log.append(&redacted).unwrap();
+
+        let text = fs::read_to_string(log.path()).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.lines().all(|line| line.starts_with('{')));
+
+        let entries = log.read(&AuditFilter::default()).unwrap();
+        assert_eq!(entries, vec![denied.clone(), redacted.clone()]);
+        assert!(entries[0].policy_applied() && !entries[0].redacted());
+        assert!(entries[1].redacted() && !entries[1].policy_applied());
+    }
+
+    #[test]
+    fn test_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl")).with_rotation(300, 2);
+
+        for hour in 0..10 {
+            let timestamp = format!("2026-03-01T{:02}:00:00.000Z", hour);
+            log.append(&entry("capture_window", &timestamp, "Code"))
+                .unwrap();
+        }
+
+        assert!(log.rotated_path(1).exists());
+        assert!(log.rotated_path(2).exists());
+        assert!(!log.rotated_path(3).exists());
+        for path in [log.path().to_path_buf(), log.rotated_path(1)] {
+            assert!(fs::metadata(path).unwrap().len() <= 300);
+        }
+
+        // The oldest entries were dropped; the rest read back in order
+        let entries = log.read(&AuditFilter::default()).unwrap();
+        assert!(!entries.is_empty() && entries.len() < 10);
+        assert_eq!(entries.last().unwrap().timestamp, "2026-03-01T09:00:00.000Z");
+        assert!(entries.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+    }
+
+    #[test]
+    fn test_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(dir.path().join("audit.jsonl"));
+        log.append(&entry("capture_window", "2026-03-01T09:00:00.000Z", "Firefox"))
+            .unwrap();
+        log.append(&entry("capture_window", "2026-03-02T09:00:00.000Z", "Code"))
+            .unwrap();
+        log.append(&entry("mark_windows", "2026-03-03T09:00:00.000Z", "Firefox"))
+            .unwrap();
+        fs::write(log.rotated_path(1), "not json\n{\"tool\":\"missing fields\"}\n").unwrap();
+
+        let now = Utc::now();
+        let filter = AuditFilter {
+            since: Some(parse_audit_time("2026-03-02", now).unwrap()),
+            until: None,
+            app: Some("FIREFOX".to_string()),
+        };
+        let entries = log.read(&filter).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].tool, "mark_windows");
+
+        let filter = AuditFilter {
+            until: Some(parse_audit_time("2026-03-02T09:00:00Z", now).unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(log.read(&filter).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_audit_time() {
+        let now = DateTime::parse_from_rfc3339("2026-03-10T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let at = |value: &str| parse_audit_time(value, now).unwrap().to_rfc3339();
+
+        assert_eq!(at("2026-03-01T09:30:00+01:00"), "2026-03-01T08:30:00+00:00");
+        assert_eq!(at("2026-03-01"), "2026-03-01T00:00:00+00:00");
+        assert_eq!(at("90m"), "2026-03-10T10:30:00+00:00");
+        assert_eq!(at("7d"), "2026-03-03T12:00:00+00:00");
+
+        for value in ["", "yesterday", "5w", "-", "2026-13-01"] {
+            assert!(
+                matches!(parse_audit_time(value, now), Err(CaptureError::InvalidParameter { .. })),
+                "{:?} should not parse",
+                value
+            );
+        }
+    }
+}
@@ -1,5 +1,9 @@
 // Utility modules
 
+#[cfg(feature = "image-processing")]
+pub mod animation;
+
+pub mod audit;
 pub mod detect;
 
 #[cfg(feature = "image-processing")]
@@ -34,7 +34,7 @@ use chrono::{DateTime, Utc};
 
 use crate::{
     error::{CaptureError, CaptureResult},
-    model::ImageFormat,
+    model::{AnimationFormat, ImageFormat},
 };
 
 /// Represents a tracked temporary file
@@ -113,6 +113,42 @@ impl TempFileManager {
         std::env::temp_dir().join("screenshot-mcp")
     }
 
+    /// Resolves a reference to a stored capture into a file path
+    ///
+    /// Accepts the `file://` URI from a capture's resource link, a bare file
+    /// name of a capture in the temp directory, or any other path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    ///
+    /// use screenshot_core::util::temp_files::TempFileManager;
+    ///
+    /// let path = TempFileManager::resolve_reference("file:///tmp/shot.png");
+    /// # #[cfg(not(target_os = "windows"))]
+    /// assert_eq!(path, PathBuf::from("/tmp/shot.png"));
+    /// ```
+    pub fn resolve_reference(reference: &str) -> PathBuf {
+        let reference = reference.trim();
+        if let Some(rest) = reference.strip_prefix("file://") {
+            // Windows URIs are written as file:///C:/...
+            #[cfg(target_os = "windows")]
+            let rest = rest.strip_prefix('/').unwrap_or(rest);
+            return PathBuf::from(percent_decode(rest));
+        }
+
+        let path = PathBuf::from(reference);
+        let is_bare_name = path.components().count() == 1;
+        if is_bare_name && !path.exists() {
+            let stored = Self::temp_dir().join(&path);
+            if stored.exists() {
+                return stored;
+            }
+        }
+        path
+    }
+
     /// Ensures the temp directory exists, creating it if necessary
     fn ensure_temp_dir() -> CaptureResult<PathBuf> {
         let dir = Self::temp_dir();
@@ -227,6 +263,20 @@ impl TempFileManager {
         Ok((path, size))
     }
 
+    /// Writes an encoded animation to a temporary file
+    ///
+    /// Like [`write_image`](Self::write_image), but the file is named
+    /// `recording-*` with the animation format's extension.
+    pub fn write_animation(
+        &self,
+        data: &[u8],
+        format: AnimationFormat,
+    ) -> CaptureResult<(PathBuf, u64)> {
+        let path = self.create_temp_file("recording", format.extension())?;
+        fs::write(&path, data).map_err(CaptureError::IoError)?;
+        Ok((path, data.len() as u64))
+    }
+
     /// Manually cleans up all tracked temporary files
     ///
     /// Removes all tracked files from the filesystem and clears the internal
@@ -300,6 +350,34 @@ impl TempFileManager {
     }
 }
 
+/// Decodes `%XX` escapes in the path part of a `file://` URI
+///
+/// Returns the input unchanged if an escape is malformed or the decoded bytes
+/// are not UTF-8.
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = bytes
+                .get(i + 1..i + 3)
+                .and_then(|h| std::str::from_utf8(h).ok());
+            match hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                    continue;
+                }
+                None => return path.to_string(),
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| path.to_string())
+}
+
 impl Default for TempFileManager {
     fn default() -> Self {
         Self::new()
@@ -435,6 +513,23 @@ mod tests {
         manager.cleanup_all();
     }
 
+    #[test]
+    fn test_write_animation_gif() {
+        let manager = TempFileManager::new();
+        let data = b"GIF89a".to_vec();
+
+        let (path, size) = manager
+            .write_animation(&data, AnimationFormat::Gif)
+            .unwrap();
+
+        assert_eq!(size, 6);
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        assert!(name.starts_with("recording-") && name.ends_with(".gif"));
+        assert_eq!(fs::read(&path).unwrap(), data);
+
+        manager.cleanup_all();
+    }
+
     #[test]
     fn test_write_image_webp() {
         let manager = TempFileManager::new();
@@ -553,4 +648,39 @@ mod tests {
         let manager = TempFileManager::default();
         assert_eq!(manager.count(), 0);
     }
+
+    #[test]
+    fn test_resolve_reference() {
+        let manager = TempFileManager::new();
+        let path = manager.create_temp_file("resolve", "png").unwrap();
+        let name = path.file_name().unwrap().to_str().unwrap();
+
+        // Bare names are looked up in the temp directory
+        assert_eq!(TempFileManager::resolve_reference(name), path);
+
+        #[cfg(not(target_os = "windows"))]
+        assert_eq!(TempFileManager::resolve_reference(&format!("file://{}", path.display())), path);
+
+        // Anything else is taken as a path
+        assert_eq!(
+            TempFileManager::resolve_reference("no-such-capture.png"),
+            PathBuf::from("no-such-capture.png")
+        );
+
+        manager.cleanup_all();
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_resolve_reference_decodes_file_uris() {
+        assert_eq!(
+            TempFileManager::resolve_reference("file:///tmp/My%20Shots/caf%C3%A9.png"),
+            PathBuf::from("/tmp/My Shots/café.png")
+        );
+        // Malformed escapes are left alone
+        assert_eq!(
+            TempFileManager::resolve_reference("file:///tmp/100%.png"),
+            PathBuf::from("/tmp/100%.png")
+        );
+    }
 }
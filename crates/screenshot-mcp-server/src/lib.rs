@@ -4,4 +4,7 @@
 //! and response formatting.
 
 pub mod mcp;
+pub mod mcp_consent;
 pub mod mcp_content;
+pub mod mcp_events;
+pub mod mcp_timelapse;
@@ -6,10 +6,10 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use rmcp::{ServiceExt, transport::stdio};
-use screenshot_core::capture::create_default_backend;
-use screenshot_core::util::temp_files::TempFileManager;
+use screenshot_core::capture::{TargetRegistry, create_default_backend};
+use screenshot_core::util::{audit::AuditLog, temp_files::TempFileManager};
 use screenshot_mcp_server::mcp::ScreenshotMcpServer;
-use tracing::info;
+use tracing::{info, warn};
 use tracing_subscriber::{EnvFilter, fmt};
 
 #[tokio::main]
@@ -48,8 +48,25 @@ async fn main() -> Result<()> {
     let temp_files = Arc::new(TempFileManager::new());
     info!("Temp file manager initialized");
 
+    // Load named targets (an absent config file means no targets)
+    let targets = TargetRegistry::load_default()?;
+    info!(
+        "Loaded {} targets from {}",
+        targets.len(),
+        TargetRegistry::default_path().display()
+    );
+
     // Create the MCP server with backend and temp file manager
-    let server = ScreenshotMcpServer::new(backend, temp_files);
+    let mut server = ScreenshotMcpServer::new(backend, temp_files).with_targets(targets);
+
+    // Record captures for later review
+    match AuditLog::open_default() {
+        Ok(audit) => {
+            info!("Audit log: {}", audit.path().display());
+            server = server.with_audit_log(audit);
+        }
+        Err(e) => warn!("Audit log disabled: {}", e),
+    }
 
     info!("Initializing stdio transport...");
 
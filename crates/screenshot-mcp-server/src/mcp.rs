@@ -3,23 +3,57 @@
 //! This module provides the screenshot-mcp MCP server implementation
 //! with tools for screenshot capture across different platforms.
 
-use std::sync::Arc;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use rmcp::{
-    ServerHandler,
+    Peer, RoleServer, ServerHandler,
     handler::server::tool::ToolRouter,
-    model::{CallToolResult, Content, ErrorData as McpError, ServerInfo},
+    model::{
+        CallToolResult, Content, ErrorData as McpError, LoggingMessageNotificationParam, ServerInfo,
+    },
+    service::NotificationContext,
     tool, tool_router,
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::mcp_content::build_capture_result;
+use crate::{
+    mcp_consent::{ConsentGate, ConsentPrompter, ConsentSubject},
+    mcp_content::{
+        EncodedRegion, build_animation_result, build_capture_result_with_metadata,
+        build_delta_result,
+    },
+    mcp_events::EventSubscriptions,
+    mcp_timelapse::TimelapseJobs,
+};
 use screenshot_core::{
-    capture::{CompositeBackend, MockBackend, composite_from_mock},
-    error::CaptureError,
-    model::{CaptureOptions, HealthCheckResponse, ImageFormat, SourceType, WindowSelector},
-    util::{detect::detect_platform, encode::encode_image, temp_files::TempFileManager},
+    capture::{
+        BaselineStore, CompositeBackend, FrameCache, ImageBuffer, MockBackend, Permission,
+        SettledCapture, TargetRegistry, WaitForWindowOptions, WindowMatcher, WindowResolver,
+        compare_images, composite_from_mock, compute_window_marks,
+        delta::frame_key,
+        draw_window_marks, locate_image, parse_selector_query,
+        record::encode_recording,
+        timelapse::{TIMELAPSE_MANIFEST, check_timelapse_options, run_timelapse},
+        wait_for_window,
+    },
+    error::{CaptureError, CaptureResult},
+    model::{
+        AmbiguityPolicy, AnimationFormat, Annotation, BaselineCheckOptions, CaptureOptions,
+        CompareOptions, CoordinateSpace, DeltaOptions, FrameDelta, GridOptions,
+        HealthCheckResponse, ImageFormat, LocateOptions, OverviewOptions, OverviewSkip,
+        RecordOptions, RedactOptions, RedactionMode, Region, SettleOptions, SourceType,
+        TimelapseOptions, WindowEventKind, WindowHandle, WindowSelector,
+    },
+    util::{
+        audit::{AuditEntry, AuditLog, AuditWindow},
+        detect::detect_platform,
+        encode::encode_image,
+        temp_files::TempFileManager,
+    },
 };
 
 /// Output image format for screenshot capture
@@ -92,11 +126,186 @@ impl CaptureRegion {
     }
 }
 
+/// Visual settling parameters for capture tools
+///
+/// The capture is repeated every `intervalMs` until two consecutive frames
+/// differ by at most `threshold` (0.0 = identical, 1.0 = black vs. white),
+/// or `maxWaitMs` has elapsed.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureSettle {
+    /// Delay between frames in milliseconds (default: 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<u64>,
+    /// Maximum perceptual difference to count as settled (default: 0.005)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f32>,
+    /// Maximum total wait in milliseconds (default: 3000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_wait_ms: Option<u64>,
+}
+
+impl CaptureSettle {
+    /// Convert to core SettleOptions, filling in defaults
+    pub fn to_settle_options(self) -> SettleOptions {
+        let defaults = SettleOptions::default();
+        SettleOptions {
+            interval_ms: self.interval_ms.unwrap_or(defaults.interval_ms),
+            threshold: self.threshold.unwrap_or(defaults.threshold),
+            max_wait_ms: self.max_wait_ms.unwrap_or(defaults.max_wait_ms),
+        }
+    }
+}
+
+/// Coordinate grid parameters for capture tools
+///
+/// Grid lines are drawn every `spacing` screen pixels and labelled with
+/// screen coordinates, so a position can be read off the image.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureGrid {
+    /// Distance between grid lines in screen pixels (default: 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spacing: Option<u32>,
+    /// Draw tick rulers along the top and left edges (default: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rulers: Option<bool>,
+}
+
+impl CaptureGrid {
+    /// Convert to core GridOptions, filling in defaults
+    pub fn to_grid_options(self) -> GridOptions {
+        let defaults = GridOptions::default();
+        GridOptions {
+            spacing: self.spacing.unwrap_or(defaults.spacing),
+            rulers: self.rulers.unwrap_or(defaults.rulers),
+        }
+    }
+}
+
+/// Redaction parameters for capture tools
+///
+/// Regions are screen rectangles, like grid labels; for a window the
+/// backend cannot place on screen they are relative to the window.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureRedact {
+    /// Rectangles to hide
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regions: Option<Vec<CaptureRegion>>,
+    /// How to hide them: "fill" (default), "pixelate" or "blur"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<RedactionMode>,
+}
+
+impl CaptureRedact {
+    /// Convert to core RedactOptions, filling in defaults
+    pub fn to_redact_options(self) -> RedactOptions {
+        RedactOptions {
+            regions: self
+                .regions
+                .unwrap_or_default()
+                .into_iter()
+                .map(CaptureRegion::to_region)
+                .collect(),
+            mode: self.mode.unwrap_or_default(),
+        }
+    }
+}
+
+/// Delta capture parameters for `capture_window`
+///
+/// The server remembers the last frame captured of each window and returns
+/// only the crops of regions that changed since then, or the full frame if
+/// more than `maxChangedRatio` of it changed.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureDelta {
+    /// Return the full frame when changed regions cover more than this
+    /// fraction of it (0.0-1.0, default: 0.5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_changed_ratio: Option<f64>,
+    /// Largest per-channel difference still treated as unchanged (0-255,
+    /// default: 16)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pixel_tolerance: Option<u8>,
+}
+
+impl CaptureDelta {
+    /// Convert to core DeltaOptions, filling in defaults
+    pub fn to_delta_options(self) -> DeltaOptions {
+        let defaults = DeltaOptions::default();
+        DeltaOptions {
+            max_changed_ratio: self.max_changed_ratio.unwrap_or(defaults.max_changed_ratio),
+            compare: CompareOptions {
+                pixel_tolerance: self
+                    .pixel_tolerance
+                    .unwrap_or(defaults.compare.pixel_tolerance),
+                ..defaults.compare
+            },
+        }
+    }
+}
+
+/// Screen point in root window / virtual desktop coordinates
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturePoint {
+    /// X coordinate (pixels)
+    pub x: i32,
+    /// Y coordinate (pixels)
+    pub y: i32,
+}
+
+/// One alternative in an `anyOf` selector group
+///
+/// Fields combine with AND; the group matches when any alternative does.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SelectorAlternative {
+    /// Window title substring or regex pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_substring_or_regex: Option<String>,
+    /// Window class name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// Executable name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    /// Process ID of the window owner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Exact window ID (numeric IDs accept decimal or 0x hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+}
+
+impl SelectorAlternative {
+    fn to_selector(&self) -> WindowSelector {
+        WindowSelector {
+            title_substring_or_regex: self.title_substring_or_regex.clone(),
+            class: self.class.clone(),
+            exe: self.exe.clone(),
+            pid: self.pid,
+            window_id: self.window_id.clone(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Parameters for the capture_window tool
 #[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CaptureWindowParams {
     // --- Window selection (at least one required) ---
+    /// Name of a target defined in the targets config file; its selector and
+    /// capture options apply unless overridden by explicit fields
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Selector query, e.g. "class:firefox !title:Private" (explicit fields
+    /// take precedence over the same field in the query)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
     /// Window title substring or regex pattern
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub title_substring_or_regex: Option<String>,
@@ -106,11 +315,40 @@ pub struct CaptureWindowParams {
     /// Executable name
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub exe: Option<String>,
+    /// Process ID of the window owner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Exact window ID (numeric IDs accept decimal or 0x hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+    /// Title patterns (regex or substring) that rule a window out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+    /// Alternative selectors; at least one must also match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<SelectorAlternative>>,
+    /// Treat accented and lookalike letters as distinct when matching titles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    /// Keep capturing the window this selector matched last time while it
+    /// still matches, instead of re-ranking candidates on every call
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sticky: Option<bool>,
+    /// When several windows match equally well: "first" (default), "error",
+    /// or {"index": n} to pick the n-th ranked match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambiguity: Option<AmbiguityPolicy>,
+    /// Capture the topmost window under the pointer (ignores other selectors)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub under_pointer: Option<bool>,
+    /// Capture the topmost window at this screen point (ignores other selectors)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at_point: Option<CapturePoint>,
 
     // --- Capture options (all optional with defaults) ---
-    /// Output image format (default: webp)
-    #[serde(default)]
-    pub format: CaptureFormat,
+    /// Output image format (default: webp, or the target's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<CaptureFormat>,
 
     /// Image quality for JPEG/WebP (0-100, default: 80)
     /// Ignored for PNG format.
@@ -133,339 +371,1151 @@ pub struct CaptureWindowParams {
     /// Region to capture (crop). If omitted, captures full window.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub region: Option<CaptureRegion>,
+
+    /// Wait for the window content to stop changing before returning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settle: Option<CaptureSettle>,
+
+    /// Return only regions changed since this window's last delta capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta: Option<CaptureDelta>,
+
+    /// Overlay a coordinate grid labelled with screen coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<CaptureGrid>,
+
+    /// Hide screen rectangles before the image is encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<CaptureRedact>,
 }
 
-/// Parameters for the prime_wayland_consent tool
+/// Parameters for the capture_pointer_region tool
 #[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct PrimeWaylandConsentParams {
-    /// Type of content to capture: "monitor", "window", or "virtual"
-    /// Default: "monitor"
-    #[serde(default = "default_source_type")]
-    pub source_type: String,
+pub struct CapturePointerRegionParams {
+    /// Width of the region centred on the pointer (pixels, default: 400)
+    #[serde(default = "default_pointer_region_width")]
+    pub width: u32,
 
-    /// Stable identifier for this source (e.g., "wayland-default",
-    /// "firefox-dev") Default: "wayland-default"
-    #[serde(default = "default_source_id")]
-    pub source_id: String,
+    /// Height of the region centred on the pointer (pixels, default: 300)
+    #[serde(default = "default_pointer_region_height")]
+    pub height: u32,
 
-    /// Whether to include cursor in captures
-    /// Default: false
+    /// Output image format (default: webp)
     #[serde(default)]
-    pub include_cursor: bool,
+    pub format: CaptureFormat,
+
+    /// Image quality for JPEG/WebP (0-100, default: 80)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+
+    /// Scale factor applied after cropping (0.1-2.0, default: 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+
+    /// Output mode: "inline" (base64), "file" (path), or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
+
+    /// Overlay a coordinate grid labelled with screen coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<CaptureGrid>,
+
+    /// Hide screen rectangles before the image is encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<CaptureRedact>,
 }
 
-fn default_source_type() -> String {
-    "monitor".to_string()
+impl Default for CapturePointerRegionParams {
+    fn default() -> Self {
+        Self {
+            width: default_pointer_region_width(),
+            height: default_pointer_region_height(),
+            format: CaptureFormat::default(),
+            quality: None,
+            scale: None,
+            output: CaptureOutputMode::default(),
+            grid: None,
+            redact: None,
+        }
+    }
 }
 
-fn default_source_id() -> String {
-    "wayland-default".to_string()
+fn default_pointer_region_width() -> u32 {
+    400
 }
 
-/// Parses a source type string to SourceType enum
-#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
-fn parse_source_type(source_type_str: &str) -> Result<SourceType, String> {
-    match source_type_str.to_lowercase().as_str() {
-        "monitor" => Ok(SourceType::Monitor),
-        "window" => Ok(SourceType::Window),
-        "virtual" => Ok(SourceType::Virtual),
-        _ => Err(format!(
-            "Invalid source_type '{}'. Must be 'monitor', 'window', or 'virtual'",
-            source_type_str
-        )),
-    }
+fn default_pointer_region_height() -> u32 {
+    300
 }
 
-/// Converts a CaptureError to an MCP ErrorData
-///
-/// Maps screenshot capture errors to appropriate MCP error codes with
-/// user-friendly messages and remediation hints.
-fn convert_capture_error_to_mcp(error: CaptureError) -> McpError {
-    match &error {
-        CaptureError::WindowNotFound { .. } => McpError::invalid_params(format!("{}", error), None),
-        CaptureError::PortalUnavailable { .. } => {
-            McpError::internal_error(format!("{}", error), None)
-        }
-        CaptureError::PermissionDenied { .. } => {
-            McpError::internal_error(format!("{}", error), None)
-        }
-        CaptureError::EncodingFailed { .. } => McpError::internal_error(format!("{}", error), None),
-        CaptureError::CaptureTimeout { .. } => McpError::internal_error(format!("{}", error), None),
-        CaptureError::InvalidParameter { parameter, reason } => {
-            McpError::invalid_params(format!("Invalid parameter '{}': {}", parameter, reason), None)
-        }
-        CaptureError::BackendNotAvailable { .. } => {
-            McpError::internal_error(format!("{}", error), None)
-        }
-        CaptureError::IoError(_) => McpError::internal_error(format!("{}", error), None),
-        CaptureError::ImageError(_) => McpError::internal_error(format!("{}", error), None),
-        CaptureError::KeyringUnavailable { .. } => {
-            McpError::internal_error(format!("{}", error), None)
-        }
-        CaptureError::KeyringOperationFailed { .. } => {
-            McpError::internal_error(format!("{}", error), None)
-        }
-        CaptureError::TokenNotFound { .. } => McpError::invalid_params(format!("{}", error), None),
-        CaptureError::EncryptionFailed { .. } => {
-            McpError::internal_error(format!("{}", error), None)
-        }
-        CaptureError::UnsupportedWindowsVersion { .. } => {
-            McpError::internal_error(format!("{}", error), None)
+/// Parameters for the compare_images tool
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareImagesParams {
+    /// Earlier image: a capture's file:// URI, its file name, or a path
+    pub before: String,
+
+    /// Later image: a capture's file:// URI, its file name, or a path
+    pub after: String,
+
+    /// Largest per-channel difference still treated as unchanged (0-255,
+    /// default: 16)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pixel_tolerance: Option<u8>,
+
+    /// Ignore pixels that look like shifted anti-aliased edges (default: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_antialiasing: Option<bool>,
+
+    /// Changed pixels this close together are merged into one region
+    /// (pixels, default: 8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub merge_distance: Option<u32>,
+
+    /// Drop regions with fewer changed pixels than this (default: 4)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_region_pixels: Option<u32>,
+
+    /// Output image format of the diff image (default: webp)
+    #[serde(default)]
+    pub format: CaptureFormat,
+
+    /// Output mode: "inline" (base64), "file" (path), or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
+}
+
+impl CompareImagesParams {
+    /// Builds the core comparison options, using defaults for unset fields
+    fn to_compare_options(&self) -> CompareOptions {
+        let defaults = CompareOptions::default();
+        CompareOptions {
+            pixel_tolerance: self.pixel_tolerance.unwrap_or(defaults.pixel_tolerance),
+            ignore_antialiasing: self
+                .ignore_antialiasing
+                .unwrap_or(defaults.ignore_antialiasing),
+            merge_distance: self.merge_distance.unwrap_or(defaults.merge_distance),
+            min_region_pixels: self.min_region_pixels.unwrap_or(defaults.min_region_pixels),
         }
-        CaptureError::WindowClosed => McpError::invalid_params(format!("{}", error), None),
-        CaptureError::NotSupported { .. } => McpError::internal_error(format!("{}", error), None),
     }
 }
 
-/// Screenshot MCP server
-///
-/// Provides MCP tools for screenshot capture across different platforms.
+/// Parameters for the save_baseline tool
 ///
-/// # Tools
+/// The image comes from `capture`, or else the window selected by `target`
+/// and/or `query` is captured now.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveBaselineParams {
+    /// Baseline name (letters, digits, '-', '_' and '.')
+    pub name: String,
+    /// Stored capture: a capture's file:// URI, its file name, or a path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    /// Name of a configured target to capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Selector query of the window to capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+}
+
+/// Parameters for the check_baseline tool
 ///
-/// - `health_check`: Platform detection and server health status
-/// - `list_windows`: Enumerate all capturable windows
-/// - `capture_window`: Capture a screenshot of a specific window
-/// - `prime_wayland_consent`: (Wayland only) Prime consent for headless capture
-#[derive(Clone)]
-pub struct ScreenshotMcpServer {
-    /// Tool router for dispatching tool calls
-    /// Note: This field is used by the #[tool_router] macro
-    #[allow(dead_code)]
-    tool_router: ToolRouter<Self>,
-    /// Backend for screenshot capture with typed capability access
-    ///
-    /// Using `CompositeBackend` provides type-safe access to capabilities:
-    /// - `backend.enumerator` for window enumeration (not available on Wayland)
-    /// - `backend.resolver` for window resolution
-    /// - `backend.capture` for screenshot capture
-    /// - `backend.wayland_restore` for Wayland restore token workflow
-    backend: Arc<CompositeBackend>,
-    /// Temporary file manager for storing captured screenshots
-    temp_files: Arc<TempFileManager>,
+/// The image is chosen as for `save_baseline`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckBaselineParams {
+    /// Baseline name
+    pub name: String,
+    /// Stored capture: a capture's file:// URI, its file name, or a path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    /// Name of a configured target to capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Selector query of the window to capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Largest per-channel difference still treated as unchanged (0-255,
+    /// default: 16)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pixel_tolerance: Option<u8>,
+    /// Largest fraction of changed pixels that still passes (0.0-1.0,
+    /// default: 0.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_changed_ratio: Option<f64>,
+    /// Areas excluded from the comparison, e.g. clocks or cursors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_regions: Option<Vec<CaptureRegion>>,
+
+    /// Output image format of the diff image (default: webp)
+    #[serde(default)]
+    pub format: CaptureFormat,
+    /// Output mode for the diff image: "inline" (base64), "file" (path), or
+    /// "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
 }
 
-#[tool_router]
-impl ScreenshotMcpServer {
-    /// Creates a new ScreenshotMcpServer instance with specified backend and
-    /// temp file manager
-    ///
-    /// # Arguments
-    ///
-    /// * `backend` - The composite backend providing type-safe capability access
-    /// * `temp_files` - The temporary file manager for storing screenshots
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use std::sync::Arc;
-    ///
-    /// use screenshot_core::{
-    ///     capture::{composite_from_mock, MockBackend},
-    ///     util::temp_files::TempFileManager,
-    /// };
-    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
-    ///
-    /// let mock = Arc::new(MockBackend::new());
-    /// let backend = Arc::new(composite_from_mock(mock));
-    /// let temp_files = Arc::new(TempFileManager::new());
-    /// let server = ScreenshotMcpServer::new(backend, temp_files);
-    /// ```
-    pub fn new(backend: Arc<CompositeBackend>, temp_files: Arc<TempFileManager>) -> Self {
-        Self {
-            tool_router: Self::tool_router(),
-            backend,
-            temp_files,
+impl CheckBaselineParams {
+    /// Builds the core check options, using defaults for unset fields
+    fn to_check_options(&self) -> BaselineCheckOptions {
+        let defaults = BaselineCheckOptions::default();
+        BaselineCheckOptions {
+            compare: CompareOptions {
+                pixel_tolerance: self
+                    .pixel_tolerance
+                    .unwrap_or(defaults.compare.pixel_tolerance),
+                ..defaults.compare
+            },
+            max_changed_ratio: self.max_changed_ratio.unwrap_or(defaults.max_changed_ratio),
+            ignore_regions: self
+                .ignore_regions
+                .iter()
+                .flatten()
+                .map(|r| r.to_region())
+                .collect(),
         }
     }
+}
 
-    /// Creates a new ScreenshotMcpServer with MockBackend for testing
-    ///
-    /// This is a convenience constructor that initializes the server with a
-    /// MockBackend wrapped in a CompositeBackend and a fresh TempFileManager.
-    /// Useful for testing and development.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
-    ///
-    /// let server = ScreenshotMcpServer::new_with_mock();
-    /// ```
-    pub fn new_with_mock() -> Self {
-        let mock = Arc::new(MockBackend::new());
-        let backend = Arc::new(composite_from_mock(mock));
-        let temp_files = Arc::new(TempFileManager::new());
-        Self::new(backend, temp_files)
+/// Parameters for the approve_baseline tool
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproveBaselineParams {
+    /// Baseline name whose pending capture becomes the new baseline
+    pub name: String,
+}
+
+/// Parameters for the locate_image tool
+///
+/// The template comes from `templateData` or `template`. The image searched
+/// comes from `capture`, or else the window selected by `target` and/or
+/// `query` is captured now.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LocateImageParams {
+    /// Template to find as base64-encoded image data (PNG, JPEG or WebP)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template_data: Option<String>,
+    /// Template to find as a stored capture: a capture's file:// URI, its
+    /// file name, or a path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+
+    /// Stored capture to search: a capture's file:// URI, its file name, or
+    /// a path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<String>,
+    /// Name of a configured target to capture and search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Selector query of the window to capture and search
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Screen area shown by `capture`, in root window coordinates, so that
+    /// matches can be reported in screen coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_area: Option<CaptureRegion>,
+
+    /// Lowest match score reported (0.0-1.0, default: 0.8)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_confidence: Option<f32>,
+    /// Most matches returned, best first (1-100, default: 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_matches: Option<usize>,
+    /// Template scale factors to search, e.g. [1.0, 1.5, 2.0] for HiDPI
+    /// (default: [1.0])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scales: Option<Vec<f32>>,
+}
+
+impl LocateImageParams {
+    /// Builds the core locate options, using defaults for unset fields
+    fn to_locate_options(&self) -> LocateOptions {
+        let defaults = LocateOptions::default();
+        LocateOptions {
+            min_confidence: self.min_confidence.unwrap_or(defaults.min_confidence),
+            max_matches: self.max_matches.unwrap_or(defaults.max_matches),
+            scales: self.scales.clone().unwrap_or(defaults.scales),
+        }
     }
+}
 
-    /// Health check tool - verifies server status and detects platform/backend
-    ///
-    /// This tool:
-    /// - Detects the current operating system (Linux, Windows, macOS)
-    /// - Identifies the display backend (Wayland, X11, Windows, macOS, or None)
-    /// - Returns a status indicating if the server is operational
-    ///
-    /// # Returns
-    ///
-    /// A `CallToolResult` containing a JSON object with:
-    /// - `platform`: The OS name ("linux", "windows", "macos", "unknown")
-    /// - `backend`: The display backend ("wayland", "x11", "windows", "macos",
-    ///   "none")
-    /// - `ok`: Boolean indicating server health (always true unless an error
-    ///   occurs)
-    ///
-    /// # Examples
-    ///
-    /// Request:
-    /// ```json
-    /// {
-    ///   "method": "tools/call",
-    ///   "params": {
-    ///     "name": "health_check",
-    ///     "arguments": {}
-    ///   }
-    /// }
-    /// ```
-    ///
-    /// Response:
-    /// ```json
-    /// {
-    ///   "content": [{
-    ///     "type": "text",
-    ///     "text": "{\"platform\":\"linux\",\"backend\":\"wayland\",\"ok\":true}"
-    ///   }]
-    /// }
-    /// ```
-    #[tool(description = "Check server health and detect platform/backend")]
-    pub async fn health_check(&self) -> Result<CallToolResult, McpError> {
-        // Detect the current platform and backend
-        let platform_info = detect_platform();
+/// Parameters for the annotate_capture tool
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotateCaptureParams {
+    /// Capture to annotate: a capture's file:// URI, its file name, or a path
+    pub capture: String,
 
-        // Create health check response
-        let response = HealthCheckResponse::from_platform(platform_info);
+    /// Shapes to draw in order, e.g. `{"type": "rectangle", "x": 10, "y": 20,
+    /// "width": 80, "height": 30}`. Types: rectangle, ellipse, arrow
+    /// (`from`/`to` points), polyline (`points`) and text (`x`, `y`,
+    /// `text`, `size`). Optional `color` and `strokeWidth` on each.
+    pub annotations: Vec<Annotation>,
 
-        // Serialize to JSON
-        let json_str = serde_json::to_string(&response).map_err(|e| {
-            McpError::internal_error(
-                format!("Failed to serialize health check response: {}", e),
-                None,
-            )
-        })?;
+    /// Coordinate space of the shapes: "image" (default) or "screen"
+    #[serde(default)]
+    pub coordinates: CoordinateSpace,
 
-        // Return as text content wrapped in success result
-        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    /// Screen area shown by `capture`, in root window coordinates (required
+    /// for screen coordinates)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screen_area: Option<CaptureRegion>,
+
+    /// Output image format (default: webp)
+    #[serde(default)]
+    pub format: CaptureFormat,
+
+    /// Output mode: "inline" (base64), "file" (path), or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
+}
+
+/// Parameters for the mark_windows tool
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MarkWindowsParams {
+    /// Output image format (default: webp)
+    #[serde(default)]
+    pub format: CaptureFormat,
+
+    /// Output mode: "inline" (base64), "file" (path), or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
+
+    /// Longest side of the returned image in pixels (default: 1920)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_dimension: Option<u32>,
+}
+
+/// Parameters for the capture_overview tool
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureOverviewParams {
+    /// Selector query limiting which windows are shown (default: all)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+
+    /// Grid columns (1-16, default: about square)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub columns: Option<u32>,
+    /// Thumbnail width in pixels (64-1024, default: 320)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_width: Option<u32>,
+    /// Thumbnail height in pixels (48-1024, default: 200)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_height: Option<u32>,
+    /// Most windows shown (1-64, default: 24)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_windows: Option<u32>,
+    /// Time budget for all captures in milliseconds (100-60000, default:
+    /// 5000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+
+    /// Output image format (default: webp)
+    #[serde(default)]
+    pub format: CaptureFormat,
+
+    /// Output mode: "inline" (base64), "file" (path), or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
+}
+
+impl CaptureOverviewParams {
+    /// Builds the core overview options, using defaults for unset fields
+    fn to_overview_options(&self) -> OverviewOptions {
+        let defaults = OverviewOptions::default();
+        let mut options = OverviewOptions {
+            columns: self.columns,
+            thumbnail_width: self.thumbnail_width.unwrap_or(defaults.thumbnail_width),
+            thumbnail_height: self.thumbnail_height.unwrap_or(defaults.thumbnail_height),
+            max_windows: self.max_windows.unwrap_or(defaults.max_windows),
+            timeout_ms: self.timeout_ms.unwrap_or(defaults.timeout_ms),
+            ..defaults
+        };
+        options.validate();
+        options
     }
+}
 
-    /// Lists all capturable windows on the system
-    ///
-    /// Returns metadata about windows that can be captured, including their
-    /// IDs, titles, classes, and owning processes. The exact information
-    /// available depends on the backend capabilities.
-    ///
-    /// # Returns
-    ///
-    /// A `CallToolResult` containing a JSON array of window information
-    /// objects. Each object contains:
-    /// - `id`: Platform-specific window identifier
-    /// - `title`: Window title
-    /// - `class`: Window class name
-    /// - `owner`: Window owner/application name
-    /// - `pid`: Process ID of the window owner
-    /// - `backend`: Backend that detected this window
-    ///
-    /// # Examples
-    ///
-    /// Request:
-    /// ```json
-    /// {
-    ///   "method": "tools/call",
-    ///   "params": {
-    ///     "name": "list_windows",
-    ///     "arguments": {}
-    ///   }
-    /// }
-    /// ```
-    ///
-    /// Response:
-    /// ```json
-    /// {
-    ///   "content": [{
-    ///     "type": "text",
-    ///     "text": "[{\"id\":\"0x1\",\"title\":\"Firefox\",\"class\":\"Navigator\",\"owner\":\"firefox\",\"pid\":1234,\"backend\":\"x11\"}]"
-    ///   }]
-    /// }
-    /// ```
-    #[tool(description = "List all capturable windows on the system")]
-    pub async fn list_windows(&self) -> Result<CallToolResult, McpError> {
-        // Get window enumerator capability (not available on Wayland)
-        let enumerator = self.backend.enumerator.as_ref().ok_or_else(|| {
-            McpError::internal_error(
-                "Window enumeration is not available on this backend. \
-                 On Wayland, use prime_wayland_consent to select sources.",
-                None,
-            )
-        })?;
+/// Parameters for the record_window tool
+///
+/// The window is selected by `target` and/or `query`; the target's capture
+/// options are defaults for `maxDimension` and `redact`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordWindowParams {
+    /// Name of a configured target to record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Selector query of the window to record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
 
-        // Call backend to enumerate windows
-        let windows = enumerator
-            .list_windows()
-            .await
-            .map_err(convert_capture_error_to_mcp)?;
+    /// Frames captured per second (1-30, default: 5)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fps: Option<u32>,
+    /// Recording length in milliseconds (100-30000, default: 3000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    /// Most distinct frames kept before recording stops (1-300, default: 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_frames: Option<u32>,
+    /// Largest animation in bytes; frames are downscaled to fit (64 KiB-50
+    /// MiB, default: 5 MiB)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    /// Animation format: "webp" (default), "apng" or "gif"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<AnimationFormat>,
 
-        // Serialize to JSON
-        let json_str = serde_json::to_string(&windows).map_err(|e| {
-            McpError::internal_error(format!("Failed to serialize window list: {}", e), None)
-        })?;
+    /// Output mode: "inline" (base64), "file" (path), or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
 
-        // Return as text content
-        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    /// Longest side of each frame in pixels (default: 1280)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_dimension: Option<u32>,
+
+    /// Hide screen rectangles in every frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<CaptureRedact>,
+}
+
+impl RecordWindowParams {
+    /// Builds the core record options, using defaults for unset fields
+    fn to_record_options(&self) -> RecordOptions {
+        let defaults = RecordOptions::default();
+        let mut record = RecordOptions {
+            fps: self.fps.unwrap_or(defaults.fps),
+            duration_ms: self.duration_ms.unwrap_or(defaults.duration_ms),
+            max_frames: self.max_frames.unwrap_or(defaults.max_frames),
+            max_bytes: self.max_bytes.unwrap_or(defaults.max_bytes),
+            format: self.format.unwrap_or_default(),
+        };
+        record.validate();
+        record
     }
 }
 
-// Manual implementation for prime_wayland_consent tool (not using #[tool] macro
-// due to feature gate limitations with tool_router)
-impl ScreenshotMcpServer {
-    /// Prime Wayland consent - requests permission and stores restore tokens
-    ///
-    /// **Wayland-only tool** that opens the XDG Desktop Portal screencast
-    /// picker, requests user permission for screen capture, and stores the
-    /// resulting restore tokens for future headless captures.
-    ///
-    /// # Workflow
-    ///
-    /// 1. Opens portal picker dialog (user selects screen/window)
-    /// 2. User grants permission
-    /// 3. Restore tokens are stored securely in KeyStore
-    /// 4. Returns source IDs for use with `capture_window`
-    ///
-    /// # Parameters
+/// Parameters for the start_timelapse tool
+///
+/// The window is selected by `target` and/or `query`; the target's capture
+/// options are defaults for the image options. Set `maxFrames` and/or
+/// `maxDurationSecs` so that the run ends.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StartTimelapseParams {
+    /// Name of a configured target to capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Selector query of the window to capture
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Directory for the frames and manifest.json (default: a new
+    /// directory under the system temp directory)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub directory: Option<String>,
+
+    /// Seconds between captures (1-86400, default: 60)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<u64>,
+    /// Stop after this many frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_frames: Option<u32>,
+    /// Stop after this many seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_duration_secs: Option<u64>,
+    /// File name template with {target}, {index}, {timestamp} and {ext}
+    /// (default: "{target}-{index}-{timestamp}.{ext}")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    /// Keep only the newest this many frame files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<u32>,
+    /// Stop once the window has been missing this many seconds (default:
+    /// 600)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_missing_secs: Option<u64>,
+
+    /// Frame image format (default: webp, or the target's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<CaptureFormat>,
+    /// Quality 0-100 for JPEG/WebP (default: 80, or the target's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+    /// Longest side of each frame in pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_dimension: Option<u32>,
+    /// Hide screen rectangles in every frame
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<CaptureRedact>,
+}
+
+impl StartTimelapseParams {
+    /// Builds the core time-lapse options, using defaults for unset fields
+    fn to_timelapse_options(&self) -> TimelapseOptions {
+        let defaults = TimelapseOptions::default();
+        let mut options = TimelapseOptions {
+            interval_secs: self.interval_secs.unwrap_or(defaults.interval_secs),
+            max_frames: self.max_frames,
+            max_duration_secs: self.max_duration_secs,
+            filename: self.filename.clone().unwrap_or(defaults.filename),
+            keep: self.keep,
+            max_missing_secs: self.max_missing_secs.unwrap_or(defaults.max_missing_secs),
+        };
+        options.validate();
+        options
+    }
+}
+
+/// Parameters for the stop_timelapse tool
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StopTimelapseParams {
+    /// Time-lapse ID returned by start_timelapse
+    pub timelapse_id: String,
+}
+
+/// Parameters for the subscribe_window_events tool
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeWindowEventsParams {
+    /// Event kinds to forward: "created", "destroyed", "mapped", "unmapped",
+    /// "title_changed", "focus_changed". Default: all kinds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kinds: Option<Vec<WindowEventKind>>,
+}
+
+/// Parameters for the unsubscribe_window_events tool
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsubscribeWindowEventsParams {
+    /// Subscription ID returned by subscribe_window_events
+    pub subscription_id: String,
+}
+
+/// Parameters for the find_windows tool
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FindWindowsParams {
+    /// Selector query, e.g. "class:firefox !title:Private" (explicit fields
+    /// take precedence over the same field in the query)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Window title substring or regex pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_substring_or_regex: Option<String>,
+    /// Window class name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// Executable name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    /// Process ID of the window owner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Exact window ID (numeric IDs accept decimal or 0x hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+    /// Title patterns (regex or substring) that rule a window out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+    /// Alternative selectors; at least one must also match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<SelectorAlternative>>,
+    /// Treat accented and lookalike letters as distinct when matching titles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+}
+
+/// Parameters for the wait_for_window tool
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitForWindowParams {
+    // --- Window selection (at least one of these or titleRegex required) ---
+    /// Selector query, e.g. "class:firefox !title:Private" (explicit fields
+    /// take precedence over the same field in the query)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// Window title substring or regex pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_substring_or_regex: Option<String>,
+    /// Window class name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// Executable name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    /// Process ID of the window owner
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+    /// Exact window ID (numeric IDs accept decimal or 0x hex)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+    /// Title patterns (regex or substring) that rule a window out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+    /// Alternative selectors; at least one must also match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<SelectorAlternative>>,
+    /// Treat accented and lookalike letters as distinct when matching titles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict: Option<bool>,
+    /// When several windows match equally well: "first" (default), "error",
+    /// or {"index": n} to pick the n-th ranked match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ambiguity: Option<AmbiguityPolicy>,
+
+    // --- Conditions ---
+    /// Regex the window title must match (case-insensitive)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title_regex: Option<String>,
+    /// Require the window to be mapped and visible (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visible: Option<bool>,
+    /// Require the window size to be unchanged for this many milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stable_for_ms: Option<u64>,
+
+    // --- Timing ---
+    /// Deadline in milliseconds (default: 10000, max: 120000)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Interval between polls in milliseconds (default: 100, min: 10)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll_interval_ms: Option<u64>,
+
+    // --- Optional capture once ready ---
+    /// Capture the window as soon as the conditions are met (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture: Option<bool>,
+
+    /// Output image format when capturing (default: webp)
+    #[serde(default)]
+    pub format: CaptureFormat,
+
+    /// Image quality for JPEG/WebP (0-100, default: 80)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<u8>,
+
+    /// Scale factor when capturing (0.1-2.0, default: 1.0)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f32>,
+
+    /// Output mode when capturing: "inline", "file", or "both" (default)
+    #[serde(default)]
+    pub output: CaptureOutputMode,
+
+    /// Wait for the window content to stop changing before capturing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settle: Option<CaptureSettle>,
+
+    /// Overlay a coordinate grid labelled with screen coordinates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid: Option<CaptureGrid>,
+
+    /// Hide screen rectangles before the image is encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redact: Option<CaptureRedact>,
+}
+
+/// Parameters for the prime_wayland_consent tool
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PrimeWaylandConsentParams {
+    /// Type of content to capture: "monitor", "window", or "virtual"
+    /// Default: "monitor"
+    #[serde(default = "default_source_type")]
+    pub source_type: String,
+
+    /// Stable identifier for this source (e.g., "wayland-default",
+    /// "firefox-dev") Default: "wayland-default"
+    #[serde(default = "default_source_id")]
+    pub source_id: String,
+
+    /// Whether to include cursor in captures
+    /// Default: false
+    #[serde(default)]
+    pub include_cursor: bool,
+}
+
+fn default_source_type() -> String {
+    "monitor".to_string()
+}
+
+fn default_source_id() -> String {
+    "wayland-default".to_string()
+}
+
+/// Parses a source type string to SourceType enum
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_source_type(source_type_str: &str) -> Result<SourceType, String> {
+    match source_type_str.to_lowercase().as_str() {
+        "monitor" => Ok(SourceType::Monitor),
+        "window" => Ok(SourceType::Window),
+        "virtual" => Ok(SourceType::Virtual),
+        _ => Err(format!(
+            "Invalid source_type '{}'. Must be 'monitor', 'window', or 'virtual'",
+            source_type_str
+        )),
+    }
+}
+
+/// Converts a CaptureError to an MCP ErrorData
+///
+/// Maps screenshot capture errors to appropriate MCP error codes with
+/// user-friendly messages and remediation hints.
+fn convert_capture_error_to_mcp(error: CaptureError) -> McpError {
+    match &error {
+        CaptureError::WindowNotFound { .. } => McpError::invalid_params(format!("{}", error), None),
+        CaptureError::PortalUnavailable { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::PermissionDenied { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::EncodingFailed { .. } => McpError::internal_error(format!("{}", error), None),
+        CaptureError::CaptureTimeout { .. } => McpError::internal_error(format!("{}", error), None),
+        CaptureError::InvalidParameter { parameter, reason } => {
+            McpError::invalid_params(format!("Invalid parameter '{}': {}", parameter, reason), None)
+        }
+        CaptureError::BackendNotAvailable { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::IoError(_) => McpError::internal_error(format!("{}", error), None),
+        CaptureError::ImageError(_) => McpError::internal_error(format!("{}", error), None),
+        CaptureError::KeyringUnavailable { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::KeyringOperationFailed { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::TokenNotFound { .. } => McpError::invalid_params(format!("{}", error), None),
+        CaptureError::EncryptionFailed { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::UnsupportedWindowsVersion { .. } => {
+            McpError::internal_error(format!("{}", error), None)
+        }
+        CaptureError::WindowClosed => McpError::invalid_params(format!("{}", error), None),
+        CaptureError::NotSupported { .. } => McpError::internal_error(format!("{}", error), None),
+        // The hint carries the tied candidates so the client can pick one
+        CaptureError::AmbiguousWindow { .. } => McpError::invalid_params(
+            format!("{}", error),
+            serde_json::to_value(error.structured_hint()).ok(),
+        ),
+        CaptureError::PolicyDenied { .. } => McpError::invalid_request(
+            format!("{}", error),
+            serde_json::to_value(error.structured_hint()).ok(),
+        ),
+    }
+}
+
+/// Combines a selector `query` string with explicitly given selector fields
+///
+/// Explicit fields take precedence over the same field in the query, and
+/// `exclude` patterns from both are kept. Like any other field, an explicit
+/// `strict` overrides the query's. Query parse errors are reported as invalid
+/// params.
+fn merge_selector_query(
+    query: Option<&str>,
+    explicit: WindowSelector,
+) -> Result<WindowSelector, McpError> {
+    let Some(query) = query else {
+        return Ok(explicit);
+    };
+    let parsed = parse_selector_query(query).map_err(convert_capture_error_to_mcp)?;
+
+    Ok(explicit.with_fallback(parsed))
+}
+
+/// Screenshot MCP server
+///
+/// Provides MCP tools for screenshot capture across different platforms.
+///
+/// # Tools
+///
+/// - `health_check`: Platform detection and server health status
+/// - `list_windows`: Enumerate all capturable windows
+/// - `capture_window`: Capture a screenshot of a specific window
+/// - `capture_pointer_region`: Capture a region centred on the pointer
+/// - `find_windows`: Rank every window against a selector without capturing
+/// - `list_targets`: Show which configured targets resolve to a window
+/// - `compare_images`: Diff two stored captures with SSIM and changed regions
+/// - `locate_image`: Find a template image in a capture or window
+/// - `annotate_capture`: Draw boxes, arrows and labels on a stored capture
+/// - `mark_windows`: Capture the desktop with every visible window numbered
+/// - `record_window`: Record a window as an animated WebP, APNG or GIF
+/// - `capture_overview`: Capture every window as a thumbnail in one labelled
+///   grid image
+/// - `start_timelapse` / `stop_timelapse`: Capture a window into a directory
+///   at a fixed interval, in the background
+/// - `save_baseline` / `check_baseline` / `approve_baseline` /
+///   `list_baselines`: Golden-image visual regression checks
+/// - `wait_for_window`: Wait for a window to appear and settle, optionally
+///   capturing it
+/// - `subscribe_window_events` / `unsubscribe_window_events`: Window lifecycle
+///   notifications
+/// - `prime_wayland_consent`: (Wayland only) Prime consent for headless capture
+///
+/// Tools other than `health_check` and `unsubscribe_window_events` fail with
+/// a policy error if the privacy file's `[policy.tools]` table denies them.
+/// Tools, windows and full-display captures the policy marks `"ask"` (as it
+/// does full-display captures by default) need the user's consent. It is
+/// requested from the client via elicitation and remembered for the session
+/// (see [`crate::mcp_consent`]).
+///
+/// With an audit log attached, every capture and every call the policy
+/// refuses is recorded (see [`screenshot_core::util::audit`]).
+#[derive(Clone)]
+pub struct ScreenshotMcpServer {
+    /// Tool router for dispatching tool calls
+    /// Note: This field is used by the #[tool_router] macro
+    #[allow(dead_code)]
+    tool_router: ToolRouter<Self>,
+    /// Backend for screenshot capture with typed capability access
     ///
-    /// - `source_type` (optional): "monitor", "window", or "virtual" (default:
-    ///   "monitor")
-    /// - `source_id` (optional): Custom identifier for this source (default:
-    ///   "wayland-default")
-    /// - `include_cursor` (optional): Include cursor in captures (default:
-    ///   false)
+    /// Using `CompositeBackend` provides type-safe access to capabilities:
+    /// - `backend.enumerator` for window enumeration (not available on Wayland)
+    /// - `backend.resolver` for window resolution
+    /// - `backend.capture` for screenshot capture
+    /// - `backend.wayland_restore` for Wayland restore token workflow
+    backend: Arc<CompositeBackend>,
+    /// Temporary file manager for storing captured screenshots
+    temp_files: Arc<TempFileManager>,
+    /// Active window event subscriptions
+    subscriptions: Arc<EventSubscriptions>,
+    /// Named targets from the targets config file
+    targets: Arc<TargetRegistry>,
+    /// Last frame per window for delta captures
+    frames: Arc<FrameCache>,
+    /// Golden images for visual regression checks
+    baselines: Arc<BaselineStore>,
+    /// Consent decisions for captures the policy marks "ask"
+    consent: Arc<ConsentGate>,
+    /// Log of captures and policy refusals, if enabled
+    audit: Option<Arc<AuditLog>>,
+    /// Time-lapse runs started with `start_timelapse`
+    timelapses: Arc<TimelapseJobs>,
+}
+
+#[tool_router]
+impl ScreenshotMcpServer {
+    /// Creates a new ScreenshotMcpServer instance with specified backend and
+    /// temp file manager
     ///
-    /// # Returns
+    /// # Arguments
     ///
-    /// JSON object with:
-    /// - `status`: "success"
-    /// - `source_id`: Primary source ID for use with capture_window
-    /// - `all_source_ids`: Array of all source IDs (if multiple streams)
-    /// - `num_streams`: Number of streams captured
-    /// - `next_steps`: Instructions for using the stored tokens
+    /// * `backend` - The composite backend providing type-safe capability access
+    /// * `temp_files` - The temporary file manager for storing screenshots
     ///
-    /// # Errors
+    /// # Examples
     ///
-    /// - Requires Wayland backend (fails on X11/Windows/macOS)
-    /// - Portal service must be running (xdg-desktop-portal)
-    /// - User must grant permission (cancelling returns error)
-    /// - Times out after 30 seconds if no user response
+    /// ```
+    /// use std::sync::Arc;
     ///
-    /// # Examples
+    /// use screenshot_core::{
+    ///     capture::{composite_from_mock, MockBackend},
+    ///     util::temp_files::TempFileManager,
+    /// };
+    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
+    ///
+    /// let mock = Arc::new(MockBackend::new());
+    /// let backend = Arc::new(composite_from_mock(mock));
+    /// let temp_files = Arc::new(TempFileManager::new());
+    /// let server = ScreenshotMcpServer::new(backend, temp_files);
+    /// ```
+    pub fn new(backend: Arc<CompositeBackend>, temp_files: Arc<TempFileManager>) -> Self {
+        Self {
+            tool_router: Self::tool_router(),
+            backend,
+            temp_files,
+            subscriptions: Arc::new(EventSubscriptions::new()),
+            targets: Arc::new(TargetRegistry::new()),
+            frames: Arc::new(FrameCache::default()),
+            baselines: Arc::new(BaselineStore::open_default()),
+            consent: Arc::new(ConsentGate::new()),
+            audit: None,
+            timelapses: Arc::new(TimelapseJobs::new()),
+        }
+    }
+
+    /// Sets the named targets accepted by `capture_window` and listed by
+    /// `list_targets`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::TargetRegistry;
+    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
+    ///
+    /// let targets = TargetRegistry::from_toml_str("[targets]\nide = \"class:code\"").unwrap();
+    /// let server = ScreenshotMcpServer::new_with_mock().with_targets(targets);
+    /// ```
+    pub fn with_targets(mut self, targets: TargetRegistry) -> Self {
+        self.targets = Arc::new(targets);
+        self
+    }
+
+    /// Sets the memory cap for frames remembered by delta captures
+    ///
+    /// Defaults to
+    /// [`DEFAULT_FRAME_CACHE_BYTES`](screenshot_core::capture::constants::DEFAULT_FRAME_CACHE_BYTES).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
+    ///
+    /// let server = ScreenshotMcpServer::new_with_mock().with_frame_cache_limit(32 * 1024 * 1024);
+    /// ```
+    pub fn with_frame_cache_limit(mut self, max_bytes: usize) -> Self {
+        self.frames = Arc::new(FrameCache::new(max_bytes));
+        self
+    }
+
+    /// Sets the baseline store used by the baseline tools
+    ///
+    /// Defaults to [`BaselineStore::open_default`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::capture::BaselineStore;
+    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
+    ///
+    /// let server =
+    ///     ScreenshotMcpServer::new_with_mock().with_baselines(BaselineStore::new("/tmp/baselines"));
+    /// ```
+    pub fn with_baselines(mut self, baselines: BaselineStore) -> Self {
+        self.baselines = Arc::new(baselines);
+        self
+    }
+
+    /// Sets how the user is asked for consent to captures the policy marks
+    /// `"ask"`
+    ///
+    /// The client peer is used automatically once a client that supports
+    /// elicitation has initialized; without a prompter such captures are
+    /// refused.
+    pub fn with_consent_prompter(self, prompter: Arc<dyn ConsentPrompter>) -> Self {
+        self.consent.set_prompter(prompter);
+        self
+    }
+
+    /// Records every capture, and every call the capture policy refuses, in
+    /// `audit`
+    ///
+    /// Auditing is off unless a log is set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_core::util::audit::AuditLog;
+    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
+    ///
+    /// let server =
+    ///     ScreenshotMcpServer::new_with_mock().with_audit_log(AuditLog::new("/tmp/audit.jsonl"));
+    /// ```
+    pub fn with_audit_log(mut self, audit: AuditLog) -> Self {
+        self.audit = Some(Arc::new(audit));
+        self
+    }
+
+    /// Creates a new ScreenshotMcpServer with MockBackend for testing
+    ///
+    /// This is a convenience constructor that initializes the server with a
+    /// MockBackend wrapped in a CompositeBackend and a fresh TempFileManager.
+    /// Useful for testing and development.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use screenshot_mcp_server::mcp::ScreenshotMcpServer;
+    ///
+    /// let server = ScreenshotMcpServer::new_with_mock();
+    /// ```
+    pub fn new_with_mock() -> Self {
+        let mock = Arc::new(MockBackend::new());
+        let backend = Arc::new(composite_from_mock(mock));
+        let temp_files = Arc::new(TempFileManager::new());
+        Self::new(backend, temp_files)
+    }
+
+    /// Health check tool - verifies server status and detects platform/backend
+    ///
+    /// This tool:
+    /// - Detects the current operating system (Linux, Windows, macOS)
+    /// - Identifies the display backend (Wayland, X11, Windows, macOS, or None)
+    /// - Returns a status indicating if the server is operational
+    ///
+    /// # Returns
+    ///
+    /// A `CallToolResult` containing a JSON object with:
+    /// - `platform`: The OS name ("linux", "windows", "macos", "unknown")
+    /// - `backend`: The display backend ("wayland", "x11", "windows", "macos",
+    ///   "none")
+    /// - `ok`: Boolean indicating server health (always true unless an error
+    ///   occurs)
+    ///
+    /// # Examples
+    ///
+    /// Request:
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "health_check",
+    ///     "arguments": {}
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Response:
+    /// ```json
+    /// {
+    ///   "content": [{
+    ///     "type": "text",
+    ///     "text": "{\"platform\":\"linux\",\"backend\":\"wayland\",\"ok\":true}"
+    ///   }]
+    /// }
+    /// ```
+    #[tool(description = "Check server health and detect platform/backend")]
+    pub async fn health_check(&self) -> Result<CallToolResult, McpError> {
+        // Detect the current platform and backend
+        let platform_info = detect_platform();
+
+        // Create health check response
+        let response = HealthCheckResponse::from_platform(platform_info);
+
+        // Serialize to JSON
+        let json_str = serde_json::to_string(&response).map_err(|e| {
+            McpError::internal_error(
+                format!("Failed to serialize health check response: {}", e),
+                None,
+            )
+        })?;
+
+        // Return as text content wrapped in success result
+        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    }
+
+    /// Lists all capturable windows on the system
+    ///
+    /// Returns metadata about windows that can be captured, including their
+    /// IDs, titles, classes, and owning processes. The exact information
+    /// available depends on the backend capabilities.
+    ///
+    /// # Returns
+    ///
+    /// A `CallToolResult` containing a JSON array of window information
+    /// objects. Each object contains:
+    /// - `id`: Platform-specific window identifier
+    /// - `title`: Window title
+    /// - `class`: Window class name
+    /// - `owner`: Window owner/application name
+    /// - `pid`: Process ID of the window owner
+    /// - `backend`: Backend that detected this window
+    ///
+    /// # Examples
+    ///
+    /// Request:
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "list_windows",
+    ///     "arguments": {}
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Response:
+    /// ```json
+    /// {
+    ///   "content": [{
+    ///     "type": "text",
+    ///     "text": "[{\"id\":\"0x1\",\"title\":\"Firefox\",\"class\":\"Navigator\",\"owner\":\"firefox\",\"pid\":1234,\"backend\":\"x11\"}]"
+    ///   }]
+    /// }
+    /// ```
+    #[tool(description = "List all capturable windows on the system")]
+    pub async fn list_windows(&self) -> Result<CallToolResult, McpError> {
+        self.check_tool("list_windows").await?;
+        // Get window enumerator capability (not available on Wayland)
+        let enumerator = self.backend.enumerator.as_ref().ok_or_else(|| {
+            McpError::internal_error(
+                "Window enumeration is not available on this backend. \
+                 On Wayland, use prime_wayland_consent to select sources.",
+                None,
+            )
+        })?;
+
+        // Call backend to enumerate windows
+        let windows = enumerator
+            .list_windows()
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // Serialize to JSON
+        let json_str = serde_json::to_string(&windows).map_err(|e| {
+            McpError::internal_error(format!("Failed to serialize window list: {}", e), None)
+        })?;
+
+        // Return as text content
+        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    }
+}
+
+// Manual implementation for prime_wayland_consent tool (not using #[tool] macro
+// due to feature gate limitations with tool_router)
+impl ScreenshotMcpServer {
+    /// Prime Wayland consent - requests permission and stores restore tokens
+    ///
+    /// **Wayland-only tool** that opens the XDG Desktop Portal screencast
+    /// picker, requests user permission for screen capture, and stores the
+    /// resulting restore tokens for future headless captures.
+    ///
+    /// # Workflow
+    ///
+    /// 1. Opens portal picker dialog (user selects screen/window)
+    /// 2. User grants permission
+    /// 3. Restore tokens are stored securely in KeyStore
+    /// 4. Returns source IDs for use with `capture_window`
+    ///
+    /// # Parameters
+    ///
+    /// - `source_type` (optional): "monitor", "window", or "virtual" (default:
+    ///   "monitor")
+    /// - `source_id` (optional): Custom identifier for this source (default:
+    ///   "wayland-default")
+    /// - `include_cursor` (optional): Include cursor in captures (default:
+    ///   false)
+    ///
+    /// # Returns
+    ///
+    /// JSON object with:
+    /// - `status`: "success"
+    /// - `source_id`: Primary source ID for use with capture_window
+    /// - `all_source_ids`: Array of all source IDs (if multiple streams)
+    /// - `num_streams`: Number of streams captured
+    /// - `next_steps`: Instructions for using the stored tokens
+    ///
+    /// # Errors
+    ///
+    /// - Requires Wayland backend (fails on X11/Windows/macOS)
+    /// - Portal service must be running (xdg-desktop-portal)
+    /// - User must grant permission (cancelling returns error)
+    /// - Times out after 30 seconds if no user response
+    ///
+    /// # Examples
     ///
     /// Request (minimal):
     /// ```json
@@ -507,6 +1557,7 @@ impl ScreenshotMcpServer {
         &self,
         params: PrimeWaylandConsentParams,
     ) -> Result<CallToolResult, McpError> {
+        self.check_tool("prime_wayland_consent").await?;
         // Step 1: Check for Wayland restore capability via typed field (no downcast needed!)
         let wayland_capability = self.backend.wayland_restore.as_ref().ok_or_else(|| {
             McpError::internal_error(
@@ -517,910 +1568,4783 @@ impl ScreenshotMcpServer {
             )
         })?;
 
-        // Step 2: Parse source_type string to enum
-        let source_type = parse_source_type(&params.source_type)
-            .map_err(|e| McpError::invalid_params(e, None))?;
+        // Step 2: Parse source_type string to enum
+        let source_type = parse_source_type(&params.source_type)
+            .map_err(|e| McpError::invalid_params(e, None))?;
+
+        // Step 3: Call backend prime_consent via trait object
+        let result = wayland_capability
+            .prime_consent(source_type, &params.source_id, params.include_cursor)
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // Step 4: Build structured JSON response
+        let response_json = serde_json::json!({
+            "status": "success",
+            "source_id": result.primary_source_id,
+            "all_source_ids": result.all_source_ids,
+            "num_streams": result.num_streams,
+            "source_type": params.source_type,
+            "details": format!(
+                "Permission granted for {} {}. Restore token(s) stored securely.",
+                result.num_streams,
+                if result.num_streams == 1 { "source" } else { "sources" }
+            ),
+            "next_steps": if result.num_streams == 1 {
+                format!(
+                    "Call capture_window with exe='wayland:{}' to capture this source.",
+                    result.primary_source_id
+                )
+            } else {
+                format!(
+                    "Call capture_window with any of: {}",
+                    result.all_source_ids.iter()
+                        .map(|id| format!("'wayland:{}'", id))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+        });
+
+        // Step 5: Return success
+        let json_str = serde_json::to_string(&response_json).map_err(|e| {
+            McpError::internal_error(
+                format!("Failed to serialize prime_wayland_consent response: {}", e),
+                None,
+            )
+        })?;
+
+        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    }
+}
+
+// Manual implementation for window event subscription tools (not using #[tool]
+// macro because forwarding needs the client peer)
+impl ScreenshotMcpServer {
+    /// Subscribes to window lifecycle events
+    ///
+    /// Starts forwarding window events (created, destroyed, mapped, unmapped,
+    /// title changed, focus changed) to the client as `notifications/message`
+    /// logging notifications with logger `"window-events"`. This lets agents
+    /// wait for "the dialog appeared" without polling `list_windows`.
+    ///
+    /// # Parameters
+    ///
+    /// - `kinds` (optional): Event kinds to forward (default: all)
+    ///
+    /// # Returns
+    ///
+    /// JSON text with the `subscription_id` to pass to
+    /// `unsubscribe_window_events`.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "subscribe_window_events",
+    ///     "arguments": {"kinds": ["created", "title_changed"]}
+    ///   }
+    /// }
+    /// ```
+    pub async fn subscribe_window_events(
+        &self,
+        params: SubscribeWindowEventsParams,
+        peer: Peer<RoleServer>,
+    ) -> Result<CallToolResult, McpError> {
+        self.subscribe_window_events_with_sink(params, move |notification| {
+            let peer = peer.clone();
+            async move { peer.notify_logging_message(notification).await.is_ok() }
+        })
+        .await
+    }
+
+    /// Subscribes to window lifecycle events with a custom notification sink
+    ///
+    /// Same as [`subscribe_window_events`](Self::subscribe_window_events), but
+    /// delivers each notification to `sink` instead of an MCP peer. The sink
+    /// returns `false` to end the subscription.
+    pub async fn subscribe_window_events_with_sink<F, Fut>(
+        &self,
+        params: SubscribeWindowEventsParams,
+        sink: F,
+    ) -> Result<CallToolResult, McpError>
+    where
+        F: FnMut(LoggingMessageNotificationParam) -> Fut + Send + 'static,
+        Fut: std::future::Future<Output = bool> + Send,
+    {
+        self.check_tool("subscribe_window_events").await?;
+        let events = self.backend.events.as_ref().ok_or_else(|| {
+            McpError::internal_error(
+                "Window event subscription is not available on this backend.",
+                None,
+            )
+        })?;
+
+        let stream = events.subscribe().map_err(convert_capture_error_to_mcp)?;
+        let subscription_id = self.subscriptions.start(stream, params.kinds.clone(), sink);
+
+        let response = serde_json::json!({
+            "subscription_id": subscription_id,
+            "kinds": params.kinds,
+        });
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+
+    /// Unsubscribes from window lifecycle events
+    ///
+    /// # Parameters
+    ///
+    /// - `subscriptionId` (required): ID returned by `subscribe_window_events`
+    ///
+    /// # Errors
+    ///
+    /// Returns `invalid_params` if the subscription ID is unknown.
+    pub async fn unsubscribe_window_events(
+        &self,
+        params: UnsubscribeWindowEventsParams,
+    ) -> Result<CallToolResult, McpError> {
+        if !self.subscriptions.stop(&params.subscription_id) {
+            return Err(McpError::invalid_params(
+                format!("Unknown subscription ID '{}'", params.subscription_id),
+                None,
+            ));
+        }
+
+        let response = serde_json::json!({
+            "subscription_id": params.subscription_id,
+            "unsubscribed": true,
+        });
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+}
+
+// Manual implementation for find_windows tool (not using #[tool] macro due to
+// parameter handling)
+impl ScreenshotMcpServer {
+    /// Explains how a window selector resolves, without capturing
+    ///
+    /// Evaluates every window against the selector and returns all
+    /// candidates ranked the way `capture_window` would pick them. Use it to
+    /// debug a selector that captures the wrong window.
+    ///
+    /// # Parameters
+    ///
+    /// - `titleSubstringOrRegex` / `class` / `exe`: Window selector (at least
+    ///   one required)
+    ///
+    /// # Returns
+    ///
+    /// JSON text with:
+    /// - `selected`: Window ID that `capture_window` would capture (or null)
+    /// - `match_count`: Number of matching windows
+    /// - `tie_break_order`: Ranking rules in the order they apply
+    /// - `candidates`: Every window with `matched`, `rank`, `selected`,
+    ///   `title_strategy` (regex/substring/fuzzy), `fuzzy_score`, per-criterion
+    ///   results in `criteria`, and the `decided_by` rule that ranked it below
+    ///   the previous candidate
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "find_windows",
+    ///     "arguments": {"titleSubstringOrRegex": "Editor"}
+    ///   }
+    /// }
+    /// ```
+    pub async fn find_windows(
+        &self,
+        params: FindWindowsParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("find_windows").await?;
+        let selector = merge_selector_query(
+            params.query.as_deref(),
+            WindowSelector {
+                title_substring_or_regex: params.title_substring_or_regex,
+                class: params.class,
+                exe: params.exe,
+                pid: params.pid,
+                window_id: params.window_id,
+                exclude: params.exclude.unwrap_or_default(),
+                any_of: params
+                    .any_of
+                    .iter()
+                    .flatten()
+                    .map(SelectorAlternative::to_selector)
+                    .collect(),
+                strict: params.strict,
+                ..Default::default()
+            },
+        )?;
+
+        if selector.is_empty() {
+            return Err(McpError::invalid_params(
+                "At least one of 'query', 'title_substring_or_regex', 'class', 'exe', 'pid', \
+                 'windowId', or 'anyOf' must be specified",
+                None,
+            ));
+        }
+
+        let enumerator = self.backend.enumerator.as_ref().ok_or_else(|| {
+            McpError::internal_error("Window enumeration is not available on this backend.", None)
+        })?;
+
+        let windows = enumerator
+            .list_windows()
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+
+        let explanation = WindowMatcher::new().explain_selector(&selector, &windows);
+
+        let json_str = serde_json::to_string(&explanation).map_err(|e| {
+            McpError::internal_error(format!("Failed to serialize explanation: {}", e), None)
+        })?;
+        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    }
+}
+
+// Manual implementation for list_targets tool (not using #[tool] macro for
+// consistency with find_windows)
+impl ScreenshotMcpServer {
+    /// Lists the configured targets and the window each one resolves to
+    ///
+    /// Targets are read from the targets config file when the server
+    /// starts. Nothing is captured.
+    ///
+    /// # Returns
+    ///
+    /// JSON text with:
+    /// - `path`: The targets config file location
+    /// - `targets`: Each target's `name`, `query`, the `window` it resolves to
+    ///   (or null) and an `error` explaining why it does not resolve
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "list_targets",
+    ///     "arguments": {}
+    ///   }
+    /// }
+    /// ```
+    pub async fn list_targets(&self) -> Result<CallToolResult, McpError> {
+        self.check_tool("list_targets").await?;
+        let statuses = match self.backend.enumerator.as_ref() {
+            Some(enumerator) => {
+                let windows = enumerator
+                    .list_windows()
+                    .await
+                    .map_err(convert_capture_error_to_mcp)?;
+                self.targets.statuses(&windows)
+            }
+            None => {
+                let mut statuses = self.targets.statuses(&[]);
+                for status in &mut statuses {
+                    status.error =
+                        Some("Window enumeration is not available on this backend.".to_string());
+                }
+                statuses
+            }
+        };
+
+        let response = serde_json::json!({
+            "path": TargetRegistry::default_path(),
+            "targets": statuses,
+        });
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+}
+
+// Manual implementation for wait_for_window tool (not using #[tool] macro due to
+// parameter handling)
+impl ScreenshotMcpServer {
+    /// Waits for a window to appear and satisfy conditions
+    ///
+    /// Repeatedly resolves the selector until a matching window exists and
+    /// all requested conditions hold, or the deadline passes. With
+    /// `capture: true` the window is captured immediately once ready.
+    ///
+    /// # Parameters
+    ///
+    /// - `query`, `titleSubstringOrRegex`, `class`, `exe`, `pid`, `windowId`,
+    ///   `exclude`, `anyOf`, `strict`, `ambiguity`: Window selector, as for
+    ///   `capture_window`
+    /// - `titleRegex` (optional): Regex the title must match
+    /// - `visible` (optional): Require the window to be mapped and visible
+    /// - `stableForMs` (optional): Require an unchanged size for N ms
+    /// - `timeoutMs` / `pollIntervalMs` (optional): Timing (default 10000/100)
+    /// - `capture` (optional): Capture once ready (default: false)
+    /// - `format`, `quality`, `scale`, `output`, `settle`, `grid`, `redact`:
+    ///   As for `capture_window`
+    ///
+    /// # Returns
+    ///
+    /// Without `capture`, JSON text describing the window, its state and how
+    /// long the wait took. With `capture`, the capture result with that
+    /// description under the `wait` metadata key.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "wait_for_window",
+    ///     "arguments": {
+    ///       "class": "gnome-calculator",
+    ///       "visible": true,
+    ///       "stableForMs": 300,
+    ///       "capture": true
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn wait_for_window(
+        &self,
+        params: WaitForWindowParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("wait_for_window").await?;
+        if let Some(scale) = params.scale
+            && !(0.1..=2.0).contains(&scale)
+        {
+            return Err(McpError::invalid_params(
+                format!("Invalid scale '{}': must be between 0.1 and 2.0", scale),
+                None,
+            ));
+        }
+
+        let selector = merge_selector_query(
+            params.query.as_deref(),
+            WindowSelector {
+                title_substring_or_regex: params.title_substring_or_regex.clone(),
+                class: params.class.clone(),
+                exe: params.exe.clone(),
+                pid: params.pid,
+                window_id: params.window_id.clone(),
+                exclude: params.exclude.clone().unwrap_or_default(),
+                any_of: params
+                    .any_of
+                    .iter()
+                    .flatten()
+                    .map(SelectorAlternative::to_selector)
+                    .collect(),
+                strict: params.strict,
+                ambiguity: params.ambiguity.unwrap_or_default(),
+                ..Default::default()
+            },
+        )?;
+
+        let defaults = WaitForWindowOptions::default();
+        let wait_options = WaitForWindowOptions {
+            timeout_ms: params.timeout_ms.unwrap_or(defaults.timeout_ms),
+            poll_interval_ms: params.poll_interval_ms.unwrap_or(defaults.poll_interval_ms),
+            title_regex: params.title_regex.clone(),
+            require_visible: params.visible.unwrap_or(false),
+            stable_for_ms: params.stable_for_ms,
+        };
+
+        let mut audit = self.audit_entry("wait_for_window", Some(&selector));
+        let outcome = wait_for_window(&self.backend, &selector, &wait_options)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let wait_json = serde_json::json!({
+            "window_id": outcome.handle,
+            "window": outcome.info,
+            "state": outcome.state,
+            "waited_ms": outcome.waited_ms,
+            "polls": outcome.polls,
+        });
+
+        if !params.capture.unwrap_or(false) {
+            return Ok(CallToolResult::success(vec![Content::text(wait_json.to_string())]));
+        }
+
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            quality: params.quality.unwrap_or(80),
+            scale: params.scale.unwrap_or(1.0),
+            include_cursor: false,
+            region: None,
+            wayland_source: None,
+            max_dimension: Some(1920),
+            settle: params.settle.map(CaptureSettle::to_settle_options),
+            grid: params.grid.map(CaptureGrid::to_grid_options),
+            redact: params.redact.map(CaptureRedact::to_redact_options),
+        };
+        opts.validate();
+
+        audit.window = match &outcome.info {
+            Some(info) => Some(AuditWindow::from(info)),
+            None => self.audit_window(&outcome.handle).await,
+        };
+        self.check_window_consent(&outcome.handle)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        let capture = self
+            .backend
+            .capture_window_settled(outcome.handle, &opts)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let mut metadata = self.pointer_metadata().await;
+        metadata.insert("wait".to_string(), wait_json);
+        if let Some(report) = capture.settle {
+            metadata.insert("settle".to_string(), serde_json::json!(report));
+        }
+        if let Some(report) = capture.redaction {
+            metadata.insert("redaction".to_string(), serde_json::json!(report));
+            audit.redaction = Some(report);
+        }
+        self.finish_capture(&capture.image, &opts, params.output, metadata, Some(audit))
+    }
+}
+
+// Manual implementation for capture_window tool (not using #[tool] macro due to
+// parameter limitations)
+impl ScreenshotMcpServer {
+    /// Captures a screenshot of a specific window
+    ///
+    /// Finds a window matching the selector criteria and captures a screenshot.
+    ///
+    /// The response shape is controlled by the `output` parameter:
+    /// - `output: "both"` (default): inline base64 image + file link + metadata
+    /// - `output: "inline"`: inline base64 image + metadata (no temp file written)
+    /// - `output: "file"`: file link + metadata (no base64 generated)
+    ///
+    /// # Window Selection Parameters (at least one required)
+    ///
+    /// - `target` (optional): Name of a configured target; its selector and
+    ///   capture options are defaults for the fields below
+    /// - `titleSubstringOrRegex` (optional): Window title substring or regex pattern
+    /// - `class` (optional): Window class name
+    /// - `exe` (optional): Executable name
+    /// - `underPointer` (optional): Capture the topmost window under the pointer
+    /// - `atPoint` (optional): Capture the topmost window at `{x, y}`
+    ///
+    /// # Capture Options (all optional)
+    ///
+    /// - `format` (optional): Output format - "png", "jpeg", or "webp" (default: "webp")
+    /// - `quality` (optional): Quality 0-100 for JPEG/WebP (default: 80, ignored for PNG)
+    /// - `scale` (optional): Scale factor 0.1-2.0 (default: 1.0)
+    /// - `output` (optional): Output mode - "inline", "file", or "both" (default: "both")
+    /// - `includeCursor` (optional): Include cursor in capture (default: false)
+    /// - `region` (optional): Crop region `{x, y, width, height}` (default: full window)
+    /// - `settle` (optional): `{intervalMs, threshold, maxWaitMs}` - recapture
+    ///   until the content stops changing
+    /// - `delta` (optional): `{maxChangedRatio, pixelTolerance}` - return only
+    ///   the regions changed since the last delta capture of this window
+    /// - `grid` (optional): `{spacing, rulers}` - overlay a coordinate grid
+    ///   labelled with screen coordinates, drawn after scaling
+    /// - `redact` (optional): `{regions, mode}` - hide screen rectangles with
+    ///   "fill" (default), "pixelate" or "blur" before encoding
+    ///
+    /// Windows denied by the privacy file's `[redaction]` policy are hidden
+    /// as well; capturing a denied window returns a blank image. Windows the
+    /// `[policy]` table marks "ask" are captured only after the user agrees.
+    ///
+    /// # Returns
+    ///
+    /// A `CallToolResult` containing:
+    /// - Inline image content (base64-encoded) if `output` includes inline
+    /// - A file link with file:// URI if `output` includes file
+    /// - Metadata (dimensions, format, size, pointer position and cursor shape)
+    ///   always, plus `settle` (frames sampled, time waited) when settling
+    ///   and `redaction` (`regions`, `windows`, `full`) when anything was
+    ///   redacted
+    ///
+    /// With `delta`, the images are crops of the changed regions instead
+    /// (none if nothing changed), and the metadata's `delta.mode` is `full`,
+    /// `regions` or `unchanged`. `regions` lists each crop's offset within
+    /// the full frame.
+    ///
+    /// # Examples
+    ///
+    /// Minimal request (uses defaults):
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "capture_window",
+    ///     "arguments": {
+    ///       "titleSubstringOrRegex": "Firefox"
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// Full options:
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "capture_window",
+    ///     "arguments": {
+    ///       "titleSubstringOrRegex": "Firefox",
+    ///       "format": "png",
+    ///       "quality": 95,
+    ///       "scale": 0.5,
+    ///       "includeCursor": true,
+    ///       "region": {"x": 0, "y": 0, "width": 800, "height": 600}
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn capture_window(
+        &self,
+        params: CaptureWindowParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("capture_window").await?;
+        let pointer_target = params.under_pointer.unwrap_or(false) || params.at_point.is_some();
+
+        let target = params
+            .target
+            .as_deref()
+            .map(|name| self.targets.require(name))
+            .transpose()
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // Build WindowSelector from parameters
+        let selector = merge_selector_query(
+            params.query.as_deref(),
+            WindowSelector {
+                title_substring_or_regex: params.title_substring_or_regex.clone(),
+                class: params.class.clone(),
+                exe: params.exe.clone(),
+                pid: params.pid,
+                window_id: params.window_id.clone(),
+                exclude: params.exclude.clone().unwrap_or_default(),
+                any_of: params
+                    .any_of
+                    .iter()
+                    .flatten()
+                    .map(SelectorAlternative::to_selector)
+                    .collect(),
+                strict: params.strict,
+                sticky: params.sticky,
+                ambiguity: params.ambiguity.unwrap_or_default(),
+            },
+        )?;
+        let selector = match target {
+            Some(target) => selector.with_fallback(target.selector.clone()),
+            None => selector,
+        };
+
+        // Validate that at least one selector field is provided
+        if !pointer_target && selector.is_empty() {
+            return Err(McpError::invalid_params(
+                "At least one of 'target', 'query', 'title_substring_or_regex', 'class', 'exe', \
+                 'pid', 'windowId', 'anyOf', 'underPointer', or 'atPoint' must be specified",
+                None,
+            ));
+        }
+
+        // Validate scale if provided (0.1-2.0 range)
+        if let Some(scale) = params.scale {
+            if !(0.1..=2.0).contains(&scale) {
+                return Err(McpError::invalid_params(
+                    format!("Invalid scale '{}': must be between 0.1 and 2.0", scale),
+                    None,
+                ));
+            }
+        }
+
+        // Validate region if provided (non-zero dimensions)
+        if let Some(ref region) = params.region {
+            if region.width == 0 || region.height == 0 {
+                return Err(McpError::invalid_params(
+                    "Invalid region: width and height must be greater than 0",
+                    None,
+                ));
+            }
+        }
+
+        // Build capture options from params, falling back to the target's
+        // options and then the defaults
+        let defaults = target.map(|t| t.options.clone()).unwrap_or_default();
+        let mut opts = CaptureOptions {
+            format: params
+                .format
+                .map_or(defaults.format, CaptureFormat::to_image_format),
+            quality: params.quality.unwrap_or(defaults.quality),
+            scale: params.scale.unwrap_or(defaults.scale),
+            include_cursor: params.include_cursor.unwrap_or(defaults.include_cursor),
+            region: params.region.map(|r| r.to_region()).or(defaults.region),
+            wayland_source: None,
+            // Auto-scale 4K to ~1080p for efficient transfer
+            max_dimension: defaults.max_dimension,
+            settle: params
+                .settle
+                .map(CaptureSettle::to_settle_options)
+                .or(defaults.settle),
+            grid: params
+                .grid
+                .map(CaptureGrid::to_grid_options)
+                .or(defaults.grid),
+            redact: params
+                .redact
+                .map(CaptureRedact::to_redact_options)
+                .or(defaults.redact),
+        };
+        opts.validate();
+
+        let mut audit = self.audit_entry("capture_window", (!pointer_target).then_some(&selector));
+
+        // Resolve window target, either geometrically or by selector
+        let handle = if pointer_target {
+            let pointer = self.backend.pointer.as_ref().ok_or_else(|| {
+                McpError::internal_error(
+                    "Pointer-based window resolution is not available on this backend.",
+                    None,
+                )
+            })?;
+
+            match params.at_point {
+                Some(point) => pointer.window_at(point.x, point.y).await,
+                None => pointer.window_under_pointer().await,
+            }
+            .map_err(convert_capture_error_to_mcp)?
+        } else {
+            // Get window resolver capability
+            let resolver = self.backend.resolver.as_ref().ok_or_else(|| {
+                McpError::internal_error(
+                    "Window resolution is not available on this backend.",
+                    None,
+                )
+            })?;
+
+            resolver
+                .resolve(&selector)
+                .await
+                .map_err(|e| self.audit_error(&audit, e))?
+        };
+
+        // Crops only line up with frames of the same window and region
+        let delta_key = frame_key(&handle, opts.region.as_ref());
+
+        audit.window = self.audit_window(&handle).await;
+        self.check_window_consent(&handle)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        // Capture the window using ScreenCapture capability (repeatedly if settling)
+        let SettledCapture {
+            image: image_buffer,
+            settle,
+            redaction,
+        } = self
+            .backend
+            .capture_window_settled(handle, &opts)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let mut metadata = self.pointer_metadata().await;
+        if let Some(report) = settle {
+            metadata.insert("settle".to_string(), serde_json::json!(report));
+        }
+        if let Some(report) = redaction {
+            metadata.insert("redaction".to_string(), serde_json::json!(report));
+            audit.redaction = Some(report);
+        }
+        if let Some(target) = target {
+            metadata.insert("target".to_string(), serde_json::json!(target.name));
+        }
+
+        if let Some(delta) = params.delta {
+            let frame_delta =
+                self.frames
+                    .delta(&delta_key, &image_buffer, &delta.to_delta_options());
+            return self.finish_delta_capture(
+                &image_buffer,
+                frame_delta,
+                &opts,
+                params.output,
+                metadata,
+                audit,
+            );
+        }
+        self.finish_capture(&image_buffer, &opts, params.output, metadata, Some(audit))
+    }
+
+    /// Captures a region of the primary display centred on the pointer
+    ///
+    /// The region is clamped so that it stays within the display when the
+    /// pointer is near an edge, and shrunk if it is larger than the display.
+    /// Fails if the pointer is on another display. The pointer position,
+    /// cursor shape, and the effective region in screen coordinates are
+    /// included in the capture metadata.
+    ///
+    /// The display is captured in full before cropping, so this needs the
+    /// same consent as a full-display capture.
+    ///
+    /// # Parameters (all optional)
+    ///
+    /// - `width` / `height`: Region size in pixels (default: 400x300)
+    /// - `format`, `quality`, `scale`, `output`, `grid`, `redact`: As for
+    ///   `capture_window`
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "capture_pointer_region",
+    ///     "arguments": {"width": 320, "height": 240, "format": "png"}
+    ///   }
+    /// }
+    /// ```
+    pub async fn capture_pointer_region(
+        &self,
+        params: CapturePointerRegionParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("capture_pointer_region").await?;
+        if params.width == 0 || params.height == 0 {
+            return Err(McpError::invalid_params(
+                "Invalid region: width and height must be greater than 0",
+                None,
+            ));
+        }
+
+        if let Some(scale) = params.scale {
+            if !(0.1..=2.0).contains(&scale) {
+                return Err(McpError::invalid_params(
+                    format!("Invalid scale '{}': must be between 0.1 and 2.0", scale),
+                    None,
+                ));
+            }
+        }
+
+        let pointer_resolver = self.backend.pointer.as_ref().ok_or_else(|| {
+            McpError::internal_error("Pointer queries are not available on this backend.", None)
+        })?;
+
+        let pointer = pointer_resolver
+            .pointer_info()
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            quality: params.quality.unwrap_or(80),
+            scale: params.scale.unwrap_or(1.0),
+            include_cursor: false,
+            region: None,
+            wayland_source: None,
+            max_dimension: Some(1920),
+            settle: None,
+            grid: params.grid.map(CaptureGrid::to_grid_options),
+            redact: params.redact.map(CaptureRedact::to_redact_options),
+        };
+        opts.validate();
+
+        // Capture the full display unscaled so the crop uses screen
+        // coordinates; redaction happens before the crop
+        let display_opts = CaptureOptions {
+            scale: 1.0,
+            grid: None,
+            ..opts.clone()
+        };
+        let mut audit = self.audit_entry("capture_pointer_region", None);
+        // The region can be as large as the display, so it needs the same
+        // consent as a full-display capture
+        self.check_display_consent()
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        let SettledCapture {
+            image: display,
+            redaction,
+            ..
+        } = self
+            .backend
+            .capture_display_settled(None, &display_opts)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        // The pointer is in screen coordinates; the capture starts at the
+        // display's origin, assumed to be the screen origin if unknown
+        let (origin_x, origin_y) = self
+            .backend
+            .capture
+            .display_origin(None)
+            .await
+            .unwrap_or((0, 0));
+        let (local_x, local_y) = (
+            i64::from(pointer.x) - i64::from(origin_x),
+            i64::from(pointer.y) - i64::from(origin_y),
+        );
+        let (display_width, display_height) = display.dimensions();
+        if !(0..i64::from(display_width)).contains(&local_x)
+            || !(0..i64::from(display_height)).contains(&local_y)
+        {
+            return Err(McpError::invalid_request(
+                format!("Pointer at ({}, {}) is not on the captured display", pointer.x, pointer.y),
+                None,
+            ));
+        }
+
+        let crop = Region::centered_on(
+            local_x as i32,
+            local_y as i32,
+            params.width,
+            params.height,
+            display.dimensions(),
+        );
+        let mut image_buffer = display.crop(crop).map_err(convert_capture_error_to_mcp)?;
+        // Report and label the region in screen coordinates where possible
+        let region = match (u32::try_from(origin_x), u32::try_from(origin_y)) {
+            (Ok(x), Ok(y)) => Region::new(crop.x + x, crop.y + y, crop.width, crop.height),
+            _ => crop,
+        };
+        if (opts.scale - 1.0).abs() > f32::EPSILON {
+            image_buffer = image_buffer
+                .scale(opts.scale)
+                .map_err(convert_capture_error_to_mcp)?;
+        }
+        if let Some(ref grid) = opts.grid {
+            image_buffer = image_buffer
+                .overlay_grid(grid, region)
+                .map_err(convert_capture_error_to_mcp)?;
+        }
+
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("pointer".to_string(), serde_json::json!(pointer));
+        metadata.insert("region".to_string(), serde_json::json!(region));
+        if let Some(report) = redaction {
+            metadata.insert("redaction".to_string(), serde_json::json!(report));
+            audit.redaction = Some(report);
+        }
+        self.finish_capture(&image_buffer, &opts, params.output, metadata, Some(audit))
+    }
+
+    /// Fails with the policy's error if the capture policy denies `tool`,
+    /// asking the user first if it is marked "ask"
+    async fn check_tool(&self, tool: &str) -> Result<(), McpError> {
+        let permission = self.backend.policy.check_tool(tool);
+        let result = match permission {
+            Ok(Permission::Ask) => {
+                self.consent
+                    .require(
+                        ConsentSubject::Tool(tool.to_string()),
+                        &format!("use the '{}' tool", tool),
+                    )
+                    .await
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(e),
+        };
+        result.map_err(|e| self.audit_error(&self.audit_entry(tool, None), e))
+    }
+
+    /// Asks the user before capturing a window the policy marks "ask"
+    ///
+    /// Windows the backend cannot enumerate are left to the capture itself.
+    async fn check_window_consent(&self, handle: &WindowHandle) -> CaptureResult<()> {
+        let policy = &self.backend.policy;
+        let Some(enumerator) = self.backend.enumerator.as_ref() else {
+            return Ok(());
+        };
+        if policy.ask.is_empty() {
+            return Ok(());
+        }
+
+        let windows = enumerator.list_windows().await?;
+        let Some(window) = windows.iter().find(|w| &w.id == handle) else {
+            return Ok(());
+        };
+        if policy.window_permission(window) != Permission::Ask {
+            return Ok(());
+        }
+        self.consent
+            .require(
+                ConsentSubject::Window(handle.clone()),
+                &format!("capture window '{}' ({})", window.title, window.class),
+            )
+            .await
+    }
+
+    /// Fails if the policy denies full-display captures, asking the user
+    /// first if they are marked "ask"
+    async fn check_display_consent(&self) -> CaptureResult<()> {
+        if self.backend.policy.check_display()? == Permission::Ask {
+            self.consent
+                .require(ConsentSubject::Display, "capture the whole display")
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Starts an audit log entry for a call of `tool`
+    fn audit_entry(&self, tool: &str, selector: Option<&WindowSelector>) -> AuditEntry {
+        let mut entry = AuditEntry::new(tool, detect_platform().backend);
+        entry.selector = selector.cloned();
+        entry
+    }
+
+    /// Looks up the window `handle` for an audit log entry
+    ///
+    /// Only the handle is known on backends without window enumeration.
+    async fn audit_window(&self, handle: &WindowHandle) -> Option<AuditWindow> {
+        self.audit.as_ref()?;
+        let windows = match &self.backend.enumerator {
+            Some(enumerator) => enumerator.list_windows().await.unwrap_or_default(),
+            None => Vec::new(),
+        };
+        Some(match windows.iter().find(|w| &w.id == handle) {
+            Some(window) => AuditWindow::from(window),
+            None => AuditWindow {
+                id: handle.clone(),
+                title: String::new(),
+                class: String::new(),
+                owner: String::new(),
+                pid: 0,
+            },
+        })
+    }
+
+    /// Appends `entry` to the audit log, if there is one
+    ///
+    /// A failed write is logged rather than failing the tool call.
+    fn record_audit(&self, entry: &AuditEntry) {
+        if let Some(audit) = &self.audit {
+            if let Err(e) = audit.append(entry) {
+                tracing::warn!("Failed to write audit log {}: {}", audit.path().display(), e);
+            }
+        }
+    }
+
+    /// Converts a capture error, recording policy refusals in the audit log
+    fn audit_error(&self, entry: &AuditEntry, error: CaptureError) -> McpError {
+        self.audit_denial(entry, &error);
+        convert_capture_error_to_mcp(error)
+    }
+
+    /// Records `error` in the audit log if it is a policy refusal
+    fn audit_denial(&self, entry: &AuditEntry, error: &CaptureError) {
+        if matches!(error, CaptureError::PolicyDenied { .. }) {
+            let mut entry = entry.clone();
+            entry.denied = Some(error.to_string());
+            self.record_audit(&entry);
+        }
+    }
+
+    /// Returns capture metadata describing the current pointer state
+    ///
+    /// The `pointer` key is always present so agents can rely on it; it is
+    /// `null` when the backend cannot report the pointer (e.g. Wayland).
+    async fn pointer_metadata(&self) -> serde_json::Map<String, serde_json::Value> {
+        let pointer = match &self.backend.pointer {
+            Some(resolver) => resolver.pointer_info().await.ok(),
+            None => None,
+        };
+
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("pointer".to_string(), serde_json::json!(pointer));
+        metadata
+    }
+
+    /// Builds the tool result of a delta capture
+    ///
+    /// Full frames go through [`Self::finish_capture`]; otherwise each
+    /// changed region is cropped, encoded and written/inlined per `output`.
+    fn finish_delta_capture(
+        &self,
+        image_buffer: &ImageBuffer,
+        frame_delta: FrameDelta,
+        opts: &CaptureOptions,
+        output: CaptureOutputMode,
+        mut metadata: serde_json::Map<String, serde_json::Value>,
+        mut audit: AuditEntry,
+    ) -> Result<CallToolResult, McpError> {
+        let regions = match &frame_delta {
+            FrameDelta::Full { .. } => Vec::new(),
+            FrameDelta::Unchanged => Vec::new(),
+            FrameDelta::Regions { regions, .. } => regions.clone(),
+        };
+        let is_full = matches!(frame_delta, FrameDelta::Full { .. });
+        metadata.insert("delta".to_string(), serde_json::json!(frame_delta));
+        if is_full {
+            return self.finish_capture(image_buffer, opts, output, metadata, Some(audit));
+        }
+
+        let should_save_file = matches!(output, CaptureOutputMode::File | CaptureOutputMode::Both);
+        let should_inline_image =
+            matches!(output, CaptureOutputMode::Inline | CaptureOutputMode::Both);
+
+        let mut encoded = Vec::with_capacity(regions.len());
+        for region in regions {
+            let crop = image_buffer
+                .crop(region)
+                .map_err(convert_capture_error_to_mcp)?;
+            let data = encode_image(&crop, opts).map_err(convert_capture_error_to_mcp)?;
+            let file_path = if should_save_file {
+                let (path, _size) = self
+                    .temp_files
+                    .write_image(&data, opts.format)
+                    .map_err(convert_capture_error_to_mcp)?;
+                Some(path)
+            } else {
+                None
+            };
+            encoded.push((region, data, file_path));
+        }
+
+        let parts: Vec<EncodedRegion<'_>> = encoded
+            .iter()
+            .map(|(region, data, file_path)| EncodedRegion {
+                region: *region,
+                data,
+                file_path: file_path.as_deref(),
+            })
+            .collect();
+
+        let (width, height) = image_buffer.dimensions();
+        audit.width = Some(width);
+        audit.height = Some(height);
+        audit.output = encoded
+            .iter()
+            .filter_map(|(_, _, file_path)| file_path.as_ref())
+            .map(|path| path.display().to_string())
+            .collect();
+        if audit.output.is_empty() && !encoded.is_empty() {
+            audit.output.push("inline".to_string());
+        }
+        self.record_audit(&audit);
+
+        Ok(build_delta_result(
+            &parts,
+            opts,
+            image_buffer.dimensions(),
+            should_inline_image,
+            metadata,
+        ))
+    }
+
+    /// Encodes a captured image and builds the tool result
+    ///
+    /// Shared tail of the capture tools: encodes per `opts`, writes a temp
+    /// file and/or inlines the image per `output`, and attaches `metadata`.
+    /// New captures pass an `audit` entry, which is completed with the
+    /// image's dimensions and destination and recorded.
+    fn finish_capture(
+        &self,
+        image_buffer: &ImageBuffer,
+        opts: &CaptureOptions,
+        output: CaptureOutputMode,
+        metadata: serde_json::Map<String, serde_json::Value>,
+        audit: Option<AuditEntry>,
+    ) -> Result<CallToolResult, McpError> {
+        // Note: scaling is applied by the backend via opts.scale
+        // Get dimensions (already scaled if scale != 1.0)
+        let dimensions = image_buffer.dimensions();
+
+        // Encode the image
+        let encoded_data =
+            encode_image(image_buffer, opts).map_err(convert_capture_error_to_mcp)?;
+
+        // Determine output modes
+        let should_save_file = matches!(output, CaptureOutputMode::File | CaptureOutputMode::Both);
+        let should_inline_image =
+            matches!(output, CaptureOutputMode::Inline | CaptureOutputMode::Both);
+
+        // Write to temp file if requested
+        let file_path = if should_save_file {
+            let (path, _size) = self
+                .temp_files
+                .write_image(&encoded_data, opts.format)
+                .map_err(convert_capture_error_to_mcp)?;
+            Some(path)
+        } else {
+            None
+        };
+
+        if let Some(mut entry) = audit {
+            entry.width = Some(dimensions.0);
+            entry.height = Some(dimensions.1);
+            entry.output = vec![match &file_path {
+                Some(path) => path.display().to_string(),
+                None => "inline".to_string(),
+            }];
+            self.record_audit(&entry);
+        }
+
+        // Build result based on requested output mode
+        let result = build_capture_result_with_metadata(
+            &encoded_data,
+            file_path.as_deref(),
+            opts,
+            dimensions,
+            should_inline_image,
+            metadata,
+        );
+
+        Ok(result)
+    }
+}
+
+// Manual implementation for compare_images tool (not using #[tool] macro for
+// consistency with capture_window)
+impl ScreenshotMcpServer {
+    /// Compares two stored captures and highlights what changed
+    ///
+    /// Both images must have the same dimensions. The returned image is a
+    /// diff of `after` with changed pixels in red, anti-aliasing noise in
+    /// yellow and changed regions outlined in magenta.
+    ///
+    /// # Parameters
+    ///
+    /// - `before` / `after` (required): Capture `file://` URIs, capture file
+    ///   names, or paths
+    /// - `pixelTolerance`, `ignoreAntialiasing`, `mergeDistance`,
+    ///   `minRegionPixels`: Noise tolerances (see `CompareOptions`)
+    /// - `format`, `output`: As for `capture_window`, applied to the diff
+    ///   image
+    ///
+    /// # Returns
+    ///
+    /// The diff image, with a `comparison` metadata entry holding changed
+    /// pixel counts, mean and max difference, SSIM and changed `regions`.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "compare_images",
+    ///     "arguments": {
+    ///       "before": "file:///tmp/screenshot-mcp/screenshot-a.png",
+    ///       "after": "screenshot-b.png",
+    ///       "pixelTolerance": 24
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn compare_images(
+        &self,
+        params: CompareImagesParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("compare_images").await?;
+        let before = open_image_reference("before", &params.before)?;
+        let after = open_image_reference("after", &params.after)?;
+
+        let (comparison, diff) = compare_images(&before, &after, &params.to_compare_options())
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // Keep the diff at full size so region coordinates match its pixels
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            max_dimension: None,
+            ..Default::default()
+        };
+        opts.validate();
+
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("comparison".to_string(), serde_json::json!(comparison));
+        self.finish_capture(&diff, &opts, params.output, metadata, None)
+    }
+}
+
+// Manual implementation for baseline tools (not using #[tool] macro for
+// consistency with compare_images)
+impl ScreenshotMcpServer {
+    /// Saves an image as a named baseline
+    ///
+    /// # Parameters
+    ///
+    /// - `name` (required): Baseline name
+    /// - `capture`: A stored capture to save, or else
+    /// - `target` / `query`: The window to capture now (using the target's
+    ///   capture options)
+    ///
+    /// # Returns
+    ///
+    /// JSON text with the baseline `name` and the `path` it was saved to.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "save_baseline",
+    ///     "arguments": {"name": "settings-dialog", "target": "settings"}
+    ///   }
+    /// }
+    /// ```
+    pub async fn save_baseline(
+        &self,
+        params: SaveBaselineParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("save_baseline").await?;
+        let image = self
+            .baseline_image(
+                "save_baseline",
+                params.capture.as_deref(),
+                params.target.as_deref(),
+                params.query.as_deref(),
+            )
+            .await?;
+        let path = self
+            .baselines
+            .save(&params.name, &image)
+            .map_err(convert_capture_error_to_mcp)?;
+
+        let response = serde_json::json!({"name": params.name, "path": path});
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+
+    /// Checks an image against a named baseline
+    ///
+    /// A failed check keeps the image as the pending capture, which
+    /// `approve_baseline` turns into the new baseline. A check of a baseline
+    /// that does not exist yet reports `missing` and can be approved the
+    /// same way.
+    ///
+    /// # Parameters
+    ///
+    /// - `name` (required): Baseline name
+    /// - `capture` / `target` / `query`: As for `save_baseline`
+    /// - `pixelTolerance`, `maxChangedRatio`, `ignoreRegions`: Tolerances
+    /// - `format`, `output`: As for `capture_window`, applied to the diff
+    ///   image
+    ///
+    /// # Returns
+    ///
+    /// When the images differ, the diff image with the report under the
+    /// `baseline` metadata key. Otherwise JSON text with the report:
+    /// `status` (`passed`, `failed`, `missing` or `size_mismatch`),
+    /// `comparison` statistics, file paths and a `message`.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "check_baseline",
+    ///     "arguments": {
+    ///       "name": "settings-dialog",
+    ///       "target": "settings",
+    ///       "ignoreRegions": [{"x": 1180, "y": 0, "width": 100, "height": 32}]
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn check_baseline(
+        &self,
+        params: CheckBaselineParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("check_baseline").await?;
+        let image = self
+            .baseline_image(
+                "check_baseline",
+                params.capture.as_deref(),
+                params.target.as_deref(),
+                params.query.as_deref(),
+            )
+            .await?;
+        let report = self
+            .baselines
+            .check(&params.name, &image, &params.to_check_options())
+            .map_err(convert_capture_error_to_mcp)?;
+
+        let Some(diff_path) = report.diff_path.as_deref() else {
+            let response = serde_json::json!(report);
+            return Ok(CallToolResult::success(vec![Content::text(response.to_string())]));
+        };
+
+        let diff = ImageBuffer::open(diff_path).map_err(convert_capture_error_to_mcp)?;
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            max_dimension: None,
+            ..Default::default()
+        };
+        opts.validate();
+
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("baseline".to_string(), serde_json::json!(report));
+        self.finish_capture(&diff, &opts, params.output, metadata, None)
+    }
+
+    /// Approves the pending capture of a baseline as the new baseline
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "approve_baseline",
+    ///     "arguments": {"name": "settings-dialog"}
+    ///   }
+    /// }
+    /// ```
+    pub async fn approve_baseline(
+        &self,
+        params: ApproveBaselineParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("approve_baseline").await?;
+        let path = self
+            .baselines
+            .approve(&params.name)
+            .map_err(convert_capture_error_to_mcp)?;
+
+        let response = serde_json::json!({"name": params.name, "path": path});
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+
+    /// Lists the saved baselines
+    ///
+    /// # Returns
+    ///
+    /// JSON text with the baseline directory (`dir`) and the `baselines`
+    /// names.
+    pub async fn list_baselines(&self) -> Result<CallToolResult, McpError> {
+        self.check_tool("list_baselines").await?;
+        let names = self
+            .baselines
+            .list()
+            .map_err(convert_capture_error_to_mcp)?;
+        let response = serde_json::json!({"dir": self.baselines.dir(), "baselines": names});
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+
+    /// Gets the image for a baseline tool from a stored capture or a window
+    async fn baseline_image(
+        &self,
+        tool: &str,
+        capture: Option<&str>,
+        target: Option<&str>,
+        query: Option<&str>,
+    ) -> Result<ImageBuffer, McpError> {
+        if let Some(reference) = capture {
+            return open_image_reference("capture", reference);
+        }
+        let (image, _, _) = self.capture_selected_window(tool, target, query).await?;
+        Ok(image)
+    }
+
+    /// Captures the window selected by a target and/or selector query
+    ///
+    /// Uses the target's capture options, if any. Returns the image along
+    /// with the captured window and the options used. The capture is
+    /// audited as a call of `tool`; the image is not returned to the client.
+    async fn capture_selected_window(
+        &self,
+        tool: &str,
+        target: Option<&str>,
+        query: Option<&str>,
+    ) -> Result<(ImageBuffer, WindowHandle, CaptureOptions), McpError> {
+        let target = target
+            .map(|name| self.targets.require(name))
+            .transpose()
+            .map_err(convert_capture_error_to_mcp)?;
+        let selector = merge_selector_query(query, WindowSelector::default())?;
+        let selector = match target {
+            Some(target) => selector.with_fallback(target.selector.clone()),
+            None => selector,
+        };
+        if selector.is_empty() {
+            return Err(McpError::invalid_params(
+                "One of 'capture', 'target' or 'query' must be specified",
+                None,
+            ));
+        }
+
+        let mut opts = target.map(|t| t.options.clone()).unwrap_or_default();
+        opts.validate();
+
+        let resolver = self.backend.resolver.as_ref().ok_or_else(|| {
+            McpError::internal_error("Window resolution is not available on this backend.", None)
+        })?;
+        let mut audit = self.audit_entry(tool, Some(&selector));
+        let handle = resolver
+            .resolve(&selector)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        audit.window = self.audit_window(&handle).await;
+        self.check_window_consent(&handle)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        let capture = self
+            .backend
+            .capture_window_settled(handle.clone(), &opts)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let (width, height) = capture.image.dimensions();
+        audit.width = Some(width);
+        audit.height = Some(height);
+        audit.redaction = capture.redaction;
+        self.record_audit(&audit);
+        Ok((capture.image, handle, opts))
+    }
+}
+
+// Manual implementation for locate_image tool (not using #[tool] macro for
+// consistency with compare_images)
+impl ScreenshotMcpServer {
+    /// Finds a template image, e.g. a button, inside a capture or window
+    ///
+    /// Scores positions by normalized cross-correlation, optionally at
+    /// several template scales.
+    ///
+    /// # Parameters
+    ///
+    /// - `templateData` or `template` (one required): The image to find, as
+    ///   base64 data or a stored capture reference
+    /// - `capture`: A stored capture to search, or else
+    /// - `target` / `query`: The window to capture now and search
+    /// - `screenArea`: Where `capture` lies on screen, for screen coordinates
+    /// - `minConfidence`, `maxMatches`, `scales`: See `LocateOptions`
+    ///
+    /// # Returns
+    ///
+    /// JSON text with the searched image's `dimensions` and the `matches`,
+    /// best first. Each match has a `region` in image pixels, a `confidence`
+    /// and the template `scale`. Matches also carry a `screen_region` when the
+    /// image's position on screen is known: given by `screenArea`, or taken
+    /// from the window geometry on backends that report it.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "locate_image",
+    ///     "arguments": {
+    ///       "template": "file:///tmp/screenshot-mcp/save-button.png",
+    ///       "query": "class:gedit",
+    ///       "scales": [1.0, 2.0]
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn locate_image(
+        &self,
+        params: LocateImageParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("locate_image").await?;
+        let template = match (&params.template_data, &params.template) {
+            (Some(data), _) => decode_image_data("templateData", data)?,
+            (None, Some(reference)) => open_image_reference("template", reference)?,
+            (None, None) => {
+                return Err(McpError::invalid_params(
+                    "One of 'templateData' or 'template' must be specified",
+                    None,
+                ));
+            }
+        };
+
+        let (image, screen_area) = match &params.capture {
+            Some(reference) => (
+                open_image_reference("capture", reference)?,
+                params.screen_area.map(CaptureRegion::to_region),
+            ),
+            None => {
+                let (image, handle, opts) = self
+                    .capture_selected_window(
+                        "locate_image",
+                        params.target.as_deref(),
+                        params.query.as_deref(),
+                    )
+                    .await?;
+                let area = self.backend.window_screen_area(&handle, &opts).await;
+                (image, area)
+            }
+        };
+
+        let mut matches = locate_image(&image, &template, &params.to_locate_options())
+            .map_err(convert_capture_error_to_mcp)?;
+        if let Some(area) = screen_area {
+            for m in &mut matches {
+                m.map_to_screen(area, image.dimensions());
+            }
+        }
+
+        let response = serde_json::json!({
+            "dimensions": image.dimensions(),
+            "template_dimensions": template.dimensions(),
+            "matches": matches,
+        });
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+}
+
+/// Loads an image named by a compare_images or baseline tool parameter
+///
+/// Unreadable or undecodable images are the caller's mistake and are
+/// reported as invalid params naming the parameter.
+fn open_image_reference(parameter: &str, reference: &str) -> Result<ImageBuffer, McpError> {
+    let path = TempFileManager::resolve_reference(reference);
+    ImageBuffer::open(&path).map_err(|e| {
+        McpError::invalid_params(
+            format!("Cannot open {} image '{}': {}", parameter, reference, e),
+            None,
+        )
+    })
+}
+
+// Manual implementation for annotate_capture tool (not using #[tool] macro for
+// consistency with compare_images)
+impl ScreenshotMcpServer {
+    /// Draws rectangles, ellipses, arrows, polylines and text labels on a
+    /// stored capture
+    ///
+    /// The original capture is left as is; the annotated copy is a new
+    /// capture.
+    ///
+    /// # Parameters
+    ///
+    /// - `capture` (required): Capture `file://` URI, capture file name, or
+    ///   path
+    /// - `annotations` (required): Shapes to draw (see `Annotation`)
+    /// - `coordinates`: "image" (default) or "screen"; screen coordinates
+    ///   need `screenArea`, e.g. the window geometry the capture shows
+    /// - `format`, `output`: As for `capture_window`
+    ///
+    /// # Returns
+    ///
+    /// The annotated image, with an `annotated` metadata entry naming the
+    /// source capture and the number of shapes drawn.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "annotate_capture",
+    ///     "arguments": {
+    ///       "capture": "screenshot-a.png",
+    ///       "annotations": [
+    ///         {"type": "ellipse", "x": 410, "y": 220, "width": 120, "height": 48},
+    ///         {"type": "text", "x": 410, "y": 180, "text": "Overlaps the footer"}
+    ///       ]
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn annotate_capture(
+        &self,
+        params: AnnotateCaptureParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("annotate_capture").await?;
+        let image = open_image_reference("capture", &params.capture)?;
+
+        let mut annotations = params.annotations;
+        if params.coordinates == CoordinateSpace::Screen {
+            let area = params.screen_area.ok_or_else(|| {
+                McpError::invalid_params(
+                    "'screenArea' is required when coordinates is 'screen'",
+                    None,
+                )
+            })?;
+            for annotation in &mut annotations {
+                annotation.map_from_screen(area.to_region(), image.dimensions());
+            }
+        }
+
+        let annotated = image
+            .annotate(&annotations)
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // Keep full size so annotation coordinates stay meaningful
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            max_dimension: None,
+            ..Default::default()
+        };
+        opts.validate();
+
+        let mut metadata = serde_json::Map::new();
+        metadata.insert(
+            "annotated".to_string(),
+            serde_json::json!({"source": params.capture, "annotations": annotations.len()}),
+        );
+        self.finish_capture(&annotated, &opts, params.output, metadata, None)
+    }
+}
+
+// Manual implementation for mark_windows tool (not using #[tool] macro for
+// consistency with compare_images)
+impl ScreenshotMcpServer {
+    /// Captures the desktop with every visible window outlined and numbered
+    ///
+    /// Windows are numbered from 1, topmost first. Each outline follows the
+    /// largest part of the window not covered by windows above it, and
+    /// windows hidden behind others are left out. Look up a number in the
+    /// returned `labels` and pass the handle as `windowId` to
+    /// `capture_window`.
+    ///
+    /// This is a full-display capture, subject to the policy's `display`
+    /// permission, which asks the user unless set to `"allow"`.
+    ///
+    /// # Parameters
+    ///
+    /// - `format`, `output`: As for `capture_window`
+    /// - `maxDimension`: Longest side of the returned image (default: 1920)
+    ///
+    /// # Returns
+    ///
+    /// The marked display capture, with metadata entries `labels` (label to
+    /// window handle) and `marks` (title, class, geometry, visible rectangle
+    /// and visible fraction of each marked window, in screen coordinates).
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "mark_windows",
+    ///     "arguments": {"format": "png"}
+    ///   }
+    /// }
+    /// ```
+    pub async fn mark_windows(
+        &self,
+        params: MarkWindowsParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("mark_windows").await?;
+        let mut audit = self.audit_entry("mark_windows", None);
+        self.check_display_consent()
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        let enumerator = self.backend.enumerator.as_ref().ok_or_else(|| {
+            McpError::internal_error("Window enumeration is not available on this backend.", None)
+        })?;
+        let inspector = self.backend.inspector.as_ref().ok_or_else(|| {
+            McpError::internal_error("Window stacking is not available on this backend.", None)
+        })?;
+
+        let mut windows = enumerator
+            .list_windows()
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+        // Denied windows are blacked out; leave their titles out too
+        let denied = self.backend.policy.denied_windows(&windows);
+        windows.retain(|w| !denied.contains(&w.id));
+        let stacking = inspector
+            .stacking_order()
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // Bottom first; windows missing from the stacking list go underneath
+        let position = |id: &str| stacking.iter().position(|h| h == id);
+        let mut ordered: Vec<_> = windows.into_iter().map(|w| (position(&w.id), w)).collect();
+        ordered.sort_by_key(|(position, _)| *position);
+
+        let mut mapped = Vec::with_capacity(ordered.len());
+        for (_, window) in ordered {
+            match inspector.window_state(&window.id).await {
+                Ok(state) if state.is_visible() => mapped.push((window, state.geometry)),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Skipping window {} for marks: {}", window.id, e),
+            }
+        }
+
+        // Capture unscaled so marks line up with screen coordinates
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            max_dimension: None,
+            ..Default::default()
+        };
+        opts.validate();
+        let SettledCapture {
+            image: display,
+            redaction,
+            ..
+        } = self
+            .backend
+            .capture_display_settled(None, &opts)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let marks = compute_window_marks(&mapped, display.dimensions());
+        let marked = draw_window_marks(&display, &marks)
+            .map_err(convert_capture_error_to_mcp)?
+            .fit_to_max_dimension(params.max_dimension.or(Some(1920)))
+            .map_err(convert_capture_error_to_mcp)?;
+
+        let labels: serde_json::Map<String, serde_json::Value> = marks
+            .iter()
+            .map(|m| (m.label.to_string(), serde_json::json!(m.handle)))
+            .collect();
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("labels".to_string(), serde_json::Value::Object(labels));
+        metadata.insert("marks".to_string(), serde_json::json!(marks));
+        if let Some(report) = redaction {
+            metadata.insert("redaction".to_string(), serde_json::json!(report));
+            audit.redaction = Some(report);
+        }
+        self.finish_capture(&marked, &opts, params.output, metadata, Some(audit))
+    }
+}
+
+// Manual implementation for capture_overview tool (not using #[tool] macro
+// for consistency with mark_windows)
+impl ScreenshotMcpServer {
+    /// Captures every window, or those matching `query`, into one grid image
+    ///
+    /// Each window is captured, scaled into a thumbnail and placed in a grid
+    /// cell captioned with a number and the window title, filled row by row
+    /// in enumeration order (or best match first with `query`). Captures run
+    /// concurrently and share a `timeoutMs` budget; windows whose capture
+    /// fails, is refused, or runs out of time are reported in `skipped`
+    /// instead of failing the call. Thumbnails are redacted like
+    /// `capture_window` captures.
+    ///
+    /// # Parameters
+    ///
+    /// - `query` (optional): Only show windows matching this selector query
+    /// - `columns`, `thumbnailWidth`, `thumbnailHeight`, `maxWindows`,
+    ///   `timeoutMs`: See `OverviewOptions`
+    /// - `format`, `output`: As for `capture_window`
+    ///
+    /// # Returns
+    ///
+    /// The grid image, with metadata entries `labels` (caption number to
+    /// window handle) and `overview`: the `cells` (label, row, column,
+    /// thumbnail rectangle in the image and full `WindowInfo`), `skipped`
+    /// windows with reasons, the number `omitted` beyond `maxWindows`, and
+    /// whether the budget `timed_out`.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "capture_overview",
+    ///     "arguments": {"query": "class:firefox", "columns": 3, "timeoutMs": 3000}
+    ///   }
+    /// }
+    /// ```
+    pub async fn capture_overview(
+        &self,
+        params: CaptureOverviewParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("capture_overview").await?;
+        let selector = params
+            .query
+            .as_deref()
+            .map(|query| merge_selector_query(Some(query), WindowSelector::default()))
+            .transpose()?;
+        let audit = self.audit_entry("capture_overview", selector.as_ref());
+        let enumerator = self.backend.enumerator.as_ref().ok_or_else(|| {
+            McpError::internal_error("Window enumeration is not available on this backend.", None)
+        })?;
+
+        let mut windows = enumerator
+            .list_windows()
+            .await
+            .map_err(convert_capture_error_to_mcp)?;
+        // Denied windows cannot be captured; leave their titles out too
+        let denied = self.backend.policy.denied_windows(&windows);
+        windows.retain(|w| !denied.contains(&w.id));
+        if let Some(selector) = &selector {
+            let matches = WindowMatcher::new().all_matches(selector, &windows);
+            windows = matches
+                .iter()
+                .filter_map(|id| windows.iter().find(|w| &w.id == id).cloned())
+                .collect();
+        }
+        if windows.is_empty() {
+            return Err(McpError::invalid_params("No windows to show", None));
+        }
+
+        // Ask before capturing windows the policy marks "ask"
+        let options = params.to_overview_options();
+        let mut candidates = Vec::with_capacity(windows.len());
+        let mut declined = Vec::new();
+        for window in windows {
+            if candidates.len() < options.max_windows as usize
+                && self.backend.policy.window_permission(&window) == Permission::Ask
+            {
+                if let Err(e) = self.check_window_consent(&window.id).await {
+                    declined.push(OverviewSkip {
+                        reason: e.to_string(),
+                        window,
+                    });
+                    continue;
+                }
+            }
+            candidates.push(window);
+        }
+
+        let mut opts = CaptureOptions {
+            format: params.format.to_image_format(),
+            max_dimension: None,
+            ..Default::default()
+        };
+        opts.validate();
+        let (image, mut report) = self
+            .backend
+            .capture_overview(candidates, &opts, &options)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        report.skipped.extend(declined);
+
+        let labels: serde_json::Map<String, serde_json::Value> = report
+            .cells
+            .iter()
+            .map(|cell| (cell.label.to_string(), serde_json::json!(cell.window.id)))
+            .collect();
+        let mut metadata = serde_json::Map::new();
+        metadata.insert("labels".to_string(), serde_json::Value::Object(labels));
+        metadata.insert("overview".to_string(), serde_json::json!(report));
+        self.finish_capture(&image, &opts, params.output, metadata, Some(audit))
+    }
+}
+
+// Manual implementation for record_window tool (not using #[tool] macro for
+// consistency with compare_images)
+impl ScreenshotMcpServer {
+    /// Records a window as a short looping animation
+    ///
+    /// Captures the window `fps` times a second for `durationMs`. Identical
+    /// consecutive frames are merged into one longer frame, and recording
+    /// stops early once `maxFrames` distinct frames were kept. If the
+    /// animation is larger than `maxBytes`, its frames are downscaled until
+    /// it fits. Every frame is redacted like a `capture_window` capture.
+    ///
+    /// # Parameters
+    ///
+    /// - `target` / `query` (one required): The window to record
+    /// - `fps`, `durationMs`, `maxFrames`, `maxBytes`: See `RecordOptions`
+    /// - `format` (optional): "webp" (default), "apng" or "gif"
+    /// - `output`, `redact`: As for `capture_window`
+    /// - `maxDimension`: Longest side of each frame (default: 1280)
+    ///
+    /// # Returns
+    ///
+    /// The animation, with a `recording` metadata entry holding the frames
+    /// captured and kept, playback length, whether recording was cut short,
+    /// frame size and downscale factor, plus `redaction` when anything was
+    /// redacted.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "record_window",
+    ///     "arguments": {"query": "class:firefox", "fps": 10, "durationMs": 5000, "format": "gif"}
+    ///   }
+    /// }
+    /// ```
+    pub async fn record_window(
+        &self,
+        params: RecordWindowParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("record_window").await?;
+        let target = params
+            .target
+            .as_deref()
+            .map(|name| self.targets.require(name))
+            .transpose()
+            .map_err(convert_capture_error_to_mcp)?;
+        let selector = merge_selector_query(params.query.as_deref(), WindowSelector::default())?;
+        let selector = match target {
+            Some(target) => selector.with_fallback(target.selector.clone()),
+            None => selector,
+        };
+        if selector.is_empty() {
+            return Err(McpError::invalid_params(
+                "One of 'target' or 'query' must be specified",
+                None,
+            ));
+        }
+
+        let defaults = target.map(|t| t.options.clone()).unwrap_or_default();
+        let mut opts = CaptureOptions {
+            max_dimension: params.max_dimension.or(Some(1280)),
+            settle: None,
+            grid: None,
+            redact: params
+                .redact
+                .clone()
+                .map(CaptureRedact::to_redact_options)
+                .or(defaults.redact.clone()),
+            ..defaults
+        };
+        opts.validate();
+        let record = params.to_record_options();
+
+        let resolver = self.backend.resolver.as_ref().ok_or_else(|| {
+            McpError::internal_error("Window resolution is not available on this backend.", None)
+        })?;
+        let mut audit = self.audit_entry("record_window", Some(&selector));
+        let handle = resolver
+            .resolve(&selector)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        audit.window = self.audit_window(&handle).await;
+        self.check_window_consent(&handle)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let recording = self
+            .backend
+            .record_window(handle, &opts, &record)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        // Encoding many frames, perhaps several times over, is CPU-bound
+        let redaction = recording.redaction.clone();
+        let (data, report) =
+            tokio::task::spawn_blocking(move || encode_recording(&recording, &record))
+                .await
+                .map_err(|e| {
+                    McpError::internal_error(format!("Encoding task failed: {}", e), None)
+                })?
+                .map_err(convert_capture_error_to_mcp)?;
+
+        let file_path =
+            if matches!(params.output, CaptureOutputMode::File | CaptureOutputMode::Both) {
+                let (path, _size) = self
+                    .temp_files
+                    .write_animation(&data, record.format)
+                    .map_err(convert_capture_error_to_mcp)?;
+                Some(path)
+            } else {
+                None
+            };
+
+        let mut metadata = serde_json::Map::new();
+        if let Some(report) = redaction {
+            metadata.insert("redaction".to_string(), serde_json::json!(report));
+            audit.redaction = Some(report);
+        }
+        if let Some(target) = target {
+            metadata.insert("target".to_string(), serde_json::json!(target.name));
+        }
+
+        audit.width = Some(report.width);
+        audit.height = Some(report.height);
+        audit.output = vec![match &file_path {
+            Some(path) => path.display().to_string(),
+            None => "inline".to_string(),
+        }];
+        self.record_audit(&audit);
+
+        Ok(build_animation_result(
+            &data,
+            file_path.as_deref(),
+            &report,
+            matches!(params.output, CaptureOutputMode::Inline | CaptureOutputMode::Both),
+            metadata,
+        ))
+    }
+}
+
+// Manual implementation for time-lapse tools (not using #[tool] macro for
+// consistency with subscribe_window_events)
+impl ScreenshotMcpServer {
+    /// Starts capturing a window into a directory at a fixed interval
+    ///
+    /// The run continues in the background and ends on its frame or time
+    /// limit, after the window has been missing for `maxMissingSecs`, or
+    /// when `stop_timelapse` is called. Ticks where the window cannot be
+    /// found are recorded as missing; the window is looked up again at
+    /// every tick. Frames are redacted like `capture_window` captures.
+    ///
+    /// `manifest.json` in the directory lists the ticks (file name, window,
+    /// size, or why it was missed) and is rewritten after each one. With
+    /// `keep`, ticks older than the oldest kept frame are only counted.
+    ///
+    /// # Parameters
+    ///
+    /// - `target` / `query` (one required): The window to capture; it must
+    ///   exist when the run starts
+    /// - `directory`: Where to write (default: a new temp directory)
+    /// - `intervalSecs`, `maxFrames`, `maxDurationSecs`, `filename`, `keep`,
+    ///   `maxMissingSecs`: See `TimelapseOptions`
+    /// - `format`, `quality`, `maxDimension`, `redact`: Frame image options
+    ///
+    /// # Returns
+    ///
+    /// JSON text with the `timelapse_id`, `directory` and `manifest` path.
+    ///
+    /// # Examples
+    ///
+    /// ```json
+    /// {
+    ///   "method": "tools/call",
+    ///   "params": {
+    ///     "name": "start_timelapse",
+    ///     "arguments": {
+    ///       "query": "class:blender",
+    ///       "intervalSecs": 30,
+    ///       "maxDurationSecs": 7200,
+    ///       "keep": 100,
+    ///       "format": "jpeg"
+    ///     }
+    ///   }
+    /// }
+    /// ```
+    pub async fn start_timelapse(
+        &self,
+        params: StartTimelapseParams,
+    ) -> Result<CallToolResult, McpError> {
+        self.check_tool("start_timelapse").await?;
+        let target = params
+            .target
+            .as_deref()
+            .map(|name| self.targets.require(name))
+            .transpose()
+            .map_err(convert_capture_error_to_mcp)?;
+        let selector = merge_selector_query(params.query.as_deref(), WindowSelector::default())?;
+        let selector = match target {
+            Some(target) => selector.with_fallback(target.selector.clone()),
+            None => selector,
+        };
+        if selector.is_empty() {
+            return Err(McpError::invalid_params(
+                "One of 'target' or 'query' must be specified",
+                None,
+            ));
+        }
+
+        let defaults = target.map(|t| t.options.clone()).unwrap_or_default();
+        let mut opts = CaptureOptions {
+            format: params
+                .format
+                .map_or(defaults.format, CaptureFormat::to_image_format),
+            quality: params.quality.unwrap_or(defaults.quality),
+            max_dimension: params.max_dimension.or(defaults.max_dimension),
+            redact: params
+                .redact
+                .clone()
+                .map(CaptureRedact::to_redact_options)
+                .or(defaults.redact.clone()),
+            ..defaults
+        };
+        opts.validate();
+        let options = params.to_timelapse_options();
+        let label = match (target, params.query.as_deref()) {
+            (Some(target), _) => target.name.clone(),
+            (None, Some(query)) => query.to_string(),
+            (None, None) => "window".to_string(),
+        };
+        check_timelapse_options(&options, &label, opts.format)
+            .map_err(convert_capture_error_to_mcp)?;
+
+        // The window must exist, and be allowed, when the run starts
+        let resolver = self.backend.resolver.clone().ok_or_else(|| {
+            McpError::internal_error("Window resolution is not available on this backend.", None)
+        })?;
+        let mut audit = self.audit_entry("start_timelapse", Some(&selector));
+        let handle = resolver
+            .resolve(&selector)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+        audit.window = self.audit_window(&handle).await;
+        self.check_window_consent(&handle)
+            .await
+            .map_err(|e| self.audit_error(&audit, e))?;
+
+        let id = self.timelapses.reserve_id();
+        let dir = match params.directory.as_deref() {
+            Some(directory) => PathBuf::from(directory),
+            None => std::env::temp_dir().join("screenshot-mcp").join(format!(
+                "{}-{}",
+                id,
+                chrono::Utc::now().format("%Y%m%dT%H%M%SZ")
+            )),
+        };
+
+        // The run gets an empty registry of its own: holding ours would keep
+        // it alive, so dropping the server could never abort the run
+        let server = Self {
+            timelapses: Arc::new(TimelapseJobs::new()),
+            ..self.clone()
+        };
+        let run_dir = dir.clone();
+        self.timelapses
+            .start(id.clone(), &dir, move |cancel| async move {
+                let capture = || server.timelapse_frame(&*resolver, &selector, &opts, &run_dir);
+                run_timelapse(&run_dir, &label, &options, &opts, capture, cancel).await
+            });
+
+        let response = serde_json::json!({
+            "timelapse_id": id,
+            "directory": dir.display().to_string(),
+            "manifest": dir.join(TIMELAPSE_MANIFEST).display().to_string(),
+        });
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+
+    /// Captures one time-lapse frame, looking the window up again
+    async fn timelapse_frame(
+        &self,
+        resolver: &dyn WindowResolver,
+        selector: &WindowSelector,
+        opts: &CaptureOptions,
+        dir: &Path,
+    ) -> CaptureResult<(WindowHandle, ImageBuffer)> {
+        let handle = resolver.resolve(selector).await?;
+        let mut audit = self.audit_entry("start_timelapse", Some(selector));
+        audit.window = self.audit_window(&handle).await;
+        self.check_window_consent(&handle)
+            .await
+            .inspect_err(|e| self.audit_denial(&audit, e))?;
+        let capture = self
+            .backend
+            .capture_window_settled(handle.clone(), opts)
+            .await
+            .inspect_err(|e| self.audit_denial(&audit, e))?;
+        let image = capture.image.fit_to_max_dimension(opts.max_dimension)?;
+
+        let (width, height) = image.dimensions();
+        audit.width = Some(width);
+        audit.height = Some(height);
+        audit.output = vec![dir.display().to_string()];
+        audit.redaction = capture.redaction;
+        self.record_audit(&audit);
+        Ok((handle, image))
+    }
+
+    /// Stops a time-lapse and returns its final manifest
+    ///
+    /// Runs that already ended on their own are reported the same way.
+    ///
+    /// # Parameters
+    ///
+    /// - `timelapseId` (required): ID returned by `start_timelapse`
+    ///
+    /// # Returns
+    ///
+    /// JSON text with the `directory`, why the run `stopped`, the number of
+    /// frames `captured` and `missed`, and the `manifest` path.
+    ///
+    /// # Errors
+    ///
+    /// Returns `invalid_params` if the time-lapse ID is unknown.
+    pub async fn stop_timelapse(
+        &self,
+        params: StopTimelapseParams,
+    ) -> Result<CallToolResult, McpError> {
+        let Some((dir, result)) = self.timelapses.stop(&params.timelapse_id).await else {
+            return Err(McpError::invalid_params(
+                format!("Unknown time-lapse ID '{}'", params.timelapse_id),
+                None,
+            ));
+        };
+        let manifest = result.map_err(convert_capture_error_to_mcp)?;
+
+        let response = serde_json::json!({
+            "timelapse_id": params.timelapse_id,
+            "directory": dir.display().to_string(),
+            "stopped": manifest.stopped,
+            "error": manifest.error,
+            "captured": manifest.captured,
+            "missed": manifest.missed,
+            "manifest": dir.join(TIMELAPSE_MANIFEST).display().to_string(),
+        });
+        Ok(CallToolResult::success(vec![Content::text(response.to_string())]))
+    }
+}
+
+/// Decodes base64 image data named by a tool parameter
+fn decode_image_data(parameter: &str, data: &str) -> Result<ImageBuffer, McpError> {
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    let bytes = STANDARD.decode(data.trim()).map_err(|e| {
+        McpError::invalid_params(format!("'{}' is not valid base64: {}", parameter, e), None)
+    })?;
+    ImageBuffer::from_bytes(&bytes).map_err(|e| {
+        McpError::invalid_params(format!("Cannot decode {} image: {}", parameter, e), None)
+    })
+}
+
+impl Default for ScreenshotMcpServer {
+    fn default() -> Self {
+        Self::new_with_mock()
+    }
+}
+
+// Implement ServerHandler to make ScreenshotMcpServer a valid Service
+impl ServerHandler for ScreenshotMcpServer {
+    fn get_info(&self) -> ServerInfo {
+        ServerInfo::default()
+    }
+
+    fn on_initialized(
+        &self,
+        context: NotificationContext<RoleServer>,
+    ) -> impl std::future::Future<Output = ()> + Send + '_ {
+        // Consent prompts go to the client when it can show them
+        if context.peer.supports_elicitation() {
+            self.consent.set_prompter(Arc::new(context.peer));
+        }
+        tracing::info!("client initialized");
+        std::future::ready(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp_consent::tests::FixedPrompter;
+    use screenshot_core::{
+        capture::{CapturePolicy, RedactionPolicy, timelapse::read_timelapse_manifest},
+        model::WindowInfo,
+    };
+
+    #[test]
+    fn test_server_creation_with_mock() {
+        let _server = ScreenshotMcpServer::new_with_mock();
+        // If this compiles and runs, the server was created successfully
+    }
+
+    #[test]
+    fn test_server_creation_with_backend() {
+        let mock = Arc::new(MockBackend::new());
+        let backend = Arc::new(composite_from_mock(mock));
+        let temp_files = Arc::new(TempFileManager::new());
+        let _server = ScreenshotMcpServer::new(backend, temp_files);
+        // Verify creation with explicit backend works
+    }
+
+    #[test]
+    fn test_server_default() {
+        let _server = ScreenshotMcpServer::default();
+        // Verify default implementation works
+    }
+
+    #[tokio::test]
+    async fn test_health_check_returns_success() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let result = server.health_check().await;
+
+        // Verify the call succeeds
+        assert!(result.is_ok());
+
+        let tool_result = result.unwrap();
+
+        // Verify it's not an error result
+        assert!(!tool_result.is_error.unwrap_or(false));
+
+        // Verify we have content
+        assert!(!tool_result.content.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_health_check_structure() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let result = server.health_check().await;
+
+        assert!(result.is_ok(), "health_check should return Ok");
+
+        let tool_result = result.unwrap();
+
+        // Verify the result structure
+        assert!(!tool_result.content.is_empty(), "should have content");
+        assert!(!tool_result.is_error.unwrap_or(false), "should not be an error");
+    }
+
+    // ========== M1 Phase 9 Integration Tests ==========
+
+    #[tokio::test]
+    async fn test_list_windows_returns_mock_data() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let result = server.list_windows().await;
+
+        assert!(result.is_ok(), "list_windows should succeed");
+
+        let tool_result = result.unwrap();
+        assert!(!tool_result.is_error.unwrap_or(false), "should not be an error");
+        assert!(!tool_result.content.is_empty(), "should have content");
+
+        // Parse JSON and verify we got 3 windows
+        let content = tool_result.content[0].as_text().unwrap();
+        let windows: Vec<WindowInfo> =
+            serde_json::from_str(&content.text).expect("should parse as JSON");
+        assert_eq!(windows.len(), 3, "MockBackend should return 3 windows");
+
+        // Verify window names (Firefox, VSCode, Terminal)
+        let titles: Vec<_> = windows.iter().map(|w| w.title.as_str()).collect();
+        assert!(titles.contains(&"Mozilla Firefox"));
+        assert!(titles.contains(&"Visual Studio Code"));
+        assert!(titles.contains(&"Terminal - Alacritty"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_by_title_success() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // Capture Firefox window
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture_window should succeed");
+
+        let tool_result = result.unwrap();
+        assert!(!tool_result.is_error.unwrap_or(false), "should not be an error");
+        assert_eq!(tool_result.content.len(), 3, "should have 3 content items");
+
+        // First content should be image
+        assert!(tool_result.content[0].as_image().is_some(), "first content should be image");
+
+        // Second content should be resource link (text)
+        assert!(
+            tool_result.content[1].as_text().is_some(),
+            "second content should be resource link"
+        );
+
+        // Third content should be metadata (text)
+        let metadata_text = tool_result.content[2].as_text().unwrap();
+        assert!(metadata_text.text.contains("dimensions"), "metadata should contain dimensions");
+        assert!(metadata_text.text.contains("1920"), "should have correct dimensions");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_default_format() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Visual Studio Code".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture_window should succeed");
+
+        let tool_result = result.unwrap();
+        let image = tool_result.content[0].as_image().unwrap();
+        assert_eq!(image.mime_type, "image/webp", "should use default WebP format");
+
+        let metadata_text = tool_result.content[2].as_text().unwrap();
+        assert!(metadata_text.text.contains("webp"), "metadata should show webp format");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_default_scale() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Terminal".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture_window should succeed");
+
+        let tool_result = result.unwrap();
+        let metadata_text = tool_result.content[2].as_text().unwrap();
+
+        // Original is 1920x1080, default scale 1.0 keeps original dimensions
+        assert!(metadata_text.text.contains("1920"), "should have original width");
+        assert!(metadata_text.text.contains("1080"), "should have original height");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_not_found() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("NonexistentWindow".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "should fail for nonexistent window");
+
+        let error = result.unwrap_err();
+        let error_msg = format!("{:?}", error);
+        assert!(
+            error_msg.contains("WindowNotFound") || error_msg.contains("not found"),
+            "error should indicate window not found"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_missing_selector() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // No selector fields provided
+        let result = server.capture_window(CaptureWindowParams::default()).await;
+
+        assert!(result.is_err(), "should fail when no selector fields provided");
+
+        let error = result.unwrap_err();
+        let error_msg = format!("{:?}", error);
+        assert!(
+            error_msg.contains("must be specified"),
+            "error should indicate missing selector"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_always_webp() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // Always uses WebP format for agent-friendly defaults
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with default WebP");
+
+        let tool_result = result.unwrap();
+        let image = tool_result.content[0].as_image().unwrap();
+        assert_eq!(image.mime_type, "image/webp", "should always use WebP format");
+    }
+
+    #[tokio::test]
+    async fn test_temp_file_created_and_tracked() {
+        let mock = Arc::new(MockBackend::new());
+        let backend = Arc::new(composite_from_mock(mock));
+        let temp_files = Arc::new(TempFileManager::new());
+        let server = ScreenshotMcpServer::new(backend, Arc::clone(&temp_files));
+
+        // Verify no files initially
+        assert_eq!(temp_files.count(), 0);
+
+        // Capture a window
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok());
+
+        // Verify temp file was created and tracked
+        assert_eq!(temp_files.count(), 1, "should have 1 temp file");
+
+        let files = temp_files.list_files();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].exists(), "temp file should exist on filesystem");
+    }
+
+    #[tokio::test]
+    async fn test_multiple_captures_create_unique_files() {
+        let mock = Arc::new(MockBackend::new());
+        let backend = Arc::new(composite_from_mock(mock));
+        let temp_files = Arc::new(TempFileManager::new());
+        let server = ScreenshotMcpServer::new(backend, Arc::clone(&temp_files));
+
+        // Capture three different windows
+        let _r1 = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let _r2 = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Visual Studio Code".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let _r3 = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Terminal".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // Verify 3 unique temp files
+        assert_eq!(temp_files.count(), 3, "should have 3 temp files");
+
+        let files = temp_files.list_files();
+        assert_eq!(files.len(), 3);
+
+        // All files should be unique
+        assert_ne!(files[0], files[1]);
+        assert_ne!(files[1], files[2]);
+        assert_ne!(files[0], files[2]);
+
+        // All should exist
+        for file in files {
+            assert!(file.exists(), "each temp file should exist");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_capture_result_dual_format_structure() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        // Verify structure: image + resource link + metadata
+        assert_eq!(result.content.len(), 3);
+
+        // Content 1: Image with base64 data
+        let image = result.content[0].as_image().unwrap();
+        assert_eq!(image.mime_type, "image/webp", "should use default WebP");
+        assert!(!image.data.is_empty(), "should have base64 data");
+
+        // Content 2: Resource link with file:// URI
+        let resource = result.content[1].as_text().unwrap();
+        assert!(resource.text.contains("file://"), "should have file:// URI");
+        assert!(resource.text.contains("screenshot-"), "should reference screenshot file");
+
+        // Content 3: Metadata with JSON
+        let metadata = result.content[2].as_text().unwrap();
+        assert!(metadata.text.contains("dimensions"), "should have dimensions");
+        assert!(metadata.text.contains("webp"), "should specify WebP format");
+        assert!(metadata.text.contains("size_bytes"), "should include file size");
+    }
+
+    // ========== New Capture Parameter Tests ==========
+
+    #[tokio::test]
+    async fn test_capture_window_with_png_format() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                format: Some(CaptureFormat::Png),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with PNG format");
+
+        let tool_result = result.unwrap();
+        let image = tool_result.content[0].as_image().unwrap();
+        assert_eq!(image.mime_type, "image/png", "should use PNG format");
+
+        let metadata_text = tool_result.content[2].as_text().unwrap();
+        assert!(metadata_text.text.contains("png"), "metadata should show png format");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_jpeg_format() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                format: Some(CaptureFormat::Jpeg),
+                quality: Some(90),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with JPEG format");
+
+        let tool_result = result.unwrap();
+        let image = tool_result.content[0].as_image().unwrap();
+        assert_eq!(image.mime_type, "image/jpeg", "should use JPEG format");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_custom_quality() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                quality: Some(50),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with custom quality");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_scale() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                scale: Some(0.5),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with 0.5 scale");
+
+        let tool_result = result.unwrap();
+        let metadata_text = tool_result.content[2].as_text().unwrap();
+
+        // Original is 1920x1080, 0.5 scale -> 960x540
+        assert!(metadata_text.text.contains("960"), "should have scaled width");
+        assert!(metadata_text.text.contains("540"), "should have scaled height");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_scale_out_of_range_fails() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // Scale too low
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                scale: Some(0.05), // below 0.1 minimum
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "should fail with scale below 0.1");
+
+        let error = result.unwrap_err();
+        let error_msg = format!("{:?}", error);
+        assert!(
+            error_msg.contains("scale") && error_msg.contains("0.1"),
+            "error should mention scale range"
+        );
+
+        // Scale too high
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                scale: Some(3.0), // above 2.0 maximum
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "should fail with scale above 2.0");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_region() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                region: Some(CaptureRegion {
+                    x: 100,
+                    y: 100,
+                    width: 800,
+                    height: 600,
+                }),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with region");
+
+        let tool_result = result.unwrap();
+        let metadata_text = tool_result.content[2].as_text().unwrap();
+
+        // Region crops to 800x600
+        assert!(metadata_text.text.contains("800"), "should have region width");
+        assert!(metadata_text.text.contains("600"), "should have region height");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_region_zero_dimensions_fails() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // Zero width
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                region: Some(CaptureRegion {
+                    x: 0,
+                    y: 0,
+                    width: 0,
+                    height: 100,
+                }),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "should fail with zero width");
+
+        let error = result.unwrap_err();
+        let error_msg = format!("{:?}", error);
+        assert!(
+            error_msg.contains("region") || error_msg.contains("width"),
+            "error should mention invalid region"
+        );
+
+        // Zero height
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                region: Some(CaptureRegion {
+                    x: 0,
+                    y: 0,
+                    width: 100,
+                    height: 0,
+                }),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "should fail with zero height");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_with_cursor() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                include_cursor: Some(true),
+                ..Default::default()
+            })
+            .await;
+
+        // MockBackend supports cursor, so this should succeed
+        assert!(result.is_ok(), "capture should succeed with cursor");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_all_options_combined() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // Use a region that's smaller than the mock image (1920x1080)
+        // and a scale that keeps the result reasonable
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                format: Some(CaptureFormat::Png),
+                quality: Some(95),
+                scale: Some(1.0), // Keep scale at 1.0 to avoid complex interactions
+                include_cursor: Some(true),
+                region: Some(CaptureRegion {
+                    x: 50,
+                    y: 50,
+                    width: 800,
+                    height: 600,
+                }),
+                ..Default::default()
+            })
+            .await;
+
+        match &result {
+            Ok(_) => {}
+            Err(e) => eprintln!("Error: {:?}", e),
+        }
+
+        assert!(result.is_ok(), "capture should succeed with all options");
+
+        let tool_result = result.unwrap();
+        let image = tool_result.content[0].as_image().unwrap();
+        assert_eq!(image.mime_type, "image/png", "should use PNG format");
+    }
+
+    // ========== Output Mode Tests ==========
+
+    #[tokio::test]
+    async fn test_capture_window_output_inline() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with inline output");
+        let tool_result = result.unwrap();
+
+        // Should have 2 content items: Image + Metadata (no resource link)
+        assert_eq!(tool_result.content.len(), 2);
+
+        // First content should be image
+        assert!(tool_result.content[0].as_image().is_some(), "should include image");
+
+        // Second content should be metadata
+        let metadata = tool_result.content[1].as_text().unwrap();
+        assert!(metadata.text.contains("Capture Metadata"));
+
+        // Verify no file path in metadata
+        assert!(!metadata.text.contains("file://"), "should not have file path URI");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_output_file() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                output: CaptureOutputMode::File,
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with file output");
+        let tool_result = result.unwrap();
+
+        // Should have 2 content items: Resource Link + Metadata (no image)
+        assert_eq!(tool_result.content.len(), 2);
+
+        // First content should be resource link
+        let resource = tool_result.content[0].as_text().unwrap();
+        assert!(resource.text.contains("Screenshot File Reference"));
+        assert!(resource.text.contains("file://"));
+
+        // Second content should be metadata
+        assert!(
+            tool_result.content[1]
+                .as_text()
+                .unwrap()
+                .text
+                .contains("Capture Metadata")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_output_both() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                output: CaptureOutputMode::Both,
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture should succeed with both output");
+        let tool_result = result.unwrap();
+
+        // Should have 3 content items: Image + Resource Link + Metadata
+        assert_eq!(tool_result.content.len(), 3);
+
+        assert!(tool_result.content[0].as_image().is_some());
+        assert!(
+            tool_result.content[1]
+                .as_text()
+                .unwrap()
+                .text
+                .contains("Screenshot File Reference")
+        );
+        assert!(
+            tool_result.content[2]
+                .as_text()
+                .unwrap()
+                .text
+                .contains("Capture Metadata")
+        );
+    }
+
+    // ========== Pointer-Aware Capture Tests ==========
+
+    /// Extracts the metadata JSON object from the last content item
+    fn parse_metadata(result: &CallToolResult) -> serde_json::Value {
+        let text = &result.content.last().unwrap().as_text().unwrap().text;
+        let json = text
+            .split("```json\n")
+            .nth(1)
+            .and_then(|rest| rest.split("\n```").next())
+            .unwrap();
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_metadata_includes_pointer() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["pointer"]["x"], 960);
+        assert_eq!(metadata["pointer"]["y"], 540);
+        assert_eq!(metadata["pointer"]["cursor_name"], "left_ptr");
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_under_pointer() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                under_pointer: Some(true),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_ok(), "capture under pointer should succeed: {:?}", result.err());
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_at_point_outside_windows_fails() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                at_point: Some(CapturePoint { x: 5000, y: 5000 }),
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "no window covers (5000, 5000)");
+        assert!(format!("{:?}", result.unwrap_err()).contains("not found"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_default_size() {
+        let server = server_with_policy("[policy]\ndisplay = \"allow\"");
+
+        let result = server
+            .capture_pointer_region(CapturePointerRegionParams {
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["dimensions"], serde_json::json!([400, 300]));
+        // Mock pointer at (960, 540) on a 2560x1440 display
+        assert_eq!(metadata["region"]["x"], 760);
+        assert_eq!(metadata["region"]["y"], 390);
+        assert_eq!(metadata["pointer"]["cursor_name"], "left_ptr");
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_clamped_at_screen_edge() {
+        let mock = Arc::new(MockBackend::new().with_pointer(0, 0, None));
+        let backend = Arc::new(composite_from_mock(mock).with_policy(display_allowed()));
+        let server = ScreenshotMcpServer::new(backend, Arc::new(TempFileManager::new()));
+
+        let result = server
+            .capture_pointer_region(CapturePointerRegionParams {
+                width: 200,
+                height: 100,
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["dimensions"], serde_json::json!([200, 100]));
+        assert_eq!(metadata["region"]["x"], 0);
+        assert_eq!(metadata["region"]["y"], 0);
+        assert!(metadata["pointer"]["cursor_name"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_uses_display_origin() {
+        let server_at = |x, y| {
+            let mock = MockBackend::new()
+                .with_display_origin(1920, 0)
+                .with_pointer(x, y, None);
+            let backend =
+                Arc::new(composite_from_mock(Arc::new(mock)).with_policy(display_allowed()));
+            ScreenshotMcpServer::new(backend, Arc::new(TempFileManager::new()))
+        };
+        let params = || CapturePointerRegionParams {
+            width: 200,
+            height: 100,
+            output: CaptureOutputMode::Inline,
+            ..Default::default()
+        };
+
+        // Pointer is 80 pixels into the display, so the crop is clamped to
+        // its left edge, which is at screen x = 1920
+        let result = server_at(2000, 100)
+            .capture_pointer_region(params())
+            .await
+            .unwrap();
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["region"]["x"], 1920);
+        assert_eq!(metadata["region"]["y"], 50);
+
+        // A pointer left of the display is on another monitor
+        let err = server_at(100, 100)
+            .capture_pointer_region(params())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("not on the captured display"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_with_grid() {
+        let server = server_with_policy("[policy]\ndisplay = \"allow\"");
+        let capture = |grid: Option<CaptureGrid>| {
+            let server = &server;
+            async move {
+                let result = server
+                    .capture_pointer_region(CapturePointerRegionParams {
+                        format: CaptureFormat::Png,
+                        output: CaptureOutputMode::File,
+                        grid,
+                        ..Default::default()
+                    })
+                    .await
+                    .unwrap();
+                let path = parse_metadata(&result)["file_path"]
+                    .as_str()
+                    .unwrap()
+                    .to_string();
+                let image = ImageBuffer::open(std::path::Path::new(&path)).unwrap();
+                std::fs::remove_file(path).ok();
+                image.to_rgba8()
+            }
+        };
+
+        let plain = capture(None).await;
+        let gridded = capture(Some(CaptureGrid::default())).await;
+        assert_eq!(gridded.dimensions(), plain.dimensions());
+        // Region starts at screen x = 760, so the x = 800 line is 40 pixels in
+        assert_ne!(gridded.get_pixel(40, 200), plain.get_pixel(40, 200));
+        assert_eq!(gridded.get_pixel(60, 200), plain.get_pixel(60, 200));
+    }
+
+    #[test]
+    fn test_capture_grid_defaults() {
+        let params: CaptureWindowParams =
+            serde_json::from_str(r#"{"class": "Code", "grid": {"spacing": 50}}"#).unwrap();
+        let grid = params.grid.unwrap().to_grid_options();
+        assert_eq!(grid.spacing, 50);
+        assert!(grid.rulers);
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_redacts_denied_windows() {
+        let policy =
+            RedactionPolicy::from_toml_str("[redaction]\ndeny = [\"class:Alacritty\"]").unwrap();
+        let backend = composite_from_mock(Arc::new(MockBackend::new())).with_redaction(policy);
+        let server = ScreenshotMcpServer::new(Arc::new(backend), Arc::new(TempFileManager::new()));
+
+        // The denied terminal overlaps the editor
+        let result = server
+            .capture_window(CaptureWindowParams {
+                class: Some("Code".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["redaction"]["windows"], serde_json::json!(["mock-0x3"]));
+        assert_eq!(metadata["redaction"]["full"], false);
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                class: Some("Alacritty".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(parse_metadata(&result)["redaction"]["full"], true);
+
+        // Nothing denied is above Firefox's visible area
+        let result = server
+            .capture_window(CaptureWindowParams {
+                class: Some("Navigator".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(parse_metadata(&result).get("redaction").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_capture_policy_denies_windows_and_tools() {
+        let policy = CapturePolicy::from_toml_str(
+            "[policy]\ndeny = [\"class:Alacritty\"]\ntools = { mark_windows = \"deny\" }",
+        )
+        .unwrap();
+        let backend = composite_from_mock(Arc::new(MockBackend::new())).with_policy(policy);
+        let server = ScreenshotMcpServer::new(Arc::new(backend), Arc::new(TempFileManager::new()));
+
+        let error = server
+            .capture_window(CaptureWindowParams {
+                class: Some("Alacritty".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_REQUEST);
+        assert!(
+            error
+                .message
+                .contains("matches deny rule 'class:Alacritty'")
+        );
+        let hint = error.data.unwrap();
+        assert_eq!(hint["recovery_action"], "require_user");
+        assert_eq!(hint["category"], "permission_denied");
 
-        // Step 3: Call backend prime_consent via trait object
-        let result = wayland_capability
-            .prime_consent(source_type, &params.source_id, params.include_cursor)
+        let error = server
+            .mark_windows(MarkWindowsParams::default())
             .await
-            .map_err(convert_capture_error_to_mcp)?;
+            .unwrap_err();
+        assert!(error.message.contains("tool 'mark_windows'"));
 
-        // Step 4: Build structured JSON response
-        let response_json = serde_json::json!({
-            "status": "success",
-            "source_id": result.primary_source_id,
-            "all_source_ids": result.all_source_ids,
-            "num_streams": result.num_streams,
-            "source_type": params.source_type,
-            "details": format!(
-                "Permission granted for {} {}. Restore token(s) stored securely.",
-                result.num_streams,
-                if result.num_streams == 1 { "source" } else { "sources" }
-            ),
-            "next_steps": if result.num_streams == 1 {
-                format!(
-                    "Call capture_window with exe='wayland:{}' to capture this source.",
-                    result.primary_source_id
-                )
-            } else {
-                format!(
-                    "Call capture_window with any of: {}",
-                    result.all_source_ids.iter()
-                        .map(|id| format!("'wayland:{}'", id))
-                        .collect::<Vec<_>>()
-                        .join(", ")
-                )
-            }
-        });
+        // Other windows and tools still work
+        let result = server
+            .capture_window(CaptureWindowParams {
+                class: Some("Code".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            parse_metadata(&result)["redaction"]["blocked"],
+            serde_json::json!(["mock-0x3"])
+        );
+    }
 
-        // Step 5: Return success
-        let json_str = serde_json::to_string(&response_json).map_err(|e| {
-            McpError::internal_error(
-                format!("Failed to serialize prime_wayland_consent response: {}", e),
-                None,
-            )
-        })?;
+    /// Policy that lets tests capture the whole display without a prompt
+    fn display_allowed() -> CapturePolicy {
+        CapturePolicy::from_toml_str("[policy]\ndisplay = \"allow\"").unwrap()
+    }
 
-        Ok(CallToolResult::success(vec![Content::text(json_str)]))
+    fn server_with_policy(policy: &str) -> ScreenshotMcpServer {
+        let policy = CapturePolicy::from_toml_str(policy).unwrap();
+        let backend = composite_from_mock(Arc::new(MockBackend::new())).with_policy(policy);
+        ScreenshotMcpServer::new(Arc::new(backend), Arc::new(TempFileManager::new()))
     }
-}
 
-// Manual implementation for capture_window tool (not using #[tool] macro due to
-// parameter limitations)
-impl ScreenshotMcpServer {
-    /// Captures a screenshot of a specific window
-    ///
-    /// Finds a window matching the selector criteria and captures a screenshot.
-    ///
-    /// The response shape is controlled by the `output` parameter:
-    /// - `output: "both"` (default): inline base64 image + file link + metadata
-    /// - `output: "inline"`: inline base64 image + metadata (no temp file written)
-    /// - `output: "file"`: file link + metadata (no base64 generated)
-    ///
-    /// # Window Selection Parameters (at least one required)
-    ///
-    /// - `titleSubstringOrRegex` (optional): Window title substring or regex pattern
-    /// - `class` (optional): Window class name
-    /// - `exe` (optional): Executable name
-    ///
-    /// # Capture Options (all optional)
-    ///
-    /// - `format` (optional): Output format - "png", "jpeg", or "webp" (default: "webp")
-    /// - `quality` (optional): Quality 0-100 for JPEG/WebP (default: 80, ignored for PNG)
-    /// - `scale` (optional): Scale factor 0.1-2.0 (default: 1.0)
-    /// - `output` (optional): Output mode - "inline", "file", or "both" (default: "both")
-    /// - `includeCursor` (optional): Include cursor in capture (default: false)
-    /// - `region` (optional): Crop region `{x, y, width, height}` (default: full window)
-    ///
-    /// # Returns
-    ///
-    /// A `CallToolResult` containing:
-    /// - Inline image content (base64-encoded) if `output` includes inline
-    /// - A file link with file:// URI if `output` includes file
-    /// - Metadata (dimensions, format, size, etc.) always
-    ///
-    /// # Examples
-    ///
-    /// Minimal request (uses defaults):
-    /// ```json
-    /// {
-    ///   "method": "tools/call",
-    ///   "params": {
-    ///     "name": "capture_window",
-    ///     "arguments": {
-    ///       "titleSubstringOrRegex": "Firefox"
-    ///     }
-    ///   }
-    /// }
-    /// ```
-    ///
-    /// Full options:
-    /// ```json
-    /// {
-    ///   "method": "tools/call",
-    ///   "params": {
-    ///     "name": "capture_window",
-    ///     "arguments": {
-    ///       "titleSubstringOrRegex": "Firefox",
-    ///       "format": "png",
-    ///       "quality": 95,
-    ///       "scale": 0.5,
-    ///       "includeCursor": true,
-    ///       "region": {"x": 0, "y": 0, "width": 800, "height": 600}
-    ///     }
-    ///   }
-    /// }
-    /// ```
-    pub async fn capture_window(
-        &self,
-        params: CaptureWindowParams,
-    ) -> Result<CallToolResult, McpError> {
-        // Build WindowSelector from parameters
-        let selector = WindowSelector {
-            title_substring_or_regex: params.title_substring_or_regex.clone(),
-            class: params.class.clone(),
-            exe: params.exe.clone(),
+    #[tokio::test]
+    async fn test_capture_consent_for_ask_windows() {
+        let code = || CaptureWindowParams {
+            class: Some("Code".to_string()),
+            output: CaptureOutputMode::Inline,
+            ..Default::default()
         };
 
-        // Validate that at least one selector field is provided
-        if selector.title_substring_or_regex.is_none()
-            && selector.class.is_none()
-            && selector.exe.is_none()
-        {
-            return Err(McpError::invalid_params(
-                "At least one of 'title_substring_or_regex', 'class', or 'exe' must be specified",
-                None,
-            ));
+        // Without elicitation support the user cannot be asked
+        let server = server_with_policy("[policy]\nask = [\"class:Code\"]");
+        let error = server.capture_window(code()).await.unwrap_err();
+        assert_eq!(error.code, rmcp::model::ErrorCode::INVALID_REQUEST);
+        assert!(error.message.contains("does not support elicitation"));
+
+        let prompter = FixedPrompter::new(true, true);
+        let server = server_with_policy("[policy]\nask = [\"class:Code\"]")
+            .with_consent_prompter(prompter.clone());
+        server.capture_window(code()).await.unwrap();
+        server.capture_window(code()).await.unwrap();
+        assert_eq!(prompter.prompts(), 1, "approval is remembered");
+
+        // Windows not marked "ask" are captured without asking
+        server
+            .capture_window(CaptureWindowParams {
+                class: Some("Navigator".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(prompter.prompts(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_display_capture_asks_by_default() {
+        let error = ScreenshotMcpServer::new_with_mock()
+            .mark_windows(MarkWindowsParams::default())
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("full-display capture"));
+        assert!(error.message.contains("does not support elicitation"));
+
+        let prompter = FixedPrompter::new(true, true);
+        let server = ScreenshotMcpServer::new_with_mock().with_consent_prompter(prompter.clone());
+        let result = server
+            .mark_windows(MarkWindowsParams::default())
+            .await
+            .unwrap();
+        assert_eq!(prompter.prompts(), 1);
+        if let Some(path) = parse_metadata(&result)["file_path"].as_str() {
+            std::fs::remove_file(path).ok();
         }
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_needs_display_consent() {
+        let params = || CapturePointerRegionParams {
+            output: CaptureOutputMode::Inline,
+            ..Default::default()
+        };
+
+        let error = ScreenshotMcpServer::new_with_mock()
+            .capture_pointer_region(params())
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("full-display capture"));
+
+        let prompter = FixedPrompter::new(true, true);
+        let server = ScreenshotMcpServer::new_with_mock().with_consent_prompter(prompter.clone());
+        server.capture_pointer_region(params()).await.unwrap();
+        assert_eq!(prompter.prompts(), 1);
+
+        let error = server_with_policy("[policy]\ndisplay = \"deny\"")
+            .capture_pointer_region(params())
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("permission is 'deny'"));
+    }
+
+    #[tokio::test]
+    async fn test_capture_consent_declined_for_display_and_tools() {
+        let prompter = FixedPrompter::new(false, true);
+        let server =
+            server_with_policy("[policy]\ndisplay = \"ask\"\ntools = { find_windows = \"ask\" }")
+                .with_consent_prompter(prompter.clone());
+
+        let error = server
+            .mark_windows(MarkWindowsParams::default())
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("full-display capture"));
+        assert!(error.message.contains("the user declined"));
+
+        let error = server
+            .mark_windows(MarkWindowsParams::default())
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("earlier in this session"));
+        assert_eq!(prompter.prompts(), 1);
+
+        let error = server
+            .find_windows(FindWindowsParams {
+                class: Some("Code".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(error.message.contains("tool 'find_windows'"));
+        assert_eq!(prompter.prompts(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_records_captures_and_denials() {
+        use screenshot_core::util::audit::{AuditFilter, AuditLog};
+
+        let path =
+            std::env::temp_dir().join(format!("screenshot-mcp-audit-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let server =
+            server_with_policy("[policy]\ndeny = [\"class:Alacritty\"]\ndisplay = \"allow\"")
+                .with_audit_log(AuditLog::new(&path));
+
+        server
+            .capture_window(CaptureWindowParams {
+                class: Some("Code".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        server
+            .capture_window(CaptureWindowParams {
+                class: Some("Alacritty".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        server
+            .capture_pointer_region(CapturePointerRegionParams {
+                output: CaptureOutputMode::File,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        // Tools that do not capture are not recorded
+        server.list_windows().await.unwrap();
+
+        let entries = AuditLog::new(&path).read(&AuditFilter::default()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(entries.len(), 3);
+
+        let window = entries[0].window.as_ref().unwrap();
+        assert_eq!(entries[0].tool, "capture_window");
+        assert_eq!(entries[0].selector.as_ref().unwrap().class.as_deref(), Some("Code"));
+        assert_eq!((window.id.as_str(), window.class.as_str()), ("mock-0x2", "Code"));
+        assert_eq!((entries[0].width, entries[0].height), (Some(1920), Some(1080)));
+        assert_eq!(entries[0].output, vec!["inline".to_string()]);
+        assert!(entries[0].policy_applied(), "Alacritty is blacked out");
+
+        assert!(
+            entries[1]
+                .denied
+                .as_ref()
+                .unwrap()
+                .contains("class:Alacritty")
+        );
+        assert!(entries[1].output.is_empty());
+
+        assert_eq!(entries[2].tool, "capture_pointer_region");
+        assert!(entries[2].window.is_none());
+        assert_eq!((entries[2].width, entries[2].height), (Some(400), Some(300)));
+        assert!(entries[2].output[0].ends_with(".webp"));
+        assert_eq!(entries[2].redaction.as_ref().unwrap().blocked, vec!["mock-0x3".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_redact() {
+        let server = server_with_policy("[policy]\ndisplay = \"allow\"");
+
+        // Region starts at screen (760, 390)
+        let result = server
+            .capture_pointer_region(CapturePointerRegionParams {
+                format: CaptureFormat::Png,
+                output: CaptureOutputMode::File,
+                redact: Some(CaptureRedact {
+                    regions: Some(vec![CaptureRegion {
+                        x: 700,
+                        y: 380,
+                        width: 80,
+                        height: 30,
+                    }]),
+                    mode: None,
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["redaction"]["regions"], 1);
+        let path = metadata["file_path"].as_str().unwrap().to_string();
+        let image = ImageBuffer::open(std::path::Path::new(&path))
+            .unwrap()
+            .to_rgba8();
+        std::fs::remove_file(path).ok();
+        assert_eq!(image.get_pixel(19, 19).0, [0, 0, 0, 255]);
+        assert_ne!(image.get_pixel(25, 25).0, [0, 0, 0, 255]);
+    }
+
+    #[tokio::test]
+    async fn test_capture_pointer_region_zero_size_fails() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_pointer_region(CapturePointerRegionParams {
+                width: 0,
+                ..Default::default()
+            })
+            .await;
+
+        assert!(result.is_err(), "zero-width region should be rejected");
+    }
+
+    // ========== Settle Tests ==========
+
+    #[tokio::test]
+    async fn test_capture_window_settle_reports_metadata() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                output: CaptureOutputMode::Inline,
+                settle: Some(CaptureSettle {
+                    interval_ms: Some(10),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["settle"]["settled"], true);
+        assert_eq!(metadata["settle"]["frames"], 2);
+        assert!(metadata["settle"]["waited_ms"].is_u64());
+    }
+
+    #[tokio::test]
+    async fn test_capture_window_without_settle_omits_metadata() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("Firefox".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
 
-        // Validate scale if provided (0.1-2.0 range)
-        if let Some(scale) = params.scale {
-            if !(0.1..=2.0).contains(&scale) {
-                return Err(McpError::invalid_params(
-                    format!("Invalid scale '{}': must be between 0.1 and 2.0", scale),
-                    None,
-                ));
-            }
-        }
+        assert!(parse_metadata(&result).get("settle").is_none());
+    }
 
-        // Validate region if provided (non-zero dimensions)
-        if let Some(ref region) = params.region {
-            if region.width == 0 || region.height == 0 {
-                return Err(McpError::invalid_params(
-                    "Invalid region: width and height must be greater than 0",
-                    None,
-                ));
-            }
-        }
+    #[test]
+    fn test_capture_settle_defaults() {
+        let settle: CaptureSettle = serde_json::from_str(r#"{"maxWaitMs": 500}"#).unwrap();
+        let options = settle.to_settle_options();
+        assert_eq!(options.max_wait_ms, 500);
+        assert_eq!(options.interval_ms, SettleOptions::default().interval_ms);
+    }
 
-        // Build capture options from params (with defaults)
-        let scale = params.scale.unwrap_or(1.0);
-        let quality = params.quality.unwrap_or(80);
-        let include_cursor = params.include_cursor.unwrap_or(false);
-        let format = params.format.to_image_format();
-        let region = params.region.map(|r| r.to_region());
+    // ========== Delta capture Tests ==========
 
-        let mut opts = CaptureOptions {
-            format,
-            quality,
-            scale,
-            include_cursor,
-            region,
-            wayland_source: None,
-            max_dimension: Some(1920), // Auto-scale 4K to ~1080p for efficient transfer
-        };
-        opts.validate();
+    fn delta_params(delta: CaptureDelta) -> CaptureWindowParams {
+        CaptureWindowParams {
+            window_id: Some("mock-0x1".to_string()),
+            format: Some(CaptureFormat::Png),
+            output: CaptureOutputMode::Inline,
+            delta: Some(delta),
+            ..Default::default()
+        }
+    }
 
-        // Get window resolver capability
-        let resolver = self.backend.resolver.as_ref().ok_or_else(|| {
-            McpError::internal_error("Window resolution is not available on this backend.", None)
-        })?;
+    #[tokio::test]
+    async fn test_capture_window_delta_full_then_unchanged() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-        // Resolve window target
-        let handle = resolver
-            .resolve(&selector)
+        let result = server
+            .capture_window(delta_params(CaptureDelta::default()))
             .await
-            .map_err(convert_capture_error_to_mcp)?;
+            .unwrap();
+        assert!(result.content[0].as_image().is_some());
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["delta"]["mode"], "full");
+        assert_eq!(metadata["delta"]["reason"], "no_previous_frame");
 
-        // Capture the window using ScreenCapture capability
-        let image_buffer = self
-            .backend
-            .capture
-            .capture_window(handle, &opts)
+        let result = server
+            .capture_window(delta_params(CaptureDelta::default()))
             .await
-            .map_err(convert_capture_error_to_mcp)?;
+            .unwrap();
+        assert_eq!(result.content.len(), 1, "unchanged frames carry no image");
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["delta"]["mode"], "unchanged");
+        assert_eq!(metadata["dimensions"], serde_json::json!([1920, 1080]));
+    }
 
-        // Note: scaling is applied by the backend via opts.scale
-        // Get dimensions (already scaled if scale != 1.0)
-        let dimensions = image_buffer.dimensions();
+    #[tokio::test]
+    async fn test_capture_window_delta_returns_changed_region_crops() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-        // Encode the image
-        let encoded_data =
-            encode_image(&image_buffer, &opts).map_err(convert_capture_error_to_mcp)?;
+        // Remember a frame that differs from the mock capture in one patch
+        let mut previous = ImageBuffer::from_test_pattern(1920, 1080).to_rgba8();
+        for y in 100..150 {
+            for x in 200..300 {
+                previous.put_pixel(x, y, image::Rgba([255, 255, 255, 255]));
+            }
+        }
+        server.frames.delta(
+            &frame_key(&"mock-0x1".to_string(), None),
+            &ImageBuffer::new(image::DynamicImage::ImageRgba8(previous)),
+            &DeltaOptions::default(),
+        );
 
-        // Determine output modes
-        let should_save_file =
-            matches!(params.output, CaptureOutputMode::File | CaptureOutputMode::Both);
-        let should_inline_image =
-            matches!(params.output, CaptureOutputMode::Inline | CaptureOutputMode::Both);
+        let result = server
+            .capture_window(delta_params(CaptureDelta::default()))
+            .await
+            .unwrap();
 
-        // Write to temp file if requested
-        let file_path = if should_save_file {
-            let (path, _size) = self
-                .temp_files
-                .write_image(&encoded_data, opts.format)
-                .map_err(convert_capture_error_to_mcp)?;
-            Some(path)
-        } else {
-            None
-        };
+        assert_eq!(result.content.len(), 2, "one crop plus metadata");
+        assert!(result.content[0].as_image().is_some());
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["delta"]["mode"], "regions");
+        assert_eq!(
+            metadata["delta"]["regions"],
+            serde_json::json!([{"x": 200, "y": 100, "width": 100, "height": 50}])
+        );
+        assert_eq!(metadata["regions"][0]["x"], 200);
+        assert_eq!(metadata["dimensions"], serde_json::json!([1920, 1080]));
+    }
 
-        // Build result based on requested output mode
-        let result = build_capture_result(
-            &encoded_data,
-            file_path.as_deref(),
-            &opts,
-            dimensions,
-            should_inline_image,
+    #[tokio::test]
+    async fn test_capture_window_delta_falls_back_to_full_frame() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let mut previous = ImageBuffer::from_test_pattern(1920, 1080).to_rgba8();
+        previous.put_pixel(10, 10, image::Rgba([255, 255, 255, 255]));
+        for x in 0..40 {
+            previous.put_pixel(x, 20, image::Rgba([255, 255, 255, 255]));
+        }
+        server.frames.delta(
+            &frame_key(&"mock-0x1".to_string(), None),
+            &ImageBuffer::new(image::DynamicImage::ImageRgba8(previous)),
+            &DeltaOptions::default(),
         );
 
-        Ok(result)
-    }
-}
+        let result = server
+            .capture_window(delta_params(CaptureDelta {
+                max_changed_ratio: Some(0.0),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
 
-impl Default for ScreenshotMcpServer {
-    fn default() -> Self {
-        Self::new_with_mock()
+        assert!(result.content[0].as_image().is_some());
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["delta"]["mode"], "full");
+        assert_eq!(metadata["delta"]["reason"], "too_many_changes");
     }
-}
 
-// Implement ServerHandler to make ScreenshotMcpServer a valid Service
-impl ServerHandler for ScreenshotMcpServer {
-    fn get_info(&self) -> ServerInfo {
-        ServerInfo::default()
+    #[tokio::test]
+    async fn test_capture_window_ambiguity_error_lists_candidates() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        // All three mock titles contain "i" and match equally well
+        let err = server
+            .capture_window(CaptureWindowParams {
+                title_substring_or_regex: Some("i".to_string()),
+                ambiguity: Some(AmbiguityPolicy::Error),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        let hint = err.data.unwrap();
+        assert_eq!(hint["suggested_tool"], "find_windows");
+        assert_eq!(hint["tool_params"]["candidates"].as_array().unwrap().len(), 3);
+        assert_eq!(hint["tool_params"]["candidates"][0]["windowId"], "mock-0x1");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use screenshot_core::model::WindowInfo;
+    #[tokio::test]
+    async fn test_capture_window_by_pid_and_window_id() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-    #[test]
-    fn test_server_creation_with_mock() {
-        let _server = ScreenshotMcpServer::new_with_mock();
-        // If this compiles and runs, the server was created successfully
+        let result = server
+            .capture_window(CaptureWindowParams {
+                pid: Some(2000),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await;
+        assert!(result.is_ok(), "pid alone should select a window");
+
+        let err = server
+            .capture_window(CaptureWindowParams {
+                window_id: Some("mock-0x9".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
     }
 
-    #[test]
-    fn test_server_creation_with_backend() {
-        let mock = Arc::new(MockBackend::new());
-        let backend = Arc::new(composite_from_mock(mock));
-        let temp_files = Arc::new(TempFileManager::new());
-        let _server = ScreenshotMcpServer::new(backend, temp_files);
-        // Verify creation with explicit backend works
+    #[tokio::test]
+    async fn test_capture_window_with_query() {
+        let server = ScreenshotMcpServer::new_with_mock();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                query: Some("exe:code !title:Firefox".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await;
+        assert!(result.is_ok(), "query alone should select a window");
+
+        let err = server
+            .capture_window(CaptureWindowParams {
+                query: Some("exe:code title~/(/".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("'query'"));
+        assert!(err.message.contains("at column 10"));
     }
 
     #[test]
-    fn test_server_default() {
-        let _server = ScreenshotMcpServer::default();
-        // Verify default implementation works
+    fn test_merge_selector_query_explicit_fields_win() {
+        let selector = merge_selector_query(
+            Some("class:Navigator exe:firefox !title:Private ambiguity:error"),
+            WindowSelector {
+                exe: Some("code".to_string()),
+                exclude: vec!["Draft".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(selector.class.as_deref(), Some("Navigator"));
+        assert_eq!(selector.exe.as_deref(), Some("code"));
+        assert_eq!(selector.exclude, vec!["Private".to_string(), "Draft".to_string()]);
+        assert_eq!(selector.ambiguity, AmbiguityPolicy::Error);
     }
 
+    // ========== find_windows Tests ==========
+
     #[tokio::test]
-    async fn test_health_check_returns_success() {
+    async fn test_find_windows_ranks_candidates() {
         let server = ScreenshotMcpServer::new_with_mock();
-        let result = server.health_check().await;
 
-        // Verify the call succeeds
-        assert!(result.is_ok());
+        let result = server
+            .find_windows(FindWindowsParams {
+                title_substring_or_regex: Some("i".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
 
-        let tool_result = result.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let explanation: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        // All three mock titles contain "i"; ties are broken by window ID
+        assert_eq!(explanation["match_count"], 3);
+        assert_eq!(explanation["selected"], "mock-0x1");
+        let candidates = explanation["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0]["rank"], 1);
+        assert_eq!(candidates[0]["selected"], true);
+        assert_eq!(candidates[1]["decided_by"], "window_id");
+        assert_eq!(candidates[0]["criteria"]["title"], true);
+    }
 
-        // Verify it's not an error result
-        assert!(!tool_result.is_error.unwrap_or(false));
+    #[tokio::test]
+    async fn test_find_windows_reports_non_matching() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-        // Verify we have content
-        assert!(!tool_result.content.is_empty());
+        let result = server
+            .find_windows(FindWindowsParams {
+                class: Some("Nonexistent".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let text = &result.content[0].as_text().unwrap().text;
+        let explanation: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert!(explanation["selected"].is_null());
+        assert_eq!(explanation["match_count"], 0);
+        assert_eq!(explanation["candidates"][0]["criteria"]["class"], false);
     }
 
     #[tokio::test]
-    async fn test_health_check_structure() {
+    async fn test_find_windows_exclude_and_any_of() {
         let server = ScreenshotMcpServer::new_with_mock();
-        let result = server.health_check().await;
 
-        assert!(result.is_ok(), "health_check should return Ok");
+        let result = server
+            .find_windows(FindWindowsParams {
+                exclude: Some(vec!["Firefox".to_string()]),
+                any_of: Some(vec![
+                    SelectorAlternative {
+                        exe: Some("firefox".to_string()),
+                        ..Default::default()
+                    },
+                    SelectorAlternative {
+                        pid: Some(3000),
+                        ..Default::default()
+                    },
+                ]),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
 
-        let tool_result = result.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let explanation: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(explanation["match_count"], 1);
+        assert_eq!(explanation["selected"], "mock-0x3");
 
-        // Verify the result structure
-        assert!(!tool_result.content.is_empty(), "should have content");
-        assert!(!tool_result.is_error.unwrap_or(false), "should not be an error");
+        let firefox = explanation["candidates"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|c| c["window"]["id"] == "mock-0x1")
+            .unwrap();
+        assert_eq!(firefox["criteria"]["any_of"], true);
+        assert_eq!(firefox["criteria"]["exclude"], false);
     }
 
-    // ========== M1 Phase 9 Integration Tests ==========
-
     #[tokio::test]
-    async fn test_list_windows_returns_mock_data() {
+    async fn test_find_windows_with_query() {
         let server = ScreenshotMcpServer::new_with_mock();
-        let result = server.list_windows().await;
-
-        assert!(result.is_ok(), "list_windows should succeed");
-
-        let tool_result = result.unwrap();
-        assert!(!tool_result.is_error.unwrap_or(false), "should not be an error");
-        assert!(!tool_result.content.is_empty(), "should have content");
 
-        // Parse JSON and verify we got 3 windows
-        let content = tool_result.content[0].as_text().unwrap();
-        let windows: Vec<WindowInfo> =
-            serde_json::from_str(&content.text).expect("should parse as JSON");
-        assert_eq!(windows.len(), 3, "MockBackend should return 3 windows");
+        let result = server
+            .find_windows(FindWindowsParams {
+                query: Some("(class:Code | pid:3000) !title:Terminal".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
 
-        // Verify window names (Firefox, VSCode, Terminal)
-        let titles: Vec<_> = windows.iter().map(|w| w.title.as_str()).collect();
-        assert!(titles.contains(&"Mozilla Firefox"));
-        assert!(titles.contains(&"Visual Studio Code"));
-        assert!(titles.contains(&"Terminal - Alacritty"));
+        let text = &result.content[0].as_text().unwrap().text;
+        let explanation: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(explanation["match_count"], 1);
+        assert_eq!(explanation["selected"], "mock-0x2");
     }
 
     #[tokio::test]
-    async fn test_capture_window_by_title_success() {
+    async fn test_find_windows_folds_unicode_unless_strict() {
         let server = ScreenshotMcpServer::new_with_mock();
 
-        // Capture Firefox window
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
+            .find_windows(FindWindowsParams {
+                title_substring_or_regex: Some("ｍｏｚｉｌｌａ".to_string()),
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let explanation: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(explanation["selected"], "mock-0x1");
 
-        assert!(result.is_ok(), "capture_window should succeed");
+        let result = server
+            .find_windows(FindWindowsParams {
+                title_substring_or_regex: Some("Vísual Stúdio".to_string()),
+                strict: Some(true),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let explanation: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(explanation["match_count"], 0);
+    }
 
-        let tool_result = result.unwrap();
-        assert!(!tool_result.is_error.unwrap_or(false), "should not be an error");
-        assert_eq!(tool_result.content.len(), 3, "should have 3 content items");
+    #[test]
+    fn test_merge_selector_query_strict_prefers_explicit() {
+        let selector =
+            merge_selector_query(Some("title:Café strict:true"), WindowSelector::default())
+                .unwrap();
+        assert_eq!(selector.strict, Some(true));
+
+        let selector = merge_selector_query(
+            Some("title:Café"),
+            WindowSelector {
+                strict: Some(true),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(selector.strict, Some(true));
+
+        // An explicit false wins over the query
+        let selector = merge_selector_query(
+            Some("title:Café strict:true"),
+            WindowSelector {
+                strict: Some(false),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(selector.strict, Some(false));
+    }
 
-        // First content should be image
-        assert!(tool_result.content[0].as_image().is_some(), "first content should be image");
+    #[tokio::test]
+    async fn test_find_windows_requires_selector() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let err = server
+            .find_windows(FindWindowsParams::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+    }
 
-        // Second content should be resource link (text)
-        assert!(
-            tool_result.content[1].as_text().is_some(),
-            "second content should be resource link"
-        );
+    // ========== target Tests ==========
+
+    fn server_with_targets() -> ScreenshotMcpServer {
+        let targets = TargetRegistry::from_toml_str(
+            r#"
+            [targets]
+            term = "exe:alacritty"
+            ghost = "class:NoSuchClass"
+
+            [targets.editor]
+            query = "class:Code"
+            format = "png"
+            scale = 0.5
+            "#,
+        )
+        .unwrap();
+        ScreenshotMcpServer::new_with_mock().with_targets(targets)
+    }
 
-        // Third content should be metadata (text)
-        let metadata_text = tool_result.content[2].as_text().unwrap();
-        assert!(metadata_text.text.contains("dimensions"), "metadata should contain dimensions");
-        assert!(metadata_text.text.contains("1920"), "should have correct dimensions");
+    #[tokio::test]
+    async fn test_capture_window_target_applies_selector_and_options() {
+        let server = server_with_targets();
+
+        let result = server
+            .capture_window(CaptureWindowParams {
+                target: Some("editor".to_string()),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(result.content[0].as_image().unwrap().mime_type, "image/png");
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["target"], "editor");
+        assert_eq!(metadata["dimensions"], serde_json::json!([960, 540]));
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_default_format() {
-        let server = ScreenshotMcpServer::new_with_mock();
+    async fn test_capture_window_explicit_params_override_target() {
+        let server = server_with_targets();
 
         let result = server
             .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Visual Studio Code".to_string()),
+                target: Some("editor".to_string()),
+                format: Some(CaptureFormat::Jpeg),
+                scale: Some(1.0),
+                output: CaptureOutputMode::Inline,
                 ..Default::default()
             })
-            .await;
-
-        assert!(result.is_ok(), "capture_window should succeed");
+            .await
+            .unwrap();
+        assert_eq!(result.content[0].as_image().unwrap().mime_type, "image/jpeg");
+        assert_eq!(parse_metadata(&result)["dimensions"], serde_json::json!([1920, 1080]));
 
-        let tool_result = result.unwrap();
-        let image = tool_result.content[0].as_image().unwrap();
-        assert_eq!(image.mime_type, "image/webp", "should use default WebP format");
+        // Asking for the default format explicitly still overrides the target's
+        let result = server
+            .capture_window(CaptureWindowParams {
+                target: Some("editor".to_string()),
+                format: Some(CaptureFormat::Webp),
+                output: CaptureOutputMode::Inline,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.content[0].as_image().unwrap().mime_type, "image/webp");
 
-        let metadata_text = tool_result.content[2].as_text().unwrap();
-        assert!(metadata_text.text.contains("webp"), "metadata should show webp format");
+        // An explicit selector field replaces the target's class
+        let err = server
+            .capture_window(CaptureWindowParams {
+                target: Some("editor".to_string()),
+                class: Some("NoSuchClass".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("not found"));
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_default_scale() {
-        let server = ScreenshotMcpServer::new_with_mock();
+    async fn test_capture_window_unknown_target() {
+        let server = server_with_targets();
 
-        let result = server
+        let err = server
             .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Terminal".to_string()),
+                target: Some("browser".to_string()),
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("unknown target 'browser'"));
+        assert!(err.message.contains("editor, ghost, term"));
+    }
 
-        assert!(result.is_ok(), "capture_window should succeed");
+    #[tokio::test]
+    async fn test_list_targets_reports_resolution() {
+        let server = server_with_targets();
+
+        let result = server.list_targets().await.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+
+        let targets = response["targets"].as_array().unwrap();
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets[0]["name"], "editor");
+        assert_eq!(targets[0]["window"]["id"], "mock-0x2");
+        assert_eq!(targets[1]["name"], "ghost");
+        assert!(targets[1]["window"].is_null());
+        assert!(targets[1]["error"].as_str().unwrap().contains("not found"));
+        assert_eq!(targets[2]["window"]["id"], "mock-0x3");
+        assert!(response["path"].is_string());
+    }
 
-        let tool_result = result.unwrap();
-        let metadata_text = tool_result.content[2].as_text().unwrap();
+    // ========== compare_images Tests ==========
 
-        // Original is 1920x1080, default scale 1.0 keeps original dimensions
-        assert!(metadata_text.text.contains("1920"), "should have original width");
-        assert!(metadata_text.text.contains("1080"), "should have original height");
+    /// Stores an image as a PNG capture and returns its path
+    fn store_png(files: &TempFileManager, image: &ImageBuffer) -> std::path::PathBuf {
+        let opts = CaptureOptions {
+            format: ImageFormat::Png,
+            ..Default::default()
+        };
+        let data = encode_image(image, &opts).unwrap();
+        files.write_image(&data, ImageFormat::Png).unwrap().0
+    }
+
+    fn compare_params(before: String, after: String) -> CompareImagesParams {
+        CompareImagesParams {
+            before,
+            after,
+            pixel_tolerance: None,
+            ignore_antialiasing: None,
+            merge_distance: None,
+            min_region_pixels: None,
+            format: CaptureFormat::Png,
+            output: CaptureOutputMode::Inline,
+        }
     }
 
     #[tokio::test]
-    async fn test_capture_window_not_found() {
+    async fn test_compare_images_reports_changes() {
         let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+
+        // The gradient of a taller pattern changes more slowly, so only the
+        // bottom rows of the cropped copy stay close to the original
+        let before = store_png(&files, &ImageBuffer::from_test_pattern(64, 48));
+        let taller = ImageBuffer::from_test_pattern(64, 96);
+        let after = store_png(&files, &taller.crop(Region::new(0, 0, 64, 48)).unwrap());
 
+        // Accepts a file:// URI and a bare capture file name
+        let after_name = after.file_name().unwrap().to_string_lossy().to_string();
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("NonexistentWindow".to_string()),
-                ..Default::default()
-            })
-            .await;
+            .compare_images(compare_params(format!("file://{}", before.display()), after_name))
+            .await
+            .unwrap();
 
-        assert!(result.is_err(), "should fail for nonexistent window");
+        assert!(result.content[0].as_image().is_some());
+        let comparison = &parse_metadata(&result)["comparison"];
+        assert_eq!(comparison["identical"], false);
+        assert!(comparison["changed_pixels"].as_u64().unwrap() > 0);
+        assert!(comparison["ssim"].as_f64().unwrap() < 1.0);
+        let regions = comparison["regions"].as_array().unwrap();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0]["width"], 64);
 
-        let error = result.unwrap_err();
-        let error_msg = format!("{:?}", error);
-        assert!(
-            error_msg.contains("WindowNotFound") || error_msg.contains("not found"),
-            "error should indicate window not found"
-        );
+        files.cleanup_all();
     }
 
     #[tokio::test]
-    async fn test_capture_window_missing_selector() {
+    async fn test_compare_images_identical() {
         let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let path = store_png(&files, &ImageBuffer::from_test_pattern(32, 32));
+        let path = path.to_string_lossy().to_string();
 
-        // No selector fields provided
-        let result = server.capture_window(CaptureWindowParams::default()).await;
-
-        assert!(result.is_err(), "should fail when no selector fields provided");
+        let result = server
+            .compare_images(compare_params(path.clone(), path))
+            .await
+            .unwrap();
+        let comparison = &parse_metadata(&result)["comparison"];
+        assert_eq!(comparison["identical"], true);
+        assert_eq!(comparison["ssim"], 1.0);
 
-        let error = result.unwrap_err();
-        let error_msg = format!("{:?}", error);
-        assert!(
-            error_msg.contains("must be specified"),
-            "error should indicate missing selector"
-        );
+        files.cleanup_all();
     }
 
     #[tokio::test]
-    async fn test_capture_window_always_webp() {
+    async fn test_compare_images_invalid_inputs() {
         let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let big = store_png(&files, &ImageBuffer::from_test_pattern(32, 32));
+        let small = store_png(&files, &ImageBuffer::from_test_pattern(16, 16));
+
+        let err = server
+            .compare_images(compare_params(
+                big.to_string_lossy().to_string(),
+                small.to_string_lossy().to_string(),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("16x16"));
+
+        let err = server
+            .compare_images(compare_params(
+                "missing-capture.png".to_string(),
+                big.to_string_lossy().to_string(),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("before"));
+
+        files.cleanup_all();
+    }
+
+    // ========== Baseline Tests ==========
+
+    /// A mock server with its own baseline directory
+    fn server_with_baselines(test: &str) -> (ScreenshotMcpServer, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "screenshot-mcp-baselines-{}-{}",
+            std::process::id(),
+            test
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        let server = ScreenshotMcpServer::new_with_mock().with_baselines(BaselineStore::new(&dir));
+        (server, dir)
+    }
+
+    fn response_json(result: &CallToolResult) -> serde_json::Value {
+        serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_baseline_save_and_check_window() {
+        let (server, dir) = server_with_baselines("window");
 
-        // Always uses WebP format for agent-friendly defaults
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
+            .save_baseline(SaveBaselineParams {
+                name: "browser".to_string(),
+                query: Some("class:navigator".to_string()),
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
+        assert!(dir.join("browser.png").exists());
+        assert_eq!(response_json(&result)["name"], "browser");
 
-        assert!(result.is_ok(), "capture should succeed with default WebP");
+        let result = server
+            .check_baseline(CheckBaselineParams {
+                name: "browser".to_string(),
+                query: Some("class:navigator".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let report = response_json(&result);
+        assert_eq!(report["status"], "passed");
+        assert_eq!(report["comparison"]["identical"], true);
 
-        let tool_result = result.unwrap();
-        let image = tool_result.content[0].as_image().unwrap();
-        assert_eq!(image.mime_type, "image/webp", "should always use WebP format");
+        let result = server.list_baselines().await.unwrap();
+        assert_eq!(response_json(&result)["baselines"], serde_json::json!(["browser"]));
+
+        let _ = std::fs::remove_dir_all(dir);
     }
 
     #[tokio::test]
-    async fn test_temp_file_created_and_tracked() {
-        let mock = Arc::new(MockBackend::new());
-        let backend = Arc::new(composite_from_mock(mock));
-        let temp_files = Arc::new(TempFileManager::new());
-        let server = ScreenshotMcpServer::new(backend, Arc::clone(&temp_files));
+    async fn test_baseline_check_failure_and_approve() {
+        let (server, dir) = server_with_baselines("approve");
+        let files = TempFileManager::new();
+        let original = store_png(&files, &ImageBuffer::from_test_pattern(64, 48));
+        let taller = ImageBuffer::from_test_pattern(64, 96);
+        let changed = store_png(&files, &taller.crop(Region::new(0, 0, 64, 48)).unwrap());
+
+        server
+            .save_baseline(SaveBaselineParams {
+                name: "panel".to_string(),
+                capture: Some(original.to_string_lossy().to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
 
-        // Verify no files initially
-        assert_eq!(temp_files.count(), 0);
+        let check = CheckBaselineParams {
+            name: "panel".to_string(),
+            capture: Some(changed.to_string_lossy().to_string()),
+            output: CaptureOutputMode::Inline,
+            ..Default::default()
+        };
+        let result = server.check_baseline(check.clone()).await.unwrap();
+        assert!(result.content[0].as_image().is_some(), "failed checks return the diff");
+        let report = &parse_metadata(&result)["baseline"];
+        assert_eq!(report["status"], "failed");
+        assert!(dir.join("panel.actual.png").exists());
+
+        server
+            .approve_baseline(ApproveBaselineParams {
+                name: "panel".to_string(),
+            })
+            .await
+            .unwrap();
+        let result = server.check_baseline(check).await.unwrap();
+        assert_eq!(response_json(&result)["status"], "passed");
 
-        // Capture a window
-        let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
+        files.cleanup_all();
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_baseline_invalid_requests() {
+        let (server, dir) = server_with_baselines("invalid");
+
+        let err = server
+            .save_baseline(SaveBaselineParams {
+                name: "nothing".to_string(),
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
 
-        assert!(result.is_ok());
+        let err = server
+            .approve_baseline(ApproveBaselineParams {
+                name: "never-checked".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("no pending capture"));
+
+        let err = server
+            .save_baseline(SaveBaselineParams {
+                name: "../outside".to_string(),
+                query: Some("class:navigator".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
 
-        // Verify temp file was created and tracked
-        assert_eq!(temp_files.count(), 1, "should have 1 temp file");
+        let _ = std::fs::remove_dir_all(dir);
+    }
 
-        let files = temp_files.list_files();
-        assert_eq!(files.len(), 1);
-        assert!(files[0].exists(), "temp file should exist on filesystem");
+    // ========== wait_for_window Tests ==========
+
+    /// A white screen with the test pattern pasted at (40, 20)
+    fn icon_screen() -> (ImageBuffer, ImageBuffer) {
+        let icon = ImageBuffer::from_test_pattern(16, 16);
+        let mut screen = image::RgbaImage::from_pixel(120, 80, image::Rgba([255, 255, 255, 255]));
+        image::imageops::replace(&mut screen, &icon.to_rgba8(), 40, 20);
+        (ImageBuffer::new(image::DynamicImage::ImageRgba8(screen)), icon)
     }
 
     #[tokio::test]
-    async fn test_multiple_captures_create_unique_files() {
-        let mock = Arc::new(MockBackend::new());
-        let backend = Arc::new(composite_from_mock(mock));
-        let temp_files = Arc::new(TempFileManager::new());
-        let server = ScreenshotMcpServer::new(backend, Arc::clone(&temp_files));
+    async fn test_locate_image_in_capture_with_screen_area() {
+        use base64::{Engine, engine::general_purpose::STANDARD};
 
-        // Capture three different windows
-        let _r1 = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
+        let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let (screen, icon) = icon_screen();
+        let capture = store_png(&files, &screen);
+        let icon_png = std::fs::read(store_png(&files, &icon)).unwrap();
+
+        let result = server
+            .locate_image(LocateImageParams {
+                template_data: Some(STANDARD.encode(icon_png)),
+                capture: Some(capture.to_string_lossy().to_string()),
+                screen_area: Some(CaptureRegion {
+                    x: 100,
+                    y: 200,
+                    width: 120,
+                    height: 80,
+                }),
                 ..Default::default()
             })
             .await
             .unwrap();
+        let response = response_json(&result);
+        assert_eq!(response["template_dimensions"], serde_json::json!([16, 16]));
+        let matches = response["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            matches[0]["region"],
+            serde_json::json!({"x": 40, "y": 20, "width": 16, "height": 16})
+        );
+        assert_eq!(
+            matches[0]["screen_region"],
+            serde_json::json!({"x": 140, "y": 220, "width": 16, "height": 16})
+        );
+        assert!(matches[0]["confidence"].as_f64().unwrap() > 0.99);
 
-        let _r2 = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Visual Studio Code".to_string()),
+        files.cleanup_all();
+    }
+
+    #[tokio::test]
+    async fn test_locate_image_in_window_reports_screen_coordinates() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let template = ImageBuffer::from_test_pattern(1920, 1080)
+            .crop(Region::new(100, 300, 64, 64))
+            .unwrap();
+        let template = store_png(&files, &template);
+
+        let result = server
+            .locate_image(LocateImageParams {
+                template: Some(template.to_string_lossy().to_string()),
+                query: Some("class:code".to_string()),
+                max_matches: Some(1),
                 ..Default::default()
             })
             .await
             .unwrap();
+        let response = response_json(&result);
+        let found = &response["matches"][0];
+        // Mock window mock-0x2 sits at x=640 on screen
+        assert!(found["screen_region"]["x"].as_u64().unwrap() >= 640);
+        assert!(found["confidence"].as_f64().unwrap() > 0.9);
 
-        let _r3 = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Terminal".to_string()),
+        files.cleanup_all();
+    }
+
+    #[tokio::test]
+    async fn test_locate_image_invalid_requests() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let (screen, _) = icon_screen();
+        let capture = store_png(&files, &screen).to_string_lossy().to_string();
+
+        let cases = [
+            LocateImageParams {
+                capture: Some(capture.clone()),
                 ..Default::default()
-            })
+            },
+            LocateImageParams {
+                template_data: Some("not base64!".to_string()),
+                capture: Some(capture.clone()),
+                ..Default::default()
+            },
+            LocateImageParams {
+                template: Some(capture.clone()),
+                ..Default::default()
+            },
+            // Template larger than the searched image
+            LocateImageParams {
+                template: Some(capture.clone()),
+                capture: Some(capture.clone()),
+                scales: Some(vec![2.0]),
+                ..Default::default()
+            },
+        ];
+        for params in cases {
+            let err = server.locate_image(params.clone()).await.unwrap_err();
+            assert_eq!(
+                err.code,
+                rmcp::model::ErrorCode::INVALID_PARAMS,
+                "{:?}: {}",
+                params,
+                err.message
+            );
+        }
+
+        files.cleanup_all();
+    }
+
+    fn annotate_params(capture: String, annotations: serde_json::Value) -> AnnotateCaptureParams {
+        serde_json::from_value(serde_json::json!({
+            "capture": capture,
+            "annotations": annotations,
+            "format": "png",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_annotate_capture_returns_new_capture() {
+        let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let source = store_png(&files, &ImageBuffer::from_test_pattern(200, 100));
+        let source_name = source.to_string_lossy().to_string();
+
+        let result = server
+            .annotate_capture(annotate_params(
+                source_name.clone(),
+                serde_json::json!([
+                    {"type": "rectangle", "x": 10, "y": 10, "width": 50, "height": 30},
+                    {"type": "text", "x": 80, "y": 10, "text": "Bug", "color": "#ff8800"}
+                ]),
+            ))
             .await
             .unwrap();
 
-        // Verify 3 unique temp files
-        assert_eq!(temp_files.count(), 3, "should have 3 temp files");
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["annotated"]["annotations"], 2);
+        assert_eq!(metadata["annotated"]["source"], source_name);
+        assert_eq!(metadata["dimensions"], serde_json::json!([200, 100]));
+
+        // The annotated copy is a new stored capture; the source is unchanged
+        let path = std::path::PathBuf::from(metadata["file_path"].as_str().unwrap());
+        assert_ne!(path, source);
+        let annotated = ImageBuffer::open(&path).unwrap();
+        assert_eq!(annotated.to_rgba8().get_pixel(10, 25)[0], 230);
+        let original = ImageBuffer::open(&source).unwrap();
+        assert_eq!(original.to_rgba8().get_pixel(10, 25)[0], 0);
+
+        files.cleanup_all();
+        std::fs::remove_file(path).ok();
+    }
 
-        let files = temp_files.list_files();
-        assert_eq!(files.len(), 3);
+    #[tokio::test]
+    async fn test_annotate_capture_screen_coordinates() {
+        use base64::{Engine, engine::general_purpose::STANDARD};
 
-        // All files should be unique
-        assert_ne!(files[0], files[1]);
-        assert_ne!(files[1], files[2]);
-        assert_ne!(files[0], files[2]);
+        let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let source = store_png(&files, &ImageBuffer::from_test_pattern(200, 100));
+
+        let mut params = annotate_params(
+            source.to_string_lossy().to_string(),
+            serde_json::json!([
+                {"type": "rectangle", "x": 650, "y": 310, "width": 50, "height": 30}
+            ]),
+        );
+        params.coordinates = CoordinateSpace::Screen;
+        params.output = CaptureOutputMode::Inline;
+
+        let err = server.annotate_capture(params.clone()).await.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("screenArea"));
+
+        params.screen_area = Some(CaptureRegion {
+            x: 640,
+            y: 300,
+            width: 200,
+            height: 100,
+        });
+        let result = server.annotate_capture(params).await.unwrap();
+        let image = result.content[0].as_image().expect("inline image");
+        let annotated = ImageBuffer::from_bytes(&STANDARD.decode(&image.data).unwrap()).unwrap();
+        // Screen (650, 325) is image (10, 25)
+        assert_eq!(annotated.to_rgba8().get_pixel(10, 25)[0], 230);
 
-        // All should exist
-        for file in files {
-            assert!(file.exists(), "each temp file should exist");
-        }
+        files.cleanup_all();
     }
 
     #[tokio::test]
-    async fn test_capture_result_dual_format_structure() {
+    async fn test_annotate_capture_invalid_annotations() {
         let server = ScreenshotMcpServer::new_with_mock();
+        let files = TempFileManager::new();
+        let source = store_png(&files, &ImageBuffer::from_test_pattern(20, 20));
+
+        let err = server
+            .annotate_capture(annotate_params(
+                source.to_string_lossy().to_string(),
+                serde_json::json!([{"type": "polyline", "points": [[1, 1]]}]),
+            ))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("Annotation 0"), "{}", err.message);
+
+        files.cleanup_all();
+    }
+
+    #[tokio::test]
+    async fn test_mark_windows_labels_topmost_first() {
+        let server = server_with_policy("[policy]\ndisplay = \"allow\"");
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                ..Default::default()
-            })
+            .mark_windows(MarkWindowsParams::default())
             .await
             .unwrap();
 
-        // Verify structure: image + resource link + metadata
-        assert_eq!(result.content.len(), 3);
-
-        // Content 1: Image with base64 data
-        let image = result.content[0].as_image().unwrap();
-        assert_eq!(image.mime_type, "image/webp", "should use default WebP");
-        assert!(!image.data.is_empty(), "should have base64 data");
+        let metadata = parse_metadata(&result);
+        assert_eq!(
+            metadata["labels"],
+            serde_json::json!({"1": "mock-0x3", "2": "mock-0x2", "3": "mock-0x1"})
+        );
 
-        // Content 2: Resource link with file:// URI
-        let resource = result.content[1].as_text().unwrap();
-        assert!(resource.text.contains("file://"), "should have file:// URI");
-        assert!(resource.text.contains("screenshot-"), "should reference screenshot file");
+        let marks = metadata["marks"].as_array().unwrap();
+        assert_eq!(marks.len(), 3);
+        assert_eq!(marks[0]["class"], "Alacritty");
+        assert_eq!(marks[0]["visible_ratio"], 1.0);
+        // Code is half covered by Alacritty, Firefox half covered by Code
+        assert_eq!(
+            marks[1]["visible"],
+            serde_json::json!({"x": 640, "y": 0, "width": 1280, "height": 540})
+        );
+        assert_eq!(marks[2]["visible_ratio"], 0.5);
 
-        // Content 3: Metadata with JSON
-        let metadata = result.content[2].as_text().unwrap();
-        assert!(metadata.text.contains("dimensions"), "should have dimensions");
-        assert!(metadata.text.contains("webp"), "should specify WebP format");
-        assert!(metadata.text.contains("size_bytes"), "should include file size");
+        // The 2560x1440 display is fitted to the default 1920 limit
+        assert_eq!(metadata["dimensions"], serde_json::json!([1920, 1080]));
+        if let Some(path) = metadata["file_path"].as_str() {
+            std::fs::remove_file(path).ok();
+        }
     }
 
-    // ========== New Capture Parameter Tests ==========
+    #[tokio::test]
+    async fn test_mark_windows_requires_inspector() {
+        let mut backend =
+            composite_from_mock(Arc::new(MockBackend::new())).with_policy(display_allowed());
+        backend.inspector = None;
+        let server = ScreenshotMcpServer::new(Arc::new(backend), Arc::new(TempFileManager::new()));
+
+        let err = server
+            .mark_windows(MarkWindowsParams::default())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("not available"));
+    }
 
     #[tokio::test]
-    async fn test_capture_window_with_png_format() {
+    async fn test_capture_overview_of_all_windows() {
         let server = ScreenshotMcpServer::new_with_mock();
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                format: CaptureFormat::Png,
+            .capture_overview(CaptureOverviewParams {
+                output: CaptureOutputMode::File,
                 ..Default::default()
             })
-            .await;
-
-        assert!(result.is_ok(), "capture should succeed with PNG format");
-
-        let tool_result = result.unwrap();
-        let image = tool_result.content[0].as_image().unwrap();
-        assert_eq!(image.mime_type, "image/png", "should use PNG format");
+            .await
+            .unwrap();
 
-        let metadata_text = tool_result.content[2].as_text().unwrap();
-        assert!(metadata_text.text.contains("png"), "metadata should show png format");
+        let metadata = parse_metadata(&result);
+        assert_eq!(
+            metadata["labels"],
+            serde_json::json!({"1": "mock-0x1", "2": "mock-0x2", "3": "mock-0x3"})
+        );
+        let cells = metadata["overview"]["cells"].as_array().unwrap();
+        assert_eq!(cells.len(), 3);
+        // Three windows make a 2x2 grid, filled row by row
+        assert_eq!((cells[2]["row"].as_u64(), cells[2]["column"].as_u64()), (Some(1), Some(0)));
+        assert_eq!(cells[1]["window"]["class"], "Code");
+        // The 1920x1080 mock captures fit the default 320x200 box
+        assert_eq!(cells[0]["thumbnail"]["width"], 320);
+        assert_eq!(cells[0]["thumbnail"]["height"], 180);
+        assert_eq!(metadata["overview"]["timed_out"], false);
+
+        if let Some(path) = metadata["file_path"].as_str() {
+            std::fs::remove_file(path).ok();
+        }
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_jpeg_format() {
+    async fn test_capture_overview_with_query() {
         let server = ScreenshotMcpServer::new_with_mock();
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                format: CaptureFormat::Jpeg,
-                quality: Some(90),
+            .capture_overview(CaptureOverviewParams {
+                // Firefox and Code, but not "Terminal - Alacritty"
+                query: Some("title:o".to_string()),
+                max_windows: Some(1),
+                output: CaptureOutputMode::Inline,
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_ok(), "capture should succeed with JPEG format");
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["overview"]["cells"].as_array().unwrap().len(), 1);
+        assert_eq!(metadata["overview"]["omitted"], 1);
 
-        let tool_result = result.unwrap();
-        let image = tool_result.content[0].as_image().unwrap();
-        assert_eq!(image.mime_type, "image/jpeg", "should use JPEG format");
+        let err = server
+            .capture_overview(CaptureOverviewParams {
+                query: Some("class:NoSuchApp".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("No windows"));
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_custom_quality() {
-        let server = ScreenshotMcpServer::new_with_mock();
+    async fn test_denied_windows_left_out_of_marks_and_overviews() {
+        let server =
+            server_with_policy("[policy]\ndeny = [\"class:Alacritty\"]\ndisplay = \"allow\"");
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                quality: Some(50),
+            .mark_windows(MarkWindowsParams::default())
+            .await
+            .unwrap();
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["labels"], serde_json::json!({"1": "mock-0x2", "2": "mock-0x1"}));
+        assert!(!metadata.to_string().contains("Alacritty"));
+        if let Some(path) = metadata["file_path"].as_str() {
+            std::fs::remove_file(path).ok();
+        }
+
+        let result = server
+            .capture_overview(CaptureOverviewParams {
+                output: CaptureOutputMode::Inline,
                 ..Default::default()
             })
-            .await;
-
-        assert!(result.is_ok(), "capture should succeed with custom quality");
+            .await
+            .unwrap();
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["overview"]["cells"].as_array().unwrap().len(), 2);
+        assert!(!metadata.to_string().contains("Alacritty"));
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_scale() {
+    async fn test_record_window_merges_static_frames() {
         let server = ScreenshotMcpServer::new_with_mock();
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                scale: Some(0.5),
+            .record_window(RecordWindowParams {
+                query: Some("class:Alacritty".to_string()),
+                fps: Some(10),
+                duration_ms: Some(300),
+                format: Some(AnimationFormat::Gif),
+                max_dimension: Some(320),
+                output: CaptureOutputMode::File,
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_ok(), "capture should succeed with 0.5 scale");
+        // Resource link + metadata
+        assert_eq!(result.content.len(), 2);
+        let metadata = parse_metadata(&result);
+        // The mock window never changes: three captures, one frame
+        assert_eq!(metadata["recording"]["captured"], 3);
+        assert_eq!(metadata["recording"]["frames"], 1);
+        assert_eq!(metadata["recording"]["duration_ms"], 300);
+        assert_eq!(metadata["dimensions"], serde_json::json!([320, 180]));
+
+        let path = metadata["file_path"].as_str().unwrap();
+        assert!(path.ends_with(".gif"));
+        assert!(std::fs::read(path).unwrap().starts_with(b"GIF89a"));
+        std::fs::remove_file(path).ok();
+    }
 
-        let tool_result = result.unwrap();
-        let metadata_text = tool_result.content[2].as_text().unwrap();
+    #[tokio::test]
+    async fn test_record_window_requires_a_selector() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-        // Original is 1920x1080, 0.5 scale -> 960x540
-        assert!(metadata_text.text.contains("960"), "should have scaled width");
-        assert!(metadata_text.text.contains("540"), "should have scaled height");
+        let err = server
+            .record_window(RecordWindowParams::default())
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("'query'"));
     }
 
     #[tokio::test]
-    async fn test_capture_window_scale_out_of_range_fails() {
+    async fn test_timelapse_writes_frames_and_manifest() {
         let server = ScreenshotMcpServer::new_with_mock();
+        let dir = std::env::temp_dir().join(format!("screenshot-timelapse-{}", std::process::id()));
 
-        // Scale too low
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                scale: Some(0.05), // below 0.1 minimum
+            .start_timelapse(StartTimelapseParams {
+                query: Some("class:Alacritty".to_string()),
+                directory: Some(dir.display().to_string()),
+                interval_secs: Some(1),
+                max_frames: Some(1),
+                filename: Some("shot-{index}.{ext}".to_string()),
+                format: Some(CaptureFormat::Png),
+                max_dimension: Some(64),
                 ..Default::default()
             })
-            .await;
-
-        assert!(result.is_err(), "should fail with scale below 0.1");
-
-        let error = result.unwrap_err();
-        let error_msg = format!("{:?}", error);
-        assert!(
-            error_msg.contains("scale") && error_msg.contains("0.1"),
-            "error should mention scale range"
-        );
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(response["timelapse_id"], "timelapse-1");
+        // The run does not keep the registry alive, so dropping the server
+        // aborts it
+        assert_eq!(Arc::strong_count(&server.timelapses), 1);
 
-        // Scale too high
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                scale: Some(3.0), // above 2.0 maximum
-                ..Default::default()
+            .stop_timelapse(StopTimelapseParams {
+                timelapse_id: "timelapse-1".to_string(),
             })
-            .await;
-
-        assert!(result.is_err(), "should fail with scale above 2.0");
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(response["stopped"], "frame_limit");
+        assert_eq!(response["captured"], 1);
+
+        let frame = image::open(dir.join("shot-00001.png")).unwrap();
+        assert_eq!(frame.width(), 64);
+        let manifest = read_timelapse_manifest(&dir).unwrap();
+        assert_eq!(manifest.frames[0].window.as_deref(), Some("mock-0x3"));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let err = server
+            .stop_timelapse(StopTimelapseParams {
+                timelapse_id: "timelapse-1".to_string(),
+            })
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("Unknown time-lapse ID"));
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_region() {
+    async fn test_timelapse_requires_a_limit() {
         let server = ScreenshotMcpServer::new_with_mock();
 
-        let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                region: Some(CaptureRegion {
-                    x: 100,
-                    y: 100,
-                    width: 800,
-                    height: 600,
-                }),
+        let err = server
+            .start_timelapse(StartTimelapseParams {
+                query: Some("class:Alacritty".to_string()),
                 ..Default::default()
             })
-            .await;
-
-        assert!(result.is_ok(), "capture should succeed with region");
-
-        let tool_result = result.unwrap();
-        let metadata_text = tool_result.content[2].as_text().unwrap();
-
-        // Region crops to 800x600
-        assert!(metadata_text.text.contains("800"), "should have region width");
-        assert!(metadata_text.text.contains("600"), "should have region height");
+            .await
+            .unwrap_err();
+        assert!(err.message.contains("maxFrames") || err.message.contains("limit"));
     }
 
     #[tokio::test]
-    async fn test_capture_window_region_zero_dimensions_fails() {
+    async fn test_wait_for_window_without_capture() {
         let server = ScreenshotMcpServer::new_with_mock();
 
-        // Zero width
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                region: Some(CaptureRegion {
-                    x: 0,
-                    y: 0,
-                    width: 0,
-                    height: 100,
-                }),
+            .wait_for_window(WaitForWindowParams {
+                class: Some("Code".to_string()),
+                visible: Some(true),
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_err(), "should fail with zero width");
+        assert_eq!(result.content.len(), 1);
+        let text = &result.content[0].as_text().unwrap().text;
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(response["window_id"], "mock-0x2");
+        assert_eq!(response["window"]["title"], "Visual Studio Code");
+        assert_eq!(response["state"]["mapped"], true);
+        assert_eq!(response["polls"], 1);
+    }
 
-        let error = result.unwrap_err();
-        let error_msg = format!("{:?}", error);
-        assert!(
-            error_msg.contains("region") || error_msg.contains("width"),
-            "error should mention invalid region"
-        );
+    #[tokio::test]
+    async fn test_wait_for_window_with_capture() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-        // Zero height
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                region: Some(CaptureRegion {
-                    x: 0,
-                    y: 0,
-                    width: 100,
-                    height: 0,
-                }),
+            .wait_for_window(WaitForWindowParams {
+                title_regex: Some("firefox$".to_string()),
+                stable_for_ms: Some(30),
+                poll_interval_ms: Some(10),
+                capture: Some(true),
+                output: CaptureOutputMode::Inline,
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
 
-        assert!(result.is_err(), "should fail with zero height");
+        let metadata = parse_metadata(&result);
+        assert_eq!(metadata["wait"]["window_id"], "mock-0x1");
+        assert!(metadata["wait"]["waited_ms"].as_u64().unwrap() >= 30);
+        assert!(metadata["dimensions"].is_array());
     }
 
     #[tokio::test]
-    async fn test_capture_window_with_cursor() {
+    async fn test_wait_for_window_ambiguity_index_selects_ranked_match() {
         let server = ScreenshotMcpServer::new_with_mock();
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                include_cursor: Some(true),
+            .wait_for_window(WaitForWindowParams {
+                title_substring_or_regex: Some("i".to_string()),
+                ambiguity: Some(AmbiguityPolicy::Index(2)),
                 ..Default::default()
             })
-            .await;
+            .await
+            .unwrap();
 
-        // MockBackend supports cursor, so this should succeed
-        assert!(result.is_ok(), "capture should succeed with cursor");
+        let text = &result.content[0].as_text().unwrap().text;
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(response["window_id"], "mock-0x3");
     }
 
     #[tokio::test]
-    async fn test_capture_window_all_options_combined() {
+    async fn test_wait_for_window_accepts_full_selector() {
         let server = ScreenshotMcpServer::new_with_mock();
+        let wait = |params: WaitForWindowParams| async {
+            let result = server.wait_for_window(params).await.unwrap();
+            let text = &result.content[0].as_text().unwrap().text;
+            serde_json::from_str::<serde_json::Value>(text).unwrap()["window_id"].clone()
+        };
 
-        // Use a region that's smaller than the mock image (1920x1080)
-        // and a scale that keeps the result reasonable
-        let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                format: CaptureFormat::Png,
-                quality: Some(95),
-                scale: Some(1.0), // Keep scale at 1.0 to avoid complex interactions
-                include_cursor: Some(true),
-                region: Some(CaptureRegion {
-                    x: 50,
-                    y: 50,
-                    width: 800,
-                    height: 600,
-                }),
-                ..Default::default()
-            })
-            .await;
-
-        match &result {
-            Ok(_) => {}
-            Err(e) => eprintln!("Error: {:?}", e),
-        }
-
-        assert!(result.is_ok(), "capture should succeed with all options");
+        let by_id = wait(WaitForWindowParams {
+            window_id: Some("mock-0x2".to_string()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(by_id, "mock-0x2");
 
-        let tool_result = result.unwrap();
-        let image = tool_result.content[0].as_image().unwrap();
-        assert_eq!(image.mime_type, "image/png", "should use PNG format");
+        let alternatives = wait(WaitForWindowParams {
+            any_of: Some(vec![SelectorAlternative {
+                class: Some("Alacritty".to_string()),
+                ..Default::default()
+            }]),
+            exclude: Some(vec!["Firefox".to_string()]),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(alternatives, "mock-0x3");
     }
 
-    // ========== Output Mode Tests ==========
-
     #[tokio::test]
-    async fn test_capture_window_output_inline() {
+    async fn test_wait_for_window_times_out() {
         let server = ScreenshotMcpServer::new_with_mock();
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                output: CaptureOutputMode::Inline,
+            .wait_for_window(WaitForWindowParams {
+                title_substring_or_regex: Some("Nonexistent Dialog".to_string()),
+                timeout_ms: Some(50),
+                poll_interval_ms: Some(10),
                 ..Default::default()
             })
             .await;
 
-        assert!(result.is_ok(), "capture should succeed with inline output");
-        let tool_result = result.unwrap();
-
-        // Should have 2 content items: Image + Metadata (no resource link)
-        assert_eq!(tool_result.content.len(), 2);
+        let err = result.unwrap_err();
+        assert!(format!("{:?}", err).contains("timed out"), "unexpected error: {:?}", err);
+    }
 
-        // First content should be image
-        assert!(tool_result.content[0].as_image().is_some(), "should include image");
+    #[tokio::test]
+    async fn test_wait_for_window_requires_selector() {
+        let server = ScreenshotMcpServer::new_with_mock();
 
-        // Second content should be metadata
-        let metadata = tool_result.content[1].as_text().unwrap();
-        assert!(metadata.text.contains("Capture Metadata"));
+        let result = server.wait_for_window(WaitForWindowParams::default()).await;
 
-        // Verify no file path in metadata
-        assert!(!metadata.text.contains("file://"), "should not have file path URI");
+        let err = result.unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::INVALID_PARAMS);
     }
 
+    // ========== Window Event Subscription Tests ==========
+
     #[tokio::test]
-    async fn test_capture_window_output_file() {
-        let server = ScreenshotMcpServer::new_with_mock();
+    async fn test_subscribe_window_events_forwards_notifications() {
+        use screenshot_core::model::{WindowEvent, WindowEventKind};
 
+        let mock = Arc::new(MockBackend::new());
+        let backend = Arc::new(composite_from_mock(mock.clone()));
+        let server = ScreenshotMcpServer::new(backend, Arc::new(TempFileManager::new()));
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                output: CaptureOutputMode::File,
-                ..Default::default()
-            })
-            .await;
+            .subscribe_window_events_with_sink(
+                SubscribeWindowEventsParams {
+                    kinds: Some(vec![WindowEventKind::TitleChanged]),
+                },
+                move |notification| {
+                    let tx = tx.clone();
+                    async move { tx.send(notification).is_ok() }
+                },
+            )
+            .await
+            .unwrap();
 
-        assert!(result.is_ok(), "capture should succeed with file output");
-        let tool_result = result.unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let response: serde_json::Value = serde_json::from_str(text).unwrap();
+        let subscription_id = response["subscription_id"].as_str().unwrap().to_string();
 
-        // Should have 2 content items: Resource Link + Metadata (no image)
-        assert_eq!(tool_result.content.len(), 2);
+        // Filtered out, then forwarded
+        mock.emit_event(WindowEvent::new(WindowEventKind::Mapped, "mock-0x1", None));
+        mock.emit_event(WindowEvent::new(WindowEventKind::TitleChanged, "mock-0x1", Some("Save")));
 
-        // First content should be resource link
-        let resource = tool_result.content[0].as_text().unwrap();
-        assert!(resource.text.contains("Screenshot File Reference"));
-        assert!(resource.text.contains("file://"));
+        let notification = rx.recv().await.unwrap();
+        assert_eq!(notification.logger.as_deref(), Some("window-events"));
+        assert_eq!(notification.data["subscription_id"], subscription_id);
+        assert_eq!(notification.data["event"]["kind"], "title_changed");
+        assert_eq!(notification.data["event"]["title"], "Save");
 
-        // Second content should be metadata
-        assert!(
-            tool_result.content[1]
-                .as_text()
-                .unwrap()
-                .text
-                .contains("Capture Metadata")
-        );
+        let result = server
+            .unsubscribe_window_events(UnsubscribeWindowEventsParams { subscription_id })
+            .await;
+        assert!(result.is_ok());
     }
 
     #[tokio::test]
-    async fn test_capture_window_output_both() {
+    async fn test_unsubscribe_unknown_id_fails() {
         let server = ScreenshotMcpServer::new_with_mock();
 
         let result = server
-            .capture_window(CaptureWindowParams {
-                title_substring_or_regex: Some("Firefox".to_string()),
-                output: CaptureOutputMode::Both,
-                ..Default::default()
+            .unsubscribe_window_events(UnsubscribeWindowEventsParams {
+                subscription_id: "window-events-999".to_string(),
             })
             .await;
 
-        assert!(result.is_ok(), "capture should succeed with both output");
-        let tool_result = result.unwrap();
-
-        // Should have 3 content items: Image + Resource Link + Metadata
-        assert_eq!(tool_result.content.len(), 3);
-
-        assert!(tool_result.content[0].as_image().is_some());
-        assert!(
-            tool_result.content[1]
-                .as_text()
-                .unwrap()
-                .text
-                .contains("Screenshot File Reference")
-        );
-        assert!(
-            tool_result.content[2]
-                .as_text()
-                .unwrap()
-                .text
-                .contains("Capture Metadata")
-        );
+        assert!(result.is_err());
+        assert!(format!("{:?}", result.unwrap_err()).contains("Unknown subscription"));
     }
 
     // ========== Error Path Tests for MCP Error Code Mapping ==========
@@ -1451,7 +6375,7 @@ mod tests {
         #[test]
         fn test_window_not_found_maps_to_invalid_params() {
             let error = CaptureError::WindowNotFound {
-                selector: WindowSelector::by_title("NonExistent"),
+                selector: Box::new(WindowSelector::by_title("NonExistent")),
             };
             let mcp_error = convert_capture_error_to_mcp(error);
 
@@ -1623,7 +6547,7 @@ mod tests {
         #[test]
         fn test_error_message_content_preserved() {
             let error = CaptureError::WindowNotFound {
-                selector: WindowSelector::by_title("MyCustomWindow"),
+                selector: Box::new(WindowSelector::by_title("MyCustomWindow")),
             };
             let mcp_error = convert_capture_error_to_mcp(error);
 
@@ -0,0 +1,295 @@
+//! User consent for sensitive captures via MCP elicitation
+//!
+//! The capture policy can mark windows, tools and full-display captures as
+//! `"ask"` (see [`CapturePolicy`](screenshot_core::capture::CapturePolicy)).
+//! Before such a capture the server sends the client an
+//! `elicitation/create` request; the user accepts or declines it. A decision
+//! is remembered for the rest of the session unless the user unticks
+//! `remember` or dismisses the prompt.
+//!
+//! Clients that do not support elicitation cannot be asked, so captures that
+//! need consent are refused for them.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use futures::future::BoxFuture;
+use rmcp::{
+    RoleServer,
+    model::{CreateElicitationRequestParam, ElicitationAction, ElicitationSchema},
+    service::Peer,
+};
+use screenshot_core::{
+    error::{CaptureError, CaptureResult},
+    model::WindowHandle,
+};
+
+/// Something the user can be asked to allow
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ConsentSubject {
+    /// Captures of one window
+    Window(WindowHandle),
+    /// Full-display captures
+    Display,
+    /// Calls of one tool
+    Tool(String),
+}
+
+impl fmt::Display for ConsentSubject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsentSubject::Window(handle) => write!(f, "window {}", handle),
+            ConsentSubject::Display => write!(f, "full-display capture"),
+            ConsentSubject::Tool(tool) => write!(f, "tool '{}'", tool),
+        }
+    }
+}
+
+/// The user's answer to a consent prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConsentAnswer {
+    /// Whether the capture may go ahead
+    pub approved: bool,
+    /// Whether to apply the answer to the rest of the session
+    pub remember: bool,
+}
+
+/// Asks the user a yes/no consent question
+pub trait ConsentPrompter: Send + Sync {
+    /// Shows `message` and waits for the answer
+    ///
+    /// Errors are reported as text and treated as a refusal.
+    fn ask<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<ConsentAnswer, String>>;
+}
+
+impl ConsentPrompter for Peer<RoleServer> {
+    fn ask<'a>(&'a self, message: &'a str) -> BoxFuture<'a, Result<ConsentAnswer, String>> {
+        Box::pin(async move {
+            let result = self
+                .create_elicitation(CreateElicitationRequestParam {
+                    message: message.to_string(),
+                    requested_schema: ElicitationSchema::builder()
+                        .optional_bool("remember", true)
+                        .build_unchecked(),
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let remember = |default: bool| {
+                result
+                    .content
+                    .as_ref()
+                    .and_then(|content| content["remember"].as_bool())
+                    .unwrap_or(default)
+            };
+            Ok(match result.action {
+                ElicitationAction::Accept => ConsentAnswer {
+                    approved: true,
+                    remember: remember(true),
+                },
+                ElicitationAction::Decline => ConsentAnswer {
+                    approved: false,
+                    remember: true,
+                },
+                // Dismissed without an answer: refuse, but ask again next time
+                ElicitationAction::Cancel => ConsentAnswer {
+                    approved: false,
+                    remember: false,
+                },
+            })
+        })
+    }
+}
+
+/// Consent decisions of one session, and the prompter that obtains them
+#[derive(Default)]
+pub struct ConsentGate {
+    decisions: Mutex<HashMap<ConsentSubject, bool>>,
+    prompter: RwLock<Option<Arc<dyn ConsentPrompter>>>,
+}
+
+impl ConsentGate {
+    /// Creates a gate with no decisions and no way to ask
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how the user is asked, normally the MCP client peer
+    pub fn set_prompter(&self, prompter: Arc<dyn ConsentPrompter>) {
+        *self.prompter.write().unwrap() = Some(prompter);
+    }
+
+    /// Returns the remembered decision for `subject`, if any
+    pub fn decision(&self, subject: &ConsentSubject) -> Option<bool> {
+        self.decisions.lock().unwrap().get(subject).copied()
+    }
+
+    /// Ensures the user allows `subject`, asking if no decision is remembered
+    ///
+    /// `description` completes "Allow the assistant to …?", e.g. "capture the
+    /// whole display".
+    ///
+    /// # Errors
+    ///
+    /// `CaptureError::PolicyDenied` if the user declines, declined earlier
+    /// in the session, or cannot be asked.
+    pub async fn require(&self, subject: ConsentSubject, description: &str) -> CaptureResult<()> {
+        let denied = |reason: &str| CaptureError::PolicyDenied {
+            target: subject.to_string(),
+            reason: reason.to_string(),
+        };
+
+        match self.decision(&subject) {
+            Some(true) => return Ok(()),
+            Some(false) => return Err(denied("the user declined earlier in this session")),
+            None => {}
+        }
+
+        let prompter = self.prompter.read().unwrap().clone();
+        let Some(prompter) = prompter else {
+            return Err(denied(
+                "needs the user's consent, but the client does not support elicitation",
+            ));
+        };
+
+        let message = format!("Allow the assistant to {}?", description);
+        let answer = match prompter.ask(&message).await {
+            Ok(answer) => answer,
+            Err(e) => {
+                tracing::warn!("Consent prompt for {} failed: {}", subject, e);
+                return Err(denied(&format!("asking the user failed: {}", e)));
+            }
+        };
+        tracing::info!(
+            "User {} {}",
+            if answer.approved {
+                "allowed"
+            } else {
+                "declined"
+            },
+            subject
+        );
+        if answer.remember {
+            self.decisions
+                .lock()
+                .unwrap()
+                .insert(subject.clone(), answer.approved);
+        }
+
+        if answer.approved {
+            Ok(())
+        } else {
+            Err(denied("the user declined"))
+        }
+    }
+}
+
+impl fmt::Debug for ConsentGate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConsentGate")
+            .field("decisions", &self.decisions)
+            .field("has_prompter", &self.prompter.read().unwrap().is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Prompter that always gives the same answer and counts prompts
+    pub(crate) struct FixedPrompter {
+        answer: ConsentAnswer,
+        prompts: AtomicUsize,
+    }
+
+    impl FixedPrompter {
+        pub(crate) fn new(approved: bool, remember: bool) -> Arc<Self> {
+            Arc::new(Self {
+                answer: ConsentAnswer { approved, remember },
+                prompts: AtomicUsize::new(0),
+            })
+        }
+
+        pub(crate) fn prompts(&self) -> usize {
+            self.prompts.load(Ordering::SeqCst)
+        }
+    }
+
+    impl ConsentPrompter for FixedPrompter {
+        fn ask<'a>(&'a self, _message: &'a str) -> BoxFuture<'a, Result<ConsentAnswer, String>> {
+            self.prompts.fetch_add(1, Ordering::SeqCst);
+            Box::pin(async move { Ok(self.answer) })
+        }
+    }
+
+    fn prompting_gate(approved: bool, remember: bool) -> (ConsentGate, Arc<FixedPrompter>) {
+        let prompter = FixedPrompter::new(approved, remember);
+        let gate = ConsentGate::new();
+        gate.set_prompter(prompter.clone());
+        (gate, prompter)
+    }
+
+    #[tokio::test]
+    async fn test_decisions_are_remembered() {
+        let (gate, prompter) = prompting_gate(true, true);
+        let subject = ConsentSubject::Window("0x1".to_string());
+
+        gate.require(subject.clone(), "capture window 'Mail'")
+            .await
+            .unwrap();
+        gate.require(subject.clone(), "capture window 'Mail'")
+            .await
+            .unwrap();
+        assert_eq!(prompter.prompts(), 1);
+        assert_eq!(gate.decision(&subject), Some(true));
+
+        // Other subjects are asked about separately
+        gate.require(ConsentSubject::Display, "capture the whole display")
+            .await
+            .unwrap();
+        assert_eq!(prompter.prompts(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_declined_and_unremembered_answers() {
+        let (gate, prompter) = prompting_gate(false, true);
+        for _ in 0..2 {
+            assert!(matches!(
+                gate.require(ConsentSubject::Display, "capture the whole display")
+                    .await,
+                Err(CaptureError::PolicyDenied { .. })
+            ));
+        }
+        assert_eq!(prompter.prompts(), 1);
+
+        let (gate, prompter) = prompting_gate(true, false);
+        let subject = ConsentSubject::Tool("mark_windows".to_string());
+        gate.require(subject.clone(), "use the 'mark_windows' tool")
+            .await
+            .unwrap();
+        gate.require(subject.clone(), "use the 'mark_windows' tool")
+            .await
+            .unwrap();
+        assert_eq!(prompter.prompts(), 2);
+        assert_eq!(gate.decision(&subject), None);
+    }
+
+    #[tokio::test]
+    async fn test_refused_without_prompter() {
+        let gate = ConsentGate::new();
+        let Err(CaptureError::PolicyDenied { target, reason }) = gate
+            .require(ConsentSubject::Display, "capture the whole display")
+            .await
+        else {
+            panic!("consent without a prompter should be refused");
+        };
+        assert_eq!(target, "full-display capture");
+        assert!(reason.contains("elicitation"));
+    }
+}
@@ -33,7 +33,7 @@ use std::path::Path;
 
 use base64::{Engine, engine::general_purpose::STANDARD};
 use rmcp::model::{CallToolResult, Content};
-use screenshot_core::model::CaptureOptions;
+use screenshot_core::model::{CaptureOptions, RecordReport, Region};
 
 /// Builds MCP image content from raw image bytes
 ///
@@ -196,6 +196,58 @@ pub fn build_capture_result(
     opts: &CaptureOptions,
     dimensions: (u32, u32),
     include_inline_image: bool,
+) -> CallToolResult {
+    build_capture_result_with_metadata(
+        image_data,
+        file_path,
+        opts,
+        dimensions,
+        include_inline_image,
+        serde_json::Map::new(),
+    )
+}
+
+/// Builds a capture result with additional metadata fields
+///
+/// Identical to [`build_capture_result`], but merges `extra_metadata` into
+/// the metadata JSON object. Extra keys never override the standard fields
+/// (dimensions, format, size_bytes, quality, scale, file_path).
+///
+/// # Arguments
+///
+/// * `image_data` - Raw encoded image bytes (PNG/JPEG/WebP)
+/// * `file_path` - Optional path where the screenshot was saved
+/// * `opts` - Capture options (contains format information)
+/// * `dimensions` - Image dimensions as (width, height)
+/// * `include_inline_image` - Whether to include the base64 image data
+/// * `extra_metadata` - Additional key/value pairs for the metadata block
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::model::CaptureOptions;
+/// use screenshot_mcp_server::mcp_content::build_capture_result_with_metadata;
+///
+/// let mut extra = serde_json::Map::new();
+/// extra.insert("pointer".to_string(), serde_json::json!({"x": 10, "y": 20}));
+///
+/// let result = build_capture_result_with_metadata(
+///     &[0u8; 16],
+///     None,
+///     &CaptureOptions::default(),
+///     (800, 600),
+///     true,
+///     extra,
+/// );
+/// assert_eq!(result.content.len(), 2); // Image + Metadata
+/// ```
+pub fn build_capture_result_with_metadata(
+    image_data: &[u8],
+    file_path: Option<&Path>,
+    opts: &CaptureOptions,
+    dimensions: (u32, u32),
+    include_inline_image: bool,
+    extra_metadata: serde_json::Map<String, serde_json::Value>,
 ) -> CallToolResult {
     // Get MIME type from format
     let mime_type = opts.format.mime_type();
@@ -225,15 +277,172 @@ pub fn build_capture_result(
         "file_path": file_path_value,
     });
 
-    let metadata_str = serde_json::to_string_pretty(&metadata)
-        .unwrap_or_else(|_| r#"{"error": "Failed to serialize metadata"}"#.to_string());
-
-    content.push(Content::text(format!("## Capture Metadata\n\n```json\n{}\n```", metadata_str)));
+    content.push(build_metadata_content(metadata, extra_metadata));
 
     // Combine all content into success result
     CallToolResult::success(content)
 }
 
+/// One changed region of a delta capture, already encoded
+#[derive(Debug, Clone, Copy)]
+pub struct EncodedRegion<'a> {
+    /// Position and size of the crop within the full frame
+    pub region: Region,
+    /// Encoded image bytes of the crop
+    pub data: &'a [u8],
+    /// Path where the crop was saved, if written to a file
+    pub file_path: Option<&'a Path>,
+}
+
+/// Builds the result of a delta capture from its changed regions
+///
+/// Each region contributes an inline image and/or a resource link, in the
+/// order given. The metadata describes the full frame (`dimensions`) and
+/// lists each region's offset, size and file path under `regions`, so
+/// clients can paste the crops onto the previous frame. An empty `regions`
+/// slice yields a metadata-only result (nothing changed).
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::model::{CaptureOptions, Region};
+/// use screenshot_mcp_server::mcp_content::{EncodedRegion, build_delta_result};
+///
+/// let crop = [0u8; 16];
+/// let regions = [EncodedRegion {
+///     region: Region::new(10, 20, 4, 4),
+///     data: &crop,
+///     file_path: None,
+/// }];
+///
+/// let result = build_delta_result(
+///     &regions,
+///     &CaptureOptions::default(),
+///     (800, 600),
+///     true,
+///     serde_json::Map::new(),
+/// );
+/// assert_eq!(result.content.len(), 2); // Image + Metadata
+/// ```
+pub fn build_delta_result(
+    regions: &[EncodedRegion<'_>],
+    opts: &CaptureOptions,
+    dimensions: (u32, u32),
+    include_inline_image: bool,
+    extra_metadata: serde_json::Map<String, serde_json::Value>,
+) -> CallToolResult {
+    let mime_type = opts.format.mime_type();
+    let mut content = Vec::new();
+
+    for encoded in regions {
+        if include_inline_image {
+            content.push(build_image_content(encoded.data, mime_type));
+        }
+        if let Some(path) = encoded.file_path {
+            content.push(build_resource_link(path, mime_type, encoded.data.len() as u64));
+        }
+    }
+
+    let region_metadata: Vec<serde_json::Value> = regions
+        .iter()
+        .map(|encoded| {
+            serde_json::json!({
+                "x": encoded.region.x,
+                "y": encoded.region.y,
+                "width": encoded.region.width,
+                "height": encoded.region.height,
+                "size_bytes": encoded.data.len(),
+                "file_path": encoded.file_path.map(|p| p.to_string_lossy().to_string()),
+            })
+        })
+        .collect();
+
+    let metadata = serde_json::json!({
+        "dimensions": [dimensions.0, dimensions.1],
+        "format": opts.format.to_string(),
+        "size_bytes": regions.iter().map(|r| r.data.len()).sum::<usize>(),
+        "quality": opts.quality,
+        "scale": opts.scale,
+        "regions": region_metadata,
+    });
+    content.push(build_metadata_content(metadata, extra_metadata));
+
+    CallToolResult::success(content)
+}
+
+/// Builds the result of a window recording
+///
+/// Like [`build_capture_result_with_metadata`], with the MIME type taken
+/// from the animation format and the recording outcome under `recording`.
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::model::{AnimationFormat, RecordReport};
+/// use screenshot_mcp_server::mcp_content::build_animation_result;
+///
+/// let report = RecordReport {
+///     format: AnimationFormat::Gif,
+///     captured: 10,
+///     frames: 4,
+///     duration_ms: 2000,
+///     truncated: false,
+///     width: 640,
+///     height: 480,
+///     scale: 1.0,
+///     bytes: 64,
+/// };
+/// let result = build_animation_result(&[0u8; 64], None, &report, true, serde_json::Map::new());
+/// assert_eq!(result.content.len(), 2); // Image + Metadata
+/// ```
+pub fn build_animation_result(
+    data: &[u8],
+    file_path: Option<&Path>,
+    report: &RecordReport,
+    include_inline_image: bool,
+    extra_metadata: serde_json::Map<String, serde_json::Value>,
+) -> CallToolResult {
+    let mime_type = report.format.mime_type();
+    let mut content = Vec::new();
+
+    if include_inline_image {
+        content.push(build_image_content(data, mime_type));
+    }
+    if let Some(path) = file_path {
+        content.push(build_resource_link(path, mime_type, data.len() as u64));
+    }
+
+    let metadata = serde_json::json!({
+        "dimensions": [report.width, report.height],
+        "format": report.format.to_string(),
+        "size_bytes": data.len(),
+        "file_path": file_path.map(|p| p.to_string_lossy().to_string()),
+        "recording": report,
+    });
+    content.push(build_metadata_content(metadata, extra_metadata));
+
+    CallToolResult::success(content)
+}
+
+/// Formats capture metadata as a JSON code block
+///
+/// Keys from `extra_metadata` are added unless `metadata` already has them.
+fn build_metadata_content(
+    mut metadata: serde_json::Value,
+    extra_metadata: serde_json::Map<String, serde_json::Value>,
+) -> Content {
+    if let Some(fields) = metadata.as_object_mut() {
+        for (key, value) in extra_metadata {
+            fields.entry(key).or_insert(value);
+        }
+    }
+
+    let metadata_str = serde_json::to_string_pretty(&metadata)
+        .unwrap_or_else(|_| r#"{"error": "Failed to serialize metadata"}"#.to_string());
+
+    Content::text(format!("## Capture Metadata\n\n```json\n{}\n```", metadata_str))
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -452,4 +661,113 @@ mod tests {
         assert!(metadata_text.text.contains("90"));
         assert!(metadata_text.text.contains("0.5"));
     }
+
+    #[test]
+    fn test_build_capture_result_with_metadata_merges_extra_fields() {
+        let image_data = vec![0u8; 100];
+        let opts = CaptureOptions::default();
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("pointer".to_string(), serde_json::json!({"x": 12, "y": 34}));
+        // Standard fields must not be overridden
+        extra.insert("format".to_string(), serde_json::json!("bogus"));
+
+        let result =
+            build_capture_result_with_metadata(&image_data, None, &opts, (640, 480), false, extra);
+
+        assert_eq!(result.content.len(), 1);
+        let metadata_text = &result.content[0].as_text().unwrap().text;
+        assert!(metadata_text.contains("\"pointer\""));
+        assert!(metadata_text.contains("webp"));
+        assert!(!metadata_text.contains("bogus"));
+    }
+
+    // ========== build_delta_result Tests ==========
+
+    #[test]
+    fn test_build_delta_result_lists_regions() {
+        let crop_a = vec![1u8; 10];
+        let crop_b = vec![2u8; 20];
+        let path = PathBuf::from("/tmp/screenshot-mcp/crop.png");
+        let regions = [
+            EncodedRegion {
+                region: Region::new(0, 0, 5, 5),
+                data: &crop_a,
+                file_path: None,
+            },
+            EncodedRegion {
+                region: Region::new(40, 30, 8, 6),
+                data: &crop_b,
+                file_path: Some(&path),
+            },
+        ];
+        let opts = CaptureOptions::builder().format(ImageFormat::Png).build();
+
+        let mut extra = serde_json::Map::new();
+        extra.insert("delta".to_string(), serde_json::json!({"mode": "regions"}));
+        let result = build_delta_result(&regions, &opts, (100, 80), true, extra);
+
+        // Image, image + link, metadata
+        assert_eq!(result.content.len(), 4);
+        assert!(result.content[0].as_image().is_some());
+        assert!(result.content[1].as_image().is_some());
+        assert!(
+            result.content[2]
+                .as_text()
+                .unwrap()
+                .text
+                .contains("file://")
+        );
+
+        let metadata = &result.content[3].as_text().unwrap().text;
+        assert!(metadata.contains("\"delta\""));
+        assert!(metadata.contains("\"size_bytes\": 30"));
+        assert!(metadata.contains("\"x\": 40"));
+    }
+
+    #[test]
+    fn test_build_delta_result_without_regions_is_metadata_only() {
+        let result = build_delta_result(
+            &[],
+            &CaptureOptions::default(),
+            (100, 80),
+            true,
+            serde_json::Map::new(),
+        );
+        assert_eq!(result.content.len(), 1);
+        assert!(
+            result.content[0]
+                .as_text()
+                .unwrap()
+                .text
+                .contains("\"regions\": []")
+        );
+    }
+
+    // ========== build_animation_result Tests ==========
+
+    #[test]
+    fn test_build_animation_result_uses_animation_mime_type() {
+        let report = RecordReport {
+            format: screenshot_core::model::AnimationFormat::Apng,
+            captured: 15,
+            frames: 3,
+            duration_ms: 3000,
+            truncated: true,
+            width: 320,
+            height: 200,
+            scale: 0.5,
+            bytes: 8,
+        };
+        let path = PathBuf::from("/tmp/screenshot-mcp/recording.png");
+        let result =
+            build_animation_result(&[0u8; 8], Some(&path), &report, true, serde_json::Map::new());
+
+        assert_eq!(result.content.len(), 3);
+        assert_eq!(result.content[0].as_image().unwrap().mime_type, "image/apng");
+        let metadata = &result.content[2].as_text().unwrap().text;
+        assert!(metadata.contains("\"format\": \"apng\""));
+        assert!(metadata.contains("\"truncated\": true"));
+        assert!(metadata.contains("recording.png"));
+    }
 }
@@ -0,0 +1,244 @@
+//! Window event subscriptions forwarded as MCP notifications
+//!
+//! This module keeps track of active `subscribe_window_events` subscriptions.
+//! Each subscription is a background task that drains a core
+//! [`WindowEventStream`] and forwards matching events to a notification sink,
+//! normally the MCP peer via `notifications/message` (logging) with the
+//! logger name [`WINDOW_EVENTS_LOGGER`].
+//!
+//! # Notification Shape
+//!
+//! ```json
+//! {
+//!   "level": "info",
+//!   "logger": "window-events",
+//!   "data": {
+//!     "subscription_id": "window-events-1",
+//!     "event": {
+//!       "kind": "title_changed",
+//!       "window": "73400323",
+//!       "title": "Save As",
+//!       "timestamp": "2025-01-01T12:00:00.000Z"
+//!     }
+//!   }
+//! }
+//! ```
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures::StreamExt;
+use rmcp::model::{LoggingLevel, LoggingMessageNotificationParam};
+use screenshot_core::{
+    capture::WindowEventStream,
+    model::{WindowEvent, WindowEventKind},
+};
+use tokio::task::JoinHandle;
+
+/// Logger name used for forwarded window event notifications
+pub const WINDOW_EVENTS_LOGGER: &str = "window-events";
+
+/// Builds the logging notification for a window event
+///
+/// # Examples
+///
+/// ```
+/// use screenshot_core::model::{WindowEvent, WindowEventKind};
+/// use screenshot_mcp_server::mcp_events::{WINDOW_EVENTS_LOGGER, build_event_notification};
+///
+/// let event = WindowEvent::new(WindowEventKind::Created, "42", None);
+/// let notification = build_event_notification("window-events-1", &event);
+/// assert_eq!(notification.logger.as_deref(), Some(WINDOW_EVENTS_LOGGER));
+/// assert_eq!(notification.data["event"]["kind"], "created");
+/// ```
+pub fn build_event_notification(
+    subscription_id: &str,
+    event: &WindowEvent,
+) -> LoggingMessageNotificationParam {
+    LoggingMessageNotificationParam {
+        level: LoggingLevel::Info,
+        logger: Some(WINDOW_EVENTS_LOGGER.to_string()),
+        data: serde_json::json!({
+            "subscription_id": subscription_id,
+            "event": event,
+        }),
+    }
+}
+
+/// Registry of active window event subscriptions
+///
+/// Dropping the registry aborts every forwarding task.
+#[derive(Debug, Default)]
+pub struct EventSubscriptions {
+    /// Monotonic counter for subscription IDs
+    next_id: AtomicU64,
+    /// Forwarding tasks keyed by subscription ID
+    tasks: Mutex<HashMap<String, JoinHandle<()>>>,
+}
+
+impl EventSubscriptions {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts forwarding events from `stream` to `sink`
+    ///
+    /// Only events whose kind is in `kinds` are forwarded (all events when
+    /// `None`). The sink returns `false` to end the subscription, e.g. when
+    /// the client has disconnected.
+    ///
+    /// # Returns
+    ///
+    /// The new subscription ID.
+    pub fn start<F, Fut>(
+        &self,
+        mut stream: WindowEventStream,
+        kinds: Option<Vec<WindowEventKind>>,
+        mut sink: F,
+    ) -> String
+    where
+        F: FnMut(LoggingMessageNotificationParam) -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send,
+    {
+        let id = format!("window-events-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+
+        let task_id = id.clone();
+        let handle = tokio::spawn(async move {
+            while let Some(event) = stream.next().await {
+                if kinds
+                    .as_ref()
+                    .is_some_and(|kinds| !kinds.contains(&event.kind))
+                {
+                    continue;
+                }
+                if !sink(build_event_notification(&task_id, &event)).await {
+                    tracing::debug!("Window event sink closed for {}", task_id);
+                    break;
+                }
+            }
+            tracing::debug!("Window event subscription {} ended", task_id);
+        });
+
+        let mut tasks = self.tasks.lock().unwrap();
+        // Forget subscriptions whose stream or sink has already ended
+        tasks.retain(|_, task| !task.is_finished());
+        tasks.insert(id.clone(), handle);
+
+        tracing::info!("Started window event subscription {}", id);
+        id
+    }
+
+    /// Stops a subscription
+    ///
+    /// # Returns
+    ///
+    /// `true` if the subscription existed and was stopped.
+    pub fn stop(&self, subscription_id: &str) -> bool {
+        match self.tasks.lock().unwrap().remove(subscription_id) {
+            Some(task) => {
+                task.abort();
+                tracing::info!("Stopped window event subscription {}", subscription_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the number of subscriptions that are still forwarding events
+    pub fn active_count(&self) -> usize {
+        self.tasks
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|task| !task.is_finished())
+            .count()
+    }
+}
+
+impl Drop for EventSubscriptions {
+    fn drop(&mut self) {
+        if let Ok(tasks) = self.tasks.get_mut() {
+            for (_, task) in tasks.drain() {
+                task.abort();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn stream_of(events: Vec<WindowEvent>) -> WindowEventStream {
+        Box::pin(futures::stream::iter(events))
+    }
+
+    #[test]
+    fn test_build_event_notification() {
+        let event = WindowEvent::new(WindowEventKind::TitleChanged, "7", Some("Save As"));
+        let notification = build_event_notification("window-events-3", &event);
+
+        assert_eq!(notification.level, LoggingLevel::Info);
+        assert_eq!(notification.data["subscription_id"], "window-events-3");
+        assert_eq!(notification.data["event"]["title"], "Save As");
+    }
+
+    #[tokio::test]
+    async fn test_start_forwards_filtered_events() {
+        let subscriptions = EventSubscriptions::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let stream = stream_of(vec![
+            WindowEvent::new(WindowEventKind::Created, "1", None),
+            WindowEvent::new(WindowEventKind::Mapped, "1", None),
+            WindowEvent::new(WindowEventKind::Destroyed, "1", None),
+        ]);
+        let id = subscriptions.start(
+            stream,
+            Some(vec![WindowEventKind::Created, WindowEventKind::Destroyed]),
+            move |notification| {
+                let tx = tx.clone();
+                async move { tx.send(notification).is_ok() }
+            },
+        );
+        assert_eq!(id, "window-events-1");
+
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+        assert_eq!(first.data["event"]["kind"], "created");
+        assert_eq!(second.data["event"]["kind"], "destroyed");
+        // Stream exhausted: sender dropped with the task
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stop_aborts_subscription() {
+        let subscriptions = EventSubscriptions::new();
+        let id =
+            subscriptions.start(Box::pin(futures::stream::pending()), None, |_| async { true });
+
+        assert_eq!(subscriptions.active_count(), 1);
+        assert!(subscriptions.stop(&id));
+        assert!(!subscriptions.stop(&id), "second stop should report unknown ID");
+
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert_eq!(subscriptions.active_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_subscription_ids_are_unique() {
+        let subscriptions = EventSubscriptions::new();
+        let a = subscriptions.start(Box::pin(futures::stream::pending()), None, |_| async { true });
+        let b = subscriptions.start(Box::pin(futures::stream::pending()), None, |_| async { true });
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,214 @@
+//! Background time-lapse runs started with `start_timelapse`
+//!
+//! Each run is a background task executing
+//! [`run_timelapse`](screenshot_core::capture::timelapse::run_timelapse),
+//! which writes frames and `manifest.json` into the run's directory.
+//! `stop_timelapse` asks a run to finish after its current tick and returns
+//! the final manifest; runs that end on their own keep their result until
+//! they are stopped.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use futures::future::BoxFuture;
+use screenshot_core::{error::CaptureResult, model::TimelapseManifest};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+/// A running or finished time-lapse
+#[derive(Debug)]
+struct TimelapseJob {
+    /// Directory the frames are written to
+    dir: PathBuf,
+    /// Ends the run when sent to or dropped
+    stop: oneshot::Sender<()>,
+    /// The run itself
+    task: JoinHandle<CaptureResult<TimelapseManifest>>,
+}
+
+/// Summary of a time-lapse run, as listed by [`TimelapseJobs::list`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimelapseStatus {
+    /// Run ID
+    pub id: String,
+    /// Directory the frames are written to
+    pub dir: PathBuf,
+    /// Whether the run is still capturing
+    pub running: bool,
+}
+
+/// Registry of time-lapse runs
+///
+/// Dropping the registry aborts every run.
+#[derive(Debug, Default)]
+pub struct TimelapseJobs {
+    /// Monotonic counter for run IDs
+    next_id: AtomicU64,
+    /// Runs keyed by ID
+    jobs: Mutex<HashMap<String, TimelapseJob>>,
+}
+
+impl TimelapseJobs {
+    /// Creates an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocates the ID for a new run
+    ///
+    /// Reserved up front so the run's directory can be named after it; every
+    /// call returns a different ID, even when runs start concurrently.
+    pub fn reserve_id(&self) -> String {
+        format!("timelapse-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    /// Starts a run in the background under an ID from
+    /// [`reserve_id`](Self::reserve_id)
+    ///
+    /// `run` receives a future that completes when the run should stop. It
+    /// must not own the registry, or dropping the registry could never abort
+    /// it.
+    pub fn start<F, Fut>(&self, id: String, dir: &Path, run: F)
+    where
+        F: FnOnce(BoxFuture<'static, ()>) -> Fut,
+        Fut: Future<Output = CaptureResult<TimelapseManifest>> + Send + 'static,
+    {
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(run(Box::pin(async move {
+            let _ = stopped.await;
+        })));
+
+        self.jobs.lock().unwrap().insert(
+            id.clone(),
+            TimelapseJob {
+                dir: dir.to_path_buf(),
+                stop,
+                task,
+            },
+        );
+        tracing::info!("Started time-lapse {} into {}", id, dir.display());
+    }
+
+    /// Stops a run and waits for its final manifest
+    ///
+    /// # Returns
+    ///
+    /// `None` if there is no run with this ID, otherwise the run's outcome
+    /// and directory.
+    pub async fn stop(&self, id: &str) -> Option<(PathBuf, CaptureResult<TimelapseManifest>)> {
+        let job = self.jobs.lock().unwrap().remove(id)?;
+        let _ = job.stop.send(());
+        let result = match job.task.await {
+            Ok(result) => result,
+            Err(e) => Err(screenshot_core::error::CaptureError::ImageError(format!(
+                "time-lapse task failed: {}",
+                e
+            ))),
+        };
+        tracing::info!("Stopped time-lapse {}", id);
+        Some((job.dir, result))
+    }
+
+    /// Lists the runs, oldest first
+    pub fn list(&self) -> Vec<TimelapseStatus> {
+        let mut list: Vec<TimelapseStatus> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, job)| TimelapseStatus {
+                id: id.clone(),
+                dir: job.dir.clone(),
+                running: !job.task.is_finished(),
+            })
+            .collect();
+        list.sort_by_key(|status| {
+            status
+                .id
+                .trim_start_matches("timelapse-")
+                .parse::<u64>()
+                .unwrap_or_default()
+        });
+        list
+    }
+}
+
+impl Drop for TimelapseJobs {
+    fn drop(&mut self) {
+        if let Ok(jobs) = self.jobs.get_mut() {
+            for (_, job) in jobs.drain() {
+                job.task.abort();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use screenshot_core::model::{ImageFormat, TimelapseOptions, TimelapseStop};
+
+    use super::*;
+
+    fn manifest(stopped: TimelapseStop) -> TimelapseManifest {
+        TimelapseManifest {
+            target: "app".to_string(),
+            options: TimelapseOptions::default(),
+            format: ImageFormat::Png,
+            started: "2025-01-01T00:00:00.000Z".to_string(),
+            finished: None,
+            stopped: Some(stopped),
+            error: None,
+            captured: 0,
+            missed: 0,
+            frames: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stop_cancels_a_running_job() {
+        let jobs = TimelapseJobs::new();
+        let id = jobs.reserve_id();
+        assert_eq!(id, "timelapse-1");
+        assert_eq!(jobs.reserve_id(), "timelapse-2");
+
+        jobs.start(id.clone(), Path::new("/tmp/a"), |cancel| async move {
+            cancel.await;
+            Ok(manifest(TimelapseStop::Cancelled))
+        });
+        assert_eq!(
+            jobs.list(),
+            vec![TimelapseStatus {
+                id: id.clone(),
+                dir: PathBuf::from("/tmp/a"),
+                running: true,
+            }]
+        );
+
+        let (dir, result) = jobs.stop(&id).await.unwrap();
+        assert_eq!(dir, PathBuf::from("/tmp/a"));
+        assert_eq!(result.unwrap().stopped, Some(TimelapseStop::Cancelled));
+        assert!(jobs.list().is_empty());
+        assert!(jobs.stop(&id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_finished_job_keeps_its_result() {
+        let jobs = TimelapseJobs::new();
+        let id = jobs.reserve_id();
+        jobs.start(id.clone(), Path::new("/tmp/b"), |_cancel| async {
+            Ok(manifest(TimelapseStop::FrameLimit))
+        });
+
+        while jobs.list()[0].running {
+            tokio::task::yield_now().await;
+        }
+        let (_, result) = jobs.stop(&id).await.unwrap();
+        assert_eq!(result.unwrap().stopped, Some(TimelapseStop::FrameLimit));
+    }
+}
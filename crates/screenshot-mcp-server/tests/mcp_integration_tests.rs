@@ -276,7 +276,7 @@ async fn test_capture_with_injected_window_not_found() {
     use screenshot_core::{capture::MockBackend, error::CaptureError, model::WindowSelector};
 
     let mock = MockBackend::new().with_error(CaptureError::WindowNotFound {
-        selector: WindowSelector::by_title("test"),
+        selector: Box::new(WindowSelector::by_title("test")),
     });
     let ctx = McpTestContext::new_with_configured_mock(mock);
 